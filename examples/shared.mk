@@ -33,7 +33,7 @@ endif
 
 # Standard rules
 %.o: %.s
-	ca65 $(DEBUGARG) -U -I $(SHAREDLIBDIR) -l $(@:o=lst) -o $@ $<
+	ca65 $(DEBUGARG) -g -U -I $(SHAREDLIBDIR) -l $(@:o=lst) -o $@ $<
 
 # ld65 rule
 # $(1): output
@@ -46,10 +46,11 @@ $(1): $(4) prereqs $(1:.$(BINEXT)=.cfg) $(2) $(3) $(4)
 		-C $(1:.$(BINEXT)=.cfg) \
 		-vm \
 		-m $(1:.$(BINEXT)=.map) \
+		--dbgfile $(1:.$(BINEXT)=.dbg) \
 		-o $(1) \
 		$(2) \
 		$(patsubst %,--lib %,$(3))
-ARTIFACTS := $$(ARTIFACTS) $(1) $(1:.$(BINEXT)=.map)
+ARTIFACTS := $$(ARTIFACTS) $(1) $(1:.$(BINEXT)=.map) $(1:.$(BINEXT)=.dbg)
 endef
 
 # ld65v2 rule
@@ -64,8 +65,9 @@ $(1): $(5) $(2) $(3) $(4)
 		-C $(2) \
 		-vm \
 		-m $(1:.$(BINEXT)=.map) \
+		--dbgfile $(1:.$(BINEXT)=.dbg) \
 		-o $(1) \
 		$(3) \
 		$(patsubst %,--lib %,$(4))
-ARTIFACTS := $$(ARTIFACTS) $(1) $(1:.$(BINEXT)=.map)
+ARTIFACTS := $$(ARTIFACTS) $(1) $(1:.$(BINEXT)=.map) $(1:.$(BINEXT)=.dbg)
 endef
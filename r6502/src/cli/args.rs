@@ -1,6 +1,7 @@
 use clap::{Parser, Subcommand, ValueEnum};
 use clap_num::maybe_hex;
 use path_absolutize::Absolutize;
+use r6502core::sanitizer::{SanitizerCheck, SanitizerOptions};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
@@ -14,9 +15,50 @@ pub enum Command {
     #[command(name = "debug", about = "Debug program", value_parser = parse_absolute_path)]
     Debug(DebugOptions),
 
-    #[command(name = "run", about = "Run program")]
+    #[command(
+        name = "dap",
+        about = "Debug programs using Debug Adapter Protocol over standard input and output"
+    )]
+    Dap,
+
+    #[command(name = "disasm", about = "Disassemble image")]
+    Disasm(DisasmOptions),
+
+    #[command(name = "gdbserver", about = "Debug program using GDB remote protocol")]
+    GdbServer(GdbOptions),
+
+    #[command(
+        name = "opcodes",
+        about = "Show bytes, cycles and effects of each opcode"
+    )]
+    Opcodes {
+        #[arg(help = "Show only opcodes of instruction e.g. LDA")]
+        mnemonic: Option<String>,
+    },
+
+    #[command(
+        name = "rpc",
+        about = "Debug program using line-delimited JSON-RPC over standard input and output or TCP"
+    )]
+    Rpc(RpcOptions),
+
+    #[command(
+        name = "run",
+        about = "Run program",
+        long_about = "Run program until it halts, stopping with an error at any BRK while the IRQ \
+            vector is $0000 since there is no handler for it to go to"
+    )]
     Run(RunOptions),
 
+    #[command(
+        name = "trace",
+        about = "Show or compare binary traces recorded by run"
+    )]
+    Trace {
+        #[command(subcommand)]
+        command: TraceCommand,
+    },
+
     #[command(name = "test-gui")]
     TestGraphicsTerminal {
         #[clap(long = "font", value_enum, default_value_t = Font::Bedstead)]
@@ -45,6 +87,18 @@ pub enum Command {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum TraceCommand {
+    #[command(name = "show", about = "Show trace with symbols")]
+    Show(TraceShowOptions),
+
+    #[command(
+        name = "diff",
+        about = "Find first instruction at which two traces differ"
+    )]
+    Diff(TraceDiffOptions),
+}
+
 #[derive(Debug, Parser)]
 pub struct DebugOptions {
     #[arg(value_parser = parse_absolute_path)]
@@ -62,6 +116,23 @@ pub struct DebugOptions {
         short = 'm'
     )]
     pub machine: Option<String>,
+
+    #[arg(
+        help = "Run debugger commands from script without text UI",
+        long = "script",
+        value_parser = parse_absolute_path
+    )]
+    pub script: Option<PathBuf>,
+
+    #[arg(
+        help = "Open post-mortem bundle read-only",
+        long = "post-mortem",
+        default_value_t = false
+    )]
+    pub post_mortem: bool,
+
+    #[command(flatten)]
+    pub sanitizer: SanitizerArgs,
 }
 
 impl From<DebugOptions> for r6502ui::text_ui::DebugOptions {
@@ -71,6 +142,131 @@ impl From<DebugOptions> for r6502ui::text_ui::DebugOptions {
             load: value.load,
             start: value.start,
             machine: value.machine,
+            script: value.script,
+            post_mortem: value.post_mortem,
+            sanitize: value.sanitizer.into(),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct DisasmOptions {
+    #[arg(value_parser = parse_absolute_path)]
+    pub path: PathBuf,
+
+    #[arg(
+        help = "Load address if not in image header (saved in project file)",
+        long = "load",
+        value_parser = maybe_hex::<u16>
+    )]
+    pub load: Option<u16>,
+
+    #[arg(
+        help = "Address range to disassemble instead of whole image e.g. $0E00:$0EFF",
+        long = "range"
+    )]
+    pub range: Option<String>,
+
+    #[arg(
+        help = "Output source that ca65 can assemble",
+        long = "ca65",
+        default_value_t = false
+    )]
+    pub ca65: bool,
+
+    #[arg(
+        help = "Separate code from data by following jumps, calls and branches from entry points",
+        long = "trace",
+        default_value_t = false
+    )]
+    pub trace: bool,
+
+    #[arg(
+        help = "Entry point for tracing in addition to vectors and start address (saved in project file)",
+        long = "entry",
+        value_parser = maybe_hex::<u16>
+    )]
+    pub entry_points: Vec<u16>,
+
+    #[arg(
+        help = "Address range that is never code e.g. $8071:$80FF (saved in project file)",
+        long = "data"
+    )]
+    pub data: Vec<String>,
+
+    #[arg(
+        help = "Label name for address e.g. $8000=language (saved in project file)",
+        long = "label"
+    )]
+    pub labels: Vec<String>,
+}
+
+impl From<DisasmOptions> for r6502ui::disasm::DisasmOptions {
+    fn from(value: DisasmOptions) -> Self {
+        Self {
+            path: value.path,
+            load: value.load,
+            range: value.range,
+            ca65: value.ca65,
+            trace: value.trace,
+            entry_points: value.entry_points,
+            data: value.data,
+            labels: value.labels,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct GdbOptions {
+    #[arg(value_parser = parse_absolute_path)]
+    pub path: PathBuf,
+
+    #[arg(help = "TCP port to listen on", long = "port", default_value_t = 6502)]
+    pub port: u16,
+
+    #[arg(
+        help = "Machine hint if machine tag not in image header",
+        long = "machine",
+        short = 'm'
+    )]
+    pub machine: Option<String>,
+}
+
+impl From<GdbOptions> for r6502ui::gdb_server::GdbOptions {
+    fn from(value: GdbOptions) -> Self {
+        Self {
+            path: value.path,
+            port: value.port,
+            machine: value.machine,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct RpcOptions {
+    #[arg(value_parser = parse_absolute_path)]
+    pub path: PathBuf,
+
+    #[arg(
+        help = "TCP port to listen on instead of using standard input and output",
+        long = "port"
+    )]
+    pub port: Option<u16>,
+
+    #[arg(
+        help = "Machine hint if machine tag not in image header",
+        long = "machine",
+        short = 'm'
+    )]
+    pub machine: Option<String>,
+}
+
+impl From<RpcOptions> for r6502ui::rpc_server::RpcOptions {
+    fn from(value: RpcOptions) -> Self {
+        Self {
+            path: value.path,
+            port: value.port,
+            machine: value.machine,
         }
     }
 }
@@ -93,12 +289,70 @@ pub struct RunOptions {
     )]
     pub trace: bool,
 
+    #[arg(
+        help = "Write binary trace of instructions, registers and memory writes to file",
+        long = "trace-file",
+        value_parser = parse_absolute_path
+    )]
+    pub trace_file: Option<PathBuf>,
+
+    #[arg(
+        help = "Trace only instructions in address range e.g. $0E00:$0EFF",
+        long = "trace-range"
+    )]
+    pub trace_ranges: Vec<String>,
+
+    #[arg(help = "Trace only from given cycle", long = "trace-from")]
+    pub trace_from: Option<u64>,
+
+    #[arg(help = "Trace only before given cycle", long = "trace-until")]
+    pub trace_until: Option<u64>,
+
     #[arg(help = "Report cycles", long = "cycles", default_value_t = false)]
     pub cycles: bool,
 
     #[arg(help = "Stop after given number of cycles", long = "stop-after")]
     pub stop_after: Option<u64>,
 
+    #[arg(
+        help = "Write cycles per function to report file and folded stacks for flamegraphs alongside it",
+        long = "profile",
+        value_parser = parse_absolute_path
+    )]
+    pub profile: Option<PathBuf>,
+
+    #[arg(
+        help = "Write line and branch coverage in lcov format and summary per module alongside it",
+        long = "coverage",
+        value_parser = parse_absolute_path
+    )]
+    pub coverage: Option<PathBuf>,
+
+    #[arg(
+        help = "Write reads, writes and executes per address as CSV and heatmap image alongside it",
+        long = "heatmap",
+        value_parser = parse_absolute_path
+    )]
+    pub heatmap: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub sanitizer: SanitizerArgs,
+
+    #[arg(
+        help = "Write instruction, branch, interrupt and timing statistics to file",
+        long = "stats",
+        value_parser = parse_absolute_path
+    )]
+    pub stats: Option<PathBuf>,
+
+    #[arg(
+        help = "Format of statistics file",
+        long = "stats-format",
+        value_enum,
+        default_value_t = StatsFormat::Text
+    )]
+    pub stats_format: StatsFormat,
+
     #[arg(
         help = "Machine hint if machine tag not in image header",
         long = "machine",
@@ -114,13 +368,79 @@ impl From<RunOptions> for r6502ui::terminal_ui::RunOptions {
             load: value.load,
             start: value.start,
             trace: value.trace,
+            trace_file: value.trace_file,
+            trace_ranges: value.trace_ranges,
+            trace_from: value.trace_from,
+            trace_until: value.trace_until,
             cycles: value.cycles,
             stop_after: value.stop_after,
+            profile: value.profile,
+            coverage: value.coverage,
+            heatmap: value.heatmap,
+            sanitize: value.sanitizer.into(),
+            stats: value.stats,
+            stats_format: value.stats_format.into(),
             machine: value.machine,
         }
     }
 }
 
+#[derive(Debug, Parser)]
+pub struct TraceShowOptions {
+    #[arg(value_parser = parse_absolute_path)]
+    pub path: PathBuf,
+
+    #[arg(
+        help = "Image to take symbols from instead of the one traced",
+        long = "image",
+        value_parser = parse_absolute_path
+    )]
+    pub image: Option<PathBuf>,
+}
+
+impl From<TraceShowOptions> for r6502ui::trace::TraceShowOptions {
+    fn from(value: TraceShowOptions) -> Self {
+        Self {
+            path: value.path,
+            image: value.image,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct TraceDiffOptions {
+    #[arg(value_parser = parse_absolute_path)]
+    pub left: PathBuf,
+
+    #[arg(value_parser = parse_absolute_path)]
+    pub right: PathBuf,
+
+    #[arg(
+        help = "Image to take symbols from instead of the one traced",
+        long = "image",
+        value_parser = parse_absolute_path
+    )]
+    pub image: Option<PathBuf>,
+
+    #[arg(
+        help = "Number of matching instructions to show before the divergence",
+        long = "context",
+        default_value_t = 10
+    )]
+    pub context: usize,
+}
+
+impl From<TraceDiffOptions> for r6502ui::trace::TraceDiffOptions {
+    fn from(value: TraceDiffOptions) -> Self {
+        Self {
+            left: value.left,
+            right: value.right,
+            image: value.image,
+            context: value.context,
+        }
+    }
+}
+
 #[derive(Clone, Debug, ValueEnum)]
 pub enum Font {
     #[clap(name = "acorn")]
@@ -143,6 +463,106 @@ impl From<Font> for r6502vdu::font::Font {
     }
 }
 
+#[derive(Clone, Debug, ValueEnum)]
+pub enum StatsFormat {
+    #[clap(name = "text")]
+    Text,
+
+    #[clap(name = "json")]
+    Json,
+}
+
+impl From<StatsFormat> for r6502core::stats::StatsFormat {
+    fn from(value: StatsFormat) -> Self {
+        match value {
+            StatsFormat::Text => Self::Text,
+            StatsFormat::Json => Self::Json,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SanitizerArgs {
+    #[arg(
+        help = "Report problems found at run time e.g. --sanitize=uninit,stack or --sanitize for all",
+        long = "sanitize",
+        value_enum,
+        value_delimiter = ',',
+        num_args = 0..,
+        require_equals = true,
+        default_missing_value = "all"
+    )]
+    pub sanitize: Vec<SanitizeCheck>,
+
+    #[arg(
+        help = "Lowest value of SP allowed by stack sanitizer",
+        long = "stack-min",
+        value_parser = maybe_hex::<u8>,
+        default_value_t = 0x00
+    )]
+    pub stack_min: u8,
+
+    #[arg(
+        help = "Highest value of SP allowed by stack sanitizer",
+        long = "stack-max",
+        value_parser = maybe_hex::<u8>,
+        default_value_t = 0xff
+    )]
+    pub stack_max: u8,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum SanitizeCheck {
+    #[clap(name = "all")]
+    All,
+
+    #[clap(name = "uninit")]
+    Uninitialized,
+
+    #[clap(name = "stack")]
+    Stack,
+
+    #[clap(name = "rom")]
+    Rom,
+
+    #[clap(name = "unmapped")]
+    Unmapped,
+
+    #[clap(name = "exec-data")]
+    ExecuteData,
+}
+
+impl From<SanitizerArgs> for Option<SanitizerOptions> {
+    fn from(value: SanitizerArgs) -> Self {
+        if value.sanitize.is_empty() {
+            return None;
+        }
+
+        let mut checks = Vec::new();
+        for check in &value.sanitize {
+            let expanded = match check {
+                SanitizeCheck::All => &SanitizerCheck::ALL[..],
+                SanitizeCheck::Uninitialized => &[SanitizerCheck::Uninitialized],
+                SanitizeCheck::Stack => &[SanitizerCheck::Stack],
+                SanitizeCheck::Rom => &[SanitizerCheck::Rom],
+                SanitizeCheck::Unmapped => &[SanitizerCheck::Unmapped],
+                SanitizeCheck::ExecuteData => &[SanitizerCheck::ExecuteData],
+            };
+            for check in expanded {
+                if !checks.contains(check) {
+                    checks.push(*check);
+                }
+            }
+        }
+
+        Some(SanitizerOptions {
+            checks,
+            stack_min: value.stack_min,
+            stack_max: value.stack_max,
+        })
+    }
+}
+
 fn parse_absolute_path(s: &str) -> Result<PathBuf, String> {
     PathBuf::from(s)
         .absolutize()
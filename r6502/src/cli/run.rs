@@ -1,13 +1,19 @@
 use crate::cli::Command::{
-    Debug, Run, TestGraphicsTerminal, TestTextTerminal, Validate, ValidateJson,
+    Dap, Debug, Disasm, GdbServer, Opcodes, Rpc, Run, TestGraphicsTerminal, TestTextTerminal,
+    Trace, Validate, ValidateJson,
 };
-use crate::cli::{Args, Command};
+use crate::cli::{Args, Command, TraceCommand};
 use crate::scenario_util;
 use anyhow::Result;
 use clap::Parser;
 use log::LevelFilter;
+use r6502ui::dap_server::run_dap_server;
+use r6502ui::disasm::{run_disasm, run_opcodes};
+use r6502ui::gdb_server::run_gdb_server;
+use r6502ui::rpc_server::run_rpc_server;
 use r6502ui::terminal_ui::run_terminal_ui;
-use r6502ui::text_ui::run_text_ui;
+use r6502ui::text_ui::{run_post_mortem, run_script, run_text_ui};
+use r6502ui::trace::{run_trace_diff, run_trace_show};
 use r6502validation::scenario_runner::{run_scenario, run_scenarios_with_filter};
 use simple_logging::{log_to_file, log_to_stderr};
 
@@ -26,10 +32,23 @@ pub fn run() -> Result<()> {
     start_logging(&command)?;
 
     match command {
+        Dap => run_dap_server()?,
+        Debug(opts) if opts.post_mortem => run_post_mortem(&opts.into())?,
+        Debug(opts) if opts.script.is_some() => run_script(&opts.into())?,
         Debug(opts) => run_text_ui(&opts.into())?,
+        Disasm(opts) => run_disasm(&opts.into())?,
+        GdbServer(opts) => run_gdb_server(&opts.into())?,
+        Opcodes { mnemonic } => run_opcodes(mnemonic.as_deref())?,
+        Rpc(opts) => run_rpc_server(&opts.into())?,
         Run(opts) => run_terminal_ui(&opts.into())?,
         TestGraphicsTerminal { font } => r6502vdu::run_gui::run_gui(&font.into())?,
         TestTextTerminal => r6502vdu::run_tui::run_tui()?,
+        Trace {
+            command: TraceCommand::Show(opts),
+        } => run_trace_show(&opts.into())?,
+        Trace {
+            command: TraceCommand::Diff(opts),
+        } => run_trace_diff(&opts.into())?,
         Validate {
             report_path,
             filter,
@@ -0,0 +1,82 @@
+// Serde helpers for values written using Display and read back using FromStr e.g. address ranges
+// and expressions for use with #[serde(with = ...)]
+use serde::de::Error as SerdeError;
+use serde::{Deserialize, Deserializer, Serializer};
+use std::fmt::Display;
+use std::result::Result as StdResult;
+use std::str::FromStr;
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> StdResult<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.collect_str(value)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> StdResult<T, D::Error>
+where
+    T: FromStr<Err: Display>,
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(SerdeError::custom)
+}
+
+pub mod option {
+    use serde::de::Error as SerdeError;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::result::Result as StdResult;
+    use std::str::FromStr;
+
+    #[allow(clippy::ref_option)]
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> StdResult<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> StdResult<Option<T>, D::Error>
+    where
+        T: FromStr<Err: Display>,
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| s.parse().map_err(SerdeError::custom))
+            .transpose()
+    }
+}
+
+pub mod vec {
+    use serde::de::Error as SerdeError;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::result::Result as StdResult;
+    use std::str::FromStr;
+
+    pub fn serialize<T, S>(values: &[T], serializer: S) -> StdResult<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(ToString::to_string))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> StdResult<Vec<T>, D::Error>
+    where
+        T: FromStr<Err: Display>,
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| s.parse().map_err(SerdeError::custom))
+            .collect()
+    }
+}
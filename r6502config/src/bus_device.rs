@@ -1,18 +1,15 @@
-use crate::{BusDeviceType, deserialize_address_range, deserialize_word};
+use crate::BusDeviceType;
 use r6502lib::AddressRange;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BusDevice {
     #[serde(rename = "type")]
     pub r#type: BusDeviceType,
 
-    #[serde(
-        rename = "addressRange",
-        deserialize_with = "deserialize_address_range"
-    )]
+    #[serde(rename = "addressRange", with = "crate::as_string")]
     pub address_range: AddressRange,
 
-    #[serde(rename = "offset", deserialize_with = "deserialize_word")]
+    #[serde(rename = "offset", with = "crate::hex_word")]
     pub offset: u16,
 }
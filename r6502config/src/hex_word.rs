@@ -0,0 +1,76 @@
+// Serde helpers for words written as hex strings e.g. "$0E00" for use with #[serde(with = ...)]:
+// decimal strings are also accepted when reading
+use anyhow::Result;
+use serde::de::Error as SerdeError;
+use serde::{Deserialize, Deserializer, Serializer};
+use std::result::Result as StdResult;
+
+pub fn serialize<S>(value: &u16, serializer: S) -> StdResult<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("${value:04X}"))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> StdResult<u16, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    parse_word(&String::deserialize(deserializer)?).map_err(SerdeError::custom)
+}
+
+pub mod option {
+    use serde::de::Error as SerdeError;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::result::Result as StdResult;
+
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S>(value: &Option<u16>, serializer: S) -> StdResult<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> StdResult<Option<u16>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| super::parse_word(&s).map_err(SerdeError::custom))
+            .transpose()
+    }
+}
+
+pub mod vec {
+    use serde::de::Error as SerdeError;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::result::Result as StdResult;
+
+    pub fn serialize<S>(values: &[u16], serializer: S) -> StdResult<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(|value| format!("${value:04X}")))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> StdResult<Vec<u16>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| super::parse_word(s).map_err(SerdeError::custom))
+            .collect()
+    }
+}
+
+fn parse_word(s: &str) -> Result<u16> {
+    Ok(match s.strip_prefix('$') {
+        Some(suffix) => u16::from_str_radix(suffix, 16)?,
+        None => s.parse::<u16>()?,
+    })
+}
@@ -1,4 +1,4 @@
-use crate::{HostHookType, deserialize_word};
+use crate::HostHookType;
 use serde::Deserialize;
 
 #[derive(Clone, Debug, Deserialize)]
@@ -6,6 +6,6 @@ pub struct HostHook {
     #[serde(rename = "type")]
     pub r#type: HostHookType,
 
-    #[serde(rename = "address", deserialize_with = "deserialize_word")]
+    #[serde(rename = "address", with = "crate::hex_word")]
     pub addr: u16,
 }
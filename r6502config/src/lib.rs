@@ -1,3 +1,6 @@
+pub mod as_string;
+pub mod hex_word;
+
 mod bus_device;
 mod bus_device_type;
 mod char_set;
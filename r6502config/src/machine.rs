@@ -1,6 +1,4 @@
-use crate::{
-    BusDevice, CharSet, HostHook, OutputDeviceType, deserialize_machine_tag, deserialize_word_opt,
-};
+use crate::{BusDevice, CharSet, HostHook, OutputDeviceType, deserialize_machine_tag};
 use r6502lib::MachineTag;
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -16,11 +14,7 @@ pub struct Machine {
     #[serde(rename = "baseImage")]
     pub base_image_path: Option<PathBuf>,
 
-    #[serde(
-        rename = "haltAddress",
-        deserialize_with = "deserialize_word_opt",
-        default
-    )]
+    #[serde(rename = "haltAddress", with = "crate::hex_word::option", default)]
     pub halt_addr: Option<u16>,
 
     #[serde(rename = "hostHook")]
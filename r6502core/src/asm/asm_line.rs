@@ -0,0 +1,209 @@
+use crate::Opcode;
+use crate::asm::AsmOperand;
+use crate::debugger::Expr;
+use crate::emulator::{AddressingMode, OpInfo};
+use anyhow::{Error, Result, anyhow, bail};
+use r6502lib::util::split_word;
+use std::str::FromStr;
+
+// Single line of assembly language as typed into a machine-code monitor e.g. "LDA ($12),Y"
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsmLine {
+    pub mnemonic: &'static str,
+    pub operand: AsmOperand,
+}
+
+impl AsmLine {
+    // Bytes to store at the address given the value of the operand's expression, if any: zero
+    // page addressing is used wherever the value allows it
+    pub fn encode(&self, pc: u16, value: Option<i32>) -> Result<Vec<u8>> {
+        let value = || value.ok_or_else(|| anyhow!("operand has not been evaluated"));
+        match &self.operand {
+            AsmOperand::None => {
+                let opcode = self
+                    .find(AddressingMode::Implied)
+                    .or_else(|| self.find(AddressingMode::Accumulator))
+                    .ok_or_else(|| self.invalid())?;
+                Ok(vec![opcode as u8])
+            }
+            AsmOperand::Accumulator => {
+                let opcode = self
+                    .find(AddressingMode::Accumulator)
+                    .ok_or_else(|| self.invalid())?;
+                Ok(vec![opcode as u8])
+            }
+            AsmOperand::Immediate(_) => {
+                let opcode = self
+                    .find(AddressingMode::Immediate)
+                    .ok_or_else(|| self.invalid())?;
+                let value = value()?;
+                let byte = i8::try_from(value)
+                    .map(i8::cast_unsigned)
+                    .or_else(|_| u8::try_from(value))
+                    .map_err(|_| anyhow!("immediate value {value} out of range"))?;
+                Ok(vec![opcode as u8, byte])
+            }
+            AsmOperand::Direct(_) => match self.find(AddressingMode::Relative) {
+                Some(opcode) => {
+                    let target = Expr::to_addr(value()?)?;
+                    let offset = i32::from(target) - i32::from(pc.wrapping_add(2));
+                    let offset = i8::try_from(offset)
+                        .map_err(|_| anyhow!("branch to ${target:04X} out of range"))?;
+                    Ok(vec![opcode as u8, offset.cast_unsigned()])
+                }
+                None => {
+                    self.encode_direct(AddressingMode::ZeroPage, AddressingMode::Absolute, value()?)
+                }
+            },
+            AsmOperand::DirectX(_) => self.encode_direct(
+                AddressingMode::ZeroPageX,
+                AddressingMode::AbsoluteX,
+                value()?,
+            ),
+            AsmOperand::DirectY(_) => self.encode_direct(
+                AddressingMode::ZeroPageY,
+                AddressingMode::AbsoluteY,
+                value()?,
+            ),
+            // Parentheses only group the expression for instructions without indirect addressing
+            AsmOperand::Indirect(expr) => match self.find(AddressingMode::Indirect) {
+                Some(opcode) => Self::encode_word(opcode, value()?),
+                None => Self {
+                    mnemonic: self.mnemonic,
+                    operand: AsmOperand::Direct(expr.clone()),
+                }
+                .encode(pc, Some(value()?)),
+            },
+            AsmOperand::IndexedIndirectX(_) => {
+                self.encode_zero_page(AddressingMode::IndexedIndirectX, value()?)
+            }
+            AsmOperand::IndirectIndexedY(_) => {
+                self.encode_zero_page(AddressingMode::IndirectIndexedY, value()?)
+            }
+        }
+    }
+
+    fn encode_direct(
+        &self,
+        zero_page_mode: AddressingMode,
+        absolute_mode: AddressingMode,
+        value: i32,
+    ) -> Result<Vec<u8>> {
+        if let (Some(opcode), Ok(addr)) = (self.find(zero_page_mode), u8::try_from(value)) {
+            return Ok(vec![opcode as u8, addr]);
+        }
+        let opcode = self.find(absolute_mode).ok_or_else(|| self.invalid())?;
+        Self::encode_word(opcode, value)
+    }
+
+    fn encode_zero_page(&self, mode: AddressingMode, value: i32) -> Result<Vec<u8>> {
+        let opcode = self.find(mode).ok_or_else(|| self.invalid())?;
+        let addr = u8::try_from(value)
+            .map_err(|_| anyhow!("zero page address required instead of {value}"))?;
+        Ok(vec![opcode as u8, addr])
+    }
+
+    fn encode_word(opcode: Opcode, value: i32) -> Result<Vec<u8>> {
+        let (hi, lo) = split_word(Expr::to_addr(value)?);
+        Ok(vec![opcode as u8, lo, hi])
+    }
+
+    fn find(&self, mode: AddressingMode) -> Option<Opcode> {
+        OpInfo::iter()
+            .find(|op_info| {
+                op_info.opcode().mnemonic() == self.mnemonic && *op_info.addressing_mode() == mode
+            })
+            .map(OpInfo::opcode)
+    }
+
+    fn invalid(&self) -> Error {
+        anyhow!("invalid addressing mode for {}", self.mnemonic)
+    }
+}
+
+impl FromStr for AsmLine {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (mnemonic, operand) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let Some(mnemonic) = OpInfo::iter()
+            .map(|op_info| op_info.opcode().mnemonic())
+            .find(|m| m.eq_ignore_ascii_case(mnemonic))
+        else {
+            bail!("unknown mnemonic {mnemonic}")
+        };
+        Ok(Self {
+            mnemonic,
+            operand: operand.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Reg;
+    use crate::asm::{AsmLine, AsmOperand};
+    use crate::debugger::Expr;
+    use anyhow::Result;
+    use rstest::rstest;
+
+    fn assemble(s: &str, pc: u16) -> Result<Vec<u8>> {
+        let line = s.parse::<AsmLine>()?;
+        let value = line
+            .operand
+            .expr()
+            .map(|expr| expr.eval(&Reg::default(), &|_| 0))
+            .transpose()?;
+        line.encode(pc, value)
+    }
+
+    #[rstest]
+    #[case(&[0xea], "NOP", 0x0e00)]
+    #[case(&[0x0a], "asl", 0x0e00)]
+    #[case(&[0x0a], "ASL A", 0x0e00)]
+    #[case(&[0xa9, 0x41], "LDA #$41", 0x0e00)]
+    #[case(&[0xa9, 0xff], "LDA #-1", 0x0e00)]
+    #[case(&[0xa9, 0x34], "LDA #<$1234", 0x0e00)]
+    #[case(&[0xa5, 0x12], "lda $12", 0x0e00)]
+    #[case(&[0xad, 0x34, 0x12], "LDA $1234", 0x0e00)]
+    #[case(&[0xb5, 0x12], "LDA $12,X", 0x0e00)]
+    #[case(&[0xbd, 0x34, 0x12], "LDA $1234, x", 0x0e00)]
+    #[case(&[0xb6, 0x12], "LDX $12,Y", 0x0e00)]
+    #[case(&[0xb9, 0x12, 0x00], "LDA $12,Y", 0x0e00)]
+    #[case(&[0xa1, 0x12], "LDA ($12,X)", 0x0e00)]
+    #[case(&[0xb1, 0x12], "LDA ($12),Y", 0x0e00)]
+    #[case(&[0x6c, 0x34, 0x12], "JMP ($1234)", 0x0e00)]
+    #[case(&[0x20, 0x12, 0x00], "JSR $12", 0x0e00)]
+    #[case(&[0xa5, 0x09], "LDA (1+2)*3", 0x0e00)]
+    #[case(&[0xa5, 0x03], "LDA (1+2)", 0x0e00)]
+    #[case(&[0xd0, 0xf9], "BNE $0E00", 0x0e05)]
+    #[case(&[0xf0, 0x7f], "BEQ $0E81", 0x0e00)]
+    fn encode(#[case] expected_result: &[u8], #[case] s: &str, #[case] pc: u16) -> Result<()> {
+        assert_eq!(expected_result, assemble(s, pc)?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("FOO", 0x0e00)]
+    #[case("LDA", 0x0e00)]
+    #[case("LDA #$100", 0x0e00)]
+    #[case("LDA ($1234),Y", 0x0e00)]
+    #[case("STX $1234,X", 0x0e00)]
+    #[case("BEQ $0E82", 0x0e00)]
+    #[case("JMP #$12", 0x0e00)]
+    fn encode_fails(#[case] s: &str, #[case] pc: u16) {
+        assert!(assemble(s, pc).is_err());
+    }
+
+    #[test]
+    fn parse() -> Result<()> {
+        let line = "sta ( buffer ),y".parse::<AsmLine>()?;
+        assert_eq!("STA", line.mnemonic);
+        assert_eq!(
+            AsmOperand::IndirectIndexedY(Expr::Symbol(String::from("buffer"))),
+            line.operand
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use crate::debugger::Expr;
+use anyhow::{Error, Result};
+use std::str::FromStr;
+
+// Operand as written: which addressing modes it can be assembled with depends on the mnemonic
+// and the operand's value e.g. "$12,X" is zero page unless only absolute is available
+#[derive(Clone, Debug, PartialEq)]
+pub enum AsmOperand {
+    None,
+    Accumulator,
+    Immediate(Expr),
+    Direct(Expr),
+    DirectX(Expr),
+    DirectY(Expr),
+    Indirect(Expr),
+    IndexedIndirectX(Expr),
+    IndirectIndexedY(Expr),
+}
+
+impl AsmOperand {
+    #[must_use]
+    pub const fn expr(&self) -> Option<&Expr> {
+        match self {
+            Self::None | Self::Accumulator => None,
+            Self::Immediate(expr)
+            | Self::Direct(expr)
+            | Self::DirectX(expr)
+            | Self::DirectY(expr)
+            | Self::Indirect(expr)
+            | Self::IndexedIndirectX(expr)
+            | Self::IndirectIndexedY(expr) => Some(expr),
+        }
+    }
+
+    pub fn map_expr(&self, f: impl Fn(&Expr) -> Result<Expr>) -> Result<Self> {
+        Ok(match self {
+            Self::None => Self::None,
+            Self::Accumulator => Self::Accumulator,
+            Self::Immediate(expr) => Self::Immediate(f(expr)?),
+            Self::Direct(expr) => Self::Direct(f(expr)?),
+            Self::DirectX(expr) => Self::DirectX(f(expr)?),
+            Self::DirectY(expr) => Self::DirectY(f(expr)?),
+            Self::Indirect(expr) => Self::Indirect(f(expr)?),
+            Self::IndexedIndirectX(expr) => Self::IndexedIndirectX(f(expr)?),
+            Self::IndirectIndexedY(expr) => Self::IndirectIndexedY(f(expr)?),
+        })
+    }
+
+    fn strip_suffix_ignore_case<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+        let i = s.len().checked_sub(suffix.len())?;
+        (s.is_char_boundary(i) && s[i..].eq_ignore_ascii_case(suffix)).then(|| s[..i].trim_end())
+    }
+}
+
+impl FromStr for AsmOperand {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(Self::None);
+        }
+        if s.eq_ignore_ascii_case("A") {
+            return Ok(Self::Accumulator);
+        }
+        if let Some(s) = s.strip_prefix('#') {
+            return Ok(Self::Immediate(s.parse()?));
+        }
+
+        let compact = s.split_whitespace().collect::<String>();
+        if compact.starts_with('(') {
+            if let Some(inner) = Self::strip_suffix_ignore_case(&compact, ",X)") {
+                return Ok(Self::IndexedIndirectX(inner[1..].parse()?));
+            }
+            if let Some(inner) = Self::strip_suffix_ignore_case(&compact, "),Y") {
+                return Ok(Self::IndirectIndexedY(inner[1..].parse()?));
+            }
+            if let Some(inner) = compact.strip_suffix(')') {
+                return Ok(Self::Indirect(inner[1..].parse()?));
+            }
+        }
+        if let Some(s) = Self::strip_suffix_ignore_case(&compact, ",X") {
+            return Ok(Self::DirectX(s.parse()?));
+        }
+        if let Some(s) = Self::strip_suffix_ignore_case(&compact, ",Y") {
+            return Ok(Self::DirectY(s.parse()?));
+        }
+        Ok(Self::Direct(s.parse()?))
+    }
+}
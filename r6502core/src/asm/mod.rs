@@ -0,0 +1,5 @@
+mod asm_line;
+mod asm_operand;
+
+pub use asm_line::*;
+pub use asm_operand::*;
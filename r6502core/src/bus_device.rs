@@ -1,3 +1,5 @@
+use crate::DeviceState;
+
 pub trait BusDevice {
     fn start(&self) {}
 
@@ -9,4 +11,12 @@ pub trait BusDevice {
     fn load(&self, addr: u16) -> u8;
 
     fn store(&self, addr: u16, value: u8);
+
+    // Must not disturb the device e.g. by acknowledging pending input like a load would
+    fn inspect(&self) -> DeviceState {
+        DeviceState {
+            kind: String::from("Device"),
+            ..Default::default()
+        }
+    }
 }
@@ -0,0 +1,12 @@
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BranchCoverage {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+impl BranchCoverage {
+    #[must_use]
+    pub const fn both_ways(&self) -> bool {
+        self.taken > 0 && self.not_taken > 0
+    }
+}
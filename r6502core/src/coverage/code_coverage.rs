@@ -0,0 +1,391 @@
+use crate::coverage::BranchCoverage;
+use crate::emulator::{BranchResult, InstructionInfo};
+use crate::symbols::{DbgLineKind, DebugInfo, MapFile};
+use crate::{Operand, Reg};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::rc::Rc;
+
+const WORD_BITS: usize = 64;
+const WORDS: usize = 0x10000 / WORD_BITS;
+
+pub type SharedCoverage = Rc<RefCell<CodeCoverage>>;
+
+// Source line that generated code, with the address ranges it occupies
+struct SourceLine {
+    file: usize,
+    line: usize,
+    ranges: Vec<(u16, u32)>,
+}
+
+struct ModuleSummary {
+    name: String,
+    executed: usize,
+    lines_hit: usize,
+    lines: usize,
+    branches_both_ways: usize,
+    branches: usize,
+}
+
+// Addresses of instructions executed and outcomes of each branch executed
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeCoverage {
+    executed: Vec<u64>,
+    branches: BTreeMap<u16, BranchCoverage>,
+}
+
+impl Default for CodeCoverage {
+    fn default() -> Self {
+        Self {
+            executed: vec![0; WORDS],
+            branches: BTreeMap::new(),
+        }
+    }
+}
+
+impl CodeCoverage {
+    // Registers are those before the instruction executes
+    pub fn record(&mut self, reg: &Reg, instruction_info: &InstructionInfo) {
+        let pc = instruction_info.pc;
+        self.executed[usize::from(pc) / WORD_BITS] |= 1 << (usize::from(pc) % WORD_BITS);
+
+        if let Operand::Byte(offset) = instruction_info.operand
+            && let Some(result) = BranchResult::evaluate(instruction_info.opcode, reg, offset)
+        {
+            let branch = self.branches.entry(pc).or_default();
+            if result == BranchResult::NotTaken {
+                branch.not_taken += 1;
+            } else {
+                branch.taken += 1;
+            }
+        }
+    }
+
+    // Combine coverage from another run e.g. of another test program
+    pub fn merge(&mut self, other: &Self) {
+        for (word, other_word) in self.executed.iter_mut().zip(&other.executed) {
+            *word |= other_word;
+        }
+        for (addr, other_branch) in &other.branches {
+            let branch = self.branches.entry(*addr).or_default();
+            branch.taken += other_branch.taken;
+            branch.not_taken += other_branch.not_taken;
+        }
+    }
+
+    #[must_use]
+    pub fn is_executed(&self, addr: u16) -> bool {
+        self.executed[usize::from(addr) / WORD_BITS] & (1 << (usize::from(addr) % WORD_BITS)) != 0
+    }
+
+    #[must_use]
+    pub fn executed_count(&self) -> usize {
+        self.executed.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    #[must_use]
+    pub const fn branches(&self) -> &BTreeMap<u16, BranchCoverage> {
+        &self.branches
+    }
+
+    // Line and branch coverage in the lcov tracefile format used by genhtml and most CI services
+    #[must_use]
+    pub fn lcov(&self, debug_info: &DebugInfo) -> String {
+        let source_lines = Self::source_lines(debug_info);
+        let mut s = String::new();
+        for (file_id, file) in debug_info.files.iter().enumerate() {
+            let lines = source_lines
+                .iter()
+                .filter(|l| l.file == file_id)
+                .collect::<Vec<_>>();
+            if lines.is_empty() {
+                continue;
+            }
+
+            _ = writeln!(s, "TN:");
+            _ = writeln!(s, "SF:{}", debug_info.source_path(file).display());
+            let mut lines_hit = 0;
+            let mut branches = 0;
+            let mut branches_hit = 0;
+            for line in &lines {
+                let hit = self.is_line_hit(line);
+                if hit {
+                    lines_hit += 1;
+                }
+                _ = writeln!(s, "DA:{},{}", line.line, u8::from(hit));
+
+                for (addr, branch) in self.branches_in(&line.ranges) {
+                    for (i, count) in [branch.taken, branch.not_taken].into_iter().enumerate() {
+                        _ = writeln!(s, "BRDA:{},{addr},{i},{count}", line.line);
+                        branches += 1;
+                        if count > 0 {
+                            branches_hit += 1;
+                        }
+                    }
+                }
+            }
+            _ = writeln!(s, "BRF:{branches}");
+            _ = writeln!(s, "BRH:{branches_hit}");
+            _ = writeln!(s, "LF:{}", lines.len());
+            _ = writeln!(s, "LH:{lines_hit}");
+            _ = writeln!(s, "end_of_record");
+        }
+        s
+    }
+
+    // One row per module in the map file, or a single row for the whole image if there isn't one
+    #[must_use]
+    pub fn summary(&self, map_file: &MapFile, debug_info: &DebugInfo) -> String {
+        let source_lines = Self::source_lines(debug_info);
+        let mut modules = map_file
+            .modules
+            .iter()
+            .map(|module| {
+                let ranges = module
+                    .segments
+                    .iter()
+                    .filter_map(|ms| {
+                        let segment = map_file.segments.iter().find(|s| s.name == ms.name)?;
+                        let start = u16::try_from(segment.start + ms.offset).ok()?;
+                        Some((start, ms.size))
+                    })
+                    .collect::<Vec<_>>();
+                self.summarize(module.name.name.clone(), &ranges, &source_lines)
+            })
+            .filter(|m| m.lines > 0 || m.executed > 0 || m.branches > 0)
+            .collect::<Vec<_>>();
+        if modules.is_empty() {
+            modules.push(self.summarize(
+                String::from("(image)"),
+                &[(0x0000, 0x10000)],
+                &source_lines,
+            ));
+        }
+
+        let mut s = format!(
+            "{:<24} {:>8} {:>12} {:>7} {:>12}\n",
+            "Module", "Executed", "Lines", "%", "Branches"
+        );
+        for m in modules {
+            #[allow(clippy::cast_precision_loss)]
+            let percent = if m.lines == 0 {
+                0.0
+            } else {
+                m.lines_hit as f64 * 100.0 / m.lines as f64
+            };
+            _ = writeln!(
+                s,
+                "{:<24} {:>8} {:>12} {:>6.1}% {:>12}",
+                m.name,
+                m.executed,
+                format!("{}/{}", m.lines_hit, m.lines),
+                percent,
+                format!("{}/{}", m.branches_both_ways, m.branches)
+            );
+        }
+        s
+    }
+
+    fn summarize(
+        &self,
+        name: String,
+        ranges: &[(u16, u32)],
+        lines: &[SourceLine],
+    ) -> ModuleSummary {
+        let contains = |addr: u16| {
+            ranges
+                .iter()
+                .any(|(start, size)| u32::from(addr.wrapping_sub(*start)) < *size)
+        };
+        let executed = ranges
+            .iter()
+            .flat_map(|(start, size)| (0..*size).map(move |offset| u32::from(*start) + offset))
+            .filter_map(|addr| u16::try_from(addr).ok())
+            .filter(|addr| self.is_executed(*addr))
+            .count();
+        let lines = lines
+            .iter()
+            .filter(|l| l.ranges.first().is_some_and(|(start, _)| contains(*start)))
+            .collect::<Vec<_>>();
+        let branches = self
+            .branches
+            .iter()
+            .filter(|(addr, _)| contains(**addr))
+            .collect::<Vec<_>>();
+        ModuleSummary {
+            name,
+            executed,
+            lines_hit: lines.iter().filter(|l| self.is_line_hit(l)).count(),
+            lines: lines.len(),
+            branches_both_ways: branches.iter().filter(|(_, b)| b.both_ways()).count(),
+            branches: branches.len(),
+        }
+    }
+
+    fn is_line_hit(&self, line: &SourceLine) -> bool {
+        line.ranges.iter().any(|(start, size)| {
+            (0..*size).any(|offset| {
+                u16::try_from(offset)
+                    .is_ok_and(|offset| self.is_executed(start.wrapping_add(offset)))
+            })
+        })
+    }
+
+    fn branches_in<'a>(
+        &'a self,
+        ranges: &'a [(u16, u32)],
+    ) -> impl Iterator<Item = (u16, &'a BranchCoverage)> {
+        self.branches
+            .iter()
+            .filter(|(addr, _)| {
+                ranges
+                    .iter()
+                    .any(|(start, size)| u32::from(addr.wrapping_sub(*start)) < *size)
+            })
+            .map(|(addr, branch)| (*addr, branch))
+    }
+
+    // Lines generating code, as opposed to data, in order of file and line number
+    fn source_lines(debug_info: &DebugInfo) -> Vec<SourceLine> {
+        let mut lines = BTreeMap::<(usize, usize), Vec<(u16, u32)>>::new();
+        for line in &debug_info.lines {
+            let is_code = line.kind != DbgLineKind::Macro
+                && line
+                    .spans
+                    .iter()
+                    .any(|id| debug_info.spans.get(*id).is_some_and(|s| !s.data));
+            if is_code {
+                lines
+                    .entry((line.file, line.line))
+                    .or_default()
+                    .extend(debug_info.line_ranges(line));
+            }
+        }
+        lines
+            .into_iter()
+            .map(|((file, line), ranges)| SourceLine { file, line, ranges })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coverage::{BranchCoverage, CodeCoverage};
+    use crate::emulator::{Bus, Cpu, InstructionInfo};
+    use crate::symbols::{DebugInfo, MapFile};
+    use crate::{InterruptChannel, Opcode};
+    use anyhow::Result;
+
+    const DEBUG_INFO: &str = r#"version	major=2,minor=0
+file	id=0,name="main.s",size=200,mtime=0x60000000,mod=0
+line	id=0,file=0,line=3,span=0
+line	id=1,file=0,line=4,span=1
+line	id=2,file=0,line=5,span=2
+line	id=3,file=0,line=7,span=3
+seg	id=0,name="CODE",start=0x000E00,size=0x0007,addrsize=absolute,type=ro,oname="main.r6502",ooffs=0
+span	id=0,seg=0,start=0,size=2
+span	id=1,seg=0,start=2,size=2
+span	id=2,seg=0,start=4,size=1
+span	id=3,seg=0,start=5,size=2,type=0
+"#;
+
+    // LDA #value; BEQ +1; NOP; BRK; .byte $12, $34
+    fn run(value: u8, steps: usize) -> CodeCoverage {
+        let bus = Bus::default();
+        for (addr, value) in [
+            (0x0e00, Opcode::LdaImm as u8),
+            (0x0e01, value),
+            (0x0e02, Opcode::Beq as u8),
+            (0x0e03, 0x01),
+            (0x0e04, Opcode::Nop as u8),
+            (0x0e05, 0x12),
+            (0x0e06, 0x34),
+        ] {
+            bus.store(addr, value);
+        }
+
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        let mut coverage = CodeCoverage::default();
+        for _ in 0..steps {
+            coverage.record(&cpu.reg, &InstructionInfo::fetch(&cpu));
+            cpu.step_no_spin();
+        }
+        coverage
+    }
+
+    #[test]
+    fn basics() -> Result<()> {
+        let coverage = run(0x00, 2);
+        assert!(coverage.is_executed(0x0e00));
+        assert!(coverage.is_executed(0x0e02));
+        assert!(!coverage.is_executed(0x0e04));
+        assert_eq!(2, coverage.executed_count());
+        assert_eq!(
+            Some(&BranchCoverage {
+                taken: 1,
+                not_taken: 0
+            }),
+            coverage.branches().get(&0x0e02)
+        );
+
+        let debug_info = DEBUG_INFO.parse::<DebugInfo>()?;
+        assert_eq!(
+            "TN:\nSF:main.s\nDA:3,1\nDA:4,1\nBRDA:4,3586,0,1\nBRDA:4,3586,1,0\nDA:5,0\n\
+            BRF:2\nBRH:1\nLF:3\nLH:2\nend_of_record\n",
+            coverage.lcov(&debug_info)
+        );
+
+        let summary = coverage.summary(&MapFile::default(), &debug_info);
+        let row = summary.lines().nth(1).expect("Must exist");
+        assert!(row.starts_with("(image)"));
+        assert!(row.contains("2/3"));
+        assert!(row.contains("0/1"));
+
+        let mut other = CodeCoverage::default();
+        other.merge(&coverage);
+        assert_eq!(coverage, other);
+        Ok(())
+    }
+
+    #[test]
+    fn never_executed_branch() -> Result<()> {
+        let coverage = run(0x00, 1);
+        assert!(coverage.branches().is_empty());
+
+        // Branch outcomes are only known once the branch has executed
+        let debug_info = DEBUG_INFO.parse::<DebugInfo>()?;
+        assert_eq!(
+            "TN:\nSF:main.s\nDA:3,1\nDA:4,0\nDA:5,0\nBRF:0\nBRH:0\nLF:3\nLH:1\nend_of_record\n",
+            coverage.lcov(&debug_info)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn merge() -> Result<()> {
+        let mut coverage = run(0x00, 2);
+        coverage.merge(&run(0x01, 3));
+        coverage.merge(&run(0x00, 2));
+
+        assert_eq!(3, coverage.executed_count());
+        assert!(coverage.is_executed(0x0e04));
+        assert_eq!(
+            Some(&BranchCoverage {
+                taken: 2,
+                not_taken: 1
+            }),
+            coverage.branches().get(&0x0e02)
+        );
+
+        let debug_info = DEBUG_INFO.parse::<DebugInfo>()?;
+        assert_eq!(
+            "TN:\nSF:main.s\nDA:3,1\nDA:4,1\nBRDA:4,3586,0,2\nBRDA:4,3586,1,1\nDA:5,1\n\
+            BRF:2\nBRH:2\nLF:3\nLH:3\nend_of_record\n",
+            coverage.lcov(&debug_info)
+        );
+        Ok(())
+    }
+}
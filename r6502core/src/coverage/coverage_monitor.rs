@@ -0,0 +1,29 @@
+use crate::Reg;
+use crate::coverage::SharedCoverage;
+use crate::emulator::{InstructionInfo, Monitor};
+use r6502lib::TotalCycles;
+use std::rc::Rc;
+
+pub struct CoverageMonitor {
+    coverage: SharedCoverage,
+}
+
+impl CoverageMonitor {
+    #[must_use]
+    pub fn new(coverage: &SharedCoverage) -> Self {
+        Self {
+            coverage: Rc::clone(coverage),
+        }
+    }
+}
+
+impl Monitor for CoverageMonitor {
+    fn on_before_execute(
+        &self,
+        _total_cycles: TotalCycles,
+        reg: Reg,
+        instruction_info: InstructionInfo,
+    ) {
+        self.coverage.borrow_mut().record(&reg, &instruction_info);
+    }
+}
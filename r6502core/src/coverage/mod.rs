@@ -0,0 +1,7 @@
+mod branch_coverage;
+mod code_coverage;
+mod coverage_monitor;
+
+pub use branch_coverage::*;
+pub use code_coverage::*;
+pub use coverage_monitor::*;
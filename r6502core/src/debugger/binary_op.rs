@@ -0,0 +1,72 @@
+use anyhow::{Result, anyhow, bail};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Mul,
+    Div,
+    And,
+    Add,
+    Sub,
+    Or,
+    Xor,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    LogicalAnd,
+    LogicalOr,
+}
+
+impl BinaryOp {
+    pub fn apply(self, lhs: i32, rhs: i32) -> Result<i32> {
+        let overflow = || anyhow!("overflow in expression");
+        Ok(match self {
+            Self::Mul => lhs.checked_mul(rhs).ok_or_else(overflow)?,
+            Self::Div => {
+                if rhs == 0 {
+                    bail!("division by zero")
+                }
+                lhs.checked_div(rhs).ok_or_else(overflow)?
+            }
+            Self::And => lhs & rhs,
+            Self::Add => lhs.checked_add(rhs).ok_or_else(overflow)?,
+            Self::Sub => lhs.checked_sub(rhs).ok_or_else(overflow)?,
+            Self::Or => lhs | rhs,
+            Self::Xor => lhs ^ rhs,
+            Self::Eq => i32::from(lhs == rhs),
+            Self::Ne => i32::from(lhs != rhs),
+            Self::Lt => i32::from(lhs < rhs),
+            Self::Le => i32::from(lhs <= rhs),
+            Self::Gt => i32::from(lhs > rhs),
+            Self::Ge => i32::from(lhs >= rhs),
+            Self::LogicalAnd => i32::from(lhs != 0 && rhs != 0),
+            Self::LogicalOr => i32::from(lhs != 0 || rhs != 0),
+        })
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = match self {
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::And => "&",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Or => "|",
+            Self::Xor => "^",
+            Self::Eq => "=",
+            Self::Ne => "<>",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::LogicalAnd => "&&",
+            Self::LogicalOr => "||",
+        };
+        write!(f, "{s}")
+    }
+}
@@ -0,0 +1,12 @@
+use crate::debugger::FrameKind;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallFrame {
+    pub kind: FrameKind,
+    // Address of the JSR/BRK instruction or the interrupted instruction
+    pub site: u16,
+    pub target: u16,
+    pub return_addr: u16,
+    // Stack pointer after the return address (and flags) were pushed
+    pub sp: u8,
+}
@@ -0,0 +1,282 @@
+use crate::debugger::{CallFrame, FrameKind, StackIssue};
+use crate::emulator::Cpu;
+use crate::{InterruptEvent, Opcode};
+use r6502lib::util::make_word;
+
+const MAX_ISSUES: usize = 100;
+
+struct Before {
+    pc: u16,
+    sp: u8,
+    opcode: u8,
+}
+
+// Shadow call stack maintained by watching JSR/RTS/BRK/RTI and interrupt entry
+#[derive(Default)]
+pub struct CallStack {
+    frames: Vec<CallFrame>,
+    issues: Vec<StackIssue>,
+    before: Option<Before>,
+}
+
+impl CallStack {
+    // Call stack recorded earlier e.g. in a post-mortem bundle
+    #[must_use]
+    pub const fn new(frames: Vec<CallFrame>, issues: Vec<StackIssue>) -> Self {
+        Self {
+            frames,
+            issues,
+            before: None,
+        }
+    }
+
+    #[must_use]
+    pub fn frames(&self) -> &[CallFrame] {
+        &self.frames
+    }
+
+    #[must_use]
+    pub fn issues(&self) -> &[StackIssue] {
+        &self.issues
+    }
+
+    pub fn before_step(&mut self, cpu: &Cpu) {
+        self.before = Some(Before {
+            pc: cpu.reg.pc,
+            sp: cpu.reg.sp,
+            opcode: cpu.bus.peek(cpu.reg.pc),
+        });
+    }
+
+    pub fn after_step(&mut self, cpu: &Cpu) {
+        let Some(mut before) = self.before.take() else {
+            return;
+        };
+
+        if let Some(interrupt) = cpu.last_interrupt {
+            // The instruction actually executed was the first one in the handler
+            let vector = interrupt.vector();
+            let target = make_word(cpu.bus.peek(vector.wrapping_add(1)), cpu.bus.peek(vector));
+            self.interrupt(interrupt, before.pc, before.sp, target);
+            if interrupt == InterruptEvent::Reset {
+                return;
+            }
+            before = Before {
+                pc: target,
+                sp: before.sp.wrapping_sub(3),
+                opcode: cpu.bus.peek(target),
+            };
+        }
+
+        match Opcode::from_u8(before.opcode) {
+            Some(Opcode::Jsr) => self.frames.push(CallFrame {
+                kind: FrameKind::Jsr,
+                site: before.pc,
+                target: cpu.reg.pc,
+                return_addr: before.pc.wrapping_add(3),
+                sp: cpu.reg.sp,
+            }),
+            Some(Opcode::Brk) => self.frames.push(CallFrame {
+                kind: FrameKind::Brk,
+                site: before.pc,
+                target: cpu.reg.pc,
+                return_addr: before.pc.wrapping_add(2),
+                sp: cpu.reg.sp,
+            }),
+            Some(Opcode::Rts) => self.pop(&before, cpu.reg.pc, false),
+            Some(Opcode::Rti) => self.pop(&before, cpu.reg.pc, true),
+            _ => {}
+        }
+
+        self.discard(before.pc, cpu.reg.sp);
+    }
+
+    // Record an interrupt taken at the given PC and SP that vectored to the target
+    pub fn interrupt(&mut self, interrupt: InterruptEvent, pc: u16, sp: u8, target: u16) {
+        let kind = match interrupt {
+            InterruptEvent::Irq => FrameKind::Irq,
+            InterruptEvent::Nmi => FrameKind::Nmi,
+            InterruptEvent::Reset => {
+                self.frames.clear();
+                return;
+            }
+        };
+        self.frames.push(CallFrame {
+            kind,
+            site: pc,
+            target,
+            return_addr: pc,
+            sp: sp.wrapping_sub(3),
+        });
+    }
+
+    fn pop(&mut self, before: &Before, new_pc: u16, rti: bool) {
+        let instruction = if rti { "RTI" } else { "RTS" };
+        let Some(frame) = self.frames.last() else {
+            self.add_issue(before.pc, format!("{instruction} with empty call stack"));
+            return;
+        };
+
+        if frame.sp != before.sp {
+            let message = format!(
+                "{instruction} with SP=${sp:02X} but {kind} at ${site:04X} left SP=${frame_sp:02X}",
+                sp = before.sp,
+                kind = frame.kind,
+                site = frame.site,
+                frame_sp = frame.sp
+            );
+            self.add_issue(before.pc, message);
+            return;
+        }
+
+        let frame = self.frames.pop().expect("Must exist");
+        if (frame.kind == FrameKind::Jsr) == rti {
+            let message = format!(
+                "{instruction} returns from {kind} at ${site:04X}",
+                kind = frame.kind,
+                site = frame.site
+            );
+            self.add_issue(before.pc, message);
+        } else if new_pc != frame.return_addr {
+            let message = format!(
+                "{instruction} returned to ${new_pc:04X} instead of ${return_addr:04X} ({kind} at ${site:04X})",
+                return_addr = frame.return_addr,
+                kind = frame.kind,
+                site = frame.site
+            );
+            self.add_issue(before.pc, message);
+        }
+    }
+
+    // Drop frames whose return addresses were pulled off the stack by something other than RTS/RTI
+    fn discard(&mut self, pc: u16, sp: u8) {
+        while let Some(frame) = self.frames.last()
+            && frame.sp < sp
+        {
+            let message = format!(
+                "{kind} at ${site:04X} discarded from stack",
+                kind = frame.kind,
+                site = frame.site
+            );
+            self.add_issue(pc, message);
+            _ = self.frames.pop();
+        }
+    }
+
+    fn add_issue(&mut self, pc: u16, message: String) {
+        if self.issues.len() == MAX_ISSUES {
+            _ = self.issues.remove(0);
+        }
+        self.issues.push(StackIssue { pc, message });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debugger::{CallStack, FrameKind};
+    use crate::emulator::{Bus, Cpu};
+    use crate::{InterruptChannel, InterruptEvent};
+    use r6502lib::constants::IRQ;
+
+    fn run(cpu: &mut Cpu, call_stack: &mut CallStack, steps: usize) {
+        for _ in 0..steps {
+            call_stack.before_step(cpu);
+            cpu.step_no_spin();
+            call_stack.after_step(cpu);
+        }
+    }
+
+    fn store(bus: &Bus, addr: u16, bytes: &[u8]) {
+        for (i, value) in bytes.iter().enumerate() {
+            bus.store(addr + u16::try_from(i).unwrap(), *value);
+        }
+    }
+
+    #[test]
+    fn balanced() {
+        let bus = Bus::default();
+        store(&bus, 0x0e00, &[0x20, 0x10, 0x0e, 0xea]); // JSR $0E10; NOP
+        store(&bus, 0x0e10, &[0x20, 0x20, 0x0e, 0x60]); // JSR $0E20; RTS
+        store(&bus, 0x0e20, &[0x60]); // RTS
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        let mut call_stack = CallStack::default();
+
+        run(&mut cpu, &mut call_stack, 2);
+        let frames = call_stack.frames();
+        assert_eq!(2, frames.len());
+        assert_eq!(0x0e10, frames[1].site);
+        assert_eq!(0x0e20, frames[1].target);
+        assert_eq!(0x0e13, frames[1].return_addr);
+
+        run(&mut cpu, &mut call_stack, 2);
+        assert!(call_stack.frames().is_empty());
+        assert!(call_stack.issues().is_empty());
+    }
+
+    #[test]
+    fn manual_manipulation() {
+        let bus = Bus::default();
+        store(&bus, 0x0e00, &[0x20, 0x10, 0x0e]); // JSR $0E10
+        store(&bus, 0x0e10, &[0x68, 0x68, 0x60]); // PLA; PLA; RTS
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        let mut call_stack = CallStack::default();
+
+        run(&mut cpu, &mut call_stack, 4);
+        assert!(call_stack.frames().is_empty());
+        let issues = call_stack.issues();
+        assert_eq!(2, issues.len());
+        assert_eq!(0x0e10, issues[0].pc);
+        assert_eq!(
+            "$0E10: JSR at $0E00 discarded from stack",
+            issues[0].to_string()
+        );
+        assert_eq!("$0E12: RTS with empty call stack", issues[1].to_string());
+    }
+
+    #[test]
+    fn interrupt() {
+        let bus = Bus::default();
+        store(&bus, 0x0e00, &[0xea]); // NOP
+        store(&bus, 0x0f00, &[0x40]); // RTI
+        store(&bus, IRQ, &[0x00, 0x0f]);
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        let mut call_stack = CallStack::default();
+
+        interrupt_channel.tx.send(InterruptEvent::Irq).unwrap();
+        call_stack.before_step(&cpu);
+        cpu.step_no_spin();
+        call_stack.after_step(&cpu);
+        assert_eq!(0x0e00, cpu.reg.pc);
+        assert!(call_stack.frames().is_empty());
+        assert!(call_stack.issues().is_empty());
+
+        store(&bus, 0x0f00, &[0xea]); // NOP
+        interrupt_channel.tx.send(InterruptEvent::Irq).unwrap();
+        run(&mut cpu, &mut call_stack, 1);
+        let frames = call_stack.frames();
+        assert_eq!(1, frames.len());
+        assert_eq!(FrameKind::Irq, frames[0].kind);
+        assert_eq!(0x0f00, frames[0].target);
+        assert_eq!(0x0e00, frames[0].return_addr);
+    }
+
+    #[test]
+    fn injected_interrupt() {
+        let mut call_stack = CallStack::default();
+        call_stack.interrupt(InterruptEvent::Nmi, 0x0e00, 0xff, 0x0d00);
+        let frames = call_stack.frames();
+        assert_eq!(1, frames.len());
+        assert_eq!(FrameKind::Nmi, frames[0].kind);
+        assert_eq!(0x0d00, frames[0].target);
+        assert_eq!(0xfc, frames[0].sp);
+
+        call_stack.interrupt(InterruptEvent::Reset, 0x0d00, 0xfc, 0xd9cd);
+        assert!(call_stack.frames().is_empty());
+    }
+}
@@ -0,0 +1,460 @@
+use crate::Reg;
+use crate::debugger::{BinaryOp, Register, UnaryOp};
+use anyhow::{Error, Result, anyhow, bail};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+// Debugger expression e.g. "buffer+$20", "{$FFFC}" or "A=$41 && [$0200]<>0" with numbers given as
+// "$" hex, "%" binary or decimal
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(i32),
+    Symbol(String),
+    Register(Register),
+    Byte(Box<Expr>),
+    Word(Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    // Bare numbers are in the given radix e.g. 16 for addresses typed into commands as in a
+    // machine-code monitor
+    pub fn parse_radix(s: &str, radix: u32) -> Result<Self> {
+        let mut parser = Parser {
+            input: s,
+            pos: 0,
+            radix,
+        };
+        let expr = parser.parse_logical_or()?;
+        if let Some(c) = parser.peek() {
+            bail!("unexpected \"{c}\" in expression {s}")
+        }
+        Ok(expr)
+    }
+
+    // Parse "<START>" or "<START>:<END>" where a single colon separates the two expressions
+    pub fn parse_range(s: &str, radix: u32) -> Result<(Self, Self)> {
+        let bytes = s.as_bytes();
+        let separator = (0..bytes.len()).find(|&i| {
+            bytes[i] == b':'
+                && (i == 0 || bytes[i - 1] != b':')
+                && bytes.get(i + 1).is_none_or(|b| *b != b':')
+        });
+        if let Some(i) = separator {
+            Ok((
+                Self::parse_radix(&s[..i], radix)?,
+                Self::parse_radix(&s[i + 1..], radix)?,
+            ))
+        } else {
+            let start = Self::parse_radix(s, radix)?;
+            Ok((start.clone(), start))
+        }
+    }
+
+    pub fn to_addr(value: i32) -> Result<u16> {
+        u16::try_from(value).map_err(|_| anyhow!("address {value} out of range"))
+    }
+
+    // Evaluate against registers and memory: symbols must already have been resolved
+    pub fn eval(&self, reg: &Reg, load: &impl Fn(u16) -> u8) -> Result<i32> {
+        Ok(match self {
+            Self::Number(value) => *value,
+            Self::Symbol(name) => bail!("unresolved symbol {name}"),
+            Self::Register(register) => register.value(reg),
+            Self::Byte(addr) => i32::from(load(Self::to_addr(addr.eval(reg, load)?)?)),
+            Self::Word(addr) => {
+                let addr = Self::to_addr(addr.eval(reg, load)?)?;
+                i32::from(load(addr)) | (i32::from(load(addr.wrapping_add(1))) << 8)
+            }
+            Self::Unary(op, operand) => op.apply(operand.eval(reg, load)?)?,
+            Self::Binary(op, lhs, rhs) => op.apply(lhs.eval(reg, load)?, rhs.eval(reg, load)?)?,
+        })
+    }
+
+    // Replace symbols with their values: names not found are taken to be registers and then hex
+    // numbers so a symbol such as "a" or "pc" takes precedence over the register with the same
+    // name and "e00" is $0E00 unless there's a symbol or register of that name
+    pub fn resolve(&self, lookup: &impl Fn(&str) -> Option<u16>) -> Result<Self> {
+        Ok(match self {
+            Self::Number(_) | Self::Register(_) => self.clone(),
+            Self::Symbol(name) => match lookup(name) {
+                Some(value) => Self::Number(i32::from(value)),
+                None => match Register::from_name(name) {
+                    Some(register) => Self::Register(register),
+                    None => Self::Number(
+                        i32::from_str_radix(name, 16)
+                            .map_err(|_| anyhow!("unknown symbol {name}"))?,
+                    ),
+                },
+            },
+            Self::Byte(addr) => Self::Byte(Box::new(addr.resolve(lookup)?)),
+            Self::Word(addr) => Self::Word(Box::new(addr.resolve(lookup)?)),
+            Self::Unary(op, operand) => Self::Unary(*op, Box::new(operand.resolve(lookup)?)),
+            Self::Binary(op, lhs, rhs) => Self::Binary(
+                *op,
+                Box::new(lhs.resolve(lookup)?),
+                Box::new(rhs.resolve(lookup)?),
+            ),
+        })
+    }
+
+    fn fmt_operand(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if matches!(self, Self::Binary(..)) {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Number(value @ 0..=0xff) => write!(f, "${value:02X}"),
+            Self::Number(value @ 0x100..=0xffff) => write!(f, "${value:04X}"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::Symbol(name) => write!(f, "{name}"),
+            Self::Register(register) => write!(f, "{register}"),
+            Self::Byte(addr) => write!(f, "[{addr}]"),
+            Self::Word(addr) => write!(f, "{{{addr}}}"),
+            Self::Unary(op, operand) => {
+                write!(f, "{op}")?;
+                operand.fmt_operand(f)
+            }
+            Self::Binary(op, lhs, rhs) => {
+                lhs.fmt_operand(f)?;
+                write!(f, " {op} ")?;
+                rhs.fmt_operand(f)
+            }
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_radix(s, 10)
+    }
+}
+
+// Recursive descent parser with operators from lowest to highest precedence: "||", "&&",
+// comparisons, "+ - | ^", "* / &" and unary "- ~ < >"
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    radix: u32,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.input.len() - trimmed.len();
+        trimmed.chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        _ = self.peek();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    // Single-character operator that is not the first half of a doubled one e.g. "&" but not "&&"
+    fn eat_single(&mut self, c: char) -> bool {
+        _ = self.peek();
+        let mut chars = self.rest().chars();
+        if chars.next() == Some(c) && chars.next() != Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_logical_or(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_logical_and()?;
+        while self.eat("||") {
+            let rhs = self.parse_logical_and()?;
+            lhs = Expr::Binary(BinaryOp::LogicalOr, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_logical_and(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_comparison()?;
+        while self.eat("&&") {
+            let rhs = self.parse_comparison()?;
+            lhs = Expr::Binary(BinaryOp::LogicalAnd, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let lhs = self.parse_sum()?;
+        let op = if self.eat("==") || self.eat("=") {
+            BinaryOp::Eq
+        } else if self.eat("<>") || self.eat("!=") {
+            BinaryOp::Ne
+        } else if self.eat("<=") {
+            BinaryOp::Le
+        } else if self.eat(">=") {
+            BinaryOp::Ge
+        } else if self.eat("<") {
+            BinaryOp::Lt
+        } else if self.eat(">") {
+            BinaryOp::Gt
+        } else {
+            return Ok(lhs);
+        };
+        let rhs = self.parse_sum()?;
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_product()?;
+        loop {
+            let op = if self.eat("+") {
+                BinaryOp::Add
+            } else if self.eat("-") {
+                BinaryOp::Sub
+            } else if self.eat_single('|') {
+                BinaryOp::Or
+            } else if self.eat("^") {
+                BinaryOp::Xor
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.parse_product()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_product(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            let op = if self.eat("*") {
+                BinaryOp::Mul
+            } else if self.eat("/") {
+                BinaryOp::Div
+            } else if self.eat_single('&') {
+                BinaryOp::And
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.parse_unary()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        let op = if self.eat("-") {
+            UnaryOp::Neg
+        } else if self.eat("~") {
+            UnaryOp::Not
+        } else if self.eat("<") {
+            UnaryOp::Lo
+        } else if self.eat(">") {
+            UnaryOp::Hi
+        } else {
+            return self.parse_primary();
+        };
+        Ok(Expr::Unary(op, Box::new(self.parse_unary()?)))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        if self.eat("(") {
+            return self.parse_closing(")");
+        }
+        if self.eat("[") {
+            return Ok(Expr::Byte(Box::new(self.parse_closing("]")?)));
+        }
+        if self.eat("{") {
+            return Ok(Expr::Word(Box::new(self.parse_closing("}")?)));
+        }
+        if self.eat("$") {
+            return self.parse_number(16);
+        }
+        if self.eat("%") {
+            return self.parse_number(2);
+        }
+
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() => self.parse_number(self.radix),
+            Some(c) if c.is_ascii_alphabetic() || matches!(c, '_' | '@' | '.') => {
+                Ok(self.parse_name())
+            }
+            Some(c) => bail!("unexpected \"{c}\" in expression {}", self.input),
+            None => bail!("incomplete expression {}", self.input),
+        }
+    }
+
+    fn parse_closing(&mut self, closing: &str) -> Result<Expr> {
+        let expr = self.parse_logical_or()?;
+        if !self.eat(closing) {
+            bail!("missing \"{closing}\" in expression {}", self.input)
+        }
+        Ok(expr)
+    }
+
+    fn parse_number(&mut self, radix: u32) -> Result<Expr> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(rest.len());
+        let value = i32::from_str_radix(&rest[..len], radix)
+            .map_err(|_| anyhow!("invalid number in expression {}", self.input))?;
+        self.pos += len;
+        Ok(Expr::Number(value))
+    }
+
+    // Symbol names may be qualified e.g. "print::loop": register names are left to resolve
+    fn parse_name(&mut self) -> Expr {
+        let rest = self.rest();
+        let mut len = 0;
+        loop {
+            let tail = &rest[len..];
+            if tail.starts_with("::") {
+                len += 2;
+            } else if let Some(c) = tail.chars().next()
+                && (c.is_ascii_alphanumeric() || matches!(c, '_' | '@' | '.'))
+            {
+                len += 1;
+            } else {
+                break;
+            }
+        }
+
+        let name = &rest[..len];
+        self.pos += len;
+        Expr::Symbol(String::from(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Reg;
+    use crate::debugger::Expr;
+    use anyhow::Result;
+    use rstest::rstest;
+
+    fn eval(s: &str) -> Result<i32> {
+        let reg = Reg {
+            a: 0x41,
+            x: 0x02,
+            pc: 0x0e09,
+            ..Reg::default()
+        };
+        let lookup = |name: &str| match name {
+            "buffer" => Some(0x0200),
+            "print::loop" => Some(0x0e10),
+            _ => None,
+        };
+        let load = |addr: u16| match addr {
+            0x0200 => 0x34,
+            0x0201 => 0x12,
+            0x0202 => 0x56,
+            _ => 0x00,
+        };
+        s.parse::<Expr>()?.resolve(&lookup)?.eval(&reg, &load)
+    }
+
+    #[rstest]
+    #[case(0x0e00, "$e00")]
+    #[case(5, "%101")]
+    #[case(100, "100")]
+    #[case(0x0220, "buffer+$20")]
+    #[case(0x0e10, "print::loop")]
+    #[case(14, "2+3*4")]
+    #[case(20, "(2+3)*4")]
+    #[case(-1, "-1")]
+    #[case(0x34, "<$1234")]
+    #[case(0x12, ">$1234")]
+    #[case(0x34, "[buffer]")]
+    #[case(0x1234, "{buffer}")]
+    #[case(0x56, "[buffer + X]")]
+    #[case(1, "A=$41")]
+    #[case(1, "a == 65")]
+    #[case(0, "pc<>$0e09")]
+    #[case(1, "X<3")]
+    #[case(0, ">buffer >= 3")]
+    #[case(1, "A=$41 && X=2 || C")]
+    #[case(0x03, "$0f & 3")]
+    #[case(0x0f, "$0c | 3")]
+    fn basics(#[case] expected_result: i32, #[case] input: &str) -> Result<()> {
+        assert_eq!(expected_result, eval(input)?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("$")]
+    #[case("1+")]
+    #[case("(1")]
+    #[case("[1")]
+    #[case("1 2")]
+    #[case("unknown")]
+    #[case("1/0")]
+    #[case("[-1]")]
+    fn invalid(#[case] input: &str) {
+        assert!(eval(input).is_err());
+    }
+
+    #[rstest]
+    #[case(("$0E00", "$0EFF"), "$e00:$eff", 10)]
+    #[case(("print::loop", "print::loop + $10"), "print::loop:print::loop+16", 10)]
+    #[case(("buffer", "buffer"), "buffer", 10)]
+    #[case(("$0E00", "$0EFF"), "0e00:0eff", 16)]
+    #[case(("$0200", "$02FF"), "200:2ff", 16)]
+    #[case(("buffer", "buffer + $10"), "buffer:buffer+10", 16)]
+    #[case(("e00", "eff"), "e00:eff", 16)]
+    fn parse_range(
+        #[case] expected_result: (&str, &str),
+        #[case] input: &str,
+        #[case] radix: u32,
+    ) -> Result<()> {
+        let (start, end) = Expr::parse_range(input, radix)?;
+        assert_eq!(
+            expected_result,
+            (start.to_string().as_str(), end.to_string().as_str())
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("(a = $41) && ([$0200 + x] <> $00)", "a=$41&&[$200+x]!=0")]
+    #[case("<(buffer + $20)", "<(buffer+$20)")]
+    #[case("{$FFFC}", "{$fffc}")]
+    fn display(#[case] expected_result: &str, #[case] input: &str) -> Result<()> {
+        assert_eq!(expected_result, input.parse::<Expr>()?.to_string());
+        Ok(())
+    }
+
+    // Symbols take precedence over registers with the same name and both over hex numbers
+    #[rstest]
+    #[case("A = $0100", "A=a")]
+    #[case("$0100 + X", "pc+x")]
+    #[case("A + Y", "A+y")]
+    #[case("$0E00 + $0EFF", "e00+eff")]
+    #[case("$0100 + $DEAD", "add+dead")]
+    #[case("C", "c")]
+    fn resolve(#[case] expected_result: &str, #[case] input: &str) -> Result<()> {
+        let lookup = |name: &str| match name {
+            "a" | "pc" | "add" => Some(0x0100),
+            _ => None,
+        };
+        assert_eq!(
+            expected_result,
+            input.parse::<Expr>()?.resolve(&lookup)?.to_string()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameKind {
+    Jsr,
+    Brk,
+    Irq,
+    Nmi,
+}
+
+impl Display for FrameKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Jsr => "JSR",
+                Self::Brk => "BRK",
+                Self::Irq => "IRQ",
+                Self::Nmi => "NMI",
+            }
+        )
+    }
+}
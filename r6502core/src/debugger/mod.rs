@@ -0,0 +1,29 @@
+mod binary_op;
+mod call_frame;
+mod call_stack;
+mod expr;
+mod frame_kind;
+mod register;
+mod stack_issue;
+mod unary_op;
+mod watch_expr;
+mod watch_format;
+mod watch_hit;
+mod watch_kind;
+mod watchpoint;
+mod watchpoints;
+
+pub use binary_op::*;
+pub use call_frame::*;
+pub use call_stack::*;
+pub use expr::*;
+pub use frame_kind::*;
+pub use register::*;
+pub use stack_issue::*;
+pub use unary_op::*;
+pub use watch_expr::*;
+pub use watch_format::*;
+pub use watch_hit::*;
+pub use watch_kind::*;
+pub use watchpoint::*;
+pub use watchpoints::*;
@@ -0,0 +1,83 @@
+use crate::{P, Reg};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// Register or status flag referenced by name in an expression e.g. "A", "PC" or "C"
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Register {
+    A,
+    X,
+    Y,
+    Sp,
+    Pc,
+    P,
+    N,
+    V,
+    B,
+    D,
+    I,
+    Z,
+    C,
+}
+
+impl Register {
+    #[must_use]
+    pub fn from_name(s: &str) -> Option<Self> {
+        Some(match s.to_ascii_uppercase().as_str() {
+            "A" => Self::A,
+            "X" => Self::X,
+            "Y" => Self::Y,
+            "SP" => Self::Sp,
+            "PC" => Self::Pc,
+            "P" => Self::P,
+            "N" => Self::N,
+            "V" => Self::V,
+            "B" => Self::B,
+            "D" => Self::D,
+            "I" => Self::I,
+            "Z" => Self::Z,
+            "C" => Self::C,
+            _ => return None,
+        })
+    }
+
+    #[must_use]
+    pub fn value(self, reg: &Reg) -> i32 {
+        let flag = |flag| i32::from(reg.p.contains(flag));
+        match self {
+            Self::A => i32::from(reg.a),
+            Self::X => i32::from(reg.x),
+            Self::Y => i32::from(reg.y),
+            Self::Sp => i32::from(reg.sp),
+            Self::Pc => i32::from(reg.pc),
+            Self::P => i32::from(reg.p.bits()),
+            Self::N => flag(P::N),
+            Self::V => flag(P::V),
+            Self::B => flag(P::B),
+            Self::D => flag(P::D),
+            Self::I => flag(P::I),
+            Self::Z => flag(P::Z),
+            Self::C => flag(P::C),
+        }
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = match self {
+            Self::A => "A",
+            Self::X => "X",
+            Self::Y => "Y",
+            Self::Sp => "SP",
+            Self::Pc => "PC",
+            Self::P => "P",
+            Self::N => "N",
+            Self::V => "V",
+            Self::B => "B",
+            Self::D => "D",
+            Self::I => "I",
+            Self::Z => "Z",
+            Self::C => "C",
+        };
+        write!(f, "{s}")
+    }
+}
@@ -0,0 +1,18 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StackIssue {
+    pub pc: u16,
+    pub message: String,
+}
+
+impl Display for StackIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "${pc:04X}: {message}",
+            pc = self.pc,
+            message = self.message
+        )
+    }
+}
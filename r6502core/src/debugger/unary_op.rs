@@ -0,0 +1,35 @@
+use anyhow::{Result, anyhow};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    Lo,
+    Hi,
+}
+
+impl UnaryOp {
+    pub fn apply(self, value: i32) -> Result<i32> {
+        Ok(match self {
+            Self::Neg => value
+                .checked_neg()
+                .ok_or_else(|| anyhow!("overflow in expression"))?,
+            Self::Not => !value,
+            Self::Lo => value & 0xff,
+            Self::Hi => (value >> 8) & 0xff,
+        })
+    }
+}
+
+impl Display for UnaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = match self {
+            Self::Neg => "-",
+            Self::Not => "~",
+            Self::Lo => "<",
+            Self::Hi => ">",
+        };
+        write!(f, "{s}")
+    }
+}
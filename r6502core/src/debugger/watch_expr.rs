@@ -0,0 +1,97 @@
+use crate::Reg;
+use crate::debugger::{Expr, WatchFormat};
+use anyhow::{Error, Result};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+// Expression shown in the watch pane e.g. "{$12}" or "buffer string"
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchExpr {
+    pub expr: Expr,
+    pub format: WatchFormat,
+}
+
+impl WatchExpr {
+    // Evaluate against registers and memory: symbols must already have been resolved
+    pub fn eval(&self, reg: &Reg, load: &impl Fn(u16) -> u8) -> Result<String> {
+        self.format.format(self.expr.eval(reg, load)?, load)
+    }
+}
+
+impl Display for WatchExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.format == WatchFormat::Value {
+            write!(f, "{}", self.expr)
+        } else {
+            write!(f, "{} {}", self.expr, self.format)
+        }
+    }
+}
+
+// Format, if any, follows the expression
+impl FromStr for WatchExpr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((expr, format)) = s.rsplit_once(char::is_whitespace)
+            && let Ok(format) = format.parse()
+        {
+            return Ok(Self {
+                expr: expr.parse()?,
+                format,
+            });
+        }
+
+        Ok(Self {
+            expr: s.parse()?,
+            format: WatchFormat::Value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Reg;
+    use crate::debugger::{BinaryOp, Expr, WatchExpr, WatchFormat};
+    use anyhow::Result;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Expr::Word(Box::new(Expr::Number(0x12))), WatchFormat::Value, "{$12}")]
+    #[case(
+        Expr::Symbol(String::from("buffer")),
+        WatchFormat::CrString,
+        "buffer crstring"
+    )]
+    #[case(
+        Expr::Binary(
+            BinaryOp::Add,
+            Box::new(Expr::Symbol(String::from("buffer"))),
+            Box::new(Expr::Number(1))
+        ),
+        WatchFormat::Byte,
+        " buffer + 1  byte"
+    )]
+    fn parse(
+        #[case] expected_expr: Expr,
+        #[case] expected_format: WatchFormat,
+        #[case] input: &str,
+    ) -> Result<()> {
+        let watch_expr = input.parse::<WatchExpr>()?;
+        assert_eq!(expected_expr, watch_expr.expr);
+        assert_eq!(expected_format, watch_expr.format);
+        assert_eq!(watch_expr, watch_expr.to_string().parse()?);
+        Ok(())
+    }
+
+    #[test]
+    fn eval() -> Result<()> {
+        let watch_expr = "[$18] * $100".parse::<WatchExpr>()?;
+        assert_eq!(
+            "$0E00 (3584)",
+            watch_expr.eval(&Reg::default(), &|addr| if addr == 0x18 { 0x0e } else { 0 })?
+        );
+        Ok(())
+    }
+}
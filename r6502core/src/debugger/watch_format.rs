@@ -0,0 +1,190 @@
+use anyhow::{Error, Result, bail};
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+use std::str::FromStr;
+
+const MAX_STRING_LEN: u16 = 64;
+
+// How a watch expression is shown: either its own value or the data at the address it evaluates to
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WatchFormat {
+    #[default]
+    Value,
+    Byte,
+    Word,
+    Signed,
+    Bcd,
+    // Zero-terminated string
+    String,
+    // CR-terminated string as used by Acorn MOS and BBC BASIC
+    CrString,
+    // BBC BASIC 5-byte floating-point number
+    Float,
+}
+
+impl WatchFormat {
+    pub fn format(self, value: i32, load: &impl Fn(u16) -> u8) -> Result<String> {
+        if self == Self::Value {
+            return Ok(format!("${value:04X} ({value})"));
+        }
+
+        let Ok(addr) = u16::try_from(value) else {
+            bail!("address {value} out of range")
+        };
+        let byte = |offset: u16| load(addr.wrapping_add(offset));
+        Ok(match self {
+            Self::Value => unreachable!(),
+            Self::Byte => format!("${:02X} ({})", byte(0), byte(0)),
+            Self::Word => {
+                let word = u16::from_le_bytes([byte(0), byte(1)]);
+                format!("${word:04X} ({word})")
+            }
+            Self::Signed => format!("{}", byte(0).cast_signed()),
+            Self::Bcd => {
+                let value = byte(0);
+                if value >> 4 > 9 || value & 0x0f > 9 {
+                    bail!("${value:02X} is not BCD")
+                }
+                format!("{value:02X}")
+            }
+            Self::String => Self::format_string(byte, 0x00),
+            Self::CrString => Self::format_string(byte, 0x0d),
+            Self::Float => {
+                format!(
+                    "{}",
+                    Self::bbc_float([byte(0), byte(1), byte(2), byte(3), byte(4)])
+                )
+            }
+        })
+    }
+
+    // Exponent in excess-128 followed by the mantissa most significant byte first with the sign
+    // replacing the mantissa's implied top bit
+    #[must_use]
+    pub fn bbc_float(bytes: [u8; 5]) -> f64 {
+        let [exponent, m0, m1, m2, m3] = bytes;
+        if exponent == 0 {
+            return 0.0;
+        }
+
+        let mantissa = f64::from(u32::from_be_bytes([m0 | 0x80, m1, m2, m3])) / 2f64.powi(32);
+        let value = mantissa * 2f64.powi(i32::from(exponent) - 128);
+        if m0 & 0x80 == 0 { value } else { -value }
+    }
+
+    fn format_string(byte: impl Fn(u16) -> u8, terminator: u8) -> String {
+        let mut s = String::from("\"");
+        for offset in 0..MAX_STRING_LEN {
+            let value = byte(offset);
+            if value == terminator {
+                s.push('"');
+                return s;
+            }
+            let c = value as char;
+            if c.is_ascii() && !c.is_ascii_control() {
+                s.push(c);
+            } else {
+                _ = write!(s, "\\x{value:02X}");
+            }
+        }
+        s.push_str("...");
+        s
+    }
+}
+
+impl Display for WatchFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Value => "value",
+                Self::Byte => "byte",
+                Self::Word => "word",
+                Self::Signed => "signed",
+                Self::Bcd => "bcd",
+                Self::String => "string",
+                Self::CrString => "crstring",
+                Self::Float => "float",
+            }
+        )
+    }
+}
+
+impl FromStr for WatchFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "value" => Self::Value,
+            "byte" => Self::Byte,
+            "word" => Self::Word,
+            "signed" => Self::Signed,
+            "bcd" => Self::Bcd,
+            "string" => Self::String,
+            "crstring" => Self::CrString,
+            "float" => Self::Float,
+            _ => bail!("invalid watch format {s}"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debugger::WatchFormat;
+    use anyhow::Result;
+    use rstest::rstest;
+
+    const MEMORY: [u8; 16] = [
+        0x81, 0x00, 0x00, 0x00, 0x00, b'H', b'I', 0x0d, 0x00, 0xff, 0x42, 0x4a, 0x82, 0xc0, 0x00,
+        0x00,
+    ];
+
+    fn load(addr: u16) -> u8 {
+        MEMORY.get(addr as usize).copied().unwrap_or_default()
+    }
+
+    #[rstest]
+    #[case("$0E00 (3584)", WatchFormat::Value, 0x0e00)]
+    #[case("$48 (72)", WatchFormat::Byte, 5)]
+    #[case("$4948 (18760)", WatchFormat::Word, 5)]
+    #[case("-1", WatchFormat::Signed, 9)]
+    #[case("42", WatchFormat::Bcd, 10)]
+    #[case("\"HI\"", WatchFormat::CrString, 5)]
+    #[case("\"HI\\x0D\"", WatchFormat::String, 5)]
+    #[case("1", WatchFormat::Float, 0)]
+    #[case("-3", WatchFormat::Float, 12)]
+    fn format(
+        #[case] expected_result: &str,
+        #[case] watch_format: WatchFormat,
+        #[case] value: i32,
+    ) {
+        assert_eq!(expected_result, watch_format.format(value, &load).unwrap());
+    }
+
+    #[rstest]
+    #[case(WatchFormat::Bcd, 11)]
+    #[case(WatchFormat::Byte, -1)]
+    #[case(WatchFormat::Word, 0x10000)]
+    fn format_fails(#[case] watch_format: WatchFormat, #[case] value: i32) {
+        assert!(watch_format.format(value, &load).is_err());
+    }
+
+    #[rstest]
+    #[case(0.0, [0x00, 0x12, 0x34, 0x56, 0x78])]
+    #[case(1.0, [0x81, 0x00, 0x00, 0x00, 0x00])]
+    #[case(0.5, [0x80, 0x00, 0x00, 0x00, 0x00])]
+    #[case(-3.0, [0x82, 0xc0, 0x00, 0x00, 0x00])]
+    #[case(10.0, [0x84, 0x20, 0x00, 0x00, 0x00])]
+    fn bbc_float(#[case] expected_result: f64, #[case] bytes: [u8; 5]) {
+        assert!((expected_result - WatchFormat::bbc_float(bytes)).abs() < f64::EPSILON);
+    }
+
+    #[rstest]
+    #[case(WatchFormat::CrString, "crstring")]
+    #[case(WatchFormat::Float, "float")]
+    fn round_trip(#[case] watch_format: WatchFormat, #[case] s: &str) -> Result<()> {
+        assert_eq!(watch_format, s.parse()?);
+        assert_eq!(s, watch_format.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+use crate::debugger::WatchKind;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WatchHit {
+    // Address actually accessed
+    pub addr: u16,
+    // Kind of the watchpoint that was triggered
+    pub kind: WatchKind,
+}
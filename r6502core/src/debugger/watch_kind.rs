@@ -0,0 +1,30 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    Access,
+}
+
+impl WatchKind {
+    // Whether a watchpoint of this kind is triggered by the given kind of access
+    #[must_use]
+    pub fn matches(self, access: Self) -> bool {
+        self == Self::Access || self == access
+    }
+}
+
+impl Display for WatchKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Read => "read",
+                Self::Write => "write",
+                Self::Access => "access",
+            }
+        )
+    }
+}
@@ -0,0 +1,8 @@
+use crate::debugger::WatchKind;
+use r6502lib::AddressRange;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Watchpoint {
+    pub address_range: AddressRange,
+    pub kind: WatchKind,
+}
@@ -0,0 +1,102 @@
+use crate::debugger::{WatchHit, WatchKind, Watchpoint};
+use crate::emulator::BusMonitor;
+use std::cell::{Cell, RefCell};
+
+// Memory watchpoints checked against every load and store made by the CPU
+#[derive(Default)]
+pub struct Watchpoints {
+    watchpoints: RefCell<Vec<Watchpoint>>,
+    hit: Cell<Option<WatchHit>>,
+}
+
+impl Watchpoints {
+    pub fn add(&self, watchpoint: Watchpoint) {
+        let mut watchpoints = self.watchpoints.borrow_mut();
+        if !watchpoints.contains(&watchpoint) {
+            watchpoints.push(watchpoint);
+        }
+    }
+
+    pub fn remove(&self, watchpoint: &Watchpoint) {
+        self.watchpoints.borrow_mut().retain(|w| w != watchpoint);
+    }
+
+    // First watchpoint triggered since the previous call
+    pub fn take_hit(&self) -> Option<WatchHit> {
+        self.hit.take()
+    }
+
+    fn check(&self, addr: u16, access: WatchKind) {
+        if self.hit.get().is_some() {
+            return;
+        }
+
+        if let Some(watchpoint) = self
+            .watchpoints
+            .borrow()
+            .iter()
+            .find(|w| w.kind.matches(access) && w.address_range.contains(addr))
+        {
+            self.hit.set(Some(WatchHit {
+                addr,
+                kind: watchpoint.kind,
+            }));
+        }
+    }
+}
+
+impl BusMonitor for Watchpoints {
+    fn on_load(&self, addr: u16, _value: u8) {
+        self.check(addr, WatchKind::Read);
+    }
+
+    fn on_store(&self, addr: u16, _value: u8) {
+        self.check(addr, WatchKind::Write);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InterruptChannel;
+    use crate::debugger::{WatchHit, WatchKind, Watchpoint, Watchpoints};
+    use crate::emulator::{Bus, BusView, Cpu};
+    use anyhow::Result;
+    use r6502lib::AddressRange;
+
+    #[test]
+    fn basics() -> Result<()> {
+        let bus = Bus::default();
+        bus.store(0x0e00, 0xad); // LDA $0200
+        bus.store(0x0e01, 0x00);
+        bus.store(0x0e02, 0x02);
+        bus.store(0x0e03, 0x8d); // STA $0201
+        bus.store(0x0e04, 0x01);
+        bus.store(0x0e05, 0x02);
+        let watchpoints = Watchpoints::default();
+        watchpoints.add(Watchpoint {
+            address_range: AddressRange::new(0x0201, 0x0201)?,
+            kind: WatchKind::Write,
+        });
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(
+            BusView::with_monitor(&bus, &watchpoints),
+            None,
+            interrupt_channel.rx,
+        );
+        cpu.reg.pc = 0x0e00;
+
+        cpu.step_no_spin();
+        assert_eq!(None, watchpoints.take_hit());
+
+        cpu.step_no_spin();
+        assert_eq!(
+            Some(WatchHit {
+                addr: 0x0201,
+                kind: WatchKind::Write
+            }),
+            watchpoints.take_hit()
+        );
+        assert_eq!(None, watchpoints.take_hit());
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use crate::DeviceState;
+use r6502lib::AddressRange;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// Device as mapped onto the bus along with its state at the time it was inspected
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceInfo {
+    pub address_range: AddressRange,
+    pub offset: u16,
+    pub state: DeviceState,
+}
+
+impl Display for DeviceInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {}", self.address_range, self.state.kind)?;
+        if self.offset != 0 {
+            write!(f, " offset ${:04X}", self.offset)?;
+        }
+        if !self.state.description.is_empty() {
+            write!(f, " ({})", self.state.description)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+// What the debugger shows for a bus device: its type, named registers and anything else of interest
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviceState {
+    pub kind: String,
+    pub registers: Vec<(String, u8)>,
+    pub description: String,
+}
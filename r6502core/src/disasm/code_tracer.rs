@@ -0,0 +1,183 @@
+use crate::disasm::DisassembledItem;
+use crate::emulator::{AddressingMode, InstructionInfo, MOS_6502};
+use crate::{Opcode, Operand};
+use r6502lib::AddressRange;
+use r6502lib::constants::{IRQ, NMI, RESET};
+use r6502lib::util::make_word;
+use std::collections::BTreeSet;
+
+// Separates code from data by following the flow of control from the entry points: anything
+// not reached is data
+pub struct CodeTracer<'a> {
+    base: u16,
+    bytes: &'a [u8],
+    data: &'a [AddressRange],
+    instructions: Vec<Option<InstructionInfo>>,
+    claimed: Vec<bool>,
+    targets: BTreeSet<u16>,
+}
+
+impl<'a> CodeTracer<'a> {
+    #[must_use]
+    pub fn new(base: u16, bytes: &'a [u8], data: &'a [AddressRange]) -> Self {
+        Self {
+            base,
+            bytes,
+            data,
+            instructions: vec![None; bytes.len()],
+            claimed: vec![false; bytes.len()],
+            targets: BTreeSet::new(),
+        }
+    }
+
+    // Vectors only count when the bytes include them
+    #[must_use]
+    pub fn vectors(&self) -> Vec<u16> {
+        [NMI, RESET, IRQ]
+            .iter()
+            .filter_map(|vector| {
+                let offset = self.offset(*vector)?;
+                let hi = *self.bytes.get(offset + 1)?;
+                Some(make_word(hi, self.bytes[offset]))
+            })
+            .collect()
+    }
+
+    pub fn trace(&mut self, entry_points: &[u16]) {
+        let mut pending = entry_points.to_vec();
+        self.targets.extend(entry_points);
+        while let Some(addr) = pending.pop() {
+            let mut addr = addr;
+            while let Some(instruction_info) = self.claim(addr) {
+                let next = addr.wrapping_add(instruction_info.size());
+                let target = match (instruction_info.opcode, &instruction_info.operand) {
+                    (Opcode::JmpAbs | Opcode::Jsr, Operand::Word(target)) => Some(*target),
+                    (opcode, Operand::Byte(offset)) if Self::is_branch(opcode) => {
+                        Some(next.wrapping_add_signed(i16::from(offset.cast_signed())))
+                    }
+                    _ => None,
+                };
+                if let Some(target) = target {
+                    _ = self.targets.insert(target);
+                    pending.push(target);
+                }
+                if matches!(
+                    instruction_info.opcode,
+                    Opcode::JmpAbs | Opcode::JmpInd | Opcode::Rts | Opcode::Rti | Opcode::Brk
+                ) {
+                    break;
+                }
+                addr = next;
+            }
+        }
+    }
+
+    // Addresses jumped to, called, branched to or entered at that are within the bytes
+    #[must_use]
+    pub fn targets(&self) -> Vec<u16> {
+        self.targets
+            .iter()
+            .copied()
+            .filter(|addr| self.offset(*addr).is_some())
+            .collect()
+    }
+
+    #[must_use]
+    pub fn items(&self) -> Vec<DisassembledItem> {
+        let mut items = Vec::new();
+        let mut offset = 0;
+        let mut addr = self.base;
+        while offset < self.bytes.len() {
+            let item = match &self.instructions[offset] {
+                Some(instruction_info) => DisassembledItem::Instruction(instruction_info.clone()),
+                None => DisassembledItem::Byte {
+                    addr,
+                    value: self.bytes[offset],
+                },
+            };
+            offset += usize::from(item.size());
+            addr = addr.wrapping_add(item.size());
+            items.push(item);
+        }
+        items
+    }
+
+    // Instruction at address unless it's already traced, marked as data or overlaps another
+    fn claim(&mut self, addr: u16) -> Option<InstructionInfo> {
+        let offset = self.offset(addr)?;
+        let instruction_info = InstructionInfo::decode(addr, &self.bytes[offset..])?;
+        let size = instruction_info.size();
+        let end = offset + usize::from(size);
+        if self.claimed[offset..end].iter().any(|claimed| *claimed)
+            || (0..size).any(|i| self.is_data(addr.wrapping_add(i)))
+        {
+            return None;
+        }
+
+        self.claimed[offset..end].fill(true);
+        self.instructions[offset] = Some(instruction_info.clone());
+        Some(instruction_info)
+    }
+
+    fn offset(&self, addr: u16) -> Option<usize> {
+        let offset = usize::from(addr.wrapping_sub(self.base));
+        (offset < self.bytes.len()).then_some(offset)
+    }
+
+    fn is_data(&self, addr: u16) -> bool {
+        self.data.iter().any(|r| r.contains(addr))
+    }
+
+    fn is_branch(opcode: Opcode) -> bool {
+        MOS_6502
+            .get_op_info(&opcode)
+            .is_some_and(|op_info| matches!(op_info.addressing_mode(), AddressingMode::Relative))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disasm::{CodeTracer, DisassembledItem};
+    use anyhow::Result;
+    use r6502lib::AddressRange;
+
+    // $0E00: JSR $0E0A, BEQ $0E0A, JMP $0E0B, table $01 $02, $0E0A: RTS, $0E0B: BRK, $FF
+    const BYTES: [u8; 13] = [
+        0x20, 0x0a, 0x0e, 0xf0, 0x05, 0x4c, 0x0b, 0x0e, 0x01, 0x02, 0x60, 0x00, 0xff,
+    ];
+
+    fn kinds(items: &[DisassembledItem]) -> String {
+        items
+            .iter()
+            .map(|item| match item {
+                DisassembledItem::Instruction(_) => 'I',
+                DisassembledItem::Byte { .. } => 'B',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn trace() {
+        let mut tracer = CodeTracer::new(0x0e00, &BYTES, &[]);
+        tracer.trace(&[0x0e00]);
+        assert_eq!("IIIBBIIB", kinds(&tracer.items()));
+        assert_eq!(vec![0x0e00, 0x0e0a, 0x0e0b], tracer.targets());
+    }
+
+    #[test]
+    fn trace_data() -> Result<()> {
+        let data = [AddressRange::new(0x0e0a, 0x0e0a)?];
+        let mut tracer = CodeTracer::new(0x0e00, &BYTES, &data);
+        tracer.trace(&[0x0e00]);
+        assert_eq!("IIIBBBIB", kinds(&tracer.items()));
+        Ok(())
+    }
+
+    #[test]
+    fn vectors() {
+        let mut bytes = vec![0xea; 6];
+        bytes.extend([0x00, 0x80, 0x10, 0x80, 0x20, 0x80]);
+        let tracer = CodeTracer::new(0xfff4, &bytes, &[]);
+        assert_eq!(vec![0x8000, 0x8010, 0x8020], tracer.vectors());
+    }
+}
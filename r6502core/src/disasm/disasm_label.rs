@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+// Name given to an address in a disassembly project
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisasmLabel {
+    #[serde(rename = "address", with = "r6502config::hex_word")]
+    pub addr: u16,
+    pub name: String,
+}
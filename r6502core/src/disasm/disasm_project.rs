@@ -0,0 +1,152 @@
+use crate::disasm::DisasmLabel;
+use anyhow::{Result, anyhow};
+use r6502lib::AddressRange;
+use serde::{Deserialize, Serialize};
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+
+// Hints for disassembling an image, kept next to it so they accumulate between runs
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct DisasmProject {
+    #[serde(with = "r6502config::hex_word::option", default)]
+    pub load: Option<u16>,
+
+    #[serde(rename = "entryPoints", with = "r6502config::hex_word::vec", default)]
+    pub entry_points: Vec<u16>,
+
+    #[serde(with = "r6502config::as_string::vec", default)]
+    pub data: Vec<AddressRange>,
+
+    #[serde(default)]
+    pub labels: Vec<DisasmLabel>,
+}
+
+impl DisasmProject {
+    // e.g. "bbc-basic-2.disasm.json" for "bbc-basic-2.rom"
+    pub fn path(image_path: &Path) -> Result<PathBuf> {
+        let mut file_name = image_path
+            .file_stem()
+            .ok_or_else(|| anyhow!("could not get file stem"))?
+            .to_os_string();
+        file_name.push(".disasm.json");
+        Ok(image_path
+            .parent()
+            .ok_or_else(|| anyhow!("could not get parent of path"))?
+            .join(file_name))
+    }
+
+    // Missing project file is fine: there are no hints yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        Ok(serde_json::from_str(&read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // Hints given again replace earlier ones for the same address
+    pub fn merge(&mut self, other: Self) {
+        if other.load.is_some() {
+            self.load = other.load;
+        }
+        for addr in other.entry_points {
+            if !self.entry_points.contains(&addr) {
+                self.entry_points.push(addr);
+            }
+        }
+        for address_range in other.data {
+            if !self.data.contains(&address_range) {
+                self.data.push(address_range);
+            }
+        }
+        for label in other.labels {
+            self.labels.retain(|l| l.addr != label.addr);
+            self.labels.push(label);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disasm::{DisasmLabel, DisasmProject};
+    use anyhow::Result;
+    use r6502lib::AddressRange;
+    use serde_json::{Value, json};
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, read_to_string, remove_dir_all};
+    use std::path::Path;
+    use std::process;
+
+    fn label(addr: u16, name: &str) -> DisasmLabel {
+        DisasmLabel {
+            addr,
+            name: String::from(name),
+        }
+    }
+
+    #[test]
+    fn path() -> Result<()> {
+        assert_eq!(
+            Path::new("/roms/bbc-basic-2.disasm.json"),
+            DisasmProject::path(Path::new("/roms/bbc-basic-2.rom"))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let project = DisasmProject {
+            load: Some(0x8000),
+            entry_points: vec![0x8000, 0x8023],
+            data: vec![AddressRange::new(0x8071, 0x80ff)?],
+            labels: vec![label(0x8000, "language")],
+        };
+
+        let dir = temp_dir().join(format!("r6502-disasm-project-{}", process::id()));
+        create_dir_all(&dir)?;
+        let path = DisasmProject::path(&dir.join("basic.rom"))?;
+        assert_eq!(DisasmProject::default(), DisasmProject::load(&path)?);
+        project.save(&path)?;
+        let json = read_to_string(&path);
+        let result = DisasmProject::load(&path);
+        remove_dir_all(&dir)?;
+
+        assert_eq!(
+            json!({
+                "load": "$8000",
+                "entryPoints": ["$8000", "$8023"],
+                "data": ["$8071:$80FF"],
+                "labels": [{ "address": "$8000", "name": "language" }],
+            }),
+            serde_json::from_str::<Value>(&json?)?
+        );
+        assert_eq!(project, result?);
+        Ok(())
+    }
+
+    #[test]
+    fn merge() -> Result<()> {
+        let mut project = DisasmProject {
+            load: Some(0x8000),
+            entry_points: vec![0x8000],
+            data: Vec::new(),
+            labels: vec![label(0x8000, "start")],
+        };
+        project.merge(DisasmProject {
+            load: None,
+            entry_points: vec![0x8000, 0x8023],
+            data: vec![AddressRange::new(0x8071, 0x80ff)?],
+            labels: vec![label(0x8000, "language")],
+        });
+        assert_eq!(Some(0x8000), project.load);
+        assert_eq!(vec![0x8000, 0x8023], project.entry_points);
+        assert_eq!(1, project.data.len());
+        assert_eq!(vec![label(0x8000, "language")], project.labels);
+        Ok(())
+    }
+}
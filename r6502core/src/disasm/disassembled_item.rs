@@ -0,0 +1,26 @@
+use crate::emulator::InstructionInfo;
+
+#[derive(Clone, Debug)]
+pub enum DisassembledItem {
+    Instruction(InstructionInfo),
+    // Byte that doesn't start a complete, valid instruction
+    Byte { addr: u16, value: u8 },
+}
+
+impl DisassembledItem {
+    #[must_use]
+    pub const fn addr(&self) -> u16 {
+        match self {
+            Self::Instruction(instruction_info) => instruction_info.pc,
+            Self::Byte { addr, .. } => *addr,
+        }
+    }
+
+    #[must_use]
+    pub const fn size(&self) -> u16 {
+        match self {
+            Self::Instruction(instruction_info) => instruction_info.size(),
+            Self::Byte { .. } => 1,
+        }
+    }
+}
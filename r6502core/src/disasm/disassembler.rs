@@ -0,0 +1,516 @@
+use crate::Operand;
+use crate::disasm::{CodeTracer, DisassembledItem, Syntax};
+use crate::emulator::{AddressingMode, InstructionInfo, MOS_6502};
+use crate::symbols::{AddressSize, Export, ExportKind, MapFile};
+use anyhow::Result;
+use r6502lib::AddressRange;
+use r6502snapshot::MemoryImage;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+const CA65_INDENT: &str = "        ";
+const CA65_BYTES_PER_LINE: usize = 8;
+
+// Disassembles memory without running it, labelling addresses from the map file
+pub struct Disassembler<'a> {
+    map_file: &'a MapFile,
+    syntax: Syntax,
+}
+
+impl<'a> Disassembler<'a> {
+    #[must_use]
+    pub const fn new(map_file: &'a MapFile, syntax: Syntax) -> Self {
+        Self { map_file, syntax }
+    }
+
+    // Bytes that don't start a complete instruction, or would run over a label, become data
+    #[must_use]
+    pub fn decode(&self, base: u16, bytes: &[u8]) -> Vec<DisassembledItem> {
+        let mut items = Vec::new();
+        let mut addr = base;
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let item = match InstructionInfo::decode(addr, &bytes[offset..])
+                .map(DisassembledItem::Instruction)
+            {
+                Some(item) if !self.overlaps_label(&item) => item,
+                _ => DisassembledItem::Byte {
+                    addr,
+                    value: bytes[offset],
+                },
+            };
+            offset += usize::from(item.size());
+            addr = addr.wrapping_add(item.size());
+            items.push(item);
+        }
+        items
+    }
+
+    pub fn disassemble(&self, base: u16, bytes: &[u8]) -> Result<String> {
+        self.format(base, &self.decode(base, bytes))
+    }
+
+    // Only what's reachable from the entry points or the vectors is code, with every target
+    // labelled
+    pub fn disassemble_traced(
+        &self,
+        base: u16,
+        bytes: &[u8],
+        entry_points: &[u16],
+        data: &[AddressRange],
+    ) -> Result<String> {
+        let mut tracer = CodeTracer::new(base, bytes, data);
+        let mut entry_points = entry_points.to_vec();
+        entry_points.extend(tracer.vectors());
+        tracer.trace(&entry_points);
+        let items = tracer.items();
+
+        let starts = items
+            .iter()
+            .map(DisassembledItem::addr)
+            .collect::<HashSet<_>>();
+        let mut map_file = self.map_file.clone();
+        for addr in tracer.targets() {
+            if starts.contains(&addr) && self.labels_at(addr).next().is_none() {
+                map_file.exports.push(Export {
+                    name: format!("L{addr:04X}"),
+                    value: u32::from(addr),
+                    referenced: true,
+                    kind: ExportKind::Label,
+                    address_size: AddressSize::Absolute,
+                });
+            }
+        }
+        Disassembler::new(&map_file, self.syntax).format(base, &items)
+    }
+
+    pub fn format(&self, base: u16, items: &[DisassembledItem]) -> Result<String> {
+        match self.syntax {
+            Syntax::Listing => self.format_listing(items),
+            Syntax::Ca65 => {
+                // Names ca65 can't define such as scoped "print::loop" or cheap local
+                // "start@done" from debug info are left out so that addresses are used instead
+                let mut map_file = self.map_file.clone();
+                map_file.exports.retain(|e| is_ca65_identifier(&e.name));
+                Disassembler::new(&map_file, self.syntax).format_ca65(base, items)
+            }
+        }
+    }
+
+    // Whole image unless limited to an address range
+    pub fn disassemble_image(
+        &self,
+        image: &MemoryImage,
+        load: u16,
+        address_range: Option<&AddressRange>,
+    ) -> Result<String> {
+        let (base, bytes) = Self::image_bytes(image, load, address_range);
+        self.disassemble(base, bytes)
+    }
+
+    // Part of the image within the address range along with its address
+    #[must_use]
+    pub fn image_bytes<'b>(
+        image: &'b MemoryImage,
+        load: u16,
+        address_range: Option<&AddressRange>,
+    ) -> (u16, &'b [u8]) {
+        let bytes = image.bytes();
+        let Some(address_range) = address_range else {
+            return (load, bytes);
+        };
+
+        let load = usize::from(load);
+        let start = usize::from(address_range.start()).max(load);
+        let end = (usize::from(address_range.end()) + 1).min(load + bytes.len());
+        if start >= end {
+            return (address_range.start(), &[]);
+        }
+        (
+            u16::try_from(start).expect("Must succeed"),
+            &bytes[start - load..end - load],
+        )
+    }
+
+    fn overlaps_label(&self, item: &DisassembledItem) -> bool {
+        let start = u32::from(item.addr());
+        let end = start + u32::from(item.size());
+        self.map_file
+            .exports
+            .iter()
+            .any(|e| e.kind == ExportKind::Label && e.value > start && e.value < end)
+    }
+
+    fn labels_at(&self, addr: u16) -> impl Iterator<Item = &Export> {
+        self.map_file
+            .exports
+            .iter()
+            .filter(move |e| e.kind == ExportKind::Label && e.value == u32::from(addr))
+    }
+
+    fn format_listing(&self, items: &[DisassembledItem]) -> Result<String> {
+        let mut s = String::new();
+        for item in items {
+            for label in self.labels_at(item.addr()) {
+                _ = writeln!(s, "{}:", label.name);
+            }
+            match item {
+                DisassembledItem::Instruction(instruction_info) => {
+                    _ = writeln!(s, "{}", instruction_info.disassembly(self.map_file)?);
+                }
+                DisassembledItem::Byte { addr, value } => {
+                    _ = writeln!(s, "{addr:04X}  {value:02X}        .byte ${value:02X}");
+                }
+            }
+        }
+        Ok(s)
+    }
+
+    // Symbols not defined by a label within the disassembly become equates so that the output
+    // assembles on its own
+    fn format_ca65(&self, base: u16, items: &[DisassembledItem]) -> Result<String> {
+        let mut s = String::new();
+        _ = writeln!(s, "{CA65_INDENT}.setcpu \"6502\"");
+
+        let mut names = Vec::new();
+        for item in items {
+            names.extend(self.labels_at(item.addr()).map(|e| e.name.as_str()));
+        }
+        let mut equates = self
+            .map_file
+            .exports
+            .iter()
+            .filter(|e| !names.contains(&e.name.as_str()))
+            .collect::<Vec<_>>();
+        equates.sort_by_key(|e| (e.value, e.name.as_str()));
+        equates.dedup_by_key(|e| e.name.as_str());
+        for e in equates {
+            if e.value < 0x100 {
+                _ = writeln!(s, "{} = ${:02X}", e.name, e.value);
+            } else {
+                _ = writeln!(s, "{} = ${:04X}", e.name, e.value);
+            }
+        }
+
+        _ = writeln!(s, "{CA65_INDENT}.org ${base:04X}");
+        let mut data_len = 0;
+        for item in items {
+            let labels = self.labels_at(item.addr()).collect::<Vec<_>>();
+            let is_instruction = matches!(item, DisassembledItem::Instruction(_));
+            // Runs of data share a line until the next label or instruction
+            if data_len > 0
+                && (!labels.is_empty() || is_instruction || data_len == CA65_BYTES_PER_LINE)
+            {
+                s.push('\n');
+                data_len = 0;
+            }
+            for label in labels {
+                _ = writeln!(s, "{}:", label.name);
+            }
+            match item {
+                DisassembledItem::Instruction(instruction_info) => {
+                    let instruction = self.format_ca65_instruction(instruction_info)?;
+                    _ = writeln!(s, "{CA65_INDENT}{instruction}");
+                }
+                DisassembledItem::Byte { value, .. } => {
+                    if data_len == 0 {
+                        _ = write!(s, "{CA65_INDENT}.byte ${value:02X}");
+                    } else {
+                        _ = write!(s, ",${value:02X}");
+                    }
+                    data_len += 1;
+                }
+            }
+        }
+        if data_len > 0 {
+            s.push('\n');
+        }
+        Ok(s)
+    }
+
+    // ca65 picks zero-page addressing for operands below $100 unless told otherwise with "a:"
+    fn format_ca65_instruction(&self, instruction_info: &InstructionInfo) -> Result<String> {
+        let s = instruction_info.display(self.map_file)?;
+        let is_absolute = MOS_6502
+            .get_op_info(&instruction_info.opcode)
+            .is_some_and(|op_info| {
+                matches!(
+                    op_info.addressing_mode(),
+                    AddressingMode::Absolute
+                        | AddressingMode::AbsoluteX
+                        | AddressingMode::AbsoluteY
+                )
+            });
+        Ok(match instruction_info.operand {
+            Operand::Word(value) if is_absolute && value < 0x100 => s.replacen(' ', " a:", 1),
+            _ => s,
+        })
+    }
+}
+
+// Letters, digits and underscores not starting with a digit and not a register name
+fn is_ca65_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !["A", "X", "Y"].iter().any(|r| r.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asm::AsmLine;
+    use crate::debugger::Expr;
+    use crate::disasm::{Disassembler, Syntax};
+    use crate::emulator::{AddressingMode, MOS_6502, OpInfo};
+    use crate::symbols::{AddressSize, Export, ExportKind, MapFile};
+    use crate::{Opcode, Reg};
+    use anyhow::{Result, anyhow};
+    use rstest::rstest;
+    use std::collections::HashMap;
+
+    // LDA #$41, JSR OSWRCH, JMP main, $FF (invalid opcode), LDA $0012 (absolute), JSR split by
+    // a label and a truncated INC
+    const BYTES: [u8; 15] = [
+        0xa9, 0x41, 0x20, 0xee, 0xff, 0x4c, 0x00, 0x0e, 0xff, 0xad, 0x12, 0x00, 0x20, 0xee, 0xff,
+    ];
+
+    fn map_file() -> MapFile {
+        let export = |name: &str, value, kind| Export {
+            name: String::from(name),
+            value,
+            referenced: true,
+            kind,
+            address_size: AddressSize::Absolute,
+        };
+        MapFile {
+            exports: vec![
+                export("main", 0x0e00, ExportKind::Label),
+                export("OSWRCH", 0xffee, ExportKind::Label),
+                export("data", 0x0e0d, ExportKind::Label),
+                export("ptr", 0x0012, ExportKind::Constant),
+            ],
+            ..Default::default()
+        }
+    }
+
+    // Assembles ca65 output with the line assembler: the first pass only finds the addresses of
+    // labels so that forward references are taken to be to the current address
+    fn reassemble(s: &str) -> Result<Vec<u8>> {
+        let mut symbols = HashMap::new();
+        let mut bytes = Vec::new();
+        for pass in 0..2 {
+            bytes.clear();
+            let mut pc = 0;
+            for line in s.lines().map(str::trim) {
+                let eval = |expr: &Expr, pc: u16| {
+                    expr.resolve(&|name| {
+                        symbols
+                            .get(name)
+                            .copied()
+                            .or_else(|| (pass == 0).then_some(pc))
+                    })?
+                    .eval(&Reg::default(), &|_| 0x00)
+                };
+                if line.starts_with(".setcpu") {
+                    continue;
+                }
+                if let Some(name) = line.strip_suffix(':') {
+                    _ = symbols.insert(String::from(name), pc);
+                } else if let Some((name, value)) = line.split_once(" = ") {
+                    let value = Expr::to_addr(eval(&value.parse()?, pc)?)?;
+                    _ = symbols.insert(String::from(name), value);
+                } else if let Some(addr) = line.strip_prefix(".org ") {
+                    pc = Expr::to_addr(eval(&addr.parse()?, pc)?)?;
+                } else if let Some(values) = line.strip_prefix(".byte ") {
+                    for value in values.split(',') {
+                        bytes.push(u8::try_from(eval(&value.parse()?, pc)?)?);
+                        pc = pc.wrapping_add(1);
+                    }
+                } else {
+                    let absolute = line.contains(" a:");
+                    let asm_line = line.replacen(" a:", " ", 1).parse::<AsmLine>()?;
+                    let value = asm_line
+                        .operand
+                        .expr()
+                        .map(|expr| eval(expr, pc))
+                        .transpose()?;
+                    let mut encoded = asm_line.encode(pc, value)?;
+                    if absolute && encoded.len() == 2 {
+                        encoded = vec![absolute_opcode(encoded[0])? as u8, encoded[1], 0x00];
+                    }
+                    pc = pc.wrapping_add(u16::try_from(encoded.len())?);
+                    bytes.extend(encoded);
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    // The line assembler picks zero page addressing where ca65 would be told to use "a:"
+    fn absolute_opcode(opcode: u8) -> Result<Opcode> {
+        let opcode = Opcode::from_u8(opcode).ok_or_else(|| anyhow!("invalid opcode"))?;
+        let mode = match MOS_6502.get_op_info(&opcode).map(OpInfo::addressing_mode) {
+            Some(AddressingMode::ZeroPage) => AddressingMode::Absolute,
+            Some(AddressingMode::ZeroPageX) => AddressingMode::AbsoluteX,
+            Some(AddressingMode::ZeroPageY) => AddressingMode::AbsoluteY,
+            _ => return Err(anyhow!("no absolute form of {opcode:?}")),
+        };
+        OpInfo::iter()
+            .find(|op_info| {
+                op_info.opcode().mnemonic() == opcode.mnemonic()
+                    && *op_info.addressing_mode() == mode
+            })
+            .map(OpInfo::opcode)
+            .ok_or_else(|| anyhow!("no absolute form of {opcode:?}"))
+    }
+
+    #[test]
+    fn listing() -> Result<()> {
+        let map_file = map_file();
+        let s = Disassembler::new(&map_file, Syntax::Listing).disassemble(0x0e00, &BYTES)?;
+        assert_eq!(
+            "main:\n\
+            0E00  A9 41     LDA #$41\n\
+            0E02  20 EE FF  JSR OSWRCH\n\
+            0E05  4C 00 0E  JMP main\n\
+            0E08  FF        .byte $FF\n\
+            0E09  AD 12 00  LDA ptr\n\
+            0E0C  20        .byte $20\n\
+            data:\n\
+            0E0D  EE        .byte $EE\n\
+            0E0E  FF        .byte $FF\n",
+            s
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ca65() -> Result<()> {
+        let map_file = map_file();
+        let s = Disassembler::new(&map_file, Syntax::Ca65).disassemble(0x0e00, &BYTES)?;
+        assert_eq!(
+            "        .setcpu \"6502\"\n\
+            ptr = $12\n\
+            OSWRCH = $FFEE\n\
+            \x20       .org $0E00\n\
+            main:\n\
+            \x20       LDA #$41\n\
+            \x20       JSR OSWRCH\n\
+            \x20       JMP main\n\
+            \x20       .byte $FF\n\
+            \x20       LDA a:ptr\n\
+            \x20       .byte $20\n\
+            data:\n\
+            \x20       .byte $EE,$FF\n",
+            s
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn traced() -> Result<()> {
+        // JSR sub, BEQ sub, JMP done, table, sub: RTS, done: BRK, $FF
+        let bytes = [
+            0x20, 0x0a, 0x0e, 0xf0, 0x05, 0x4c, 0x0b, 0x0e, 0x01, 0x02, 0x60, 0x00, 0xff,
+        ];
+        let map_file = map_file();
+        let s = Disassembler::new(&map_file, Syntax::Ca65).disassemble_traced(
+            0x0e00,
+            &bytes,
+            &[0x0e00],
+            &[],
+        )?;
+        assert_eq!(
+            "        .setcpu \"6502\"\n\
+            ptr = $12\n\
+            data = $0E0D\n\
+            OSWRCH = $FFEE\n\
+            \x20       .org $0E00\n\
+            main:\n\
+            \x20       JSR L0E0A\n\
+            \x20       BEQ L0E0A\n\
+            \x20       JMP L0E0B\n\
+            \x20       .byte $01,$02\n\
+            L0E0A:\n\
+            \x20       RTS\n\
+            L0E0B:\n\
+            \x20       BRK\n\
+            \x20       .byte $FF\n",
+            s
+        );
+        Ok(())
+    }
+
+    // Covers "a:" for absolute operands below $100, instructions split by labels and runs of
+    // data longer than a line
+    #[rstest]
+    #[case(&BYTES, false)]
+    #[case(&[0x20, 0x0a, 0x0e, 0xf0, 0x05, 0x4c, 0x0b, 0x0e, 0x01, 0x02, 0x60, 0x00, 0xff], true)]
+    #[case(&[0xbd, 0x12, 0x00, 0x8e, 0xff, 0x00, 0x20, 0x0e, 0x00, 0x60], false)]
+    #[case(&[0xff; 20], false)]
+    #[case(&[0xad, 0x12, 0x00, 0xd0, 0xfb, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], true)]
+    fn reassembles(#[case] bytes: &[u8], #[case] traced: bool) -> Result<()> {
+        let map_file = map_file();
+        let disassembler = Disassembler::new(&map_file, Syntax::Ca65);
+        let s = if traced {
+            disassembler.disassemble_traced(0x0e00, bytes, &[0x0e00], &[])?
+        } else {
+            disassembler.disassemble(0x0e00, bytes)?
+        };
+        assert_eq!(bytes, reassemble(&s)?, "{s}");
+        Ok(())
+    }
+
+    // Labels and equates must be valid ca65 identifiers whatever names debug info supplies
+    #[test]
+    fn ca65_identifiers() -> Result<()> {
+        let mut map_file = map_file();
+        for (name, value) in [
+            ("print::loop", 0x0e02),
+            ("start@done", 0x0e05),
+            ("x", 0x0e09),
+        ] {
+            map_file.exports.push(Export {
+                name: String::from(name),
+                value,
+                referenced: true,
+                kind: ExportKind::Label,
+                address_size: AddressSize::Absolute,
+            });
+        }
+        let bytes = [
+            0xa9, 0x41, 0x20, 0x02, 0x0e, 0x4c, 0x05, 0x0e, 0xff, 0xad, 0x09, 0x0e,
+        ];
+        for traced in [false, true] {
+            let disassembler = Disassembler::new(&map_file, Syntax::Ca65);
+            let s = if traced {
+                disassembler.disassemble_traced(0x0e00, &bytes, &[0x0e00], &[])?
+            } else {
+                disassembler.disassemble(0x0e00, &bytes)?
+            };
+            for line in s.lines() {
+                let name = match line.split_once(" = ") {
+                    Some((name, _)) => name,
+                    None => match line.strip_suffix(':') {
+                        Some(name) => name,
+                        None => continue,
+                    },
+                };
+                let mut chars = name.chars();
+                assert!(
+                    chars
+                        .next()
+                        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+                        && !name.eq_ignore_ascii_case("x"),
+                    "{name} in {s}"
+                );
+            }
+            assert!(!s.contains("::") && !s.contains('@'), "{s}");
+            assert_eq!(bytes.as_slice(), reassemble(&s)?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+mod code_tracer;
+mod disasm_label;
+mod disasm_project;
+mod disassembled_item;
+mod disassembler;
+mod syntax;
+
+pub use code_tracer::*;
+pub use disasm_label::*;
+pub use disasm_project::*;
+pub use disassembled_item::*;
+pub use disassembler::*;
+pub use syntax::*;
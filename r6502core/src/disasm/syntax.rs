@@ -0,0 +1,8 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Syntax {
+    // Addresses and bytes alongside each instruction
+    #[default]
+    Listing,
+    // Source that ca65 assembles back into the same bytes
+    Ca65,
+}
@@ -4,7 +4,7 @@ use crate::symbols::MapFile;
 use anyhow::{Result, bail};
 use r6502lib::num::{Truncate, Wrap};
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AddressingMode {
     Absolute,
     AbsoluteX,
@@ -22,6 +22,22 @@ pub enum AddressingMode {
 }
 
 impl AddressingMode {
+    // Instruction length in bytes including opcode
+    #[must_use]
+    pub const fn size(&self) -> u16 {
+        match self {
+            Self::Accumulator | Self::Implied => 1,
+            Self::Immediate
+            | Self::IndexedIndirectX
+            | Self::IndirectIndexedY
+            | Self::Relative
+            | Self::ZeroPage
+            | Self::ZeroPageX
+            | Self::ZeroPageY => 2,
+            Self::Absolute | Self::AbsoluteX | Self::AbsoluteY | Self::Indirect => 3,
+        }
+    }
+
     pub fn format_instruction_info(
         &self,
         instruction_info: &InstructionInfo,
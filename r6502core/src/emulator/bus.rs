@@ -1,5 +1,5 @@
 use crate::emulator::BusView;
-use crate::{DeviceMapping, Ram};
+use crate::{DeviceInfo, DeviceMapping, Ram};
 use anyhow::Result;
 use r6502lib::constants::{IRQ, MEMORY_SIZE, NMI, RESET};
 use r6502lib::util::make_word;
@@ -36,7 +36,7 @@ impl Bus {
                 .map(|m| m.address_range.clone())
                 .collect::<Vec<_>>()
         ));
-        mappings.sort_by(|a, b| a.address_range.start().cmp(&b.address_range.start()));
+        mappings.sort_by_key(|m| m.address_range.start());
         Self {
             machine_tag,
             mappings,
@@ -118,8 +118,21 @@ impl Bus {
         result
     }
 
+    // Memory map in address order with each device's current state
     #[must_use]
-    pub const fn view(&self) -> BusView {
+    pub fn devices(&self) -> Vec<DeviceInfo> {
+        self.mappings
+            .iter()
+            .map(|mapping| DeviceInfo {
+                address_range: mapping.address_range.clone(),
+                offset: mapping.offset,
+                state: mapping.device.inspect(),
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub const fn view(&self) -> BusView<'_> {
         BusView::new(self)
     }
 
@@ -148,7 +161,9 @@ impl Bus {
 mod tests {
     use crate::emulator::Bus;
     use crate::emulator::bus::UNMAPPED_VALUE;
-    use r6502lib::NULL_MACHINE_TAG;
+    use crate::{DeviceMapping, Ram, Rom};
+    use anyhow::Result;
+    use r6502lib::{AddressRange, NULL_MACHINE_TAG};
 
     #[test]
     fn load_no_device() {
@@ -161,4 +176,31 @@ mod tests {
         let bus = Bus::new(NULL_MACHINE_TAG, Vec::new());
         bus.store(0x0000, 0x00);
     }
+
+    #[test]
+    fn devices() -> Result<()> {
+        let bus = Bus::new(
+            NULL_MACHINE_TAG,
+            vec![
+                DeviceMapping {
+                    address_range: AddressRange::new(0xc000, 0xffff)?,
+                    device: Box::new(Rom::new(0x4000, &Vec::new())),
+                    offset: 0xc000,
+                },
+                DeviceMapping {
+                    address_range: AddressRange::new(0x0000, 0x7fff)?,
+                    device: Box::new(Ram::new(0x8000, &Vec::new())),
+                    offset: 0x0000,
+                },
+            ],
+        );
+        let devices = bus.devices();
+        assert_eq!(2, devices.len());
+        assert_eq!("$0000:$7FFF RAM (32768 bytes)", devices[0].to_string());
+        assert_eq!(
+            "$C000:$FFFF ROM offset $C000 (16384 bytes)",
+            devices[1].to_string()
+        );
+        Ok(())
+    }
 }
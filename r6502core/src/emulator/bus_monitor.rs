@@ -0,0 +1,6 @@
+// Observes memory accesses made by the CPU through a bus view
+pub trait BusMonitor {
+    fn on_load(&self, _addr: u16, _value: u8) {}
+
+    fn on_store(&self, _addr: u16, _value: u8) {}
+}
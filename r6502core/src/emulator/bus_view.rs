@@ -1,15 +1,24 @@
-use crate::emulator::Bus;
+use crate::emulator::{Bus, BusMonitor};
 use r6502lib::MachineTag;
 
 #[derive(Clone)]
 pub struct BusView<'a> {
     bus: &'a Bus,
+    monitor: Option<&'a dyn BusMonitor>,
 }
 
 impl<'a> BusView<'a> {
     #[must_use]
     pub const fn new(bus: &'a Bus) -> Self {
-        Self { bus }
+        Self { bus, monitor: None }
+    }
+
+    #[must_use]
+    pub const fn with_monitor(bus: &'a Bus, monitor: &'a dyn BusMonitor) -> Self {
+        Self {
+            bus,
+            monitor: Some(monitor),
+        }
     }
 
     #[must_use]
@@ -19,10 +28,23 @@ impl<'a> BusView<'a> {
 
     #[must_use]
     pub fn load(&self, addr: u16) -> u8 {
+        let value = self.bus.load(addr);
+        if let Some(monitor) = self.monitor {
+            monitor.on_load(addr, value);
+        }
+        value
+    }
+
+    // Read without notifying the monitor e.g. to inspect an instruction from outside its execution
+    #[must_use]
+    pub fn peek(&self, addr: u16) -> u8 {
         self.bus.load(addr)
     }
 
     pub fn store(&self, addr: u16, value: u8) {
         self.bus.store(addr, value);
+        if let Some(monitor) = self.monitor {
+            monitor.on_store(addr, value);
+        }
     }
 }
@@ -42,6 +42,20 @@ pub fn translate_out(char_set: &CharSet, value: u8) -> Option<u8> {
     }
 }
 
+// Printable character for memory displays, or '.' for anything else
+#[must_use]
+pub fn to_display_char(char_set: &CharSet, value: u8) -> char {
+    let value = match char_set {
+        CharSet::Apple1 => value & 0x7f,
+        _ => value,
+    };
+    if (32..=126).contains(&value) {
+        value as char
+    } else {
+        '.'
+    }
+}
+
 fn to_byte(char_set: CharSet, c: char) -> u8 {
     match char_set {
         CharSet::Apple1 => {
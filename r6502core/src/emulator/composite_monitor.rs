@@ -0,0 +1,44 @@
+use crate::Reg;
+use crate::emulator::{InstructionInfo, Monitor};
+use r6502lib::TotalCycles;
+
+// Passes callbacks on to each monitor in turn e.g. to trace and profile in the same run
+#[derive(Default)]
+pub struct CompositeMonitor {
+    monitors: Vec<Box<dyn Monitor>>,
+}
+
+impl CompositeMonitor {
+    pub fn push(&mut self, monitor: Box<dyn Monitor>) {
+        self.monitors.push(monitor);
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.monitors.is_empty()
+    }
+}
+
+impl Monitor for CompositeMonitor {
+    fn on_before_execute(
+        &self,
+        total_cycles: TotalCycles,
+        reg: Reg,
+        instruction_info: InstructionInfo,
+    ) {
+        for monitor in &self.monitors {
+            monitor.on_before_execute(total_cycles, reg.clone(), instruction_info.clone());
+        }
+    }
+
+    fn on_after_execute(
+        &self,
+        total_cycles: TotalCycles,
+        reg: Reg,
+        instruction_info: InstructionInfo,
+    ) {
+        for monitor in &self.monitors {
+            monitor.on_after_execute(total_cycles, reg.clone(), instruction_info.clone());
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use crate::emulator::{BusView, Instruction, InstructionInfo, Monitor};
-use crate::{_p, Frequency, InterruptEvent, Reg};
+use crate::{_p, Frequency, InterruptEvent, Reg, p_set};
 use log::{Level, debug, log_enabled};
 use r6502lib::TotalCycles;
-use r6502lib::constants::{IRQ, RESET, STACK_BASE};
+use r6502lib::constants::STACK_BASE;
 use r6502lib::util::{make_word, split_word};
-use r6502snapshot::CpuState;
+use r6502snapshot::{CpuState, Snapshot};
+use std::mem::replace;
 use std::sync::LazyLock;
 use std::sync::mpsc::{Receiver, TryRecvError};
 use std::time::{Duration, Instant};
@@ -16,6 +17,10 @@ pub struct Cpu<'a> {
     pub reg: Reg,
     pub bus: BusView<'a>,
     pub total_cycles: TotalCycles,
+    // Interrupt serviced immediately before the most recently executed instruction
+    pub last_interrupt: Option<InterruptEvent>,
+    // Time spent waiting in order to keep to the emulated clock rate
+    pub throttled: Duration,
     monitor: Box<dyn Monitor>,
     irq_rx: Receiver<InterruptEvent>,
 }
@@ -35,6 +40,8 @@ impl<'a> Cpu<'a> {
             reg: Reg::default(),
             bus,
             total_cycles: 0,
+            last_interrupt: None,
+            throttled: Duration::ZERO,
             monitor: monitor.unwrap_or_else(|| Box::new(DummyMonitor)),
             irq_rx,
         }
@@ -53,6 +60,16 @@ impl<'a> Cpu<'a> {
         }
     }
 
+    // Registers and the whole of memory as seen by the CPU, without notifying the monitor
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::new(
+            self.bus.machine_tag(),
+            self.get_state(),
+            (0..=0xffff).map(|addr| self.bus.peek(addr)).collect(),
+        )
+    }
+
     pub fn set_initial_state(&mut self, state: &CpuState) {
         self.reg.pc = state.pc;
         self.reg.a = state.a;
@@ -63,6 +80,34 @@ impl<'a> Cpu<'a> {
         self.total_cycles = state.total_cycles;
     }
 
+    // Hand the interrupt source over to another CPU e.g. one attached to a debugger
+    pub fn replace_irq_rx(&mut self, irq_rx: Receiver<InterruptEvent>) -> Receiver<InterruptEvent> {
+        replace(&mut self.irq_rx, irq_rx)
+    }
+
+    // Service an interrupt immediately e.g. one injected by a debugger
+    pub fn interrupt(&mut self, interrupt: InterruptEvent) {
+        match interrupt {
+            InterruptEvent::Irq | InterruptEvent::Nmi => self.handle_interrupt(interrupt),
+            InterruptEvent::Reset => self.handle_reset(),
+        }
+    }
+
+    // Service the next interrupt raised by a device, if any
+    pub fn poll_interrupt(&mut self) -> Option<InterruptEvent> {
+        match self.irq_rx.try_recv() {
+            Ok(interrupt) => {
+                self.interrupt(interrupt);
+                Some(interrupt)
+            }
+            Err(TryRecvError::Disconnected | TryRecvError::Empty) => {
+                // TBD: IRQ channel will never be connected when using
+                // Pia instead of Via. Handle that more gracefully.
+                None
+            }
+        }
+    }
+
     pub fn step_with_monitor_callbacks(&mut self) {
         let (instruction, instruction_info) = self.decode_next();
 
@@ -73,7 +118,7 @@ impl<'a> Cpu<'a> {
         );
 
         let instruction_cycles = instruction.execute(self);
-        Self::spin(instruction_cycles);
+        self.spin(instruction_cycles);
 
         self.monitor.on_after_execute(
             self.total_cycles,
@@ -87,7 +132,7 @@ impl<'a> Cpu<'a> {
     pub fn step(&mut self) {
         let (instruction, _) = self.decode_next();
         let instruction_cycles = instruction.execute(self);
-        Self::spin(instruction_cycles);
+        self.spin(instruction_cycles);
         self.total_cycles += TotalCycles::from(instruction_cycles);
     }
 
@@ -135,7 +180,7 @@ impl<'a> Cpu<'a> {
         make_word(hi, lo)
     }
 
-    fn spin(instruction_cycles: u8) {
+    fn spin(&mut self, instruction_cycles: u8) {
         let before = Instant::now();
         let d = *CPU_TICK * u32::from(instruction_cycles);
 
@@ -144,6 +189,7 @@ impl<'a> Cpu<'a> {
             let now = Instant::now();
             let elapsed = now - before;
             if elapsed >= d {
+                self.throttled += elapsed;
                 break;
             }
         }
@@ -161,15 +207,7 @@ impl<'a> Cpu<'a> {
     }
 
     fn decode_next(&mut self) -> (Instruction, InstructionInfo) {
-        match self.irq_rx.try_recv() {
-            Ok(InterruptEvent::Irq) => self.handle_irq(),
-            Ok(InterruptEvent::Nmi) => todo!(),
-            Ok(InterruptEvent::Reset) => self.handle_reset(),
-            Err(TryRecvError::Disconnected | TryRecvError::Empty) => {
-                // TBD: IRQ channel will never be connected when using
-                // Pia instead of Via. Handle that more gracefully.
-            }
-        }
+        self.last_interrupt = self.poll_interrupt();
 
         let instruction = Instruction::fetch(self);
         let instruction_info = InstructionInfo::from_instruction(&instruction);
@@ -181,19 +219,23 @@ impl<'a> Cpu<'a> {
     }
 
     // Reference: https://www.pagetable.com/?p=410
-    fn handle_irq(&mut self) {
+    fn handle_interrupt(&mut self, interrupt: InterruptEvent) {
         self.push_word(self.reg.pc);
         let p = self.reg.p.bits();
         self.push(p & 0b1110_1111);
-        let pc_lo = self.bus.load(IRQ);
-        let pc_hi = self.bus.load(IRQ.wrapping_add(1));
-        self.reg.pc = make_word(pc_hi, pc_lo);
+        p_set!(self.reg, I, true);
+        self.load_vector(interrupt);
     }
 
     // Reference: https://www.pagetable.com/?p=410
     fn handle_reset(&mut self) {
-        let pc_lo = self.bus.load(RESET);
-        let pc_hi = self.bus.load(RESET.wrapping_add(1));
+        self.load_vector(InterruptEvent::Reset);
+    }
+
+    fn load_vector(&mut self, interrupt: InterruptEvent) {
+        let vector = interrupt.vector();
+        let pc_lo = self.bus.load(vector);
+        let pc_hi = self.bus.load(vector.wrapping_add(1));
         self.reg.pc = make_word(pc_hi, pc_lo);
     }
 }
@@ -201,13 +243,42 @@ impl<'a> Cpu<'a> {
 #[cfg(test)]
 mod tests {
     use crate::emulator::address_util::get_brk_addr;
-    use crate::emulator::{Bus, Cpu, MOS_6502, Monitor, TracingMonitor};
-    use crate::{InterruptChannel, Opcode, P, p, p_get, p_set};
+    use crate::emulator::{Bus, BusMonitor, BusView, Cpu, MOS_6502, Monitor, TracingMonitor};
+    use crate::{InterruptChannel, InterruptEvent, Opcode, P, p, p_get, p_set};
     use anyhow::Result;
-    use r6502lib::constants::IRQ;
+    use r6502lib::constants::{IRQ, NMI, RESET};
     use r6502lib::util::{make_word, split_word};
     use r6502snapshot::MemoryImage;
     use rstest::rstest;
+    use std::cell::Cell;
+
+    #[rstest]
+    #[case(InterruptEvent::Irq, 0x1234)]
+    #[case(InterruptEvent::Nmi, 0x5678)]
+    #[case(InterruptEvent::Reset, 0x9abc)]
+    fn inject_interrupt(#[case] interrupt: InterruptEvent, #[case] expected_pc: u16) {
+        let bus = Bus::default();
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        for (vector, addr) in [(NMI, 0x5678u16), (RESET, 0x9abc), (IRQ, 0x1234)] {
+            let (hi, lo) = split_word(addr);
+            bus.store(vector, lo);
+            bus.store(vector.wrapping_add(1), hi);
+        }
+        cpu.reg.pc = 0x0e00;
+        cpu.reg.sp = 0xff;
+        cpu.reg.p = p!();
+
+        cpu.interrupt(interrupt);
+        assert_eq!(expected_pc, cpu.reg.pc);
+        if interrupt == InterruptEvent::Reset {
+            assert_eq!(0xff, cpu.reg.sp);
+        } else {
+            assert!(p_get!(cpu.reg, I));
+            assert_eq!(0xfc, cpu.reg.sp);
+            assert_eq!(0x0e00, cpu.peek_back_word(1));
+        }
+    }
 
     #[test]
     fn no_operand() {
@@ -224,6 +295,32 @@ mod tests {
         assert_eq!(0x0001, cpu.reg.pc);
     }
 
+    #[derive(Default)]
+    struct LoadCounter(Cell<usize>);
+
+    impl BusMonitor for LoadCounter {
+        fn on_load(&self, _addr: u16, _value: u8) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    // Snapshots don't show up in monitors such as the heatmap
+    #[test]
+    fn snapshot_unmonitored() {
+        let bus = Bus::default();
+        bus.store(0x1234, 0x56);
+        let counter = LoadCounter::default();
+        let interrupt_channel = InterruptChannel::new();
+        let cpu = Cpu::new(
+            BusView::with_monitor(&bus, &counter),
+            None,
+            interrupt_channel.rx,
+        );
+        let snapshot = cpu.snapshot();
+        assert_eq!(0x56, snapshot.bytes[0x1234]);
+        assert_eq!(0, counter.0.get());
+    }
+
     #[test]
     fn byte0() {
         let bus = Bus::default();
@@ -546,7 +643,7 @@ mod tests {
                 break;
             }
         }
-        assert_eq!(893, cpu.total_cycles);
+        assert_eq!(919, cpu.total_cycles);
         let lo = bus.load(NUM1);
         let hi = bus.load(NUM1 + 1);
         let quotient = make_word(hi, lo);
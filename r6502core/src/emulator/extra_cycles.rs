@@ -0,0 +1,22 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtraCycles {
+    None,
+
+    // One more cycle when indexing crosses a page boundary
+    PageCross,
+
+    // One more cycle when taken and another when the target is on a different page
+    Branch,
+}
+
+impl Display for ExtraCycles {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::None => Ok(()),
+            Self::PageCross => write!(f, "+1 if page crossed"),
+            Self::Branch => write!(f, "+1 if taken, +2 if page crossed"),
+        }
+    }
+}
@@ -1,8 +1,8 @@
-use crate::emulator::{Binding, Cpu, Instruction, MOS_6502};
+use crate::emulator::{Binding, Cpu, Instruction, MOS_6502, Op};
 use crate::symbols::MapFile;
 use crate::{Opcode, Operand};
 use anyhow::{Result, anyhow};
-use r6502lib::util::split_word;
+use r6502lib::util::{make_word, split_word};
 
 #[derive(Clone, Debug)]
 pub struct InstructionInfo {
@@ -39,6 +39,31 @@ impl InstructionInfo {
         }
     }
 
+    // Instruction at the start of the bytes if they begin with a valid opcode and its full operand
+    #[must_use]
+    pub fn decode(pc: u16, bytes: &[u8]) -> Option<Self> {
+        let opcode = Opcode::from_u8(*bytes.first()?)?;
+        let operand = match MOS_6502.get_op_info(&opcode)?.op() {
+            Op::NoOperand(_) => Operand::None,
+            Op::Byte(_) => Operand::Byte(*bytes.get(1)?),
+            Op::Word(_) => Operand::Word(make_word(*bytes.get(2)?, *bytes.get(1)?)),
+        };
+        Some(Self {
+            pc,
+            opcode,
+            operand,
+        })
+    }
+
+    #[must_use]
+    pub const fn size(&self) -> u16 {
+        match self.operand {
+            Operand::None => 1,
+            Operand::Byte(_) => 2,
+            Operand::Word(_) => 3,
+        }
+    }
+
     pub fn display(&self, map_file: &MapFile) -> Result<String> {
         let op_info = MOS_6502
             .get_op_info(&self.opcode)
@@ -6,9 +6,12 @@ mod addressing_mode;
 mod binding;
 mod bus;
 mod bus_event;
+mod bus_monitor;
 mod bus_view;
 mod byte_op;
+mod composite_monitor;
 mod cpu;
+mod extra_cycles;
 mod instruction;
 mod instruction_info;
 mod instruction_set;
@@ -17,8 +20,10 @@ mod monitor;
 mod no_operand_op;
 mod op;
 mod op_info;
+mod op_metadata;
 mod ops;
 mod output_device;
+mod register_set;
 mod tracing_monitor;
 mod word_op;
 
@@ -26,9 +31,12 @@ pub use addressing_mode::*;
 pub use binding::*;
 pub use bus::*;
 pub use bus_event::*;
+pub use bus_monitor::*;
 pub use bus_view::*;
 pub use byte_op::*;
+pub use composite_monitor::*;
 pub use cpu::*;
+pub use extra_cycles::*;
 pub use instruction::*;
 pub use instruction_info::*;
 pub use instruction_set::*;
@@ -37,6 +45,9 @@ pub use monitor::*;
 pub use no_operand_op::*;
 pub use op::*;
 pub use op_info::*;
+pub use op_metadata::*;
+pub use ops::BranchResult;
 pub use output_device::*;
+pub use register_set::*;
 pub use tracing_monitor::*;
 pub use word_op::*;
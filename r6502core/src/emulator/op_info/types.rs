@@ -1,5 +1,5 @@
 use crate::emulator::op_info::op_infos::{CONSTS, Item};
-use crate::emulator::{AddressingMode, Cpu, InstructionInfo, Op};
+use crate::emulator::{AddressingMode, Cpu, InstructionInfo, Op, OpMetadata};
 use crate::symbols::MapFile;
 use crate::{OpCycles, Opcode};
 use anyhow::Result;
@@ -45,6 +45,16 @@ impl OpInfo {
         &self.op
     }
 
+    #[must_use]
+    pub const fn addressing_mode(&self) -> &AddressingMode {
+        &self.addressing_mode
+    }
+
+    #[must_use]
+    pub fn metadata(&self) -> Option<OpMetadata> {
+        OpMetadata::get(self.opcode)
+    }
+
     pub fn format_instruction_info(
         &self,
         instruction_info: &InstructionInfo,
@@ -58,7 +58,7 @@ pub mod absolute_x {
             pub fn $f(cpu: &mut $crate::emulator::Cpu, addr: u16) -> $crate::OpCycles {
                 let effective_addr = addr.wrapping_add(u16::from(cpu.reg.x));
                 $crate::emulator::ops::$f(cpu, cpu.bus.load(effective_addr));
-                if r6502lib::util::crosses_page_boundary(effective_addr) {
+                if r6502lib::util::crosses_page_boundary(addr, effective_addr) {
                     $cross_page_cycles
                 } else {
                     $cycles
@@ -72,7 +72,7 @@ pub mod absolute_x {
             pub fn $f(cpu: &mut $crate::emulator::Cpu, addr: u16) -> $crate::OpCycles {
                 let effective_addr = addr.wrapping_add(u16::from(cpu.reg.x));
                 $crate::emulator::ops::$f(cpu, effective_addr);
-                if r6502lib::util::crosses_page_boundary(effective_addr) {
+                if r6502lib::util::crosses_page_boundary(addr, effective_addr) {
                     $cross_page_cycles
                 } else {
                     $cycles
@@ -104,7 +104,7 @@ pub mod absolute_y {
             pub fn $f(cpu: &mut $crate::emulator::Cpu, addr: u16) -> $crate::OpCycles {
                 let effective_addr = addr.wrapping_add(u16::from(cpu.reg.y));
                 $crate::emulator::ops::$f(cpu, cpu.bus.load(effective_addr));
-                if r6502lib::util::crosses_page_boundary(effective_addr) {
+                if r6502lib::util::crosses_page_boundary(addr, effective_addr) {
                     $cross_page_cycles
                 } else {
                     $cycles
@@ -118,7 +118,7 @@ pub mod absolute_y {
             pub fn $f(cpu: &mut $crate::emulator::Cpu, addr: u16) -> $crate::OpCycles {
                 let effective_addr = addr.wrapping_add(u16::from(cpu.reg.y));
                 $crate::emulator::ops::$f(cpu, effective_addr);
-                if r6502lib::util::crosses_page_boundary(effective_addr) {
+                if r6502lib::util::crosses_page_boundary(addr, effective_addr) {
                     $cross_page_cycles
                 } else {
                     $cycles
@@ -280,8 +280,9 @@ pub mod indirect_indexed_y {
                     $crate::emulator::address_util::compute_effective_addr_indirect_indexed_y(
                         cpu, addr,
                     );
+                let base_addr = effective_addr.wrapping_sub(u16::from(cpu.reg.y));
                 $crate::emulator::ops::$f(cpu, cpu.bus.load(effective_addr));
-                if r6502lib::util::crosses_page_boundary(effective_addr) {
+                if r6502lib::util::crosses_page_boundary(base_addr, effective_addr) {
                     $cross_page_cycles
                 } else {
                     $cycles
@@ -297,8 +298,9 @@ pub mod indirect_indexed_y {
                     $crate::emulator::address_util::compute_effective_addr_indirect_indexed_y(
                         cpu, addr,
                     );
+                let base_addr = effective_addr.wrapping_sub(u16::from(cpu.reg.y));
                 $crate::emulator::ops::$f(cpu, effective_addr);
-                if r6502lib::util::crosses_page_boundary(effective_addr) {
+                if r6502lib::util::crosses_page_boundary(base_addr, effective_addr) {
                     $cross_page_cycles
                 } else {
                     $cycles
@@ -314,7 +316,7 @@ pub mod indirect_indexed_y {
     wrap!(lda, 5, 6);
     wrap!(ora, 5, 6);
     wrap!(sbc, 5, 6);
-    wrap_store!(sta, 5, 6);
+    wrap_store!(sta, 6, 6);
 }
 
 pub mod relative {
@@ -323,7 +325,7 @@ pub mod relative {
             pub fn $f(cpu: &mut $crate::emulator::Cpu, offset: u8) -> $crate::OpCycles {
                 match $crate::emulator::ops::$f(cpu, offset) {
                     $crate::emulator::ops::BranchResult::NotTaken => $not_taken_cycles,
-                    $crate::emulator::ops::BranchResult::Taken => $not_taken_cycles,
+                    $crate::emulator::ops::BranchResult::Taken => $taken_cycles,
                     $crate::emulator::ops::BranchResult::TakenCrossPage => $taken_cross_page_cycles,
                 }
             }
@@ -0,0 +1,486 @@
+use crate::emulator::{AddressingMode, ExtraCycles, MOS_6502, RegisterSet};
+use crate::{OpCycles, Opcode, P};
+use std::fmt::Write;
+
+const ALL_FLAGS: P = P::N
+    .union(P::V)
+    .union(P::D)
+    .union(P::I)
+    .union(P::Z)
+    .union(P::C);
+const NZ: P = P::N.union(P::Z);
+const NZC: P = NZ.union(P::C);
+const NONE: RegisterSet = RegisterSet::empty();
+const A: RegisterSet = RegisterSet::A;
+const X: RegisterSet = RegisterSet::X;
+const Y: RegisterSet = RegisterSet::Y;
+const SP: RegisterSet = RegisterSet::SP;
+const PC: RegisterSet = RegisterSet::PC;
+const SP_PC: RegisterSet = SP.union(PC);
+
+// Mnemonic, flags read, flags written, registers read, registers written
+type Effects = (&'static str, P, P, RegisterSet, RegisterSet);
+
+const EFFECTS: [Effects; 56] = [
+    ("ADC", P::C.union(P::D), NZC.union(P::V), A, A),
+    ("AND", P::empty(), NZ, A, A),
+    ("ASL", P::empty(), NZC, NONE, NONE),
+    ("BCC", P::C, P::empty(), PC, PC),
+    ("BCS", P::C, P::empty(), PC, PC),
+    ("BEQ", P::Z, P::empty(), PC, PC),
+    ("BIT", P::empty(), NZ.union(P::V), A, NONE),
+    ("BMI", P::N, P::empty(), PC, PC),
+    ("BNE", P::Z, P::empty(), PC, PC),
+    ("BPL", P::N, P::empty(), PC, PC),
+    ("BRK", ALL_FLAGS, P::I, SP_PC, SP_PC),
+    ("BVC", P::V, P::empty(), PC, PC),
+    ("BVS", P::V, P::empty(), PC, PC),
+    ("CLC", P::empty(), P::C, NONE, NONE),
+    ("CLD", P::empty(), P::D, NONE, NONE),
+    ("CLI", P::empty(), P::I, NONE, NONE),
+    ("CLV", P::empty(), P::V, NONE, NONE),
+    ("CMP", P::empty(), NZC, A, NONE),
+    ("CPX", P::empty(), NZC, X, NONE),
+    ("CPY", P::empty(), NZC, Y, NONE),
+    ("DEC", P::empty(), NZ, NONE, NONE),
+    ("DEX", P::empty(), NZ, X, X),
+    ("DEY", P::empty(), NZ, Y, Y),
+    ("EOR", P::empty(), NZ, A, A),
+    ("INC", P::empty(), NZ, NONE, NONE),
+    ("INX", P::empty(), NZ, X, X),
+    ("INY", P::empty(), NZ, Y, Y),
+    ("JMP", P::empty(), P::empty(), NONE, PC),
+    ("JSR", P::empty(), P::empty(), SP_PC, SP_PC),
+    ("LDA", P::empty(), NZ, NONE, A),
+    ("LDX", P::empty(), NZ, NONE, X),
+    ("LDY", P::empty(), NZ, NONE, Y),
+    ("LSR", P::empty(), NZC, NONE, NONE),
+    ("NOP", P::empty(), P::empty(), NONE, NONE),
+    ("ORA", P::empty(), NZ, A, A),
+    ("PHA", P::empty(), P::empty(), A.union(SP), SP),
+    ("PHP", ALL_FLAGS, P::empty(), SP, SP),
+    ("PLA", P::empty(), NZ, SP, A.union(SP)),
+    ("PLP", P::empty(), ALL_FLAGS, SP, SP),
+    ("ROL", P::C, NZC, NONE, NONE),
+    ("ROR", P::C, NZC, NONE, NONE),
+    ("RTI", P::empty(), ALL_FLAGS, SP, SP_PC),
+    ("RTS", P::empty(), P::empty(), SP, SP_PC),
+    ("SBC", P::C.union(P::D), NZC.union(P::V), A, A),
+    ("SEC", P::empty(), P::C, NONE, NONE),
+    ("SED", P::empty(), P::D, NONE, NONE),
+    ("SEI", P::empty(), P::I, NONE, NONE),
+    ("STA", P::empty(), P::empty(), A, NONE),
+    ("STX", P::empty(), P::empty(), X, NONE),
+    ("STY", P::empty(), P::empty(), Y, NONE),
+    ("TAX", P::empty(), NZ, A, X),
+    ("TAY", P::empty(), NZ, A, Y),
+    ("TSX", P::empty(), NZ, SP, X),
+    ("TXA", P::empty(), NZ, X, A),
+    ("TXS", P::empty(), P::empty(), X, SP),
+    ("TYA", P::empty(), NZ, Y, A),
+];
+
+// Base cycles and extra-cycle rule for each opcode
+const TIMINGS: [(Opcode, OpCycles, ExtraCycles); 151] = [
+    (Opcode::AdcAbs, 4, ExtraCycles::None),
+    (Opcode::AdcAbsX, 4, ExtraCycles::PageCross),
+    (Opcode::AdcAbsY, 4, ExtraCycles::PageCross),
+    (Opcode::AdcImm, 2, ExtraCycles::None),
+    (Opcode::AdcIndX, 6, ExtraCycles::None),
+    (Opcode::AdcIndY, 5, ExtraCycles::PageCross),
+    (Opcode::AdcZp, 3, ExtraCycles::None),
+    (Opcode::AdcZpX, 4, ExtraCycles::None),
+    (Opcode::AndAbs, 4, ExtraCycles::None),
+    (Opcode::AndAbsX, 4, ExtraCycles::PageCross),
+    (Opcode::AndAbsY, 4, ExtraCycles::PageCross),
+    (Opcode::AndImm, 2, ExtraCycles::None),
+    (Opcode::AndIndX, 6, ExtraCycles::None),
+    (Opcode::AndIndY, 5, ExtraCycles::PageCross),
+    (Opcode::AndZp, 3, ExtraCycles::None),
+    (Opcode::AndZpX, 4, ExtraCycles::None),
+    (Opcode::AslAbs, 6, ExtraCycles::None),
+    (Opcode::AslAbsX, 7, ExtraCycles::None),
+    (Opcode::AslAcc, 2, ExtraCycles::None),
+    (Opcode::AslZp, 5, ExtraCycles::None),
+    (Opcode::AslZpX, 6, ExtraCycles::None),
+    (Opcode::Bcc, 2, ExtraCycles::Branch),
+    (Opcode::Bcs, 2, ExtraCycles::Branch),
+    (Opcode::Beq, 2, ExtraCycles::Branch),
+    (Opcode::BitAbs, 4, ExtraCycles::None),
+    (Opcode::BitZp, 3, ExtraCycles::None),
+    (Opcode::Bmi, 2, ExtraCycles::Branch),
+    (Opcode::Bne, 2, ExtraCycles::Branch),
+    (Opcode::Bpl, 2, ExtraCycles::Branch),
+    (Opcode::Brk, 7, ExtraCycles::None),
+    (Opcode::Bvc, 2, ExtraCycles::Branch),
+    (Opcode::Bvs, 2, ExtraCycles::Branch),
+    (Opcode::Clc, 2, ExtraCycles::None),
+    (Opcode::Cld, 2, ExtraCycles::None),
+    (Opcode::Cli, 2, ExtraCycles::None),
+    (Opcode::Clv, 2, ExtraCycles::None),
+    (Opcode::CmpAbs, 4, ExtraCycles::None),
+    (Opcode::CmpAbsX, 4, ExtraCycles::PageCross),
+    (Opcode::CmpAbsY, 4, ExtraCycles::PageCross),
+    (Opcode::CmpImm, 2, ExtraCycles::None),
+    (Opcode::CmpIndX, 6, ExtraCycles::None),
+    (Opcode::CmpIndY, 5, ExtraCycles::PageCross),
+    (Opcode::CmpZp, 3, ExtraCycles::None),
+    (Opcode::CmpZpX, 4, ExtraCycles::None),
+    (Opcode::CpxAbs, 4, ExtraCycles::None),
+    (Opcode::CpxImm, 2, ExtraCycles::None),
+    (Opcode::CpxZp, 3, ExtraCycles::None),
+    (Opcode::CpyAbs, 4, ExtraCycles::None),
+    (Opcode::CpyImm, 2, ExtraCycles::None),
+    (Opcode::CpyZp, 3, ExtraCycles::None),
+    (Opcode::DecAbs, 6, ExtraCycles::None),
+    (Opcode::DecAbsX, 7, ExtraCycles::None),
+    (Opcode::DecZp, 5, ExtraCycles::None),
+    (Opcode::DecZpX, 6, ExtraCycles::None),
+    (Opcode::Dex, 2, ExtraCycles::None),
+    (Opcode::Dey, 2, ExtraCycles::None),
+    (Opcode::EorAbs, 4, ExtraCycles::None),
+    (Opcode::EorAbsX, 4, ExtraCycles::PageCross),
+    (Opcode::EorAbsY, 4, ExtraCycles::PageCross),
+    (Opcode::EorImm, 2, ExtraCycles::None),
+    (Opcode::EorIndX, 6, ExtraCycles::None),
+    (Opcode::EorIndY, 5, ExtraCycles::PageCross),
+    (Opcode::EorZp, 3, ExtraCycles::None),
+    (Opcode::EorZpX, 4, ExtraCycles::None),
+    (Opcode::IncAbs, 6, ExtraCycles::None),
+    (Opcode::IncAbsX, 7, ExtraCycles::None),
+    (Opcode::IncZp, 5, ExtraCycles::None),
+    (Opcode::IncZpX, 6, ExtraCycles::None),
+    (Opcode::Inx, 2, ExtraCycles::None),
+    (Opcode::Iny, 2, ExtraCycles::None),
+    (Opcode::JmpAbs, 3, ExtraCycles::None),
+    (Opcode::JmpInd, 5, ExtraCycles::None),
+    (Opcode::Jsr, 6, ExtraCycles::None),
+    (Opcode::LdaAbs, 4, ExtraCycles::None),
+    (Opcode::LdaAbsX, 4, ExtraCycles::PageCross),
+    (Opcode::LdaAbsY, 4, ExtraCycles::PageCross),
+    (Opcode::LdaImm, 2, ExtraCycles::None),
+    (Opcode::LdaIndX, 6, ExtraCycles::None),
+    (Opcode::LdaIndY, 5, ExtraCycles::PageCross),
+    (Opcode::LdaZp, 3, ExtraCycles::None),
+    (Opcode::LdaZpX, 4, ExtraCycles::None),
+    (Opcode::LdxAbs, 4, ExtraCycles::None),
+    (Opcode::LdxAbsY, 4, ExtraCycles::PageCross),
+    (Opcode::LdxImm, 2, ExtraCycles::None),
+    (Opcode::LdxZp, 3, ExtraCycles::None),
+    (Opcode::LdxZpY, 4, ExtraCycles::None),
+    (Opcode::LdyAbs, 4, ExtraCycles::None),
+    (Opcode::LdyAbsX, 4, ExtraCycles::PageCross),
+    (Opcode::LdyImm, 2, ExtraCycles::None),
+    (Opcode::LdyZp, 3, ExtraCycles::None),
+    (Opcode::LdyZpX, 4, ExtraCycles::None),
+    (Opcode::LsrAbs, 6, ExtraCycles::None),
+    (Opcode::LsrAbsX, 7, ExtraCycles::None),
+    (Opcode::LsrAcc, 2, ExtraCycles::None),
+    (Opcode::LsrZp, 5, ExtraCycles::None),
+    (Opcode::LsrZpX, 6, ExtraCycles::None),
+    (Opcode::Nop, 2, ExtraCycles::None),
+    (Opcode::OraAbs, 4, ExtraCycles::None),
+    (Opcode::OraAbsX, 4, ExtraCycles::PageCross),
+    (Opcode::OraAbsY, 4, ExtraCycles::PageCross),
+    (Opcode::OraImm, 2, ExtraCycles::None),
+    (Opcode::OraIndX, 6, ExtraCycles::None),
+    (Opcode::OraIndY, 5, ExtraCycles::PageCross),
+    (Opcode::OraZp, 3, ExtraCycles::None),
+    (Opcode::OraZpX, 4, ExtraCycles::None),
+    (Opcode::Pha, 3, ExtraCycles::None),
+    (Opcode::Php, 3, ExtraCycles::None),
+    (Opcode::Pla, 4, ExtraCycles::None),
+    (Opcode::Plp, 4, ExtraCycles::None),
+    (Opcode::RolAbs, 6, ExtraCycles::None),
+    (Opcode::RolAbsX, 7, ExtraCycles::None),
+    (Opcode::RolAcc, 2, ExtraCycles::None),
+    (Opcode::RolZp, 5, ExtraCycles::None),
+    (Opcode::RolZpX, 6, ExtraCycles::None),
+    (Opcode::RorAbs, 6, ExtraCycles::None),
+    (Opcode::RorAbsX, 7, ExtraCycles::None),
+    (Opcode::RorAcc, 2, ExtraCycles::None),
+    (Opcode::RorZp, 5, ExtraCycles::None),
+    (Opcode::RorZpX, 6, ExtraCycles::None),
+    (Opcode::Rti, 6, ExtraCycles::None),
+    (Opcode::Rts, 6, ExtraCycles::None),
+    (Opcode::SbcAbs, 4, ExtraCycles::None),
+    (Opcode::SbcAbsX, 4, ExtraCycles::PageCross),
+    (Opcode::SbcAbsY, 4, ExtraCycles::PageCross),
+    (Opcode::SbcImm, 2, ExtraCycles::None),
+    (Opcode::SbcIndX, 6, ExtraCycles::None),
+    (Opcode::SbcIndY, 5, ExtraCycles::PageCross),
+    (Opcode::SbcZp, 3, ExtraCycles::None),
+    (Opcode::SbcZpX, 4, ExtraCycles::None),
+    (Opcode::Sec, 2, ExtraCycles::None),
+    (Opcode::Sed, 2, ExtraCycles::None),
+    (Opcode::Sei, 2, ExtraCycles::None),
+    (Opcode::StaAbs, 4, ExtraCycles::None),
+    (Opcode::StaAbsX, 5, ExtraCycles::None),
+    (Opcode::StaAbsY, 5, ExtraCycles::None),
+    (Opcode::StaIndX, 6, ExtraCycles::None),
+    (Opcode::StaIndY, 6, ExtraCycles::None),
+    (Opcode::StaZp, 3, ExtraCycles::None),
+    (Opcode::StaZpX, 4, ExtraCycles::None),
+    (Opcode::StxAbs, 4, ExtraCycles::None),
+    (Opcode::StxZp, 3, ExtraCycles::None),
+    (Opcode::StxZpY, 4, ExtraCycles::None),
+    (Opcode::StyAbs, 4, ExtraCycles::None),
+    (Opcode::StyZp, 3, ExtraCycles::None),
+    (Opcode::StyZpX, 4, ExtraCycles::None),
+    (Opcode::Tax, 2, ExtraCycles::None),
+    (Opcode::Tay, 2, ExtraCycles::None),
+    (Opcode::Tsx, 2, ExtraCycles::None),
+    (Opcode::Txa, 2, ExtraCycles::None),
+    (Opcode::Txs, 2, ExtraCycles::None),
+    (Opcode::Tya, 2, ExtraCycles::None),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OpMetadata {
+    pub opcode: Opcode,
+    pub mnemonic: &'static str,
+    pub addressing_mode: AddressingMode,
+    pub bytes: u16,
+    pub cycles: OpCycles,
+    pub extra_cycles: ExtraCycles,
+    pub flags_read: P,
+    pub flags_written: P,
+    pub registers_read: RegisterSet,
+    pub registers_written: RegisterSet,
+}
+
+impl OpMetadata {
+    #[must_use]
+    pub fn get(opcode: Opcode) -> Option<Self> {
+        let op_info = MOS_6502.get_op_info(&opcode)?;
+        let addressing_mode = *op_info.addressing_mode();
+        let mnemonic = opcode.mnemonic();
+        let (_, flags_read, flags_written, registers_read, registers_written) =
+            EFFECTS.iter().find(|e| e.0 == mnemonic)?;
+        let (_, cycles, extra_cycles) = TIMINGS.iter().find(|t| t.0 == opcode)?;
+
+        // Accumulator and indexed modes touch registers beyond those of the operation itself
+        let mode_registers = match addressing_mode {
+            AddressingMode::Accumulator => A,
+            AddressingMode::AbsoluteX
+            | AddressingMode::IndexedIndirectX
+            | AddressingMode::ZeroPageX => X,
+            AddressingMode::AbsoluteY
+            | AddressingMode::IndirectIndexedY
+            | AddressingMode::ZeroPageY => Y,
+            _ => NONE,
+        };
+        let registers_written = if addressing_mode == AddressingMode::Accumulator {
+            *registers_written | A
+        } else {
+            *registers_written
+        };
+
+        Some(Self {
+            opcode,
+            mnemonic,
+            addressing_mode,
+            bytes: addressing_mode.size(),
+            cycles: *cycles,
+            extra_cycles: *extra_cycles,
+            flags_read: *flags_read,
+            flags_written: *flags_written,
+            registers_read: *registers_read | mode_registers,
+            registers_written,
+        })
+    }
+
+    // All documented opcodes in opcode order
+    pub fn iter() -> impl Iterator<Item = Self> {
+        (0..=u8::MAX)
+            .filter_map(Opcode::from_u8)
+            .filter_map(Self::get)
+    }
+
+    #[must_use]
+    pub fn for_mnemonic(mnemonic: &str) -> Vec<Self> {
+        Self::iter()
+            .filter(|m| m.mnemonic.eq_ignore_ascii_case(mnemonic))
+            .collect()
+    }
+
+    // One row per opcode with a heading, as shown by "help <MNEMONIC>" and "r6502 opcodes"
+    pub fn format_table(items: impl IntoIterator<Item = Self>) -> String {
+        let mut s = format!(
+            "{:<4} {:<4} {:<16} {:>5} {:>6} {:<7} {:<7} {:<9} {:<9} {}\n",
+            "Op",
+            "Name",
+            "Mode",
+            "Bytes",
+            "Cycles",
+            "Reads",
+            "Writes",
+            "Reg reads",
+            "Reg writes",
+            "Extra cycles"
+        );
+        for m in items {
+            let row = format!(
+                "{:<4} {:<4} {:<16} {:>5} {:>6} {:<7} {:<7} {:<9} {:<9} {}",
+                m.opcode.to_string(),
+                m.mnemonic,
+                format!("{:?}", m.addressing_mode),
+                m.bytes,
+                m.cycles,
+                Self::format_flags(m.flags_read),
+                Self::format_flags(m.flags_written),
+                m.registers_read.to_string(),
+                m.registers_written.to_string(),
+                m.extra_cycles
+            );
+            _ = writeln!(s, "{}", row.trim_end());
+        }
+        s
+    }
+
+    #[must_use]
+    pub fn format_flags(flags: P) -> String {
+        let s = [
+            (P::N, 'N'),
+            (P::V, 'V'),
+            (P::D, 'D'),
+            (P::I, 'I'),
+            (P::Z, 'Z'),
+            (P::C, 'C'),
+        ]
+        .iter()
+        .filter(|(flag, _)| flags.contains(*flag))
+        .map(|(_, c)| *c)
+        .collect::<String>();
+        if s.is_empty() { String::from("-") } else { s }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::{Bus, Cpu, ExtraCycles, OpInfo, OpMetadata, RegisterSet};
+    use crate::{InterruptChannel, OpCycles, Opcode, P};
+    use rstest::rstest;
+
+    // Cycles taken by the emulator to execute the instruction at $0EF0 with the operand bytes
+    // $02 (or the given low byte), the index registers set to the given value and the zero page
+    // pointer at $20 set to $0201
+    fn execute(opcode: Opcode, lo: u8, index: u8, p: P) -> OpCycles {
+        let bus = Bus::default();
+        for (addr, value) in (0x0ef0..).zip([opcode as u8, lo, 0x02]) {
+            bus.store(addr, value);
+        }
+        bus.store(0x0020, 0x01);
+        bus.store(0x0021, 0x02);
+        // BRK jumps through the IRQ vector to $0000 which must not hold another BRK
+        bus.store(0x0000, Opcode::Nop as u8);
+
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0ef0;
+        cpu.reg.x = index;
+        cpu.reg.y = index;
+        cpu.reg.p = p;
+        cpu.step_no_spin();
+        OpCycles::try_from(cpu.total_cycles).unwrap()
+    }
+
+    #[rstest]
+    #[case(Opcode::AdcAbsX, 4, ExtraCycles::PageCross, "DC", "NVZC", "A X", "A")]
+    #[case(Opcode::AslAcc, 2, ExtraCycles::None, "-", "NZC", "A", "A")]
+    #[case(Opcode::Beq, 2, ExtraCycles::Branch, "Z", "-", "PC", "PC")]
+    #[case(Opcode::IncAbsX, 7, ExtraCycles::None, "-", "NZ", "X", "-")]
+    #[case(Opcode::Jsr, 6, ExtraCycles::None, "-", "-", "SP PC", "SP PC")]
+    #[case(Opcode::StaIndY, 6, ExtraCycles::None, "-", "-", "A Y", "-")]
+    #[case(Opcode::Txs, 2, ExtraCycles::None, "-", "-", "X", "SP")]
+    fn get(
+        #[case] opcode: Opcode,
+        #[case] expected_cycles: u8,
+        #[case] expected_extra_cycles: ExtraCycles,
+        #[case] expected_flags_read: &str,
+        #[case] expected_flags_written: &str,
+        #[case] expected_registers_read: &str,
+        #[case] expected_registers_written: &str,
+    ) {
+        let metadata = OpMetadata::get(opcode).unwrap();
+        assert_eq!(expected_cycles, metadata.cycles);
+        assert_eq!(expected_extra_cycles, metadata.extra_cycles);
+        assert_eq!(
+            expected_flags_read,
+            OpMetadata::format_flags(metadata.flags_read)
+        );
+        assert_eq!(
+            expected_flags_written,
+            OpMetadata::format_flags(metadata.flags_written)
+        );
+        assert_eq!(expected_registers_read, metadata.registers_read.to_string());
+        assert_eq!(
+            expected_registers_written,
+            metadata.registers_written.to_string()
+        );
+    }
+
+    #[test]
+    fn every_opcode() {
+        assert_eq!(OpInfo::iter().count(), OpMetadata::iter().count());
+        for op_info in OpInfo::iter() {
+            let metadata = op_info.metadata().unwrap();
+            assert_eq!(op_info.opcode().mnemonic(), metadata.mnemonic);
+            assert_eq!(op_info.addressing_mode().size(), metadata.bytes);
+            assert!(metadata.cycles >= 2);
+        }
+    }
+
+    // Indexing by $FF from $0220 or through the pointer at $20 crosses a page while indexing by
+    // zero doesn't: a branch from $0EF2 by $02 stays on the page while one by $20 leaves it
+    #[test]
+    fn every_opcode_timing() {
+        for metadata in OpMetadata::iter() {
+            let opcode = metadata.opcode;
+            let base = metadata.cycles;
+            match metadata.extra_cycles {
+                ExtraCycles::None | ExtraCycles::PageCross => {
+                    let extra = OpCycles::from(metadata.extra_cycles == ExtraCycles::PageCross);
+                    assert_eq!(base, execute(opcode, 0x20, 0x00, P::empty()), "{opcode:?}");
+                    assert_eq!(
+                        base + extra,
+                        execute(opcode, 0x20, 0xff, P::empty()),
+                        "{opcode:?}"
+                    );
+                }
+                ExtraCycles::Branch => {
+                    for (lo, extra) in [(0x02, 1), (0x20, 2)] {
+                        let mut cycles =
+                            [P::empty(), P::all()].map(|p| execute(opcode, lo, 0x00, p));
+                        cycles.sort_unstable();
+                        assert_eq!([base, base + extra], cycles, "{opcode:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn for_mnemonic() {
+        let metadata = OpMetadata::for_mnemonic("lda");
+        assert_eq!(8, metadata.len());
+        assert!(metadata.iter().all(|m| m.flags_written == P::N | P::Z));
+        assert!(
+            metadata
+                .iter()
+                .all(|m| m.registers_written == RegisterSet::A)
+        );
+        assert!(OpMetadata::for_mnemonic("XYZ").is_empty());
+    }
+
+    #[test]
+    fn format_table() {
+        let s = OpMetadata::format_table(OpMetadata::for_mnemonic("BNE"));
+        let lines = s.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+        assert_eq!(
+            "$D0  BNE  Relative             2      2 Z       -       PC        PC        +1 if taken, +2 if page crossed",
+            lines[1]
+        );
+    }
+}
@@ -1,53 +1,53 @@
-use crate::P;
+use crate::Opcode;
 use crate::emulator::Cpu;
 use crate::emulator::ops::BranchResult;
 
 // http://www.6502.org/tutorials/6502opcodes.html#BCC
 // http://www.6502.org/users/obelisk/6502/reference.html#BCC
 pub fn bcc(cpu: &mut Cpu, offset: u8) -> BranchResult {
-    BranchResult::compute(cpu, offset, P::C, false)
+    BranchResult::compute(cpu, Opcode::Bcc, offset)
 }
 
 // http://www.6502.org/tutorials/6502opcodes.html#BCS
 // http://www.6502.org/users/obelisk/6502/reference.html#BCS
 pub fn bcs(cpu: &mut Cpu, offset: u8) -> BranchResult {
-    BranchResult::compute(cpu, offset, P::C, true)
+    BranchResult::compute(cpu, Opcode::Bcs, offset)
 }
 
 // http://www.6502.org/tutorials/6502opcodes.html#BEQ
 // http://www.6502.org/users/obelisk/6502/reference.html#BEQ
 pub fn beq(cpu: &mut Cpu, offset: u8) -> BranchResult {
-    BranchResult::compute(cpu, offset, P::Z, true)
+    BranchResult::compute(cpu, Opcode::Beq, offset)
 }
 
 // http://www.6502.org/tutorials/6502opcodes.html#BMI
 // http://www.6502.org/users/obelisk/6502/reference.html#BMI
 pub fn bmi(cpu: &mut Cpu, offset: u8) -> BranchResult {
-    BranchResult::compute(cpu, offset, P::N, true)
+    BranchResult::compute(cpu, Opcode::Bmi, offset)
 }
 
 // http://www.6502.org/tutorials/6502opcodes.html#BNE
 // http://www.6502.org/users/obelisk/6502/reference.html#BNE
 pub fn bne(cpu: &mut Cpu, offset: u8) -> BranchResult {
-    BranchResult::compute(cpu, offset, P::Z, false)
+    BranchResult::compute(cpu, Opcode::Bne, offset)
 }
 
 // http://www.6502.org/tutorials/6502opcodes.html#BPL
 // http://www.6502.org/users/obelisk/6502/reference.html#BPL
 pub fn bpl(cpu: &mut Cpu, offset: u8) -> BranchResult {
-    BranchResult::compute(cpu, offset, P::N, false)
+    BranchResult::compute(cpu, Opcode::Bpl, offset)
 }
 
 // http://www.6502.org/tutorials/6502opcodes.html#BVC
 // http://www.6502.org/users/obelisk/6502/reference.html#BVC
 pub fn bvc(cpu: &mut Cpu, offset: u8) -> BranchResult {
-    BranchResult::compute(cpu, offset, P::V, false)
+    BranchResult::compute(cpu, Opcode::Bvc, offset)
 }
 
 // http://www.6502.org/tutorials/6502opcodes.html#BVS
 // http://www.6502.org/users/obelisk/6502/reference.html#BVS
 pub fn bvs(cpu: &mut Cpu, offset: u8) -> BranchResult {
-    BranchResult::compute(cpu, offset, P::V, true)
+    BranchResult::compute(cpu, Opcode::Bvs, offset)
 }
 
 #[cfg(test)]
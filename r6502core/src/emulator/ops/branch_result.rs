@@ -1,8 +1,8 @@
-use crate::P;
 use crate::emulator::Cpu;
+use crate::{Opcode, P, Reg};
 use r6502lib::num::SignExtend;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum BranchResult {
     NotTaken,
     Taken,
@@ -10,22 +10,76 @@ pub enum BranchResult {
 }
 
 impl BranchResult {
-    pub fn compute(cpu: &mut Cpu, offset: u8, p: P, flag_value: bool) -> Self {
-        if cpu.reg.p.contains(p) == flag_value {
-            let new_pc = cpu.reg.pc.wrapping_add(u16::sign_extend(offset));
+    // Executes the branch with PC already advanced past its operand
+    pub fn compute(cpu: &mut Cpu, opcode: Opcode, offset: u8) -> Self {
+        let (result, new_pc) =
+            Self::resolve(opcode, cpu.reg.p, cpu.reg.pc, offset).expect("Must be a branch");
+        cpu.reg.pc = new_pc;
+        result
+    }
 
-            let current_page = cpu.reg.pc >> 8;
-            let new_page = new_pc >> 8;
+    // Outcome of the branch instruction at PC given registers before it executes
+    #[must_use]
+    pub fn evaluate(opcode: Opcode, reg: &Reg, offset: u8) -> Option<Self> {
+        Self::resolve(opcode, reg.p, reg.pc.wrapping_add(2), offset).map(|(result, _)| result)
+    }
 
-            cpu.reg.pc = new_pc;
+    // Outcome and new PC given the address of the instruction following the branch
+    fn resolve(opcode: Opcode, p: P, pc: u16, offset: u8) -> Option<(Self, u16)> {
+        let (flag, flag_value) = Self::condition(opcode)?;
+        if p.contains(flag) != flag_value {
+            return Some((Self::NotTaken, pc));
+        }
 
-            if new_page == current_page {
-                Self::Taken
-            } else {
-                Self::TakenCrossPage
-            }
+        let new_pc = pc.wrapping_add(u16::sign_extend(offset));
+        if new_pc >> 8 == pc >> 8 {
+            Some((Self::Taken, new_pc))
         } else {
-            Self::NotTaken
+            Some((Self::TakenCrossPage, new_pc))
+        }
+    }
+
+    // Flag tested by each branch and the value for which it is taken
+    const fn condition(opcode: Opcode) -> Option<(P, bool)> {
+        match opcode {
+            Opcode::Bcc => Some((P::C, false)),
+            Opcode::Bcs => Some((P::C, true)),
+            Opcode::Beq => Some((P::Z, true)),
+            Opcode::Bmi => Some((P::N, true)),
+            Opcode::Bne => Some((P::Z, false)),
+            Opcode::Bpl => Some((P::N, false)),
+            Opcode::Bvc => Some((P::V, false)),
+            Opcode::Bvs => Some((P::V, true)),
+            _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::ops::BranchResult;
+    use crate::{Opcode, P, Reg};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Some(BranchResult::NotTaken), Opcode::Beq, false, 0x0ffe, 0x10)]
+    #[case(Some(BranchResult::Taken), Opcode::Beq, true, 0x0ffe, 0x10)]
+    #[case(Some(BranchResult::Taken), Opcode::Beq, true, 0x10ee, 0xf0)]
+    #[case(Some(BranchResult::TakenCrossPage), Opcode::Beq, true, 0x0ffe, 0xf0)]
+    #[case(Some(BranchResult::Taken), Opcode::Bne, false, 0x0ffe, 0x10)]
+    #[case(None, Opcode::Nop, true, 0x0ffe, 0x10)]
+    fn evaluate(
+        #[case] expected: Option<BranchResult>,
+        #[case] opcode: Opcode,
+        #[case] z: bool,
+        #[case] pc: u16,
+        #[case] offset: u8,
+    ) {
+        let reg = Reg {
+            p: if z { P::Z } else { P::empty() },
+            pc,
+            ..Reg::default()
+        };
+        assert_eq!(expected, BranchResult::evaluate(opcode, &reg, offset));
+    }
+}
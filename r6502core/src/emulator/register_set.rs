@@ -0,0 +1,23 @@
+use bitflags::bitflags;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct RegisterSet: u8 {
+        const A =   0b0000_0001;
+        const X =   0b0000_0010;
+        const Y =   0b0000_0100;
+        const SP =  0b0000_1000;
+        const PC =  0b0001_0000;
+    }
+}
+
+impl Display for RegisterSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        let names = self.iter_names().map(|(name, _)| name).collect::<Vec<_>>();
+        write!(f, "{}", names.join(" "))
+    }
+}
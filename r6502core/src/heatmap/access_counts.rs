@@ -0,0 +1,13 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccessCounts {
+    pub reads: u64,
+    pub writes: u64,
+    pub executes: u64,
+}
+
+impl AccessCounts {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.reads == 0 && self.writes == 0 && self.executes == 0
+    }
+}
@@ -0,0 +1,57 @@
+use crate::Reg;
+use crate::emulator::{BusMonitor, InstructionInfo, Monitor};
+use crate::heatmap::SharedHeatmap;
+use r6502lib::TotalCycles;
+use std::rc::Rc;
+
+// Attach to the CPU as a monitor and to its bus view as a bus monitor
+#[derive(Clone)]
+pub struct HeatmapMonitor {
+    heatmap: SharedHeatmap,
+}
+
+impl HeatmapMonitor {
+    #[must_use]
+    pub fn new(heatmap: &SharedHeatmap) -> Self {
+        Self {
+            heatmap: Rc::clone(heatmap),
+        }
+    }
+
+    #[must_use]
+    pub const fn heatmap(&self) -> &SharedHeatmap {
+        &self.heatmap
+    }
+}
+
+impl Monitor for HeatmapMonitor {
+    fn on_before_execute(
+        &self,
+        _total_cycles: TotalCycles,
+        _reg: Reg,
+        instruction_info: InstructionInfo,
+    ) {
+        self.heatmap
+            .borrow_mut()
+            .begin_instruction(instruction_info.pc);
+    }
+
+    fn on_after_execute(
+        &self,
+        _total_cycles: TotalCycles,
+        _reg: Reg,
+        _instruction_info: InstructionInfo,
+    ) {
+        self.heatmap.borrow_mut().end_instruction();
+    }
+}
+
+impl BusMonitor for HeatmapMonitor {
+    fn on_load(&self, addr: u16, _value: u8) {
+        self.heatmap.borrow_mut().load(addr);
+    }
+
+    fn on_store(&self, addr: u16, _value: u8) {
+        self.heatmap.borrow_mut().store(addr);
+    }
+}
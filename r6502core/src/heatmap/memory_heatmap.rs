@@ -0,0 +1,207 @@
+use crate::DeviceInfo;
+use crate::heatmap::AccessCounts;
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::rc::Rc;
+
+const SIZE: usize = 0x10000;
+const IMAGE_SIZE: usize = 256;
+const PAGE_SIZE: usize = 256;
+const BLOCK_SIZE: usize = 16;
+const ZERO_PAGE: usize = 0x00;
+const STACK_PAGE: usize = 0x01;
+
+// Background of addresses never accessed in zero page and stack so they stand out
+const ZERO_PAGE_BACKGROUND: [u8; 3] = [0x30, 0x30, 0x00];
+const STACK_BACKGROUND: [u8; 3] = [0x00, 0x30, 0x30];
+
+pub type SharedHeatmap = Rc<RefCell<MemoryHeatmap>>;
+
+// Reads, writes and executes per address over a run
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryHeatmap {
+    counts: Vec<AccessCounts>,
+    executing: bool,
+}
+
+impl Default for MemoryHeatmap {
+    fn default() -> Self {
+        Self {
+            counts: vec![AccessCounts::default(); SIZE],
+            executing: false,
+        }
+    }
+}
+
+impl MemoryHeatmap {
+    // Opcode fetches and accesses made by the debugger between instructions aren't counted
+    pub fn begin_instruction(&mut self, pc: u16) {
+        self.counts[usize::from(pc)].executes += 1;
+        self.executing = true;
+    }
+
+    pub const fn end_instruction(&mut self) {
+        self.executing = false;
+    }
+
+    pub fn load(&mut self, addr: u16) {
+        if self.executing {
+            self.counts[usize::from(addr)].reads += 1;
+        }
+    }
+
+    pub fn store(&mut self, addr: u16) {
+        if self.executing {
+            self.counts[usize::from(addr)].writes += 1;
+        }
+    }
+
+    #[must_use]
+    pub fn counts(&self, addr: u16) -> AccessCounts {
+        self.counts[usize::from(addr)]
+    }
+
+    // Addresses accessed at least once along with the device mapped there
+    #[must_use]
+    pub fn csv(&self, devices: &[DeviceInfo]) -> String {
+        let mut s = String::from("address,device,range,reads,writes,executes\n");
+        for (addr, counts) in self.iter_accessed() {
+            let device = devices.iter().find(|d| d.address_range.contains(addr));
+            _ = writeln!(
+                s,
+                "${addr:04X},{},{},{},{},{}",
+                device.map_or("", |d| d.state.kind.as_str()),
+                device.map_or_else(String::new, |d| d.address_range.to_string()),
+                counts.reads,
+                counts.writes,
+                counts.executes
+            );
+        }
+        s
+    }
+
+    // Binary PPM with each page drawn as a 16x16 block, pages in rows of 16 from zero page at top
+    // left: red is writes, green executes and blue reads, on a log scale
+    #[must_use]
+    pub fn ppm(&self) -> Vec<u8> {
+        let max = self
+            .counts
+            .iter()
+            .fold(AccessCounts::default(), |m, c| AccessCounts {
+                reads: m.reads.max(c.reads),
+                writes: m.writes.max(c.writes),
+                executes: m.executes.max(c.executes),
+            });
+
+        let mut pixels = vec![0; IMAGE_SIZE * IMAGE_SIZE * 3];
+        for (addr, counts) in self.counts.iter().enumerate() {
+            let (page, offset) = (addr / PAGE_SIZE, addr % PAGE_SIZE);
+            let x = (page % BLOCK_SIZE) * BLOCK_SIZE + offset % BLOCK_SIZE;
+            let y = (page / BLOCK_SIZE) * BLOCK_SIZE + offset / BLOCK_SIZE;
+            let rgb = if counts.is_empty() {
+                match page {
+                    ZERO_PAGE => ZERO_PAGE_BACKGROUND,
+                    STACK_PAGE => STACK_BACKGROUND,
+                    _ => [0, 0, 0],
+                }
+            } else {
+                [
+                    Self::intensity(counts.writes, max.writes),
+                    Self::intensity(counts.executes, max.executes),
+                    Self::intensity(counts.reads, max.reads),
+                ]
+            };
+            let i = (y * IMAGE_SIZE + x) * 3;
+            pixels[i..i + 3].copy_from_slice(&rgb);
+        }
+
+        let mut ppm = format!("P6\n{IMAGE_SIZE} {IMAGE_SIZE}\n255\n").into_bytes();
+        ppm.extend(pixels);
+        ppm
+    }
+
+    fn iter_accessed(&self) -> impl Iterator<Item = (u16, &AccessCounts)> {
+        (0..=u16::MAX)
+            .zip(&self.counts)
+            .filter(|(_, counts)| !counts.is_empty())
+    }
+
+    // Log scale with a floor so that a single access still shows up
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn intensity(count: u64, max: u64) -> u8 {
+        if count == 0 {
+            return 0;
+        }
+        let scale = ((count as f64).ln_1p() / (max as f64).ln_1p()).min(1.0);
+        (64.0 + scale * 191.0) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::{Bus, BusView, Cpu, Monitor};
+    use crate::heatmap::{AccessCounts, HeatmapMonitor, MemoryHeatmap};
+    use crate::{InterruptChannel, Opcode};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        // LDA $70; STA $0200
+        let bus = Bus::default();
+        for (addr, value) in [
+            (0x0e00, Opcode::LdaZp as u8),
+            (0x0e01, 0x70),
+            (0x0e02, Opcode::StaAbs as u8),
+            (0x0e03, 0x00),
+            (0x0e04, 0x02),
+        ] {
+            bus.store(addr, value);
+        }
+
+        let heatmap = Rc::new(RefCell::new(MemoryHeatmap::default()));
+        let bus_monitor = HeatmapMonitor::new(&heatmap);
+        let monitor: Box<dyn Monitor> = Box::new(bus_monitor.clone());
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(
+            BusView::with_monitor(&bus, &bus_monitor),
+            Some(monitor),
+            interrupt_channel.rx,
+        );
+        cpu.reg.pc = 0x0e00;
+        cpu.step_with_monitor_callbacks();
+        cpu.step_with_monitor_callbacks();
+        _ = cpu.bus.load(0x0e05);
+
+        let heatmap = heatmap.borrow();
+        assert_eq!(
+            AccessCounts {
+                reads: 0,
+                writes: 0,
+                executes: 1
+            },
+            heatmap.counts(0x0e00)
+        );
+        assert_eq!(1, heatmap.counts(0x0070).reads);
+        assert_eq!(1, heatmap.counts(0x0200).writes);
+        assert!(heatmap.counts(0x0e05).is_empty());
+
+        let devices = bus.devices();
+        assert_eq!(
+            "address,device,range,reads,writes,executes\n\
+            $0070,RAM,$0000:$FFFF,1,0,0\n\
+            $0200,RAM,$0000:$FFFF,0,1,0\n\
+            $0E00,RAM,$0000:$FFFF,0,0,1\n\
+            $0E02,RAM,$0000:$FFFF,0,0,1\n",
+            heatmap.csv(&devices)
+        );
+
+        let ppm = heatmap.ppm();
+        assert!(ppm.starts_with(b"P6\n256 256\n255\n"));
+        assert_eq!(15 + 256 * 256 * 3, ppm.len());
+    }
+}
@@ -0,0 +1,7 @@
+mod access_counts;
+mod heatmap_monitor;
+mod memory_heatmap;
+
+pub use access_counts::*;
+pub use heatmap_monitor::*;
+pub use memory_heatmap::*;
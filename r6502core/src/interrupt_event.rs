@@ -1,11 +1,37 @@
 use r6502lib::Channel;
+use r6502lib::constants::{IRQ, NMI, RESET};
+use std::fmt::{Display, Formatter, Result as FmtResult};
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum InterruptEvent {
     Irq,
-    #[allow(unused)]
     Nmi,
-    #[allow(unused)]
     Reset,
 }
 
+impl InterruptEvent {
+    #[must_use]
+    pub const fn vector(self) -> u16 {
+        match self {
+            Self::Irq => IRQ,
+            Self::Nmi => NMI,
+            Self::Reset => RESET,
+        }
+    }
+}
+
+impl Display for InterruptEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Irq => "IRQ",
+                Self::Nmi => "NMI",
+                Self::Reset => "RESET",
+            }
+        )
+    }
+}
+
 pub type InterruptChannel = Channel<InterruptEvent>;
@@ -1,9 +1,20 @@
+pub mod asm;
+pub mod coverage;
+pub mod debugger;
+pub mod disasm;
 pub mod emulator;
+pub mod heatmap;
 pub mod messages;
+pub mod profiler;
+pub mod sanitizer;
+pub mod stats;
 pub mod symbols;
+pub mod trace;
 
 mod bus_device;
+mod device_info;
 mod device_mapping;
+mod device_state;
 mod frequency;
 mod interrupt_event;
 mod op_cycles;
@@ -15,7 +26,9 @@ mod reg;
 mod rom;
 
 pub use bus_device::*;
+pub use device_info::*;
 pub use device_mapping::*;
+pub use device_state::*;
 pub use frequency::*;
 pub use interrupt_event::*;
 pub use op_cycles::*;
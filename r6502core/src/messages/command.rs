@@ -1,62 +1,251 @@
+use crate::InterruptEvent;
+use crate::debugger::{Expr, WatchExpr, WatchKind};
+use crate::emulator::OpMetadata;
+use crate::messages::Location;
 use anyhow::{Error, bail};
-use r6502lib::AddressRange;
+use r6502lib::TotalCycles;
 use std::str::FromStr;
 
-const HELP: &str = "?/h/help: Show help message\n\
+const HELP: &str = "?/h/help (<MNEMONIC>): Show help message or opcodes of instruction\n\
     m/mem/memory <START>(:<END>): Dump block of memory\n\
     pc <ADDRESS>: Set program counter\n\
-    go <ADDRESS>: Set program counter and start program\n";
+    go <ADDRESS>: Set program counter and start program\n\
+    v/view <ADDRESS>: Show memory at address in memory pane\n\
+    a <ADDRESS>: Assemble instructions into memory one per line until empty line\n\
+    bp <ADDRESS>|<FILE>:<LINE> (if <CONDITION>): Set breakpoint\n\
+    bc <ADDRESS>|<FILE>:<LINE>: Clear breakpoint\n\
+    bl: List breakpoints\n\
+    wp <START>(:<END>) (read|write|access): Set watchpoint, stopping on write by default\n\
+    wc <START>(:<END>): Clear watchpoints\n\
+    wl: List watchpoints\n\
+    watch <EXPRESSION> (<FORMAT>): Watch value of expression or data at address it evaluates to\n\
+    unwatch <N>: Remove watch\n\
+    irq (<CYCLES>): Hold IRQ line until IRQ is taken or for number of cycles\n\
+    nmi: Take NMI\n\
+    reset: Take RESET\n\
+    istop: Toggle stopping at first instruction of interrupt handlers\n\
+    devices: Show memory map\n\
+    Up/Down: Command history, Tab: Complete command or symbol\n\
+    Addresses and conditions are expressions e.g. $0E00, %1010, 42, buffer+$20,\n\
+    A/X/Y/SP/PC/P, flags N/V/B/D/I/Z/C, [addr] (byte), {addr} (word), <lo, >hi,\n\
+    + - * / & | ^ ~, = <> < <= > >=, && ||\n\
+    Symbols take precedence over registers and flags with the same name\n\
+    Bare numbers in addresses are hex e.g. m e00:eff, elsewhere decimal, and names that\n\
+    aren't symbols, registers or flags are hex e.g. bc ffee\n\
+    Watch formats are byte, word, signed, bcd, string, crstring (CR-terminated) and float\n\
+    (BBC BASIC) e.g. watch {$12} for BASIC's TOP, watch [$18]*$100 for PAGE\n";
+
+// Bare numbers in addresses are hex as in a machine-code monitor
+const ADDRESS_RADIX: u32 = 16;
+
+// Names offered by tab completion
+pub const COMMAND_NAMES: &[&str] = &[
+    "?", "h", "help", "m", "mem", "memory", "pc", "go", "v", "view", "a", "bp", "bc", "bl", "wp",
+    "wc", "wl", "watch", "unwatch", "irq", "nmi", "reset", "istop", "devices",
+];
 
 #[derive(Debug, PartialEq)]
 pub enum Command {
-    Help(&'static str),
-    FetchMemory(AddressRange),
-    SetPc(u16),
-    Go(u16),
+    Help(String),
+    FetchMemory(Expr, Expr),
+    SetPc(Expr),
+    Go(Expr),
+    ViewMemory(Expr),
+    Assemble(Expr),
+    SetBreakpoint(Location, Option<Expr>),
+    ClearBreakpoint(Location),
+    ListBreakpoints,
+    SetWatchpoint(Expr, Expr, WatchKind),
+    ClearWatchpoint(Expr, Expr),
+    ListWatchpoints,
+    AddWatch(WatchExpr),
+    RemoveWatch(usize),
+    AssertIrq(Option<TotalCycles>),
+    Interrupt(InterruptEvent),
+    ToggleStopOnInterrupt,
+    ListDevices,
 }
 
 impl FromStr for Command {
     type Err = Error;
 
+    #[allow(clippy::too_many_lines)]
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.split_whitespace().collect::<Vec<_>>();
-        if parts.is_empty() {
+        // Arguments are expressions which may contain spaces
+        let s = s.trim();
+        let (name, args) = s
+            .split_once(char::is_whitespace)
+            .map_or((s, ""), |(name, args)| (name, args.trim()));
+        if name.is_empty() {
             bail!("invalid command {s}")
         }
 
         // Help
-        if parts[0] == "?" || parts[0] == "h" || parts[0] == "help" {
-            return Ok(Self::Help(HELP));
+        if name == "?" || name == "h" || name == "help" {
+            if args.is_empty() {
+                return Ok(Self::Help(String::from(HELP)));
+            }
+
+            let metadata = OpMetadata::for_mnemonic(args);
+            if metadata.is_empty() {
+                bail!("unknown instruction {args}")
+            }
+
+            return Ok(Self::Help(OpMetadata::format_table(metadata)));
         }
 
         // Fetch snapshot of memory
-        if parts[0] == "m" || parts[0] == "mem" || parts[0] == "memory" {
-            if parts.len() != 2 {
+        if name == "m" || name == "mem" || name == "memory" {
+            if args.is_empty() {
                 bail!("invalid \"memory\" command")
             }
 
-            let address_range = AddressRange::parse_no_sigils(parts[1])?;
-            return Ok(Self::FetchMemory(address_range));
+            let (start, end) = Expr::parse_range(args, ADDRESS_RADIX)?;
+            return Ok(Self::FetchMemory(start, end));
         }
 
         // Set program counter
-        if parts[0] == "pc" {
-            if parts.len() != 2 {
+        if name == "pc" {
+            if args.is_empty() {
                 bail!("invalid \"pc\" command")
             }
 
-            let addr = u16::from_str_radix(parts[1].trim(), 16)?;
-            return Ok(Self::SetPc(addr));
+            return Ok(Self::SetPc(Expr::parse_radix(args, ADDRESS_RADIX)?));
         }
 
         // Set program counter, clear B and restart
-        if parts[0] == "go" {
-            if parts.len() != 2 {
+        if name == "go" {
+            if args.is_empty() {
                 bail!("invalid \"go\" command")
             }
 
-            let addr = u16::from_str_radix(parts[1].trim(), 16)?;
-            return Ok(Self::Go(addr));
+            return Ok(Self::Go(Expr::parse_radix(args, ADDRESS_RADIX)?));
+        }
+
+        // Start assembling instructions at address
+        if name == "a" {
+            if args.is_empty() {
+                bail!("invalid \"a\" command")
+            }
+
+            return Ok(Self::Assemble(Expr::parse_radix(args, ADDRESS_RADIX)?));
+        }
+
+        // Move memory pane
+        if name == "v" || name == "view" {
+            if args.is_empty() {
+                bail!("invalid \"view\" command")
+            }
+
+            return Ok(Self::ViewMemory(Expr::parse_radix(args, ADDRESS_RADIX)?));
+        }
+
+        // Set breakpoint
+        if name == "bp" {
+            if args.is_empty() {
+                bail!("invalid \"bp\" command")
+            }
+
+            let (location, condition) = Location::parse_with_condition(args, ADDRESS_RADIX)?;
+            return Ok(Self::SetBreakpoint(location, condition));
+        }
+
+        // Clear breakpoint
+        if name == "bc" {
+            if args.is_empty() {
+                bail!("invalid \"bc\" command")
+            }
+
+            return Ok(Self::ClearBreakpoint(Location::parse_radix(
+                args,
+                ADDRESS_RADIX,
+            )?));
+        }
+
+        // List breakpoints
+        if name == "bl" {
+            return Ok(Self::ListBreakpoints);
+        }
+
+        // Set watchpoint
+        if name == "wp" {
+            if args.is_empty() {
+                bail!("invalid \"wp\" command")
+            }
+
+            let (range, kind) = match args.rsplit_once(char::is_whitespace) {
+                Some((range, "read")) => (range, WatchKind::Read),
+                Some((range, "write")) => (range, WatchKind::Write),
+                Some((range, "access")) => (range, WatchKind::Access),
+                _ => (args, WatchKind::Write),
+            };
+            let (start, end) = Expr::parse_range(range.trim(), ADDRESS_RADIX)?;
+            return Ok(Self::SetWatchpoint(start, end, kind));
+        }
+
+        // Clear watchpoint
+        if name == "wc" {
+            if args.is_empty() {
+                bail!("invalid \"wc\" command")
+            }
+
+            let (start, end) = Expr::parse_range(args, ADDRESS_RADIX)?;
+            return Ok(Self::ClearWatchpoint(start, end));
+        }
+
+        // List watchpoints
+        if name == "wl" {
+            return Ok(Self::ListWatchpoints);
+        }
+
+        // Show memory map
+        if name == "devices" {
+            return Ok(Self::ListDevices);
+        }
+
+        // Add expression to watch pane
+        if name == "watch" {
+            if args.is_empty() {
+                bail!("invalid \"watch\" command")
+            }
+
+            return Ok(Self::AddWatch(args.parse()?));
+        }
+
+        // Remove expression from watch pane
+        if name == "unwatch" {
+            if args.is_empty() {
+                bail!("invalid \"unwatch\" command")
+            }
+
+            return Ok(Self::RemoveWatch(args.parse()?));
+        }
+
+        // Hold IRQ line
+        if name == "irq" {
+            if args.is_empty() {
+                return Ok(Self::AssertIrq(None));
+            }
+
+            return Ok(Self::AssertIrq(Some(args.parse()?)));
+        }
+
+        // Take NMI or RESET
+        if name == "nmi" || name == "reset" {
+            if !args.is_empty() {
+                bail!("invalid \"{name}\" command")
+            }
+
+            return Ok(Self::Interrupt(if name == "nmi" {
+                InterruptEvent::Nmi
+            } else {
+                InterruptEvent::Reset
+            }));
+        }
+
+        // Toggle stopping on interrupt entry
+        if name == "istop" {
+            return Ok(Self::ToggleStopOnInterrupt);
         }
 
         bail!("unsupported command {s}");
@@ -65,15 +254,144 @@ impl FromStr for Command {
 
 #[cfg(test)]
 mod tests {
-    use crate::messages::Command;
+    use crate::InterruptEvent;
+    use crate::debugger::{BinaryOp, Expr, WatchExpr, WatchFormat, WatchKind};
+    use crate::emulator::OpMetadata;
+    use crate::messages::{Command, Location};
     use anyhow::Result;
-    use r6502lib::AddressRange;
     use rstest::rstest;
 
+    fn symbol(name: &str) -> Expr {
+        Expr::Symbol(String::from(name))
+    }
+
     #[rstest]
-    #[case(Command::FetchMemory(AddressRange::new(0x0e00, 0x0eff).expect("Must succeed")), "m e00:eff")]
+    #[case(
+        Command::FetchMemory(Expr::Number(0x0e00), Expr::Number(0x0eff)),
+        "m $e00:$eff"
+    )]
+    #[case(Command::FetchMemory(symbol("e00"), symbol("eff")), "m e00:eff")]
+    #[case(
+        Command::FetchMemory(Expr::Number(0x0e00), Expr::Number(0x0eff)),
+        "m 0e00:0eff"
+    )]
+    #[case(Command::SetPc(Expr::Number(0x0e00)), "pc 0e00")]
+    #[case(Command::Go(symbol("e00")), "go e00")]
+    #[case(
+        Command::FetchMemory(
+            symbol("buffer"),
+            Expr::Binary(
+                BinaryOp::Add,
+                Box::new(symbol("buffer")),
+                Box::new(Expr::Number(0x20))
+            )
+        ),
+        "m buffer:buffer+$20"
+    )]
+    #[case(Command::SetPc(symbol("main")), "pc main")]
+    #[case(Command::Assemble(symbol("main")), "a main")]
+    #[case(Command::Go(Expr::Word(Box::new(Expr::Number(0xfffc)))), "go {$FFFC}")]
+    #[case(Command::ViewMemory(Expr::Number(0x0700)), "view $700")]
+    #[case(
+        Command::SetBreakpoint(Location::Expr(Expr::Number(0xffee)), None),
+        "bp $ffee"
+    )]
+    #[case(
+        Command::SetBreakpoint(Location::SourceLine(String::from("main.s"), 123), None),
+        "bp main.s:123"
+    )]
+    #[case(
+        Command::SetBreakpoint(Location::Expr(symbol("OSWRCH")), None),
+        "bp OSWRCH"
+    )]
+    #[case(
+        Command::SetBreakpoint(
+            Location::Expr(symbol("OSWRCH")),
+            Some(Expr::Binary(BinaryOp::Eq, Box::new(symbol("A")), Box::new(Expr::Number(13))))
+        ),
+        "bp OSWRCH if A = 13"
+    )]
+    #[case(
+        Command::ClearBreakpoint(Location::Expr(Expr::Number(0x0e00))),
+        "bc $e00"
+    )]
+    #[case(Command::ClearBreakpoint(Location::Expr(symbol("e00"))), "bc e00")]
+    #[case(
+        Command::SetBreakpoint(
+            Location::Expr(Expr::Number(0x0200)),
+            Some(Expr::Binary(BinaryOp::Eq, Box::new(symbol("A")), Box::new(Expr::Number(13))))
+        ),
+        "bp 200 if A = 13"
+    )]
+    #[case(Command::ListBreakpoints, "bl")]
+    #[case(
+        Command::SetWatchpoint(Expr::Number(0x70), Expr::Number(0x70), WatchKind::Write),
+        "wp $70"
+    )]
+    #[case(
+        Command::SetWatchpoint(Expr::Number(0x70), Expr::Number(0x71), WatchKind::Access),
+        "wp 70:71 access"
+    )]
+    #[case(
+        Command::SetWatchpoint(
+            symbol("buffer"),
+            Expr::Binary(
+                BinaryOp::Add,
+                Box::new(symbol("buffer")),
+                Box::new(Expr::Number(0x0f))
+            ),
+            WatchKind::Read
+        ),
+        "wp buffer:buffer + $0f read"
+    )]
+    #[case(
+        Command::ClearWatchpoint(Expr::Number(0x70), Expr::Number(0x71)),
+        "wc $70:$71"
+    )]
+    #[case(Command::ListWatchpoints, "wl")]
+    #[case(Command::ListDevices, "devices")]
+    #[case(
+        Command::AddWatch(WatchExpr {
+            expr: Expr::Word(Box::new(Expr::Number(0x12))),
+            format: WatchFormat::Value
+        }),
+        "watch {$12}"
+    )]
+    #[case(
+        Command::AddWatch(WatchExpr {
+            expr: symbol("buffer"),
+            format: WatchFormat::CrString
+        }),
+        "watch buffer crstring"
+    )]
+    #[case(Command::RemoveWatch(2), "unwatch 2")]
+    #[case(Command::AssertIrq(None), "irq")]
+    #[case(Command::AssertIrq(Some(100)), "irq 100")]
+    #[case(Command::Interrupt(InterruptEvent::Nmi), "nmi")]
+    #[case(Command::Interrupt(InterruptEvent::Reset), "reset")]
+    #[case(Command::ToggleStopOnInterrupt, "istop")]
+    #[case(
+        Command::Help(OpMetadata::format_table(OpMetadata::for_mnemonic("TAX"))),
+        "help tax"
+    )]
     fn basics(#[case] expected_result: Command, #[case] input: &str) -> Result<()> {
         assert_eq!(expected_result, input.parse()?);
         Ok(())
     }
+
+    #[rstest]
+    #[case("m")]
+    #[case("pc $e00 $e01")]
+    #[case("bp main if")]
+    #[case("jump $e00")]
+    #[case("wp")]
+    #[case("wp $70 execute")]
+    #[case("watch")]
+    #[case("unwatch x")]
+    #[case("irq -1")]
+    #[case("nmi 1")]
+    #[case("help xyz")]
+    fn invalid(#[case] input: &str) {
+        assert!(input.parse::<Command>().is_err());
+    }
 }
@@ -1,4 +1,6 @@
-use r6502lib::AddressRange;
+use crate::debugger::{Expr, WatchExpr, Watchpoint};
+use crate::{InterruptEvent, Reg};
+use r6502lib::{AddressRange, TotalCycles};
 
 pub enum DebugMessage {
     Step,
@@ -7,4 +9,25 @@ pub enum DebugMessage {
     FetchMemory(AddressRange),
     SetPc(u16),
     Go(u16),
+    WatchMemory(AddressRange),
+    SetMemory(u16, Vec<u8>),
+    StepLine,
+    StepOver,
+    StepOut,
+    // Breakpoint with optional condition whose symbols have already been resolved
+    AddBreakpoint(u16, Option<Expr>),
+    RemoveBreakpoint(u16),
+    SetRegisters(Reg),
+    AddWatchpoint(Watchpoint),
+    RemoveWatchpoint(Watchpoint),
+    // Evaluate expressions against the current registers and memory
+    Evaluate(Vec<Expr>),
+    // Expressions whose symbols have already been resolved to evaluate at every stop
+    SetWatches(Vec<WatchExpr>),
+    // Service an interrupt straight away
+    Interrupt(InterruptEvent),
+    // Hold the IRQ line for the given number of cycles or, if none, until the IRQ is taken
+    AssertIrq(Option<TotalCycles>),
+    // Stop before the first instruction of each interrupt handler
+    StopOnInterrupt(bool),
 }
@@ -0,0 +1,92 @@
+use crate::debugger::Expr;
+use anyhow::{Error, Result, bail};
+use std::str::FromStr;
+
+// Location given as expression or source line e.g. "$FFEE", "OSWRCH+3" or "main.s:123"
+#[derive(Debug, PartialEq)]
+pub enum Location {
+    Expr(Expr),
+    SourceLine(String, usize),
+}
+
+impl Location {
+    // Bare numbers in the expression are in the given radix
+    pub fn parse_radix(s: &str, radix: u32) -> Result<Self> {
+        let s = s.trim();
+
+        // Qualified symbol names such as "print::loop" also contain colons
+        if let Some((file_name, line)) = s.rsplit_once(':')
+            && let Ok(line) = line.parse()
+        {
+            if file_name.is_empty() {
+                bail!("invalid location {s}")
+            }
+
+            return Ok(Self::SourceLine(String::from(file_name), line));
+        }
+
+        Ok(Self::Expr(Expr::parse_radix(s, radix)?))
+    }
+
+    // Parse breakpoint location with optional condition e.g. "print if A=$41": only the
+    // location's bare numbers are in the given radix
+    pub fn parse_with_condition(s: &str, radix: u32) -> Result<(Self, Option<Expr>)> {
+        match s.split_once(" if ") {
+            Some((location, condition)) => Ok((
+                Self::parse_radix(location, radix)?,
+                Some(condition.parse()?),
+            )),
+            None => Ok((Self::parse_radix(s, radix)?, None)),
+        }
+    }
+}
+
+impl FromStr for Location {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_radix(s, 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debugger::{BinaryOp, Expr};
+    use crate::messages::Location;
+    use anyhow::Result;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Location::Expr(Expr::Number(0xffee)), "$ffee")]
+    #[case(Location::Expr(Expr::Number(0x0e00)), "$0E00")]
+    #[case(Location::Expr(Expr::Symbol(String::from("OSWRCH"))), "OSWRCH")]
+    #[case(
+        Location::Expr(Expr::Symbol(String::from("print::loop"))),
+        "print::loop"
+    )]
+    #[case(Location::Expr(Expr::Symbol(String::from("start@done"))), "start@done")]
+    #[case(
+        Location::Expr(Expr::Binary(
+            BinaryOp::Add,
+            Box::new(Expr::Symbol(String::from("OSWRCH"))),
+            Box::new(Expr::Number(3))
+        )),
+        "OSWRCH + 3"
+    )]
+    #[case(
+        Location::SourceLine(String::from("lib/print.inc"), 3),
+        "lib/print.inc:3"
+    )]
+    fn basics(#[case] expected_result: Location, #[case] input: &str) -> Result<()> {
+        assert_eq!(expected_result, input.parse()?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(":12")]
+    #[case("$XYZ")]
+    #[case("1+")]
+    fn invalid(#[case] input: &str) {
+        assert!(input.parse::<Location>().is_err());
+    }
+}
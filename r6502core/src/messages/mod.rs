@@ -1,11 +1,15 @@
 mod command;
 mod debug_message;
 mod io_message;
+mod location;
 mod monitor_message;
+mod script_command;
 mod state;
 
 pub use command::*;
 pub use debug_message::*;
 pub use io_message::*;
+pub use location::*;
 pub use monitor_message::*;
+pub use script_command::*;
 pub use state::*;
@@ -1,6 +1,9 @@
-use crate::Reg;
+use crate::debugger::{CallFrame, StackIssue, WatchHit};
 use crate::emulator::InstructionInfo;
 use crate::messages::State;
+use crate::sanitizer::SanitizerIssue;
+use crate::{DeviceInfo, InterruptEvent, Reg};
+use anyhow::Result;
 use r6502lib::{AddressRange, TotalCycles};
 
 pub enum MonitorMessage {
@@ -20,4 +23,24 @@ pub enum MonitorMessage {
         address_range: AddressRange,
         snapshot: Vec<u8>,
     },
+    NotifyMemoryView {
+        address_range: AddressRange,
+        snapshot: Vec<u8>,
+    },
+    NotifyBreakpoint(u16),
+    NotifyWatchpoint(WatchHit),
+    NotifySanitizer(SanitizerIssue),
+    EvaluateResponse(Result<Vec<i32>>),
+    NotifyCallStack {
+        frames: Vec<CallFrame>,
+        issues: Vec<StackIssue>,
+        sp: u8,
+        stack: Vec<u8>,
+    },
+    NotifyWatches(Vec<Result<String>>),
+    NotifyInterrupt {
+        interrupt: InterruptEvent,
+        target: u16,
+    },
+    NotifyDevices(Vec<DeviceInfo>),
 }
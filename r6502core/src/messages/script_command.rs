@@ -0,0 +1,139 @@
+use crate::debugger::Expr;
+use crate::messages::Location;
+use anyhow::{Error, bail};
+use std::str::FromStr;
+
+// Command in a debugger script run without the text UI
+#[derive(Debug, PartialEq)]
+pub enum ScriptCommand {
+    Break(Location, Option<Expr>),
+    Run,
+    Step(usize),
+    Memory(Expr, Expr),
+    Registers,
+    Assert(Expr),
+}
+
+impl FromStr for ScriptCommand {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Arguments are expressions which may contain spaces
+        let s = s.trim();
+        let (name, args) = s
+            .split_once(char::is_whitespace)
+            .map_or((s, ""), |(name, args)| (name, args.trim()));
+
+        match name {
+            "break" | "bp" => {
+                if args.is_empty() {
+                    bail!("invalid \"break\" command")
+                }
+
+                let (location, condition) = Location::parse_with_condition(args, 10)?;
+                Ok(Self::Break(location, condition))
+            }
+            "run" => {
+                if !args.is_empty() {
+                    bail!("invalid \"run\" command")
+                }
+
+                Ok(Self::Run)
+            }
+            "step" => {
+                if args.is_empty() {
+                    Ok(Self::Step(1))
+                } else {
+                    Ok(Self::Step(args.parse()?))
+                }
+            }
+            "m" | "mem" | "memory" => {
+                if args.is_empty() {
+                    bail!("invalid \"memory\" command")
+                }
+
+                let (start, end) = Expr::parse_range(args, 10)?;
+                Ok(Self::Memory(start, end))
+            }
+            "reg" => {
+                if !args.is_empty() {
+                    bail!("invalid \"reg\" command")
+                }
+
+                Ok(Self::Registers)
+            }
+            "assert" => Ok(Self::Assert(args.parse()?)),
+            _ => bail!("unsupported command {s}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debugger::{BinaryOp, Expr};
+    use crate::messages::{Location, ScriptCommand};
+    use anyhow::Result;
+    use rstest::rstest;
+
+    fn eq(lhs: Expr, rhs: Expr) -> Expr {
+        Expr::Binary(BinaryOp::Eq, Box::new(lhs), Box::new(rhs))
+    }
+
+    #[rstest]
+    #[case(
+        ScriptCommand::Break(Location::Expr(Expr::Symbol(String::from("OSWRCH"))), None),
+        "break OSWRCH"
+    )]
+    #[case(
+        ScriptCommand::Break(Location::Expr(Expr::Number(0x0e00)), None),
+        "bp $e00"
+    )]
+    #[case(
+        ScriptCommand::Break(
+            Location::Expr(Expr::Number(0x0e00)),
+            Some(eq(Expr::Symbol(String::from("X")), Expr::Number(1)))
+        ),
+        "bp $e00 if X=1"
+    )]
+    #[case(ScriptCommand::Run, "run")]
+    #[case(ScriptCommand::Step(1), "step")]
+    #[case(ScriptCommand::Step(10), "step 10")]
+    #[case(
+        ScriptCommand::Memory(Expr::Number(0x0200), Expr::Number(0x020f)),
+        "mem $200:$20f"
+    )]
+    #[case(ScriptCommand::Registers, "reg")]
+    #[case(
+        ScriptCommand::Assert(eq(Expr::Symbol(String::from("A")), Expr::Number(0x41))),
+        "assert A=$41"
+    )]
+    #[case(
+        ScriptCommand::Assert(eq(
+            Expr::Symbol(String::from("pc")),
+            Expr::Symbol(String::from("OSWRCH"))
+        )),
+        "assert pc = OSWRCH"
+    )]
+    #[case(
+        ScriptCommand::Assert(eq(Expr::Symbol(String::from("C")), Expr::Number(1))),
+        "assert C=1"
+    )]
+    #[case(
+        ScriptCommand::Assert(eq(Expr::Byte(Box::new(Expr::Number(0x0200))), Expr::Number(0x41))),
+        "assert [$0200]=$41"
+    )]
+    fn basics(#[case] expected_result: ScriptCommand, #[case] input: &str) -> Result<()> {
+        assert_eq!(expected_result, input.parse()?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("run now")]
+    #[case("step x")]
+    #[case("assert")]
+    #[case("assert A=")]
+    #[case("jump 200")]
+    fn invalid(#[case] input: &str) {
+        assert!(input.parse::<ScriptCommand>().is_err());
+    }
+}
@@ -267,9 +267,9 @@ pub enum Opcode {
     SbcZpX = 0xf5,
     #[strum(props(mnemonic = "SEC"))]
     Sec = 0x38,
-    #[strum(props(mnemonic = "Sed"))]
+    #[strum(props(mnemonic = "SED"))]
     Sed = 0xf8,
-    #[strum(props(mnemonic = "Sei"))]
+    #[strum(props(mnemonic = "SEI"))]
     Sei = 0x78,
     #[strum(props(mnemonic = "STA"))]
     StaAbs = 0x8d,
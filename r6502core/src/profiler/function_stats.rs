@@ -0,0 +1,10 @@
+use r6502lib::TotalCycles;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FunctionStats {
+    pub calls: u64,
+    // Cycles spent in the function and everything it called
+    pub inclusive_cycles: TotalCycles,
+    // Cycles spent in the function's own instructions
+    pub exclusive_cycles: TotalCycles,
+}
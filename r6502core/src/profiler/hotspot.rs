@@ -0,0 +1,7 @@
+use r6502lib::TotalCycles;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hotspot {
+    pub count: u64,
+    pub cycles: TotalCycles,
+}
@@ -0,0 +1,9 @@
+mod function_stats;
+mod hotspot;
+mod profile;
+mod profiling_monitor;
+
+pub use function_stats::*;
+pub use hotspot::*;
+pub use profile::*;
+pub use profiling_monitor::*;
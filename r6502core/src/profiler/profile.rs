@@ -0,0 +1,303 @@
+use crate::emulator::InstructionInfo;
+use crate::profiler::{FunctionStats, Hotspot};
+use crate::symbols::MapFile;
+use crate::{Opcode, Operand, Reg};
+use r6502lib::TotalCycles;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+
+const MAX_HOTSPOTS: usize = 20;
+
+pub type SharedProfile = Rc<RefCell<Profile>>;
+
+struct Pending {
+    total_cycles: TotalCycles,
+    reg: Reg,
+    instruction_info: InstructionInfo,
+}
+
+// Cycles attributed to functions, identified by entry address, using a shadow call stack
+// maintained by watching JSR/RTS, BRK/RTI and interrupt entry
+#[derive(Default)]
+pub struct Profile {
+    stack: Vec<u16>,
+    pending: Option<Pending>,
+    functions: HashMap<u16, FunctionStats>,
+    hotspots: HashMap<u16, Hotspot>,
+    stacks: HashMap<Vec<u16>, TotalCycles>,
+    total_cycles: TotalCycles,
+}
+
+impl Profile {
+    #[must_use]
+    pub const fn total_cycles(&self) -> TotalCycles {
+        self.total_cycles
+    }
+
+    #[must_use]
+    pub const fn functions(&self) -> &HashMap<u16, FunctionStats> {
+        &self.functions
+    }
+
+    #[must_use]
+    pub const fn hotspots(&self) -> &HashMap<u16, Hotspot> {
+        &self.hotspots
+    }
+
+    // An instruction's cycles are only known once the next one is about to execute
+    pub fn before_execute(
+        &mut self,
+        total_cycles: TotalCycles,
+        reg: Reg,
+        instruction_info: InstructionInfo,
+    ) {
+        if let Some(pending) = self.pending.take() {
+            self.attribute(
+                pending.instruction_info.pc,
+                total_cycles.saturating_sub(pending.total_cycles),
+            );
+            self.follow(&pending, &reg, instruction_info.pc);
+        }
+
+        if self.stack.is_empty() {
+            self.enter(instruction_info.pc);
+        }
+
+        self.pending = Some(Pending {
+            total_cycles,
+            reg,
+            instruction_info,
+        });
+    }
+
+    // Attribute the cycles of the last instruction executed
+    pub fn finish(&mut self, total_cycles: TotalCycles) {
+        if let Some(pending) = self.pending.take() {
+            self.attribute(
+                pending.instruction_info.pc,
+                total_cycles.saturating_sub(pending.total_cycles),
+            );
+        }
+    }
+
+    #[must_use]
+    pub fn report(&self, map_file: &MapFile) -> String {
+        let percent = |cycles: TotalCycles| {
+            if self.total_cycles == 0 {
+                0.0
+            } else {
+                #[allow(clippy::cast_precision_loss)]
+                let value = cycles as f64 * 100.0 / self.total_cycles as f64;
+                value
+            }
+        };
+
+        let mut s = String::new();
+        _ = writeln!(s, "Total cycles: {}", self.total_cycles);
+        _ = writeln!(s);
+        _ = writeln!(
+            s,
+            "{:<24} {:>8} {:>12} {:>7} {:>12} {:>7}",
+            "Function", "Calls", "Inclusive", "%", "Exclusive", "%"
+        );
+        let mut functions = self.functions.iter().collect::<Vec<_>>();
+        functions.sort_by(|(a_addr, a), (b_addr, b)| {
+            b.inclusive_cycles
+                .cmp(&a.inclusive_cycles)
+                .then(a_addr.cmp(b_addr))
+        });
+        for (addr, stats) in functions {
+            _ = writeln!(
+                s,
+                "{:<24} {:>8} {:>12} {:>6.1}% {:>12} {:>6.1}%",
+                map_file.symbolize(*addr),
+                stats.calls,
+                stats.inclusive_cycles,
+                percent(stats.inclusive_cycles),
+                stats.exclusive_cycles,
+                percent(stats.exclusive_cycles)
+            );
+        }
+
+        _ = writeln!(s);
+        _ = writeln!(
+            s,
+            "{:<7} {:<24} {:>10} {:>12} {:>7}",
+            "Address", "Location", "Count", "Cycles", "%"
+        );
+        let mut hotspots = self.hotspots.iter().collect::<Vec<_>>();
+        hotspots
+            .sort_by(|(a_addr, a), (b_addr, b)| b.cycles.cmp(&a.cycles).then(a_addr.cmp(b_addr)));
+        for (addr, hotspot) in hotspots.into_iter().take(MAX_HOTSPOTS) {
+            _ = writeln!(
+                s,
+                "${:04X}   {:<24} {:>10} {:>12} {:>6.1}%",
+                addr,
+                map_file.symbolize(*addr),
+                hotspot.count,
+                hotspot.cycles,
+                percent(hotspot.cycles)
+            );
+        }
+        s
+    }
+
+    // One line per distinct call stack, outermost function first, as expected by
+    // flamegraph.pl, inferno and speedscope
+    #[must_use]
+    pub fn folded(&self, map_file: &MapFile) -> String {
+        let mut lines = self
+            .stacks
+            .iter()
+            .map(|(stack, cycles)| {
+                let names = stack
+                    .iter()
+                    .map(|addr| map_file.symbolize(*addr))
+                    .collect::<Vec<_>>();
+                format!("{} {cycles}", names.join(";"))
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        let mut s = lines.join("\n");
+        if !s.is_empty() {
+            s.push('\n');
+        }
+        s
+    }
+
+    fn enter(&mut self, addr: u16) {
+        self.stack.push(addr);
+        self.functions.entry(addr).or_default().calls += 1;
+    }
+
+    fn leave(&mut self) {
+        // The outermost function has nowhere to return to that we know of
+        if self.stack.len() > 1 {
+            _ = self.stack.pop();
+        }
+    }
+
+    fn attribute(&mut self, pc: u16, cycles: TotalCycles) {
+        self.total_cycles += cycles;
+
+        let hotspot = self.hotspots.entry(pc).or_default();
+        hotspot.count += 1;
+        hotspot.cycles += cycles;
+
+        if let Some(addr) = self.stack.last() {
+            self.functions.entry(*addr).or_default().exclusive_cycles += cycles;
+        }
+
+        // Recursive functions only count once towards their own inclusive total
+        for (i, addr) in self.stack.iter().enumerate() {
+            if !self.stack[..i].contains(addr) {
+                self.functions.entry(*addr).or_default().inclusive_cycles += cycles;
+            }
+        }
+
+        *self.stacks.entry(self.stack.clone()).or_default() += cycles;
+    }
+
+    fn follow(&mut self, pending: &Pending, reg: &Reg, next_pc: u16) {
+        let before = &pending.reg;
+        let opcode = pending.instruction_info.opcode;
+        let expected_sp = match opcode {
+            Opcode::Pha | Opcode::Php => before.sp.wrapping_sub(1),
+            Opcode::Pla | Opcode::Plp => before.sp.wrapping_add(1),
+            Opcode::Jsr => before.sp.wrapping_sub(2),
+            Opcode::Rts => before.sp.wrapping_add(2),
+            Opcode::Brk => before.sp.wrapping_sub(3),
+            Opcode::Rti => before.sp.wrapping_add(3),
+            Opcode::Txs => before.x,
+            _ => before.sp,
+        };
+
+        // Interrupt entry pushes return address and flags before the handler's first instruction
+        let interrupted = reg.sp == expected_sp.wrapping_sub(3);
+
+        match (opcode, &pending.instruction_info.operand) {
+            // Calls handled by the host never reach their target
+            (Opcode::Jsr, &Operand::Word(target)) if next_pc == target || interrupted => {
+                self.enter(target);
+            }
+            (Opcode::Rts | Opcode::Rti, _) => self.leave(),
+            (Opcode::Brk, _) if !interrupted => self.enter(next_pc),
+            _ => {}
+        }
+
+        if interrupted {
+            self.enter(next_pc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::{Bus, Cpu, Monitor};
+    use crate::profiler::{Profile, ProfilingMonitor};
+    use crate::symbols::{AddressSize, Export, ExportKind, MapFile};
+    use crate::{InterruptChannel, Opcode};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn label(name: &str, value: u32) -> Export {
+        Export {
+            name: String::from(name),
+            value,
+            referenced: true,
+            kind: ExportKind::Label,
+            address_size: AddressSize::Absolute,
+        }
+    }
+
+    #[test]
+    fn basics() {
+        // main: JSR sub; JSR sub; NOP
+        // sub: NOP; RTS
+        let bus = Bus::default();
+        for (addr, value) in [
+            (0x0e00, Opcode::Jsr as u8),
+            (0x0e01, 0x00),
+            (0x0e02, 0x0f),
+            (0x0e03, Opcode::Jsr as u8),
+            (0x0e04, 0x00),
+            (0x0e05, 0x0f),
+            (0x0e06, Opcode::Nop as u8),
+            (0x0f00, Opcode::Nop as u8),
+            (0x0f01, Opcode::Rts as u8),
+        ] {
+            bus.store(addr, value);
+        }
+
+        let profile = Rc::new(RefCell::new(Profile::default()));
+        let monitor: Box<dyn Monitor> = Box::new(ProfilingMonitor::new(&profile));
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), Some(monitor), interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        for _ in 0..7 {
+            cpu.step_with_monitor_callbacks();
+        }
+        profile.borrow_mut().finish(cpu.total_cycles);
+
+        let profile = profile.borrow();
+        assert_eq!(cpu.total_cycles, profile.total_cycles());
+        let main = &profile.functions()[&0x0e00];
+        assert_eq!(1, main.calls);
+        assert_eq!(cpu.total_cycles, main.inclusive_cycles);
+        assert_eq!(6 + 6 + 2, main.exclusive_cycles);
+        let sub = &profile.functions()[&0x0f00];
+        assert_eq!(2, sub.calls);
+        assert_eq!(2 * (2 + 6), sub.inclusive_cycles);
+        assert_eq!(sub.inclusive_cycles, sub.exclusive_cycles);
+        assert_eq!(2, profile.hotspots()[&0x0f00].count);
+
+        let map_file = MapFile {
+            exports: vec![label("main", 0x0e00), label("sub", 0x0f00)],
+            ..Default::default()
+        };
+        assert_eq!("main 14\nmain;sub 16\n", profile.folded(&map_file));
+        assert!(profile.report(&map_file).contains("sub+$01"));
+    }
+}
@@ -0,0 +1,31 @@
+use crate::Reg;
+use crate::emulator::{InstructionInfo, Monitor};
+use crate::profiler::SharedProfile;
+use r6502lib::TotalCycles;
+use std::rc::Rc;
+
+pub struct ProfilingMonitor {
+    profile: SharedProfile,
+}
+
+impl ProfilingMonitor {
+    #[must_use]
+    pub fn new(profile: &SharedProfile) -> Self {
+        Self {
+            profile: Rc::clone(profile),
+        }
+    }
+}
+
+impl Monitor for ProfilingMonitor {
+    fn on_before_execute(
+        &self,
+        total_cycles: TotalCycles,
+        reg: Reg,
+        instruction_info: InstructionInfo,
+    ) {
+        self.profile
+            .borrow_mut()
+            .before_execute(total_cycles, reg, instruction_info);
+    }
+}
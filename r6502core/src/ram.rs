@@ -1,4 +1,4 @@
-use crate::BusDevice;
+use crate::{BusDevice, DeviceState};
 use r6502snapshot::MemorySlice;
 use std::sync::atomic::{AtomicU8, Ordering};
 
@@ -29,4 +29,12 @@ impl BusDevice for Ram {
     fn store(&self, addr: u16, value: u8) {
         self.bytes[addr as usize].store(value, Ordering::SeqCst);
     }
+
+    fn inspect(&self) -> DeviceState {
+        DeviceState {
+            kind: String::from("RAM"),
+            registers: Vec::new(),
+            description: format!("{} bytes", self.bytes.len()),
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::BusDevice;
+use crate::{BusDevice, DeviceState};
 use r6502snapshot::MemorySlice;
 
 pub struct Rom {
@@ -23,4 +23,12 @@ impl BusDevice for Rom {
     }
 
     fn store(&self, _addr: u16, _value: u8) {}
+
+    fn inspect(&self) -> DeviceState {
+        DeviceState {
+            kind: String::from("ROM"),
+            registers: Vec::new(),
+            description: format!("{} bytes", self.bytes.len()),
+        }
+    }
 }
@@ -0,0 +1,9 @@
+mod runtime_sanitizer;
+mod sanitizer_check;
+mod sanitizer_issue;
+mod sanitizer_options;
+
+pub use runtime_sanitizer::*;
+pub use sanitizer_check::*;
+pub use sanitizer_issue::*;
+pub use sanitizer_options::*;
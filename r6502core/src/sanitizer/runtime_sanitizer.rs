@@ -0,0 +1,388 @@
+use crate::emulator::{BusMonitor, Cpu, OpMetadata};
+use crate::sanitizer::{SanitizerCheck, SanitizerIssue, SanitizerOptions};
+use crate::{DeviceInfo, InterruptEvent, Opcode};
+use r6502lib::util::make_word;
+use r6502snapshot::MemoryImage;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+const SIZE: usize = 0x10000;
+
+// Shadow memory flags for each address
+const INITIALIZED: u8 = 0b01;
+const DATA: u8 = 0b10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Region {
+    Ram,
+    Rom,
+    Io,
+    Unmapped,
+}
+
+#[derive(Clone, Copy)]
+struct Before {
+    pc: u16,
+    sp: u8,
+}
+
+// Checks memory accesses and stack usage of each instruction stepped between before_step and
+// after_step: accesses made by the debugger or host hooks outside that window aren't checked but
+// writes still count towards initializing memory
+pub struct RuntimeSanitizer {
+    options: SanitizerOptions,
+    regions: Vec<Region>,
+    shadow: RefCell<Vec<u8>>,
+    before: Cell<Option<Before>>,
+    reported: RefCell<HashSet<(SanitizerCheck, u16)>>,
+    issues: RefCell<Vec<SanitizerIssue>>,
+}
+
+impl RuntimeSanitizer {
+    #[must_use]
+    pub fn new(options: SanitizerOptions, devices: &[DeviceInfo]) -> Self {
+        let regions = (0..=u16::MAX)
+            .map(
+                |addr| match devices.iter().find(|d| d.address_range.contains(addr)) {
+                    Some(d) if d.state.kind == "RAM" => Region::Ram,
+                    Some(d) if d.state.kind == "ROM" => Region::Rom,
+                    Some(_) => Region::Io,
+                    None => Region::Unmapped,
+                },
+            )
+            .collect();
+        Self {
+            options,
+            regions,
+            shadow: RefCell::new(vec![0; SIZE]),
+            before: Cell::new(None),
+            reported: RefCell::new(HashSet::new()),
+            issues: RefCell::new(Vec::new()),
+        }
+    }
+
+    // RAM loaded from the image counts as initialized
+    pub fn mark_image(&self, image: &MemoryImage) {
+        let load = image.load().unwrap_or_default();
+        let mut shadow = self.shadow.borrow_mut();
+        for offset in 0..image.bytes().len() {
+            let Ok(offset) = u16::try_from(offset) else {
+                break;
+            };
+            shadow[usize::from(load.wrapping_add(offset))] |= INITIALIZED;
+        }
+    }
+
+    pub fn before_step(&self, cpu: &Cpu) {
+        self.before.set(Some(Before {
+            pc: cpu.reg.pc,
+            sp: cpu.reg.sp,
+        }));
+    }
+
+    pub fn after_step(&self, cpu: &Cpu) {
+        let Some(Before { mut pc, mut sp }) = self.before.take() else {
+            return;
+        };
+
+        if let Some(interrupt) = cpu.last_interrupt {
+            if interrupt == InterruptEvent::Reset {
+                return;
+            }
+
+            // The instruction actually executed was the first one in the handler
+            self.check_stack(pc, sp, -3);
+            let vector = interrupt.vector();
+            pc = make_word(cpu.bus.peek(vector.wrapping_add(1)), cpu.bus.peek(vector));
+            sp = sp.wrapping_sub(3);
+        }
+
+        let Some(opcode) = Opcode::from_u8(cpu.bus.peek(pc)) else {
+            return;
+        };
+
+        let delta = match opcode {
+            Opcode::Pha | Opcode::Php => -1,
+            Opcode::Pla | Opcode::Plp => 1,
+            Opcode::Jsr => -2,
+            Opcode::Rts => 2,
+            Opcode::Brk => -3,
+            Opcode::Rti => 3,
+            _ => 0,
+        };
+        self.check_stack(pc, sp, delta);
+
+        if self.is_enabled(SanitizerCheck::ExecuteData) {
+            let size = OpMetadata::get(opcode).map_or(1, |m| m.bytes);
+            if let Some(addr) = (0..size)
+                .map(|offset| pc.wrapping_add(offset))
+                .find(|addr| self.shadow.borrow()[usize::from(*addr)] & DATA != 0)
+            {
+                self.report(
+                    SanitizerCheck::ExecuteData,
+                    pc,
+                    format!("execution of byte written as data at ${addr:04X}"),
+                );
+            }
+        }
+    }
+
+    // Issues found since the previous call, each reported only once per instruction address
+    pub fn take_issues(&self) -> Vec<SanitizerIssue> {
+        self.issues.take()
+    }
+
+    fn check_stack(&self, pc: u16, sp: u8, delta: i16) {
+        if delta == 0 || !self.is_enabled(SanitizerCheck::Stack) {
+            return;
+        }
+
+        let new_sp = i16::from(sp) + delta;
+        let count = delta.unsigned_abs();
+        let bytes = if count == 1 { "byte" } else { "bytes" };
+        if new_sp < i16::from(self.options.stack_min) {
+            self.report(
+                SanitizerCheck::Stack,
+                pc,
+                format!(
+                    "stack overflow: push of {count} {bytes} with SP=${sp:02X} passes limit ${:02X}",
+                    self.options.stack_min
+                ),
+            );
+        } else if new_sp > i16::from(self.options.stack_max) {
+            self.report(
+                SanitizerCheck::Stack,
+                pc,
+                format!(
+                    "stack underflow: pull of {count} {bytes} with SP=${sp:02X} passes limit ${:02X}",
+                    self.options.stack_max
+                ),
+            );
+        }
+    }
+
+    fn is_enabled(&self, check: SanitizerCheck) -> bool {
+        self.options.checks.contains(&check)
+    }
+
+    fn report(&self, check: SanitizerCheck, pc: u16, message: String) {
+        if self.reported.borrow_mut().insert((check, pc)) {
+            self.issues
+                .borrow_mut()
+                .push(SanitizerIssue { check, pc, message });
+        }
+    }
+}
+
+impl BusMonitor for RuntimeSanitizer {
+    fn on_load(&self, addr: u16, _value: u8) {
+        let Some(before) = self.before.get() else {
+            return;
+        };
+
+        match self.regions[usize::from(addr)] {
+            Region::Ram
+                if self.is_enabled(SanitizerCheck::Uninitialized)
+                    && self.shadow.borrow()[usize::from(addr)] & INITIALIZED == 0 =>
+            {
+                self.report(
+                    SanitizerCheck::Uninitialized,
+                    before.pc,
+                    format!("read of uninitialized RAM at ${addr:04X}"),
+                );
+            }
+            Region::Unmapped if self.is_enabled(SanitizerCheck::Unmapped) => {
+                self.report(
+                    SanitizerCheck::Unmapped,
+                    before.pc,
+                    format!("read of unmapped address ${addr:04X}"),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn on_store(&self, addr: u16, value: u8) {
+        let before = self.before.get();
+        match self.regions[usize::from(addr)] {
+            Region::Ram => {
+                self.shadow.borrow_mut()[usize::from(addr)] |= if before.is_some() {
+                    INITIALIZED | DATA
+                } else {
+                    INITIALIZED
+                };
+            }
+            Region::Rom if self.is_enabled(SanitizerCheck::Rom) => {
+                if let Some(before) = before {
+                    self.report(
+                        SanitizerCheck::Rom,
+                        before.pc,
+                        format!("write of ${value:02X} to ROM at ${addr:04X}"),
+                    );
+                }
+            }
+            Region::Unmapped if self.is_enabled(SanitizerCheck::Unmapped) => {
+                if let Some(before) = before {
+                    self.report(
+                        SanitizerCheck::Unmapped,
+                        before.pc,
+                        format!("write of ${value:02X} to unmapped address ${addr:04X}"),
+                    );
+                }
+            }
+            Region::Rom | Region::Io | Region::Unmapped => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::{Bus, BusView, Cpu};
+    use crate::sanitizer::{RuntimeSanitizer, SanitizerCheck, SanitizerIssue, SanitizerOptions};
+    use crate::{DeviceMapping, InterruptChannel, Opcode, Ram, Rom};
+    use anyhow::Result;
+    use r6502lib::{AddressRange, NULL_MACHINE_TAG};
+    use rstest::rstest;
+
+    // RAM from $0000 to $7FFF, ROM from $C000 and nothing in between
+    fn bus() -> Result<Bus> {
+        Ok(Bus::new(
+            NULL_MACHINE_TAG,
+            vec![
+                DeviceMapping {
+                    address_range: AddressRange::new(0x0000, 0x7fff)?,
+                    device: Box::new(Ram::new(0x8000, &Vec::new())),
+                    offset: 0x0000,
+                },
+                DeviceMapping {
+                    address_range: AddressRange::new(0xc000, 0xffff)?,
+                    device: Box::new(Rom::new(0x4000, &Vec::new())),
+                    offset: 0xc000,
+                },
+            ],
+        ))
+    }
+
+    #[test]
+    fn basics() -> Result<()> {
+        let bus = bus()?;
+        let sanitizer = RuntimeSanitizer::new(
+            SanitizerOptions {
+                checks: SanitizerCheck::ALL.to_vec(),
+                stack_min: 0xfe,
+                stack_max: 0xff,
+            },
+            &bus.devices(),
+        );
+
+        // LDA $70; STA $C000; LDA $9000; LDA #NOP; STA $0E12; PHA; PHA; JMP $0E12
+        let sanitizer_view = BusView::with_monitor(&bus, &sanitizer);
+        for (addr, value) in (0x0e00..).zip([
+            Opcode::LdaZp as u8,
+            0x70,
+            Opcode::StaAbs as u8,
+            0x00,
+            0xc0,
+            Opcode::LdaAbs as u8,
+            0x00,
+            0x90,
+            Opcode::LdaImm as u8,
+            Opcode::Nop as u8,
+            Opcode::StaAbs as u8,
+            0x12,
+            0x0e,
+            Opcode::Pha as u8,
+            Opcode::Pha as u8,
+            Opcode::JmpAbs as u8,
+            0x12,
+            0x0e,
+        ]) {
+            sanitizer_view.store(addr, value);
+        }
+
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(sanitizer_view, None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        cpu.reg.sp = 0xff;
+        for _ in 0..9 {
+            sanitizer.before_step(&cpu);
+            cpu.step_no_spin();
+            sanitizer.after_step(&cpu);
+        }
+
+        let issues = sanitizer.take_issues();
+        assert_eq!(
+            vec![
+                (SanitizerCheck::Uninitialized, 0x0e00),
+                (SanitizerCheck::Rom, 0x0e02),
+                (SanitizerCheck::Unmapped, 0x0e05),
+                (SanitizerCheck::Stack, 0x0e0e),
+                (SanitizerCheck::ExecuteData, 0x0e12),
+            ],
+            issues
+                .iter()
+                .map(|issue| (issue.check, issue.pc))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "$0E02: [rom] write of $00 to ROM at $C000",
+            issues[1].to_string()
+        );
+        assert_eq!(
+            "stack overflow: push of 1 byte with SP=$FE passes limit $FE",
+            issues[3].message
+        );
+        assert_eq!(Vec::<SanitizerIssue>::new(), sanitizer.take_issues());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(true, SanitizerCheck::Uninitialized, &[Opcode::LdaZp as u8, 0x70])]
+    #[case(false, SanitizerCheck::Uninitialized, &[Opcode::StaZp as u8, 0x70, Opcode::LdaZp as u8, 0x70])]
+    #[case(true, SanitizerCheck::Stack, &[Opcode::Pha as u8, Opcode::Pha as u8])]
+    #[case(true, SanitizerCheck::Stack, &[Opcode::Pla as u8])]
+    #[case(false, SanitizerCheck::Stack, &[Opcode::Pha as u8, Opcode::Pla as u8, Opcode::Pha as u8])]
+    #[case(true, SanitizerCheck::Rom, &[Opcode::StaAbs as u8, 0x00, 0xc0])]
+    #[case(false, SanitizerCheck::Rom, &[Opcode::StaAbs as u8, 0x00, 0x02, Opcode::LdaAbs as u8, 0x00, 0xc0])]
+    #[case(true, SanitizerCheck::Unmapped, &[Opcode::LdaAbs as u8, 0x00, 0x90])]
+    #[case(true, SanitizerCheck::Unmapped, &[Opcode::StaAbs as u8, 0xff, 0xbf])]
+    #[case(false, SanitizerCheck::Unmapped, &[Opcode::LdaAbs as u8, 0x00, 0x7f, Opcode::LdaAbs as u8, 0x00, 0xc0])]
+    #[case(true, SanitizerCheck::ExecuteData, &[Opcode::LdaImm as u8, Opcode::Nop as u8, Opcode::StaAbs as u8, 0x05, 0x0e, Opcode::Nop as u8])]
+    #[case(false, SanitizerCheck::ExecuteData, &[Opcode::LdaImm as u8, Opcode::Nop as u8, Opcode::StaAbs as u8, 0x06, 0x0e, Opcode::Nop as u8])]
+    fn checks(
+        #[case] expected: bool,
+        #[case] check: SanitizerCheck,
+        #[case] program: &[u8],
+    ) -> Result<()> {
+        let bus = bus()?;
+        let sanitizer = RuntimeSanitizer::new(
+            SanitizerOptions {
+                checks: vec![check],
+                stack_min: 0xfe,
+                stack_max: 0xff,
+            },
+            &bus.devices(),
+        );
+
+        // Program is loaded by the host so it counts as initialized code
+        let sanitizer_view = BusView::with_monitor(&bus, &sanitizer);
+        for (addr, value) in (0x0e00..).zip(program) {
+            sanitizer_view.store(addr, *value);
+        }
+
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(sanitizer_view, None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        cpu.reg.sp = 0xff;
+        while usize::from(cpu.reg.pc - 0x0e00) < program.len() {
+            sanitizer.before_step(&cpu);
+            cpu.step_no_spin();
+            sanitizer.after_step(&cpu);
+        }
+
+        let issues = sanitizer.take_issues();
+        assert_eq!(expected, !issues.is_empty());
+        assert!(issues.iter().all(|issue| issue.check == check));
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SanitizerCheck {
+    // Read of RAM not written since reset and not loaded from the image
+    Uninitialized,
+    // Push or pull taking SP outside the configured limits
+    Stack,
+    Rom,
+    Unmapped,
+    // Execution of bytes stored by the program rather than loaded from the image
+    ExecuteData,
+}
+
+impl SanitizerCheck {
+    pub const ALL: [Self; 5] = [
+        Self::Uninitialized,
+        Self::Stack,
+        Self::Rom,
+        Self::Unmapped,
+        Self::ExecuteData,
+    ];
+}
+
+impl Display for SanitizerCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Uninitialized => "uninit",
+                Self::Stack => "stack",
+                Self::Rom => "rom",
+                Self::Unmapped => "unmapped",
+                Self::ExecuteData => "exec-data",
+            }
+        )
+    }
+}
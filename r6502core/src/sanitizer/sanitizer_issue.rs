@@ -0,0 +1,41 @@
+use crate::sanitizer::SanitizerCheck;
+use crate::symbols::MapFile;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SanitizerIssue {
+    pub check: SanitizerCheck,
+    // Address of the instruction responsible
+    pub pc: u16,
+    pub message: String,
+}
+
+impl SanitizerIssue {
+    // Same as display but with the symbol for the instruction's address if there is one
+    #[must_use]
+    pub fn describe(&self, map_file: &MapFile) -> String {
+        let symbol = map_file.symbolize(self.pc);
+        if symbol.starts_with('$') {
+            self.to_string()
+        } else {
+            format!(
+                "${pc:04X} ({symbol}): [{check}] {message}",
+                pc = self.pc,
+                check = self.check,
+                message = self.message
+            )
+        }
+    }
+}
+
+impl Display for SanitizerIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "${pc:04X}: [{check}] {message}",
+            pc = self.pc,
+            check = self.check,
+            message = self.message
+        )
+    }
+}
@@ -0,0 +1,9 @@
+use crate::sanitizer::SanitizerCheck;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SanitizerOptions {
+    pub checks: Vec<SanitizerCheck>,
+    // Lowest and highest values SP may take after a push or pull
+    pub stack_min: u8,
+    pub stack_max: u8,
+}
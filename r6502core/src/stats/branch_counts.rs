@@ -0,0 +1,12 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BranchCounts {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+impl BranchCounts {
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.taken + self.not_taken
+    }
+}
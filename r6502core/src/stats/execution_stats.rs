@@ -0,0 +1,452 @@
+use crate::emulator::{Cpu, ExtraCycles, OpMetadata};
+use crate::stats::{BranchCounts, StatsFormat};
+use crate::{InterruptEvent, Opcode, Reg};
+use r6502lib::TotalCycles;
+use r6502lib::util::make_word;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+struct Before {
+    reg: Reg,
+    total_cycles: TotalCycles,
+}
+
+// What the program did and how quickly it was emulated, gathered around each step
+#[derive(Default)]
+pub struct ExecutionStats {
+    before: Option<Before>,
+    started: Option<Instant>,
+    elapsed: Duration,
+    throttled: Duration,
+    instructions: u64,
+    cycles: TotalCycles,
+    opcodes: BTreeMap<u8, u64>,
+    branches: BTreeMap<u8, BranchCounts>,
+    page_crossings: u64,
+    interrupts: [u64; 3],
+}
+
+impl ExecutionStats {
+    #[must_use]
+    pub const fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    #[must_use]
+    pub const fn cycles(&self) -> TotalCycles {
+        self.cycles
+    }
+
+    #[must_use]
+    pub fn opcode_count(&self, opcode: Opcode) -> u64 {
+        self.opcodes
+            .get(&(opcode as u8))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn branch_counts(&self, opcode: Opcode) -> BranchCounts {
+        self.branches
+            .get(&(opcode as u8))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // Extra cycles taken by indexed accesses and branches crossing a page boundary
+    #[must_use]
+    pub const fn page_crossings(&self) -> u64 {
+        self.page_crossings
+    }
+
+    #[must_use]
+    pub const fn interrupts(&self, interrupt: InterruptEvent) -> u64 {
+        self.interrupts[Self::interrupt_index(interrupt)]
+    }
+
+    pub fn before_step(&mut self, cpu: &Cpu) {
+        _ = self.started.get_or_insert_with(Instant::now);
+        self.before = Some(Before {
+            reg: cpu.reg.clone(),
+            total_cycles: cpu.total_cycles,
+        });
+    }
+
+    pub fn after_step(&mut self, cpu: &Cpu) {
+        let Some(Before {
+            mut reg,
+            total_cycles,
+        }) = self.before.take()
+        else {
+            return;
+        };
+
+        if let Some(interrupt) = cpu.last_interrupt {
+            // The instruction actually executed was the first one in the handler
+            self.interrupts[Self::interrupt_index(interrupt)] += 1;
+            let vector = interrupt.vector();
+            reg.pc = make_word(cpu.bus.peek(vector.wrapping_add(1)), cpu.bus.peek(vector));
+        }
+
+        let cycles = cpu.total_cycles.saturating_sub(total_cycles);
+        self.instructions += 1;
+        self.cycles += cycles;
+        self.throttled = cpu.throttled;
+
+        let value = cpu.bus.peek(reg.pc);
+        *self.opcodes.entry(value).or_default() += 1;
+
+        let Some(metadata) = Opcode::from_u8(value).and_then(OpMetadata::get) else {
+            return;
+        };
+        match metadata.extra_cycles {
+            ExtraCycles::None => {}
+            ExtraCycles::PageCross => {
+                if cycles > TotalCycles::from(metadata.cycles) {
+                    self.page_crossings += 1;
+                }
+            }
+            ExtraCycles::Branch => {
+                let branch = self.branches.entry(value).or_default();
+                match cycles.saturating_sub(TotalCycles::from(metadata.cycles)) {
+                    0 => branch.not_taken += 1,
+                    1 => branch.taken += 1,
+                    _ => {
+                        branch.taken += 1;
+                        self.page_crossings += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    // Stop the wall clock
+    pub fn finish(&mut self) {
+        if let Some(started) = self.started {
+            self.elapsed = started.elapsed();
+        }
+    }
+
+    #[must_use]
+    pub fn report(&self, format: StatsFormat) -> String {
+        match format {
+            StatsFormat::Text => self.text(),
+            StatsFormat::Json => {
+                let mut s = serde_json::to_string_pretty(&self.json()).expect("Must succeed");
+                s.push('\n');
+                s
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn text(&self) -> String {
+        let mut s = String::new();
+        _ = writeln!(s, "{:<24} {}", "Instructions executed", self.instructions);
+        _ = writeln!(s, "{:<24} {}", "Cycles", self.cycles);
+        _ = writeln!(
+            s,
+            "{:<24} {:.6} s",
+            "Wall-clock time",
+            self.elapsed.as_secs_f64()
+        );
+        _ = writeln!(
+            s,
+            "{:<24} {:.6} s ({:.1}%)",
+            "Throttling time",
+            self.throttled.as_secs_f64(),
+            Self::percent(self.throttled.as_secs_f64(), self.elapsed.as_secs_f64())
+        );
+        _ = writeln!(s, "{:<24} {:.3} MHz", "Emulated speed", self.mhz(false));
+        _ = writeln!(s, "{:<24} {:.3} MHz", "Unthrottled speed", self.mhz(true));
+        _ = writeln!(
+            s,
+            "{:<24} {}",
+            "Page-crossing penalties", self.page_crossings
+        );
+        _ = writeln!(
+            s,
+            "{:<24} IRQ {}, NMI {}, RESET {}",
+            "Interrupts serviced", self.interrupts[0], self.interrupts[1], self.interrupts[2]
+        );
+
+        _ = writeln!(s);
+        _ = writeln!(
+            s,
+            "{:<4} {:<4} {:>10} {:>10} {:>7}",
+            "Op", "Name", "Taken", "Not taken", "Taken%"
+        );
+        for (value, branch) in &self.branches {
+            #[allow(clippy::cast_precision_loss)]
+            let percent = Self::percent(branch.taken as f64, branch.total() as f64);
+            _ = writeln!(
+                s,
+                "${value:02X}  {:<4} {:>10} {:>10} {:>6.1}%",
+                Self::mnemonic(*value),
+                branch.taken,
+                branch.not_taken,
+                percent
+            );
+        }
+
+        _ = writeln!(s);
+        _ = writeln!(
+            s,
+            "{:<4} {:<4} {:<16} {:>12} {:>7}",
+            "Op", "Name", "Mode", "Count", "%"
+        );
+        for (value, count) in self.opcode_histogram() {
+            _ = writeln!(
+                s,
+                "${value:02X}  {:<4} {:<16} {count:>12} {:>6.1}%",
+                Self::mnemonic(value),
+                Self::addressing_mode(value),
+                self.share(count)
+            );
+        }
+
+        _ = writeln!(s);
+        _ = writeln!(s, "{:<16} {:>12} {:>7}", "Mode", "Count", "%");
+        for (mode, count) in self.addressing_mode_histogram() {
+            _ = writeln!(s, "{mode:<16} {count:>12} {:>6.1}%", self.share(count));
+        }
+        s
+    }
+
+    #[must_use]
+    pub fn json(&self) -> Value {
+        json!({
+            "instructions": self.instructions,
+            "cycles": self.cycles,
+            "elapsed_seconds": self.elapsed.as_secs_f64(),
+            "throttled_seconds": self.throttled.as_secs_f64(),
+            "emulated_mhz": self.mhz(false),
+            "unthrottled_mhz": self.mhz(true),
+            "page_crossings": self.page_crossings,
+            "interrupts": {
+                "irq": self.interrupts[0],
+                "nmi": self.interrupts[1],
+                "reset": self.interrupts[2],
+            },
+            "branches": self.branches.iter().map(|(value, branch)| json!({
+                "opcode": value,
+                "mnemonic": Self::mnemonic(*value),
+                "taken": branch.taken,
+                "not_taken": branch.not_taken,
+            })).collect::<Vec<_>>(),
+            "opcodes": self.opcode_histogram().into_iter().map(|(value, count)| json!({
+                "opcode": value,
+                "mnemonic": Self::mnemonic(value),
+                "addressing_mode": Self::addressing_mode(value),
+                "count": count,
+            })).collect::<Vec<_>>(),
+            "addressing_modes": self.addressing_mode_histogram().into_iter().map(|(mode, count)| json!({
+                "addressing_mode": mode,
+                "count": count,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    // Most frequently executed first
+    fn opcode_histogram(&self) -> Vec<(u8, u64)> {
+        let mut opcodes = self
+            .opcodes
+            .iter()
+            .map(|(value, count)| (*value, *count))
+            .collect::<Vec<_>>();
+        opcodes.sort_by(|(a_value, a), (b_value, b)| b.cmp(a).then(a_value.cmp(b_value)));
+        opcodes
+    }
+
+    fn addressing_mode_histogram(&self) -> Vec<(String, u64)> {
+        let mut modes = BTreeMap::<String, u64>::new();
+        for (value, count) in &self.opcodes {
+            *modes.entry(Self::addressing_mode(*value)).or_default() += count;
+        }
+        let mut modes = modes.into_iter().collect::<Vec<_>>();
+        modes.sort_by(|(a_mode, a), (b_mode, b)| b.cmp(a).then(a_mode.cmp(b_mode)));
+        modes
+    }
+
+    // Cycles per second of wall-clock time, optionally leaving out time spent throttling
+    fn mhz(&self, unthrottled: bool) -> f64 {
+        let elapsed = if unthrottled {
+            self.elapsed.saturating_sub(self.throttled)
+        } else {
+            self.elapsed
+        };
+        if elapsed.is_zero() {
+            return 0.0;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let cycles = self.cycles as f64;
+        cycles / elapsed.as_secs_f64() / 1_000_000.0
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn share(&self, count: u64) -> f64 {
+        Self::percent(count as f64, self.instructions as f64)
+    }
+
+    fn percent(value: f64, total: f64) -> f64 {
+        if total == 0.0 {
+            0.0
+        } else {
+            value * 100.0 / total
+        }
+    }
+
+    fn mnemonic(value: u8) -> &'static str {
+        Opcode::from_u8(value).map_or("???", Opcode::mnemonic)
+    }
+
+    fn addressing_mode(value: u8) -> String {
+        Opcode::from_u8(value)
+            .and_then(OpMetadata::get)
+            .map_or_else(|| String::from("?"), |m| format!("{:?}", m.addressing_mode))
+    }
+
+    const fn interrupt_index(interrupt: InterruptEvent) -> usize {
+        match interrupt {
+            InterruptEvent::Irq => 0,
+            InterruptEvent::Nmi => 1,
+            InterruptEvent::Reset => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::{Bus, Cpu};
+    use crate::stats::{BranchCounts, ExecutionStats, StatsFormat};
+    use crate::{InterruptChannel, InterruptEvent, Opcode};
+    use r6502lib::constants::IRQ;
+    use serde_json::json;
+
+    #[test]
+    fn basics() {
+        // LDX #$02; loop: LDA $0EFE,X; DEX; BNE loop; NOP
+        let bus = Bus::default();
+        for (addr, value) in (0x0e00..).zip([
+            Opcode::LdxImm as u8,
+            0x02,
+            Opcode::LdaAbsX as u8,
+            0xfe,
+            0x0e,
+            Opcode::Dex as u8,
+            Opcode::Bne as u8,
+            0xfa,
+            Opcode::Nop as u8,
+        ]) {
+            bus.store(addr, value);
+        }
+        bus.store(IRQ, 0x08);
+        bus.store(IRQ.wrapping_add(1), 0x0e);
+
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        let mut stats = ExecutionStats::default();
+        for _ in 0..8 {
+            stats.before_step(&cpu);
+            cpu.step_no_spin();
+            stats.after_step(&cpu);
+        }
+
+        // Interrupt taken before the next instruction which is the first in the handler
+        interrupt_channel
+            .tx
+            .send(InterruptEvent::Irq)
+            .expect("Must succeed");
+        stats.before_step(&cpu);
+        cpu.step_no_spin();
+        stats.after_step(&cpu);
+        stats.finish();
+
+        assert_eq!(9, stats.instructions());
+        assert_eq!(cpu.total_cycles, stats.cycles());
+        assert_eq!(2, stats.opcode_count(Opcode::LdaAbsX));
+        assert_eq!(2, stats.opcode_count(Opcode::Nop));
+        assert_eq!(
+            BranchCounts {
+                taken: 1,
+                not_taken: 1
+            },
+            stats.branch_counts(Opcode::Bne)
+        );
+        assert_eq!(1, stats.page_crossings());
+        assert_eq!(1, stats.interrupts(InterruptEvent::Irq));
+
+        let text = stats.report(StatsFormat::Text);
+        assert!(text.contains("$D0  BNE           1          1   50.0%"));
+        assert!(text.contains("AbsoluteX                   2   22.2%"));
+        let json = stats.json();
+        assert_eq!(9, json["instructions"]);
+        assert_eq!("LDA", json["opcodes"][0]["mnemonic"]);
+    }
+
+    #[test]
+    fn json() {
+        // LDX #$01; loop: DEX; BNE loop; NOP
+        let bus = Bus::default();
+        for (addr, value) in (0x0e00..).zip([
+            Opcode::LdxImm as u8,
+            0x01,
+            Opcode::Dex as u8,
+            Opcode::Bne as u8,
+            0xfd,
+            Opcode::Nop as u8,
+        ]) {
+            bus.store(addr, value);
+        }
+
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.pc = 0x0e00;
+        let mut stats = ExecutionStats::default();
+        for _ in 0..4 {
+            stats.before_step(&cpu);
+            cpu.step_no_spin();
+            stats.after_step(&cpu);
+        }
+        stats.finish();
+
+        // Timings vary from run to run
+        let mut json = stats.json();
+        let object = json.as_object_mut().expect("Must be object");
+        for key in [
+            "elapsed_seconds",
+            "throttled_seconds",
+            "emulated_mhz",
+            "unthrottled_mhz",
+        ] {
+            assert!(object.remove(key).expect("Must exist").is_f64());
+        }
+        assert_eq!(
+            json!({
+                "instructions": 4,
+                "cycles": 8,
+                "page_crossings": 0,
+                "interrupts": { "irq": 0, "nmi": 0, "reset": 0 },
+                "branches": [
+                    { "opcode": 0xd0, "mnemonic": "BNE", "taken": 0, "not_taken": 1 },
+                ],
+                "opcodes": [
+                    { "opcode": 0xa2, "mnemonic": "LDX", "addressing_mode": "Immediate", "count": 1 },
+                    { "opcode": 0xca, "mnemonic": "DEX", "addressing_mode": "Implied", "count": 1 },
+                    { "opcode": 0xd0, "mnemonic": "BNE", "addressing_mode": "Relative", "count": 1 },
+                    { "opcode": 0xea, "mnemonic": "NOP", "addressing_mode": "Implied", "count": 1 },
+                ],
+                "addressing_modes": [
+                    { "addressing_mode": "Implied", "count": 2 },
+                    { "addressing_mode": "Immediate", "count": 1 },
+                    { "addressing_mode": "Relative", "count": 1 },
+                ],
+            }),
+            json
+        );
+    }
+}
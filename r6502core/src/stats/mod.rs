@@ -0,0 +1,7 @@
+mod branch_counts;
+mod execution_stats;
+mod stats_format;
+
+pub use branch_counts::*;
+pub use execution_stats::*;
+pub use stats_format::*;
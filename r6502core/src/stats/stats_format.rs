@@ -0,0 +1,6 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StatsFormat {
+    #[default]
+    Text,
+    Json,
+}
@@ -0,0 +1,19 @@
+use crate::symbols::dbg_record::DbgRecord;
+use anyhow::Result;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbgFile {
+    pub id: usize,
+    pub name: String,
+    pub size: u32,
+}
+
+impl DbgFile {
+    pub fn from_record(record: &DbgRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id()?,
+            name: record.string("name")?,
+            size: record.number_opt("size")?.unwrap_or_default(),
+        })
+    }
+}
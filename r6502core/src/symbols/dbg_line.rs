@@ -0,0 +1,30 @@
+use crate::symbols::DbgLineKind;
+use crate::symbols::dbg_record::DbgRecord;
+use anyhow::{Result, bail};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbgLine {
+    pub id: usize,
+    pub file: usize,
+    pub line: usize,
+    pub kind: DbgLineKind,
+    pub spans: Vec<usize>,
+}
+
+impl DbgLine {
+    pub fn from_record(record: &DbgRecord) -> Result<Self> {
+        let kind = match record.number_opt("type")? {
+            None | Some(0) => DbgLineKind::Assembler,
+            Some(1) => DbgLineKind::External,
+            Some(2) => DbgLineKind::Macro,
+            Some(value) => bail!("invalid line type {value}"),
+        };
+        Ok(Self {
+            id: record.id()?,
+            file: record.index("file")?,
+            line: record.index("line")?,
+            kind,
+            spans: record.indices("span")?,
+        })
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::{Result, anyhow, bail};
+
+// One line of an ld65 debug info file e.g. "span<TAB>id=0,seg=0,start=0,size=3"
+pub struct DbgRecord<'a> {
+    pub kind: &'a str,
+    fields: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> DbgRecord<'a> {
+    pub fn parse(s: &'a str) -> Result<Self> {
+        let Some((kind, mut rest)) = s.split_once(char::is_whitespace) else {
+            bail!("invalid debug info record \"{s}\"")
+        };
+
+        let mut fields = Vec::new();
+        while !rest.is_empty() {
+            let Some((key, tail)) = rest.split_once('=') else {
+                bail!("invalid debug info record \"{s}\"")
+            };
+
+            let (value, tail) = if let Some(tail) = tail.strip_prefix('"') {
+                let Some(end) = tail.find('"') else {
+                    bail!("unterminated string in debug info record \"{s}\"")
+                };
+                (&tail[..end], tail[end + 1..].trim_start_matches(','))
+            } else {
+                tail.split_once(',').unwrap_or((tail, ""))
+            };
+
+            fields.push((key.trim(), value));
+            rest = tail;
+        }
+
+        Ok(Self { kind, fields })
+    }
+
+    pub fn id(&self) -> Result<usize> {
+        self.index_opt("id")?
+            .ok_or_else(|| anyhow!("missing id in {} record", self.kind))
+    }
+
+    pub fn string(&self, key: &str) -> Result<String> {
+        self.get(key)
+            .map(String::from)
+            .ok_or_else(|| anyhow!("missing {key} in {} record", self.kind))
+    }
+
+    pub fn number(&self, key: &str) -> Result<u32> {
+        self.number_opt(key)?
+            .ok_or_else(|| anyhow!("missing {key} in {} record", self.kind))
+    }
+
+    pub fn number_opt(&self, key: &str) -> Result<Option<u32>> {
+        self.get(key).map(Self::parse_number).transpose()
+    }
+
+    pub fn index(&self, key: &str) -> Result<usize> {
+        self.index_opt(key)?
+            .ok_or_else(|| anyhow!("missing {key} in {} record", self.kind))
+    }
+
+    pub fn index_opt(&self, key: &str) -> Result<Option<usize>> {
+        Ok(self.number_opt(key)?.map(|value| value as usize))
+    }
+
+    // Lists of IDs are separated by "+" e.g. "span=1+2+3"
+    pub fn indices(&self, key: &str) -> Result<Vec<usize>> {
+        match self.get(key) {
+            Some(s) => s
+                .split('+')
+                .map(|s| Ok(Self::parse_number(s)? as usize))
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.fields
+            .iter()
+            .find_map(|(k, v)| if *k == key { Some(*v) } else { None })
+    }
+
+    fn parse_number(s: &str) -> Result<u32> {
+        Ok(match s.strip_prefix("0x") {
+            Some(s) => u32::from_str_radix(s, 16)?,
+            None => s.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symbols::dbg_record::DbgRecord;
+    use anyhow::Result;
+
+    #[test]
+    fn parse() -> Result<()> {
+        let record =
+            DbgRecord::parse("file\tid=3,name=\"a, b.s\",size=1234,mtime=0x5F3B1C2A,mod=0+1")?;
+        assert_eq!("file", record.kind);
+        assert_eq!(3, record.id()?);
+        assert_eq!("a, b.s", record.string("name")?);
+        assert_eq!(1234, record.number("size")?);
+        assert_eq!(0x5f3b_1c2a, record.number("mtime")?);
+        assert_eq!(vec![0, 1], record.indices("mod")?);
+        assert!(record.indices("span")?.is_empty());
+        assert!(record.number_opt("type")?.is_none());
+        assert!(record.string("oname").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use crate::symbols::dbg_record::DbgRecord;
+use anyhow::Result;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbgScope {
+    pub id: usize,
+    pub name: String,
+    pub parent: Option<usize>,
+    pub symbol: Option<usize>,
+    pub spans: Vec<usize>,
+}
+
+impl DbgScope {
+    pub fn from_record(record: &DbgRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id()?,
+            name: record.string("name")?,
+            parent: record.index_opt("parent")?,
+            symbol: record.index_opt("sym")?,
+            spans: record.indices("span")?,
+        })
+    }
+}
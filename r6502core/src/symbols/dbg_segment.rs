@@ -0,0 +1,21 @@
+use crate::symbols::dbg_record::DbgRecord;
+use anyhow::Result;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbgSegment {
+    pub id: usize,
+    pub name: String,
+    pub start: u32,
+    pub size: u32,
+}
+
+impl DbgSegment {
+    pub fn from_record(record: &DbgRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id()?,
+            name: record.string("name")?,
+            start: record.number("start")?,
+            size: record.number("size")?,
+        })
+    }
+}
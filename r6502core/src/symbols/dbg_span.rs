@@ -0,0 +1,25 @@
+use crate::symbols::dbg_record::DbgRecord;
+use anyhow::Result;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbgSpan {
+    pub id: usize,
+    pub segment: usize,
+    // Offset relative to start of segment
+    pub start: u32,
+    pub size: u32,
+    // Only data such as that from .byte and .word has a type
+    pub data: bool,
+}
+
+impl DbgSpan {
+    pub fn from_record(record: &DbgRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id()?,
+            segment: record.index("seg")?,
+            start: record.number("start")?,
+            size: record.number("size")?,
+            data: record.get("type").is_some(),
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use crate::symbols::DbgSymbolKind;
+use crate::symbols::dbg_record::DbgRecord;
+use anyhow::{Result, bail};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbgSymbol {
+    pub id: usize,
+    pub name: String,
+    pub scope: Option<usize>,
+    // Set for cheap local labels e.g. "@loop" to the preceding non-local label
+    pub parent: Option<usize>,
+    pub value: Option<u32>,
+    pub segment: Option<usize>,
+    pub kind: DbgSymbolKind,
+}
+
+impl DbgSymbol {
+    pub fn from_record(record: &DbgRecord) -> Result<Self> {
+        let kind = match record.get("type") {
+            Some("lab") => DbgSymbolKind::Label,
+            Some("equ") => DbgSymbolKind::Equate,
+            Some("imp") => DbgSymbolKind::Import,
+            Some(s) => bail!("invalid symbol type {s}"),
+            None => bail!("missing type in sym record"),
+        };
+        Ok(Self {
+            id: record.id()?,
+            name: record.string("name")?,
+            scope: record.index_opt("scope")?,
+            parent: record.index_opt("parent")?,
+            value: record.number_opt("val")?,
+            segment: record.index_opt("seg")?,
+            kind,
+        })
+    }
+}
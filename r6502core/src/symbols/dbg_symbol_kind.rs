@@ -0,0 +1,6 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DbgSymbolKind {
+    Label,
+    Equate,
+    Import,
+}
@@ -0,0 +1,368 @@
+use crate::symbols::dbg_record::DbgRecord;
+use crate::symbols::{
+    AddressSize, DbgFile, DbgLine, DbgLineKind, DbgScope, DbgSegment, DbgSpan, DbgSymbol,
+    DbgSymbolKind, Export, ExportKind,
+};
+use anyhow::{Error, Result, anyhow, bail};
+use log::warn;
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Contents of debug info file generated by ld65 --dbgfile
+#[derive(Debug, Default)]
+pub struct DebugInfo {
+    pub files: Vec<DbgFile>,
+    pub lines: Vec<DbgLine>,
+    pub spans: Vec<DbgSpan>,
+    pub scopes: Vec<DbgScope>,
+    pub symbols: Vec<DbgSymbol>,
+    pub segments: Vec<DbgSegment>,
+    dir: PathBuf,
+    line_by_addr: HashMap<u16, usize>,
+}
+
+impl DebugInfo {
+    pub fn load(image_path: &Path) -> Result<Self> {
+        let mut file_name = image_path
+            .file_stem()
+            .ok_or_else(|| anyhow!("could not get file stem"))?
+            .to_os_string();
+        file_name.push(".dbg");
+        let dir = image_path
+            .parent()
+            .ok_or_else(|| anyhow!("could not get parent of path"))?;
+        let dbg_path = dir.join(file_name);
+
+        if dbg_path.is_file() {
+            let s = read_to_string(dbg_path)?;
+            let mut debug_info = s.parse::<Self>()?;
+            debug_info.dir = dir.to_path_buf();
+            Ok(debug_info)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    // Debug info is optional so a bad file only costs source-level debugging
+    #[must_use]
+    pub fn load_or_default(image_path: &Path) -> Self {
+        Self::load(image_path).unwrap_or_else(|e| {
+            warn!(
+                "could not load debug info for {}: {e}",
+                image_path.display()
+            );
+            Self::default()
+        })
+    }
+
+    #[must_use]
+    pub fn find_line(&self, addr: u16) -> Option<&DbgLine> {
+        self.line_by_addr.get(&addr).map(|id| &self.lines[*id])
+    }
+
+    #[must_use]
+    pub fn file(&self, line: &DbgLine) -> &DbgFile {
+        &self.files[line.file]
+    }
+
+    // Source file names are relative to the directory containing the debug info file
+    #[must_use]
+    pub fn source_path(&self, file: &DbgFile) -> PathBuf {
+        self.dir.join(&file.name)
+    }
+
+    // Source location of address e.g. "main.s:12"
+    #[must_use]
+    pub fn format_location(&self, addr: u16) -> Option<String> {
+        self.find_line(addr)
+            .map(|line| format!("{}:{}", self.file(line).name, line.line))
+    }
+
+    // Lowest address generated by the given line or, failing that, the next line with code
+    #[must_use]
+    pub fn find_addr(&self, file_name: &str, line: usize) -> Option<u16> {
+        self.lines
+            .iter()
+            .filter(|l| l.line >= line && Self::matches_file(&self.file(l).name, file_name))
+            .filter_map(|l| {
+                l.spans
+                    .iter()
+                    .filter_map(|id| self.span_range(&self.spans[*id]))
+                    .map(|(start, _)| start)
+                    .min()
+                    .map(|start| (l.line, start))
+            })
+            .min()
+            .map(|(_, start)| start)
+    }
+
+    // Start address and size of each span of code or data generated by line
+    #[must_use]
+    pub fn line_ranges(&self, line: &DbgLine) -> Vec<(u16, u32)> {
+        line.spans
+            .iter()
+            .filter_map(|id| self.spans.get(*id))
+            .filter_map(|span| self.span_range(span))
+            .collect()
+    }
+
+    // Name including enclosing scopes e.g. "print::loop" or, for cheap locals, "start@done"
+    #[must_use]
+    pub fn qualified_name(&self, symbol: &DbgSymbol) -> String {
+        if let Some(parent) = symbol.parent.and_then(|id| self.symbols.get(id)) {
+            return format!("{}{}", self.qualified_name(parent), symbol.name);
+        }
+
+        let mut names = Vec::new();
+        let mut scope = symbol.scope.and_then(|id| self.scopes.get(id));
+        while let Some(s) = scope {
+            if !s.name.is_empty() {
+                names.push(s.name.as_str());
+            }
+            scope = s.parent.and_then(|id| self.scopes.get(id));
+        }
+        names.reverse();
+        names.push(&symbol.name);
+        names.join("::")
+    }
+
+    // All labels, including scoped and cheap local labels, in the same form as map file exports
+    #[must_use]
+    pub fn labels(&self) -> Vec<Export> {
+        self.symbols
+            .iter()
+            .filter(|s| s.kind == DbgSymbolKind::Label)
+            .filter_map(|s| {
+                let value = s.value?;
+                Some(Export {
+                    name: self.qualified_name(s),
+                    value,
+                    referenced: true,
+                    kind: ExportKind::Label,
+                    address_size: if value < 0x100 {
+                        AddressSize::ZeroPage
+                    } else {
+                        AddressSize::Absolute
+                    },
+                })
+            })
+            .collect()
+    }
+
+    // Either path may be a suffix of the other e.g. "main.s" and "/home/user/project/main.s"
+    fn matches_file(name: &str, file_name: &str) -> bool {
+        name == file_name
+            || Path::new(name).ends_with(file_name)
+            || Path::new(file_name).ends_with(name)
+    }
+
+    fn span_range(&self, span: &DbgSpan) -> Option<(u16, u32)> {
+        let segment = self.segments.get(span.segment)?;
+        let start = u16::try_from(segment.start + span.start).ok()?;
+        Some((start, span.size))
+    }
+
+    fn index_by_id<T>(mut items: Vec<T>, id: impl Fn(&T) -> usize, kind: &str) -> Result<Vec<T>> {
+        items.sort_by_key(&id);
+        for (i, item) in items.iter().enumerate() {
+            if id(item) != i {
+                bail!("missing {kind} {i} in debug info")
+            }
+        }
+        Ok(items)
+    }
+
+    // Map each address to the most specific line, preferring source lines over macro expansions
+    fn build_line_by_addr(&mut self) -> Result<()> {
+        let mut best = HashMap::<u16, (usize, (bool, u32))>::new();
+        for line in &self.lines {
+            if line.file >= self.files.len() {
+                bail!("invalid file {} in line {}", line.file, line.id)
+            }
+
+            for id in &line.spans {
+                let Some(span) = self.spans.get(*id) else {
+                    bail!("invalid span {id} in line {}", line.id)
+                };
+
+                let Some((start, size)) = self.span_range(span) else {
+                    continue;
+                };
+
+                let rank = (line.kind == DbgLineKind::Macro, size);
+                for offset in 0..size {
+                    let addr = start.wrapping_add(u16::try_from(offset)?);
+                    match best.get(&addr) {
+                        Some((_, r)) if *r <= rank => {}
+                        _ => _ = best.insert(addr, (line.id, rank)),
+                    }
+                }
+            }
+        }
+
+        self.line_by_addr = best.into_iter().map(|(addr, (id, _))| (addr, id)).collect();
+        Ok(())
+    }
+}
+
+impl FromStr for DebugInfo {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut files = Vec::new();
+        let mut lines = Vec::new();
+        let mut spans = Vec::new();
+        let mut scopes = Vec::new();
+        let mut symbols = Vec::new();
+        let mut segments = Vec::new();
+
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let record = DbgRecord::parse(line)?;
+            match record.kind {
+                "version" => {
+                    let major = record.number("major")?;
+                    if major != 2 {
+                        bail!("unsupported debug info version {major}")
+                    }
+                }
+                "file" => files.push(DbgFile::from_record(&record)?),
+                "line" => lines.push(DbgLine::from_record(&record)?),
+                "span" => spans.push(DbgSpan::from_record(&record)?),
+                "scope" => scopes.push(DbgScope::from_record(&record)?),
+                "sym" => symbols.push(DbgSymbol::from_record(&record)?),
+                "seg" => segments.push(DbgSegment::from_record(&record)?),
+                _ => {}
+            }
+        }
+
+        let mut debug_info = Self {
+            files: Self::index_by_id(files, |f| f.id, "file")?,
+            lines: Self::index_by_id(lines, |l| l.id, "line")?,
+            spans: Self::index_by_id(spans, |s| s.id, "span")?,
+            scopes: Self::index_by_id(scopes, |s| s.id, "scope")?,
+            symbols: Self::index_by_id(symbols, |s| s.id, "sym")?,
+            segments: Self::index_by_id(segments, |s| s.id, "seg")?,
+            dir: PathBuf::new(),
+            line_by_addr: HashMap::new(),
+        };
+        debug_info.build_line_by_addr()?;
+        Ok(debug_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symbols::DebugInfo;
+    use anyhow::Result;
+    use rstest::rstest;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+    use std::process;
+
+    const INPUT: &str = r#"version	major=2,minor=0
+info	csym=0,file=2,lib=0,line=6,mod=1,scope=2,seg=1,span=5,sym=5,type=1
+file	id=0,name="main.s",size=200,mtime=0x60000000,mod=0
+file	id=1,name="lib/print.inc",size=100,mtime=0x60000000,mod=0
+line	id=0,file=0,line=5,span=0
+line	id=1,file=0,line=6,span=1
+line	id=2,file=1,line=3,span=2
+line	id=3,file=1,line=4,span=3
+line	id=4,file=0,line=9,span=4
+line	id=5,file=0,line=7,type=2,count=1,span=3
+mod	id=0,name="main.o",file=0
+seg	id=0,name="CODE",start=0x000E00,size=0x000C,addrsize=absolute,type=ro,oname="main.r6502",ooffs=0
+span	id=0,seg=0,start=0,size=2,type=0
+span	id=1,seg=0,start=2,size=3,type=0
+span	id=2,seg=0,start=5,size=3
+span	id=3,seg=0,start=8,size=1
+span	id=4,seg=0,start=9,size=3
+scope	id=0,name="",mod=0,size=12,span=0+1+2+3+4
+scope	id=1,name="print",mod=0,type=scope,size=4,parent=0,sym=1,span=2+3
+sym	id=0,name="start",addrsize=absolute,scope=0,def=0,val=0xE00,seg=0,type=lab
+sym	id=1,name="print",addrsize=absolute,scope=0,def=2,val=0xE05,seg=0,type=lab
+sym	id=2,name="loop",addrsize=absolute,scope=1,def=3,val=0xE08,seg=0,type=lab
+sym	id=3,name="@done",addrsize=absolute,scope=0,parent=0,def=4,val=0xE09,seg=0,type=lab
+sym	id=4,name="OSWRCH",addrsize=absolute,scope=0,def=1,val=0xFFEE,type=equ
+type	id=0,val="800920"
+"#;
+
+    #[test]
+    fn basics() -> Result<()> {
+        let debug_info = INPUT.parse::<DebugInfo>()?;
+        assert_eq!(2, debug_info.files.len());
+        assert_eq!(6, debug_info.lines.len());
+        assert_eq!(5, debug_info.spans.len());
+        assert_eq!(2, debug_info.scopes.len());
+        assert_eq!(5, debug_info.symbols.len());
+        assert_eq!(1, debug_info.segments.len());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Some("main.s:5"), 0x0e00)]
+    #[case(Some("main.s:6"), 0x0e04)]
+    #[case(Some("lib/print.inc:4"), 0x0e08)]
+    #[case(Some("main.s:9"), 0x0e0b)]
+    #[case(None, 0x0e0c)]
+    fn format_location(#[case] expected_result: Option<&str>, #[case] input: u16) -> Result<()> {
+        let debug_info = INPUT.parse::<DebugInfo>()?;
+        assert_eq!(
+            expected_result.map(String::from),
+            debug_info.format_location(input)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Some(0x0e02), "main.s", 6)]
+    #[case(Some(0x0e09), "main.s", 8)]
+    #[case(Some(0x0e05), "print.inc", 3)]
+    #[case(Some(0x0e05), "/home/user/project/lib/print.inc", 3)]
+    #[case(None, "main.s", 10)]
+    #[case(None, "other.s", 1)]
+    fn find_addr(
+        #[case] expected_result: Option<u16>,
+        #[case] file_name: &str,
+        #[case] line: usize,
+    ) -> Result<()> {
+        let debug_info = INPUT.parse::<DebugInfo>()?;
+        assert_eq!(expected_result, debug_info.find_addr(file_name, line));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("version\tmajor=3,minor=0\n")]
+    #[case("line\tid=0,file=zero\n")]
+    fn load_malformed(#[case] input: &str) -> Result<()> {
+        let dir = temp_dir().join(format!("r6502-malformed-dbg-{}", process::id()));
+        create_dir_all(&dir)?;
+        let image_path = dir.join("main.r6502");
+        write(dir.join("main.dbg"), input)?;
+        let result = DebugInfo::load(&image_path);
+        let debug_info = DebugInfo::load_or_default(&image_path);
+        remove_dir_all(&dir)?;
+
+        assert!(result.is_err());
+        assert!(debug_info.symbols.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn labels() -> Result<()> {
+        let debug_info = INPUT.parse::<DebugInfo>()?;
+        let names = debug_info
+            .labels()
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["start", "print", "print::loop", "start@done"], names);
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use anyhow::{Result, bail};
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::iter::Peekable;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Export {
     pub name: String,
     pub value: u32,
@@ -1,11 +1,11 @@
 use crate::symbols::util::iter_map_file_lines;
-use crate::symbols::{Export, Module, Segment};
+use crate::symbols::{DebugInfo, Export, ExportKind, Module, Segment};
 use anyhow::{Error, Result, anyhow};
 use std::fs::read_to_string;
 use std::path::Path;
 use std::str::FromStr;
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct MapFile {
     pub modules: Vec<Module>,
     pub segments: Vec<Segment>,
@@ -31,6 +31,41 @@ impl MapFile {
             Ok(Self::default())
         }
     }
+
+    // Add labels from debug info, such as scoped and cheap local labels, that aren't exported
+    pub fn merge_labels(&mut self, debug_info: &DebugInfo) {
+        for label in debug_info.labels() {
+            if !self.exports.iter().any(|e| e.name == label.name) {
+                self.exports.push(label);
+            }
+        }
+    }
+
+    // Value of export with the given name for use in expressions
+    #[must_use]
+    pub fn lookup(&self, name: &str) -> Option<u16> {
+        self.exports
+            .iter()
+            .find(|e| e.name == name)
+            .and_then(|e| u16::try_from(e.value).ok())
+    }
+
+    // Nearest label at or below address e.g. "PRBYTE+$03" or just the address if there isn't one
+    #[must_use]
+    pub fn symbolize(&self, addr: u16) -> String {
+        let value = u32::from(addr);
+        self.exports
+            .iter()
+            .filter(|e| e.kind == ExportKind::Label && e.value <= value)
+            .max_by_key(|e| e.value)
+            .map_or_else(
+                || format!("${addr:04X}"),
+                |e| match value - e.value {
+                    0 => e.name.clone(),
+                    offset => format!("{}+${offset:02X}", e.name),
+                },
+            )
+    }
 }
 
 impl FromStr for MapFile {
@@ -51,8 +86,9 @@ impl FromStr for MapFile {
 
 #[cfg(test)]
 mod tests {
-    use crate::symbols::MapFile;
+    use crate::symbols::{AddressSize, Export, ExportKind, MapFile};
     use anyhow::Result;
+    use rstest::rstest;
 
     #[test]
     fn basics() -> Result<()> {
@@ -159,6 +195,36 @@ tmp1 (zeropage.o):
         assert_eq!(6, map_file.modules.len());
         assert_eq!(9, map_file.segments.len());
         assert_eq!(16, map_file.exports.len());
+        assert_eq!(Some(0xd012), map_file.lookup("DSP"));
+        assert_eq!(None, map_file.lookup("UNKNOWN"));
         Ok(())
     }
+
+    #[rstest]
+    #[case("$0E00", 0x0e00)]
+    #[case("PRBYTE", 0xffdc)]
+    #[case("PRBYTE+$03", 0xffdf)]
+    #[case("ECHO+$10", 0xffff)]
+    fn symbolize(#[case] expected_result: &str, #[case] input: u16) {
+        fn export(name: &str, value: u32, kind: ExportKind) -> Export {
+            Export {
+                name: String::from(name),
+                value,
+                referenced: true,
+                kind,
+                address_size: AddressSize::Absolute,
+            }
+        }
+
+        let map_file = MapFile {
+            exports: vec![
+                export("DSP", 0xd012, ExportKind::Constant),
+                export("ECHO", 0xffef, ExportKind::Label),
+                export("PRBYTE", 0xffdc, ExportKind::Label),
+                export("WOZMON", 0xff00, ExportKind::Label),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(expected_result, map_file.symbolize(input));
+    }
 }
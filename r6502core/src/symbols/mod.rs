@@ -1,4 +1,14 @@
 mod address_size;
+mod dbg_file;
+mod dbg_line;
+mod dbg_line_kind;
+mod dbg_record;
+mod dbg_scope;
+mod dbg_segment;
+mod dbg_span;
+mod dbg_symbol;
+mod dbg_symbol_kind;
+mod debug_info;
 mod export;
 mod export_kind;
 mod map_file;
@@ -9,6 +19,15 @@ mod segment;
 mod util;
 
 pub use address_size::*;
+pub use dbg_file::*;
+pub use dbg_line::*;
+pub use dbg_line_kind::*;
+pub use dbg_scope::*;
+pub use dbg_segment::*;
+pub use dbg_span::*;
+pub use dbg_symbol::*;
+pub use dbg_symbol_kind::*;
+pub use debug_info::*;
 pub use export::*;
 pub use export_kind::*;
 pub use map_file::*;
@@ -2,7 +2,7 @@ use crate::symbols::{ModuleName, ModuleSegment};
 use anyhow::{Result, bail};
 use std::iter::Peekable;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Module {
     pub name: ModuleName,
     pub segments: Vec<ModuleSegment>,
@@ -45,11 +45,7 @@ impl Module {
         _ = lines.next();
 
         let mut segments = Vec::new();
-        loop {
-            let Some(s) = lines.peek() else {
-                break;
-            };
-
+        while let Some(s) = lines.peek() {
             let Ok(segment) = s.parse::<ModuleSegment>() else {
                 break;
             };
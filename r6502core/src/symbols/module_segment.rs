@@ -1,7 +1,7 @@
 use anyhow::{Error, bail};
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ModuleSegment {
     pub name: String,
     pub offset: u32,
@@ -1,7 +1,7 @@
 use anyhow::{Error, Result, bail};
 use std::{iter::Peekable, str::FromStr};
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Segment {
     pub name: String,
     pub start: u32,
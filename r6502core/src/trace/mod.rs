@@ -0,0 +1,13 @@
+mod trace_divergence;
+mod trace_filter;
+mod trace_header;
+mod trace_reader;
+mod trace_record;
+mod trace_recorder;
+
+pub use trace_divergence::*;
+pub use trace_filter::*;
+pub use trace_header::*;
+pub use trace_reader::*;
+pub use trace_record::*;
+pub use trace_recorder::*;
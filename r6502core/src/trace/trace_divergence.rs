@@ -0,0 +1,153 @@
+use crate::trace::TraceRecord;
+use anyhow::Result;
+use std::collections::VecDeque;
+
+// First point at which two traces of the same program stop matching
+#[derive(Debug, PartialEq)]
+pub struct TraceDivergence {
+    // Number of records before the divergence
+    pub index: u64,
+    // Matching records immediately before the divergence
+    pub context: Vec<TraceRecord>,
+    // None if the trace ended first
+    pub left: Option<TraceRecord>,
+    pub right: Option<TraceRecord>,
+}
+
+impl TraceDivergence {
+    // None if the traces are identical, keeping up to context_len matching records for reference
+    pub fn find<L, R>(left: L, right: R, context_len: usize) -> Result<Option<Self>>
+    where
+        L: IntoIterator<Item = Result<TraceRecord>>,
+        R: IntoIterator<Item = Result<TraceRecord>>,
+    {
+        let mut left = left.into_iter();
+        let mut right = right.into_iter();
+        let mut context = VecDeque::with_capacity(context_len);
+        let mut index = 0;
+        loop {
+            let l = left.next().transpose()?;
+            let r = right.next().transpose()?;
+            match (l, r) {
+                (None, None) => return Ok(None),
+                (Some(l), Some(r)) if l == r => {
+                    if context_len > 0 {
+                        if context.len() == context_len {
+                            _ = context.pop_front();
+                        }
+                        context.push_back(l);
+                    }
+                    index += 1;
+                }
+                (left, right) => {
+                    return Ok(Some(Self {
+                        index,
+                        context: context.into(),
+                        left,
+                        right,
+                    }));
+                }
+            }
+        }
+    }
+
+    // Names of the parts of the first differing records that don't match
+    #[must_use]
+    pub fn differences(&self) -> Vec<&'static str> {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => left.differences(right),
+            _ => vec!["length"],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::{TraceDivergence, TraceRecord};
+    use crate::{Opcode, Reg};
+    use anyhow::{Result, anyhow};
+    use rstest::rstest;
+
+    fn record(pc: u16, a: u8) -> TraceRecord {
+        TraceRecord {
+            total_cycles: u64::from(pc),
+            pc,
+            opcode: Opcode::Nop as u8,
+            operands: Vec::new(),
+            cycles: 2,
+            interrupt: None,
+            reg: Reg {
+                a,
+                pc: pc + 1,
+                ..Reg::default()
+            },
+            writes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn basics() -> Result<()> {
+        let left = || (0..5).map(|i| Ok(record(i, 0)));
+        assert_eq!(None, TraceDivergence::find(left(), left(), 2)?);
+
+        let right = (0..5).map(|i| Ok(record(i, u8::from(i == 3))));
+        let divergence = TraceDivergence::find(left(), right, 2)?.expect("Must diverge");
+        assert_eq!(3, divergence.index);
+        assert_eq!(
+            vec![1, 2],
+            divergence.context.iter().map(|r| r.pc).collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["A"], divergence.differences());
+
+        let divergence = TraceDivergence::find(left(), left().take(4), 0)?.expect("Must diverge");
+        assert_eq!(4, divergence.index);
+        assert_eq!(None, divergence.right);
+        assert_eq!(vec!["length"], divergence.differences());
+        Ok(())
+    }
+
+    // Left and right records are numbered by PC with A set to 1 at the given index
+    #[rstest]
+    #[case(None, 3, 3, None)]
+    #[case(Some((0, Some(0), Some(0), "A")), 3, 3, Some(0))]
+    #[case(Some((2, Some(2), Some(2), "A")), 3, 3, Some(2))]
+    #[case(Some((2, None, Some(2), "length")), 2, 3, None)]
+    #[case(Some((2, Some(2), None, "length")), 3, 2, None)]
+    #[case(Some((0, None, Some(0), "length")), 0, 3, None)]
+    #[case(None, 0, 0, None)]
+    fn edges(
+        #[case] expected: Option<(u64, Option<u16>, Option<u16>, &str)>,
+        #[case] left_len: u16,
+        #[case] right_len: u16,
+        #[case] changed: Option<u16>,
+    ) -> Result<()> {
+        let left = (0..left_len).map(|i| Ok(record(i, 0)));
+        let right = (0..right_len).map(|i| Ok(record(i, u8::from(Some(i) == changed))));
+        let divergence = TraceDivergence::find(left, right, 1)?;
+        assert_eq!(
+            expected,
+            divergence.as_ref().map(|d| (
+                d.index,
+                d.left.as_ref().map(|r| r.pc),
+                d.right.as_ref().map(|r| r.pc),
+                d.differences()[0]
+            ))
+        );
+
+        // Context is the last matching record if there is one
+        if let Some(divergence) = divergence {
+            assert_eq!(
+                divergence.index.checked_sub(1),
+                divergence.context.first().map(|r| u64::from(r.pc))
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn read_error() {
+        let left = [Ok(record(0, 0)), Err(anyhow!("truncated"))];
+        let right = (0..3).map(|i| Ok(record(i, 0)));
+        assert!(TraceDivergence::find(left, right, 0).is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use r6502lib::{AddressRange, TotalCycles};
+
+// Which instructions are recorded: all of them unless restricted by address or by clock
+#[derive(Clone, Debug, Default)]
+pub struct TraceFilter {
+    pub ranges: Vec<AddressRange>,
+    pub from: Option<TotalCycles>,
+    pub until: Option<TotalCycles>,
+}
+
+impl TraceFilter {
+    #[must_use]
+    pub fn matches(&self, pc: u16, total_cycles: TotalCycles) -> bool {
+        (self.ranges.is_empty() || self.ranges.iter().any(|r| r.contains(pc)))
+            && self.from.is_none_or(|from| total_cycles >= from)
+            && self.until.is_none_or(|until| total_cycles < until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::TraceFilter;
+    use anyhow::Result;
+    use r6502lib::AddressRange;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(true, 0x0e00, 0, None, None)]
+    #[case(true, 0x0e0f, 0, None, None)]
+    #[case(true, 0x2080, 0, None, None)]
+    #[case(false, 0x0e10, 0, None, None)]
+    #[case(false, 0x1fff, 0, None, None)]
+    #[case(true, 0x0e00, 100, Some(100), None)]
+    #[case(false, 0x0e00, 99, Some(100), None)]
+    #[case(true, 0x0e00, 199, None, Some(200))]
+    #[case(false, 0x0e00, 200, None, Some(200))]
+    #[case(true, 0x2000, 150, Some(100), Some(200))]
+    #[case(false, 0x0e10, 150, Some(100), Some(200))]
+    fn matches(
+        #[case] expected: bool,
+        #[case] pc: u16,
+        #[case] total_cycles: u64,
+        #[case] from: Option<u64>,
+        #[case] until: Option<u64>,
+    ) -> Result<()> {
+        let filter = TraceFilter {
+            ranges: vec![
+                AddressRange::new(0x0e00, 0x0e0f)?,
+                AddressRange::new(0x2000, 0x20ff)?,
+            ],
+            from,
+            until,
+        };
+        assert_eq!(expected, filter.matches(pc, total_cycles));
+
+        // Clock window applies to all addresses when no range is given
+        let unranged = TraceFilter {
+            ranges: Vec::new(),
+            ..filter
+        };
+        assert_eq!(
+            from.is_none_or(|from| total_cycles >= from)
+                && until.is_none_or(|until| total_cycles < until),
+            unranged.matches(pc, total_cycles)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::{Result, bail};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+const TRACE_MAGIC: &[u8; 8] = b"R6502TRC";
+const TRACE_VERSION: u8 = 1;
+
+// Start of trace file: image path is kept so that symbols can be found when showing the trace
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TraceHeader {
+    pub image_path: Option<PathBuf>,
+}
+
+impl TraceHeader {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let path = self
+            .image_path
+            .as_ref()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        writer.write_all(TRACE_MAGIC)?;
+        writer.write_all(&[TRACE_VERSION])?;
+        writer.write_all(&u16::try_from(path.len())?.to_le_bytes())?;
+        writer.write_all(path.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut magic = [0x00; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != TRACE_MAGIC {
+            bail!("not a trace file")
+        }
+
+        let mut version = [0x00; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != TRACE_VERSION {
+            bail!("unsupported trace file version {}", version[0])
+        }
+
+        let mut len = [0x00; 2];
+        reader.read_exact(&mut len)?;
+        let mut path = vec![0x00; usize::from(u16::from_le_bytes(len))];
+        reader.read_exact(&mut path)?;
+        let path = String::from_utf8(path)?;
+        Ok(Self {
+            image_path: (!path.is_empty()).then(|| PathBuf::from(path)),
+        })
+    }
+}
@@ -0,0 +1,37 @@
+use crate::trace::{TraceHeader, TraceRecord};
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+// Reads records one at a time since traces of long runs may not fit in memory
+pub struct TraceReader<R: Read> {
+    header: TraceHeader,
+    reader: R,
+}
+
+impl TraceReader<BufReader<File>> {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> TraceReader<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let header = TraceHeader::read_from(&mut reader)?;
+        Ok(Self { header, reader })
+    }
+
+    #[must_use]
+    pub const fn header(&self) -> &TraceHeader {
+        &self.header
+    }
+}
+
+impl<R: Read> Iterator for TraceReader<R> {
+    type Item = Result<TraceRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        TraceRecord::read_from(&mut self.reader).transpose()
+    }
+}
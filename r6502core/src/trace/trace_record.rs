@@ -0,0 +1,169 @@
+use crate::emulator::InstructionInfo;
+use crate::symbols::MapFile;
+use crate::{InterruptEvent, P, Reg};
+use anyhow::{Result, bail};
+use r6502lib::TotalCycles;
+use std::fmt::Write as FmtWrite;
+use std::io::{ErrorKind, Read, Write};
+
+// One executed instruction: registers are those after it executed
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceRecord {
+    // Clock when the instruction started
+    pub total_cycles: TotalCycles,
+    pub pc: u16,
+    pub opcode: u8,
+    pub operands: Vec<u8>,
+    pub cycles: u8,
+    // Interrupt serviced immediately before the instruction
+    pub interrupt: Option<InterruptEvent>,
+    pub reg: Reg,
+    pub writes: Vec<(u16, u8)>,
+}
+
+impl TraceRecord {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let interrupt = match self.interrupt {
+            None => 0,
+            Some(InterruptEvent::Irq) => 1,
+            Some(InterruptEvent::Nmi) => 2,
+            Some(InterruptEvent::Reset) => 3,
+        };
+        let operand_count = u8::try_from(self.operands.len())?;
+        if operand_count > 2 {
+            bail!("too many operands")
+        }
+
+        writer.write_all(&self.total_cycles.to_le_bytes())?;
+        writer.write_all(&self.pc.to_le_bytes())?;
+        writer.write_all(&[operand_count | (interrupt << 2), self.opcode])?;
+        writer.write_all(&self.operands)?;
+        writer.write_all(&[self.cycles])?;
+        writer.write_all(&self.reg.pc.to_le_bytes())?;
+        writer.write_all(&[
+            self.reg.a,
+            self.reg.x,
+            self.reg.y,
+            self.reg.p.bits(),
+            self.reg.sp,
+        ])?;
+        writer.write_all(&[u8::try_from(self.writes.len())?])?;
+        for (addr, value) in &self.writes {
+            writer.write_all(&addr.to_le_bytes())?;
+            writer.write_all(&[*value])?;
+        }
+        Ok(())
+    }
+
+    // None at the end of the trace
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Option<Self>> {
+        let mut total_cycles = [0x00; 8];
+        match reader.read_exact(&mut total_cycles) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut fixed = [0x00; 4];
+        reader.read_exact(&mut fixed)?;
+        let pc = u16::from_le_bytes([fixed[0], fixed[1]]);
+        let interrupt = match fixed[2] >> 2 {
+            0 => None,
+            1 => Some(InterruptEvent::Irq),
+            2 => Some(InterruptEvent::Nmi),
+            3 => Some(InterruptEvent::Reset),
+            _ => bail!("invalid trace record"),
+        };
+        let opcode = fixed[3];
+
+        let mut operands = vec![0x00; usize::from(fixed[2] & 0b11)];
+        reader.read_exact(&mut operands)?;
+
+        let mut state = [0x00; 9];
+        reader.read_exact(&mut state)?;
+        let reg = Reg {
+            a: state[3],
+            x: state[4],
+            y: state[5],
+            p: P::from_bits_retain(state[6]),
+            pc: u16::from_le_bytes([state[1], state[2]]),
+            sp: state[7],
+        };
+
+        let mut writes = Vec::with_capacity(usize::from(state[8]));
+        for _ in 0..state[8] {
+            let mut write = [0x00; 3];
+            reader.read_exact(&mut write)?;
+            writes.push((u16::from_le_bytes([write[0], write[1]]), write[2]));
+        }
+
+        Ok(Some(Self {
+            total_cycles: TotalCycles::from_le_bytes(total_cycles),
+            pc,
+            opcode,
+            operands,
+            cycles: state[0],
+            interrupt,
+            reg,
+            writes,
+        }))
+    }
+
+    // Names of the parts of the two records that don't match
+    #[must_use]
+    pub fn differences(&self, other: &Self) -> Vec<&'static str> {
+        let mut result = Vec::new();
+        let mut check = |name, differs| {
+            if differs {
+                result.push(name);
+            }
+        };
+        check("clock", self.total_cycles != other.total_cycles);
+        check("PC", self.pc != other.pc);
+        check(
+            "instruction",
+            self.opcode != other.opcode || self.operands != other.operands,
+        );
+        check("cycles", self.cycles != other.cycles);
+        check("interrupt", self.interrupt != other.interrupt);
+        check("A", self.reg.a != other.reg.a);
+        check("X", self.reg.x != other.reg.x);
+        check("Y", self.reg.y != other.reg.y);
+        check("P", self.reg.p != other.reg.p);
+        check("SP", self.reg.sp != other.reg.sp);
+        check("next PC", self.reg.pc != other.reg.pc);
+        check("writes", self.writes != other.writes);
+        result
+    }
+
+    // Single line with the instruction disassembled and symbolized using the map file
+    #[must_use]
+    pub fn describe(&self, map_file: &MapFile) -> String {
+        let mut bytes = vec![self.opcode];
+        bytes.extend(&self.operands);
+        let mut disassembly = InstructionInfo::decode(self.pc, &bytes)
+            .and_then(|info| info.disassembly(map_file).ok())
+            .unwrap_or_else(|| format!("{:04X}  {:02X}        ???", self.pc, self.opcode));
+        let symbol = map_file.symbolize(self.pc);
+        if !symbol.starts_with('$') {
+            _ = write!(disassembly, "  ({symbol})");
+        }
+
+        let mut s = format!(
+            "{total_cycles:>10}  {disassembly:<50}  A={a:02X} X={x:02X} Y={y:02X} P={p} SP={sp:02X}",
+            total_cycles = self.total_cycles,
+            a = self.reg.a,
+            x = self.reg.x,
+            y = self.reg.y,
+            p = self.reg.p,
+            sp = self.reg.sp
+        );
+        if let Some(interrupt) = self.interrupt {
+            _ = write!(s, "  [{interrupt}]");
+        }
+        for (addr, value) in &self.writes {
+            _ = write!(s, "  ${addr:04X}={value:02X}");
+        }
+        s
+    }
+}
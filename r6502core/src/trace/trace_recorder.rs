@@ -0,0 +1,174 @@
+use crate::Opcode;
+use crate::emulator::{BusMonitor, Cpu, OpMetadata};
+use crate::trace::{TraceFilter, TraceHeader, TraceRecord};
+use anyhow::{Error, Result};
+use r6502lib::TotalCycles;
+use r6502lib::util::make_word;
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+
+#[derive(Clone, Copy)]
+struct Before {
+    pc: u16,
+    total_cycles: TotalCycles,
+}
+
+// Writes a record for each instruction stepped between before_step and after_step along with the
+// memory writes it made
+pub struct TraceRecorder<W: Write> {
+    writer: RefCell<W>,
+    filter: TraceFilter,
+    before: Cell<Option<Before>>,
+    writes: RefCell<Vec<(u16, u8)>>,
+    error: RefCell<Option<Error>>,
+}
+
+impl<W: Write> TraceRecorder<W> {
+    pub fn new(mut writer: W, header: &TraceHeader, filter: TraceFilter) -> Result<Self> {
+        header.write_to(&mut writer)?;
+        Ok(Self {
+            writer: RefCell::new(writer),
+            filter,
+            before: Cell::new(None),
+            writes: RefCell::new(Vec::new()),
+            error: RefCell::new(None),
+        })
+    }
+
+    pub fn before_step(&self, cpu: &Cpu) {
+        self.writes.borrow_mut().clear();
+        self.before.set(Some(Before {
+            pc: cpu.reg.pc,
+            total_cycles: cpu.total_cycles,
+        }));
+    }
+
+    pub fn after_step(&self, cpu: &Cpu) {
+        let Some(Before { pc, total_cycles }) = self.before.take() else {
+            return;
+        };
+
+        // The instruction actually executed was the first one in the handler
+        let pc = cpu.last_interrupt.map_or(pc, |interrupt| {
+            let vector = interrupt.vector();
+            make_word(cpu.bus.peek(vector.wrapping_add(1)), cpu.bus.peek(vector))
+        });
+        if !self.filter.matches(pc, total_cycles) || self.error.borrow().is_some() {
+            return;
+        }
+
+        let opcode = cpu.bus.peek(pc);
+        let size = Opcode::from_u8(opcode)
+            .and_then(OpMetadata::get)
+            .map_or(1, |m| m.bytes);
+        let record = TraceRecord {
+            total_cycles,
+            pc,
+            opcode,
+            operands: (1..size)
+                .map(|i| cpu.bus.peek(pc.wrapping_add(i)))
+                .collect(),
+            cycles: u8::try_from(cpu.total_cycles.saturating_sub(total_cycles)).unwrap_or(u8::MAX),
+            interrupt: cpu.last_interrupt,
+            reg: cpu.reg.clone(),
+            writes: self.writes.take(),
+        };
+        if let Err(e) = record.write_to(&mut *self.writer.borrow_mut()) {
+            *self.error.borrow_mut() = Some(e);
+        }
+    }
+
+    // Flush the trace and report the first error encountered while writing it, if any
+    pub fn finish(&self) -> Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.borrow_mut().flush()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> BusMonitor for TraceRecorder<W> {
+    fn on_store(&self, addr: u16, value: u8) {
+        if self.before.get().is_some() {
+            self.writes.borrow_mut().push((addr, value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::{Bus, BusView, Cpu};
+    use crate::symbols::MapFile;
+    use crate::trace::{TraceFilter, TraceHeader, TraceReader, TraceRecorder};
+    use crate::{InterruptChannel, Opcode};
+    use anyhow::Result;
+    use r6502lib::AddressRange;
+    use std::path::PathBuf;
+
+    #[test]
+    fn basics() -> Result<()> {
+        // LDA #$41; STA $0200; JSR sub; NOP; ...; sub: INX; RTS
+        let bus = Bus::default();
+        for (addr, value) in (0x0e00..).zip([
+            Opcode::LdaImm as u8,
+            0x41,
+            Opcode::StaAbs as u8,
+            0x00,
+            0x02,
+            Opcode::Jsr as u8,
+            0x10,
+            0x0e,
+            Opcode::Nop as u8,
+        ]) {
+            bus.store(addr, value);
+        }
+        bus.store(0x0e10, Opcode::Inx as u8);
+        bus.store(0x0e11, Opcode::Rts as u8);
+
+        let header = TraceHeader {
+            image_path: Some(PathBuf::from("/images/test.r6502")),
+        };
+        let filter = TraceFilter {
+            ranges: vec![AddressRange::new(0x0e00, 0x0e0f)?],
+            from: Some(2),
+            until: None,
+        };
+        let mut bytes = Vec::new();
+        let recorder = TraceRecorder::new(&mut bytes, &header, filter)?;
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(
+            BusView::with_monitor(&bus, &recorder),
+            None,
+            interrupt_channel.rx,
+        );
+        cpu.reg.pc = 0x0e00;
+        for _ in 0..6 {
+            recorder.before_step(&cpu);
+            cpu.step_no_spin();
+            recorder.after_step(&cpu);
+        }
+        recorder.finish()?;
+        drop(cpu);
+        drop(recorder);
+
+        let reader = TraceReader::new(bytes.as_slice())?;
+        assert_eq!(&header, reader.header());
+        let records = reader.collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            vec![0x0e02, 0x0e05, 0x0e08],
+            records.iter().map(|r| r.pc).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![(0x0200, 0x41)], records[0].writes);
+        assert_eq!(vec![0x00, 0x02], records[0].operands);
+        assert_eq!(vec![(0x01ff, 0x0e), (0x01fe, 0x07)], records[1].writes);
+        assert_eq!(0x0e10, records[1].reg.pc);
+        assert_eq!(0x01, records[2].reg.x);
+        assert!(
+            records[0]
+                .describe(&MapFile::default())
+                .starts_with("         2  0E02  8D 00 02  STA $0200")
+        );
+        Ok(())
+    }
+}
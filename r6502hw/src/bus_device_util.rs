@@ -16,6 +16,11 @@ pub fn map_io_device(
 ) -> DeviceMapping {
     let device: Box<dyn BusDevice> = match bus_device.r#type {
         BusDeviceType::Pia | BusDeviceType::Via => Box::new(InterfaceAdapter::new(
+            if bus_device.r#type == BusDeviceType::Pia {
+                "PIA"
+            } else {
+                "VIA"
+            },
             output,
             io_channel,
             bus_tx.clone(),
@@ -7,7 +7,7 @@ use r6502core::emulator::IoEvent::{
 };
 use r6502core::emulator::char_set_util::translate_in;
 use r6502core::emulator::{BusEvent, IoChannel, OutputDevice};
-use r6502core::{BusDevice, InterruptEvent};
+use r6502core::{BusDevice, DeviceState, InterruptEvent};
 use r6502lib::keyboard::{KeyCode, KeyEvent, KeyModifiers};
 use std::cell::Cell;
 use std::sync::mpsc::{Receiver, Sender};
@@ -41,6 +41,7 @@ impl InterfaceAdapterState {
 
 // A barely adequate emulation of the 6821 PIA and 6522 VIA
 pub struct InterfaceAdapter {
+    kind: &'static str,
     state: Arc<Mutex<InterfaceAdapterState>>,
     io_tx: Sender<IoEvent>,
     handle: Cell<Option<JoinHandle<()>>>,
@@ -54,6 +55,7 @@ impl InterfaceAdapter {
 
     #[must_use]
     pub fn new(
+        kind: &'static str,
         output: Box<dyn OutputDevice>,
         io_channel: IoChannel,
         bus_tx: Sender<BusEvent>,
@@ -74,6 +76,7 @@ impl InterfaceAdapter {
             .expect("Must succeed");
         });
         Self {
+            kind,
             state,
             io_tx: io_channel.tx,
             handle: Cell::new(Some(handle)),
@@ -182,4 +185,25 @@ impl BusDevice for InterfaceAdapter {
         };
         _ = self.io_tx.send(m);
     }
+
+    fn inspect(&self) -> DeviceState {
+        let state = self.state.lock().unwrap();
+        let description = if !state.started {
+            String::from("not started")
+        } else if state.pa_cr & 0x80 == 0 {
+            String::from("no key pending")
+        } else {
+            format!("key ${:02X} pending", state.pa)
+        };
+        DeviceState {
+            kind: String::from(self.kind),
+            registers: vec![
+                (String::from("PA"), state.pa),
+                (String::from("PACR"), state.pa_cr),
+                (String::from("PB"), state.pb),
+                (String::from("PBCR"), state.pb_cr),
+            ],
+            description,
+        }
+    }
 }
@@ -117,9 +117,16 @@ impl MachineInfo {
                 .join("r6502"))
         }
 
-        let p0 = bin_path
+        let mut p0 = bin_path
             .parent()
             .ok_or_else(|| anyhow!("cannot get parent directory from {}", bin_path.display()))?;
+
+        // Test executables are built in a "deps" subdirectory
+        if p0.file_name().and_then(OsStr::to_str) == Some("deps") {
+            p0 = p0
+                .parent()
+                .ok_or_else(|| anyhow!("cannot get parent directory from {}", p0.display()))?;
+        }
         let d = p0.file_name().and_then(OsStr::to_str);
         if d != Some("debug") && d != Some("release") {
             return user_config_dir();
@@ -2,7 +2,9 @@ use crate::num::Truncate;
 use anyhow::Result;
 use chrono::Utc;
 use std::env::current_dir;
-use std::path::PathBuf;
+use std::fs::create_dir;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
 pub fn make_unique_snapshot_path() -> Result<PathBuf> {
     let now = Utc::now();
@@ -14,6 +16,33 @@ pub fn make_unique_snapshot_path() -> Result<PathBuf> {
     Ok(current_dir()?.join(file_name))
 }
 
+// Creates the directory so that post-mortems written within the same second each get their own
+pub fn make_unique_post_mortem_dir() -> Result<PathBuf> {
+    let now = Utc::now();
+    let prefix = format!(
+        "r6502-post-mortem-{timestamp}",
+        timestamp = now.format("%Y%m%d%H%M%S")
+    );
+
+    create_unique_dir(&current_dir()?, &prefix)
+}
+
+// Directory named after the prefix with a counter added if the name is already taken
+pub fn create_unique_dir(parent: &Path, prefix: &str) -> Result<PathBuf> {
+    let mut counter = 0;
+    loop {
+        let dir = match counter {
+            0 => parent.join(prefix),
+            _ => parent.join(format!("{prefix}-{counter}")),
+        };
+        match create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => counter += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
 #[must_use]
 pub const fn make_word(hi: u8, lo: u8) -> u16 {
     ((hi as u16) << 8) + lo as u16
@@ -27,14 +56,18 @@ pub fn split_word(value: u16) -> (u8, u8) {
 }
 
 #[must_use]
-pub const fn crosses_page_boundary(addr: u16) -> bool {
-    (addr & 0x00ff) == 0x00ff
+pub const fn crosses_page_boundary(base_addr: u16, effective_addr: u16) -> bool {
+    (base_addr ^ effective_addr) & 0xff00 != 0
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::util::{crosses_page_boundary, make_word};
+    use crate::util::{create_unique_dir, crosses_page_boundary, make_word};
+    use anyhow::Result;
     use rstest::rstest;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use std::process;
 
     #[rstest]
     #[case(0x1234, 0x12, 0x34)]
@@ -43,11 +76,39 @@ mod tests {
     }
 
     #[rstest]
-    #[case(false, 0x0000)]
-    #[case(true, 0x00ff)]
-    #[case(false, 0x0100)]
-    #[case(true, 0x01ff)]
-    fn crosses_page_boundary_basics(#[case] expected_result: bool, #[case] input: u16) {
-        assert_eq!(expected_result, crosses_page_boundary(input));
+    #[case(false, 0x0000, 0x0000)]
+    #[case(false, 0x0000, 0x00ff)]
+    #[case(true, 0x00ff, 0x0100)]
+    #[case(true, 0x01ff, 0x0200)]
+    #[case(true, 0xffff, 0x0000)]
+    fn crosses_page_boundary_basics(
+        #[case] expected_result: bool,
+        #[case] base_addr: u16,
+        #[case] effective_addr: u16,
+    ) {
+        assert_eq!(
+            expected_result,
+            crosses_page_boundary(base_addr, effective_addr)
+        );
+    }
+
+    #[test]
+    fn create_unique_dir_basics() -> Result<()> {
+        let parent = temp_dir().join(format!("r6502-unique-dir-{}", process::id()));
+        create_dir_all(&parent)?;
+        let dirs = (0..3)
+            .map(|_| create_unique_dir(&parent, "post-mortem"))
+            .collect::<Result<Vec<_>>>();
+        remove_dir_all(&parent)?;
+
+        assert_eq!(
+            vec![
+                parent.join("post-mortem"),
+                parent.join("post-mortem-1"),
+                parent.join("post-mortem-2")
+            ],
+            dirs?
+        );
+        Ok(())
     }
 }
@@ -75,7 +75,7 @@ impl MemoryImage {
     }
 
     #[must_use]
-    pub fn slice(&self, range: &AddressRange) -> MemorySlice {
+    pub fn slice(&self, range: &AddressRange) -> MemorySlice<'_> {
         let memory_start = self.load().unwrap_or_default() as usize;
         let bytes = self.bytes();
         let memory_end = memory_start + bytes.len();
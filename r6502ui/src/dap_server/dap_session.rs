@@ -0,0 +1,526 @@
+use crate::dap_server::protocol::write_message;
+use crate::headless::{HostClient, HostEvent, HostInput, Received, StopReason};
+use anyhow::{Result, anyhow, bail};
+use r6502core::debugger::Expr;
+use r6502core::messages::DebugMessage;
+use r6502core::p_get;
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502hw::MachineInfo;
+use r6502lib::AddressRange;
+use r6502snapshot::MemoryImage;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::mem::take;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender};
+
+const THREAD_ID: u64 = 1;
+const REGISTERS_REF: u64 = 1;
+const FLAGS_REF: u64 = 2;
+const ZERO_PAGE_REF: u64 = 3;
+const ZERO_PAGE_ROW_SIZE: usize = 16;
+
+// Debug Adapter Protocol front-end for TuiHost
+pub struct DapSession<W: Write> {
+    writer: W,
+    seq: u64,
+    client: HostClient<Value>,
+    events: Vec<Value>,
+    map_file: MapFile,
+    debug_info: Arc<DebugInfo>,
+    stop_on_entry: bool,
+    source_breakpoints: HashMap<String, BTreeMap<u16, Option<Expr>>>,
+    function_breakpoints: BTreeMap<u16, Option<Expr>>,
+    breakpoints: BTreeMap<u16, Option<Expr>>,
+    terminated: bool,
+}
+
+impl<W: Write> DapSession<W> {
+    pub fn new(
+        writer: W,
+        input_tx: Sender<HostInput<Value>>,
+        input_rx: Receiver<HostInput<Value>>,
+    ) -> Self {
+        Self {
+            writer,
+            seq: 0,
+            client: HostClient::new(input_tx, input_rx),
+            events: Vec::new(),
+            map_file: MapFile::default(),
+            debug_info: Arc::new(DebugInfo::default()),
+            stop_on_entry: false,
+            source_breakpoints: HashMap::new(),
+            function_breakpoints: BTreeMap::new(),
+            breakpoints: BTreeMap::new(),
+            terminated: false,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        let result = self.handle_inputs();
+
+        self.client.stop()?;
+        result
+    }
+
+    fn handle_inputs(&mut self) -> Result<()> {
+        loop {
+            match self.client.receive() {
+                Received::Request(request) => {
+                    if !self.handle_request(&request)? {
+                        return Ok(());
+                    }
+                }
+                Received::Idle => {}
+                Received::Disconnected => return Ok(()),
+            }
+
+            // Events triggered by a request must follow its response
+            self.flush()?;
+        }
+    }
+
+    // Returns false once the client disconnects
+    fn handle_request(&mut self, request: &Value) -> Result<bool> {
+        let command = request["command"].as_str().unwrap_or_default();
+        let args = &request["arguments"];
+        let result = match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsConditionalBreakpoints": true,
+                "supportsFunctionBreakpoints": true,
+                "supportsTerminateRequest": true,
+            })),
+            "launch" => self.launch(args),
+            "setBreakpoints" => self.set_breakpoints(args),
+            "setFunctionBreakpoints" => self.set_function_breakpoints(args),
+            "setExceptionBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "configurationDone" => self.configuration_done(),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "6502" }] })),
+            "stackTrace" => Ok(self.stack_trace()),
+            "scopes" => Ok(json!({
+                "scopes": [
+                    { "name": "Registers", "variablesReference": REGISTERS_REF, "expensive": false },
+                    { "name": "Flags", "variablesReference": FLAGS_REF, "expensive": false },
+                    { "name": "Zero Page", "variablesReference": ZERO_PAGE_REF, "expensive": true },
+                ]
+            })),
+            "variables" => self.variables(args),
+            "continue" => self
+                .resume(DebugMessage::Run, None)
+                .map(|()| json!({ "allThreadsContinued": true })),
+            "next" => self.step(DebugMessage::StepOver),
+            "stepIn" => self.step(DebugMessage::StepLine),
+            "stepOut" => self.step(DebugMessage::StepOut),
+            "pause" => self.pause(),
+            "evaluate" => self.evaluate(args),
+            "terminate" => {
+                self.terminate();
+                Ok(json!({}))
+            }
+            "disconnect" => {
+                self.send_response(request, Ok(json!({})))?;
+                return Ok(false);
+            }
+            _ => Err(anyhow!("unsupported request {command}")),
+        };
+
+        self.send_response(request, result)?;
+        Ok(true)
+    }
+
+    fn launch(&mut self, args: &Value) -> Result<Value> {
+        if self.client.is_launched() {
+            bail!("program already launched")
+        }
+
+        let path = PathBuf::from(
+            args["program"]
+                .as_str()
+                .ok_or_else(|| anyhow!("program not specified"))?,
+        );
+        let image = MemoryImage::from_file(&path)?;
+        let machine_info = match image.machine_tag() {
+            Some(tag) => MachineInfo::find_by_tag(tag)?,
+            None => MachineInfo::find_by_name(&args["machine"].as_str().map(String::from))?,
+        };
+
+        let mut map_file = MapFile::load(&path)?;
+        let debug_info = Arc::new(DebugInfo::load_or_default(&path));
+        map_file.merge_labels(&debug_info);
+        self.map_file = map_file;
+        self.debug_info = Arc::clone(&debug_info);
+        self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or_default();
+
+        self.client.launch(machine_info, image, debug_info)?;
+        self.sync_breakpoints()?;
+        self.send_event("initialized", &json!({}));
+        Ok(json!({}))
+    }
+
+    // Source breakpoints require debug info: without it, use function breakpoints on map symbols
+    fn set_breakpoints(&mut self, args: &Value) -> Result<Value> {
+        let source = &args["source"];
+        let path = source["path"]
+            .as_str()
+            .or_else(|| source["name"].as_str())
+            .ok_or_else(|| anyhow!("source not specified"))?;
+
+        let mut addrs = BTreeMap::new();
+        let breakpoints = Self::array(&args["breakpoints"])
+            .iter()
+            .map(|breakpoint| {
+                let line = breakpoint["line"]
+                    .as_u64()
+                    .and_then(|line| usize::try_from(line).ok())
+                    .unwrap_or_default();
+                let condition = match self.parse_condition(breakpoint) {
+                    Ok(condition) => condition,
+                    Err(e) => {
+                        return json!({ "verified": false, "line": line, "message": e.to_string() });
+                    }
+                };
+                match self.debug_info.find_addr(path, line) {
+                    Some(addr) => {
+                        _ = addrs.insert(addr, condition);
+                        let line = self.debug_info.find_line(addr).map_or(line, |l| l.line);
+                        Self::verified_breakpoint(addr, Some(line))
+                    }
+                    None => {
+                        json!({ "verified": false, "line": line, "message": "No code at line" })
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        _ = self.source_breakpoints.insert(String::from(path), addrs);
+        self.sync_breakpoints()?;
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn set_function_breakpoints(&mut self, args: &Value) -> Result<Value> {
+        let mut addrs = BTreeMap::new();
+        let breakpoints = Self::array(&args["breakpoints"])
+            .iter()
+            .map(|breakpoint| {
+                let name = breakpoint["name"].as_str().unwrap_or_default();
+                let addr = self
+                    .map_file
+                    .lookup(name)
+                    .ok_or_else(|| anyhow!("unknown symbol {name}"));
+                match addr.and_then(|addr| Ok((addr, self.parse_condition(breakpoint)?))) {
+                    Ok((addr, condition)) => {
+                        _ = addrs.insert(addr, condition);
+                        let line = self.debug_info.find_line(addr).map(|l| l.line);
+                        Self::verified_breakpoint(addr, line)
+                    }
+                    Err(e) => json!({ "verified": false, "message": e.to_string() }),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        self.function_breakpoints = addrs;
+        self.sync_breakpoints()?;
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn parse_condition(&self, breakpoint: &Value) -> Result<Option<Expr>> {
+        breakpoint["condition"]
+            .as_str()
+            .filter(|condition| !condition.trim().is_empty())
+            .map(|condition| self.resolve_symbols(&condition.parse()?))
+            .transpose()
+    }
+
+    fn resolve_symbols(&self, expr: &Expr) -> Result<Expr> {
+        expr.resolve(&|name| self.map_file.lookup(name))
+    }
+
+    fn verified_breakpoint(addr: u16, line: Option<usize>) -> Value {
+        let mut breakpoint = json!({
+            "verified": true,
+            "instructionReference": format!("0x{addr:04X}"),
+        });
+        if let Some(line) = line {
+            breakpoint["line"] = json!(line);
+        }
+        breakpoint
+    }
+
+    // Tell the host about breakpoints added or removed since the last change
+    fn sync_breakpoints(&mut self) -> Result<()> {
+        let breakpoints = self
+            .source_breakpoints
+            .values()
+            .flatten()
+            .chain(&self.function_breakpoints)
+            .map(|(addr, condition)| (*addr, condition.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        if self.client.is_launched() {
+            for addr in self.breakpoints.keys() {
+                if !breakpoints.contains_key(addr) {
+                    self.client.send(DebugMessage::RemoveBreakpoint(*addr))?;
+                }
+            }
+            for (addr, condition) in &breakpoints {
+                if self.breakpoints.get(addr) != Some(condition) {
+                    self.client
+                        .send(DebugMessage::AddBreakpoint(*addr, condition.clone()))?;
+                }
+            }
+        }
+
+        self.breakpoints = breakpoints;
+        Ok(())
+    }
+
+    fn configuration_done(&mut self) -> Result<Value> {
+        if self.stop_on_entry {
+            self.send_stopped("entry");
+        } else {
+            self.resume(DebugMessage::Run, None)?;
+        }
+        Ok(json!({}))
+    }
+
+    // Innermost frame is the current instruction followed by the call sites of enclosing frames
+    fn stack_trace(&self) -> Value {
+        let stack_frames = [self.client.reg().pc]
+            .into_iter()
+            .chain(self.client.frames().iter().rev().map(|frame| frame.site))
+            .enumerate()
+            .map(|(id, addr)| self.stack_frame(id, addr))
+            .collect::<Vec<_>>();
+        json!({ "stackFrames": stack_frames, "totalFrames": stack_frames.len() })
+    }
+
+    fn stack_frame(&self, id: usize, addr: u16) -> Value {
+        let mut frame = json!({
+            "id": id,
+            "name": self.map_file.symbolize(addr),
+            "line": 0,
+            "column": 0,
+            "instructionPointerReference": format!("0x{addr:04X}"),
+        });
+        if let Some(line) = self.debug_info.find_line(addr) {
+            let file = self.debug_info.file(line);
+            frame["source"] = json!({
+                "name": file.name,
+                "path": self.debug_info.source_path(file).display().to_string(),
+            });
+            frame["line"] = json!(line.line);
+            frame["column"] = json!(1);
+        }
+        frame
+    }
+
+    fn variables(&mut self, args: &Value) -> Result<Value> {
+        let reg = self.client.reg().clone();
+        let variables = match args["variablesReference"].as_u64() {
+            Some(REGISTERS_REF) => vec![
+                Self::variable("A", &format!("${:02X}", reg.a)),
+                Self::variable("X", &format!("${:02X}", reg.x)),
+                Self::variable("Y", &format!("${:02X}", reg.y)),
+                Self::variable("SP", &format!("${:02X}", reg.sp)),
+                Self::variable("PC", &format!("${:04X}", reg.pc)),
+                Self::variable("P", &format!("${:02X} {}", reg.p.bits(), reg.p)),
+            ],
+            Some(FLAGS_REF) => [
+                ("N", p_get!(reg, N)),
+                ("V", p_get!(reg, V)),
+                ("B", p_get!(reg, B)),
+                ("D", p_get!(reg, D)),
+                ("I", p_get!(reg, I)),
+                ("Z", p_get!(reg, Z)),
+                ("C", p_get!(reg, C)),
+            ]
+            .into_iter()
+            .map(|(name, value)| Self::variable(name, &u8::from(value).to_string()))
+            .collect(),
+            Some(ZERO_PAGE_REF) => self
+                .client
+                .fetch_memory(AddressRange::new(0x0000, 0x00ff)?)?
+                .chunks(ZERO_PAGE_ROW_SIZE)
+                .enumerate()
+                .map(|(i, chunk)| {
+                    Self::variable(
+                        &format!("${:02X}", i * ZERO_PAGE_ROW_SIZE),
+                        &Self::format_bytes(chunk),
+                    )
+                })
+                .collect(),
+            _ => bail!("invalid variables reference"),
+        };
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn variable(name: &str, value: &str) -> Value {
+        json!({ "name": name, "value": value, "variablesReference": 0 })
+    }
+
+    // Value of expression or memory at its address with optional length e.g. "buffer+2,4"
+    fn evaluate(&mut self, args: &Value) -> Result<Value> {
+        let expression = args["expression"]
+            .as_str()
+            .ok_or_else(|| anyhow!("expression not specified"))?
+            .trim();
+
+        let (expr, len) = match expression.split_once(',') {
+            Some((expr, len)) => (expr, Some(len)),
+            None => (expression, None),
+        };
+        let mut exprs = vec![self.resolve_symbols(&expr.parse()?)?];
+        if let Some(len) = len {
+            exprs.push(self.resolve_symbols(&len.parse()?)?);
+        }
+
+        let values = self.client.evaluate(exprs)?;
+        let result = if len.is_some() {
+            let addr = Expr::to_addr(values[0])?;
+            let len = u32::try_from(values[1])?;
+            if len == 0 {
+                bail!("invalid length in {expression}")
+            }
+
+            let end = u16::try_from(u32::from(addr) + len - 1)?;
+            let bytes = self.client.fetch_memory(AddressRange::new(addr, end)?)?;
+            format!("${addr:04X}: {}", Self::format_bytes(&bytes))
+        } else {
+            Expr::Number(values[0]).to_string()
+        };
+        Ok(json!({ "result": result, "variablesReference": 0 }))
+    }
+
+    fn format_bytes(bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .map(|b| format!("{b:02X}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn step(&mut self, message: DebugMessage) -> Result<Value> {
+        self.resume(message, Some(StopReason::Step))?;
+        Ok(json!({}))
+    }
+
+    // Requests to continue while running are harmless
+    fn resume(&mut self, message: DebugMessage, stop_reason: Option<StopReason>) -> Result<()> {
+        if self.client.is_stopped() {
+            self.client.resume(message, stop_reason)?;
+        }
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<Value> {
+        if self.client.is_stopped() {
+            self.send_stopped("pause");
+        } else {
+            self.client.pause()?;
+        }
+        Ok(json!({}))
+    }
+
+    fn on_host_event(&mut self, event: HostEvent) {
+        match event {
+            HostEvent::Stopped(reason) => self.send_stopped(match reason {
+                StopReason::Step => "step",
+                StopReason::Breakpoint => "breakpoint",
+                StopReason::Watchpoint => "data breakpoint",
+                StopReason::Pause => "pause",
+            }),
+            HostEvent::Halted { invalid_brk } => self.on_halted(invalid_brk),
+            HostEvent::Exited => self.terminate(),
+        }
+    }
+
+    fn on_halted(&mut self, invalid_brk: bool) {
+        if invalid_brk {
+            self.send_event(
+                "stopped",
+                &json!({
+                    "reason": "exception",
+                    "description": "Invalid BRK",
+                    "threadId": THREAD_ID,
+                    "allThreadsStopped": true,
+                }),
+            );
+        } else {
+            self.send_event("exited", &json!({ "exitCode": self.client.reg().a }));
+            self.terminate();
+        }
+    }
+
+    fn terminate(&mut self) {
+        if !self.terminated {
+            self.terminated = true;
+            self.send_event("terminated", &json!({}));
+        }
+    }
+
+    fn array(value: &Value) -> &[Value] {
+        value.as_array().map_or(&[], Vec::as_slice)
+    }
+
+    fn send_stopped(&mut self, reason: &str) {
+        self.send_event(
+            "stopped",
+            &json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+    }
+
+    // Events are sent once the current input has been handled
+    fn send_event(&mut self, event: &str, body: &Value) {
+        self.events
+            .push(json!({ "type": "event", "event": event, "body": body }));
+    }
+
+    fn send_response(&mut self, request: &Value, result: Result<Value>) -> Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+        });
+        match result {
+            Ok(body) => {
+                response["success"] = json!(true);
+                response["body"] = body;
+            }
+            Err(e) => {
+                response["success"] = json!(false);
+                response["message"] = json!(e.to_string());
+            }
+        }
+        self.send(response)
+    }
+
+    // Program output precedes any events it led up to
+    fn flush(&mut self) -> Result<()> {
+        for event in self.client.take_events() {
+            self.on_host_event(event);
+        }
+
+        let output = self.client.take_output();
+        if !output.is_empty() {
+            self.send(json!({
+                "type": "event",
+                "event": "output",
+                "body": { "category": "stdout", "output": output },
+            }))?;
+        }
+
+        for event in take(&mut self.events) {
+            self.send(event)?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, mut message: Value) -> Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        write_message(&mut self.writer, &message)
+    }
+}
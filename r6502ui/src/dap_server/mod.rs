@@ -0,0 +1,5 @@
+mod dap_session;
+mod protocol;
+mod run_dap;
+
+pub use run_dap::*;
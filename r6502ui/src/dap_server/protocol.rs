@@ -0,0 +1,81 @@
+use anyhow::{Result, anyhow, bail};
+use serde_json::Value;
+use std::io::{BufRead, Write};
+
+const CONTENT_LENGTH: &str = "Content-Length";
+
+// Read the next message or None if the client closed the stream
+pub fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow!("invalid header {line}"))?;
+        if name.trim().eq_ignore_ascii_case(CONTENT_LENGTH) {
+            content_length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+
+    let Some(content_length) = content_length else {
+        bail!("missing {CONTENT_LENGTH} header")
+    };
+
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "{CONTENT_LENGTH}: {}\r\n\r\n{content}",
+        content.len()
+    )?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dap_server::protocol::{read_message, write_message};
+    use anyhow::Result;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &json!({"seq": 1, "type": "request"}))?;
+        write_message(&mut buffer, &json!({"seq": 2, "type": "event"}))?;
+        assert!(buffer.starts_with(b"Content-Length: 26\r\n\r\n{"));
+
+        let mut reader = Cursor::new(buffer);
+        assert_eq!(
+            Some(json!({"seq": 1, "type": "request"})),
+            read_message(&mut reader)?
+        );
+        assert_eq!(
+            Some(json!({"seq": 2, "type": "event"})),
+            read_message(&mut reader)?
+        );
+        assert_eq!(None, read_message(&mut reader)?);
+        Ok(())
+    }
+
+    #[test]
+    fn missing_content_length() {
+        let mut reader = Cursor::new(b"Content-Type: application/json\r\n\r\n{}".to_vec());
+        assert!(read_message(&mut reader).is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use crate::dap_server::dap_session::DapSession;
+use crate::dap_server::protocol::read_message;
+use crate::headless::HostInput;
+use anyhow::Result;
+use std::io::{BufReader, Read, Write, stdin, stdout};
+use std::sync::mpsc::channel;
+use std::thread::spawn;
+
+pub fn run_dap_server() -> Result<()> {
+    serve_dap(stdin(), stdout())
+}
+
+// Debug programs on behalf of a client until it disconnects
+pub fn serve_dap(reader: impl Read + Send + 'static, writer: impl Write) -> Result<()> {
+    let (input_tx, input_rx) = channel();
+    let request_tx = input_tx.clone();
+
+    // Blocking reads can't be interrupted so this thread is left to finish when the client closes
+    // its end of the stream
+    _ = spawn(move || {
+        let mut reader = BufReader::new(reader);
+        while let Ok(Some(request)) = read_message(&mut reader) {
+            if request_tx.send(HostInput::Request(request)).is_err() {
+                return;
+            }
+        }
+        _ = request_tx.send(HostInput::Disconnected);
+    });
+
+    DapSession::new(writer, input_tx, input_rx).run()
+}
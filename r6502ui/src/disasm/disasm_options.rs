@@ -0,0 +1,12 @@
+use std::path::PathBuf;
+
+pub struct DisasmOptions {
+    pub path: PathBuf,
+    pub load: Option<u16>,
+    pub range: Option<String>,
+    pub ca65: bool,
+    pub trace: bool,
+    pub entry_points: Vec<u16>,
+    pub data: Vec<String>,
+    pub labels: Vec<String>,
+}
@@ -0,0 +1,7 @@
+mod disasm_options;
+mod run_disasm;
+mod run_opcodes;
+
+pub use disasm_options::*;
+pub use run_disasm::*;
+pub use run_opcodes::*;
@@ -0,0 +1,80 @@
+use crate::disasm::DisasmOptions;
+use anyhow::{Result, anyhow};
+use r6502core::disasm::{DisasmLabel, DisasmProject, Disassembler, Syntax};
+use r6502core::symbols::{AddressSize, DebugInfo, Export, ExportKind, MapFile};
+use r6502lib::AddressRange;
+use r6502snapshot::MemoryImage;
+
+pub fn run_disasm(opts: &DisasmOptions) -> Result<()> {
+    let image = MemoryImage::from_file(&opts.path)?;
+    let address_range = opts
+        .range
+        .as_deref()
+        .map(str::parse::<AddressRange>)
+        .transpose()?;
+
+    // Hints given on the command line are kept for next time
+    let project_path = DisasmProject::path(&opts.path)?;
+    let mut project = DisasmProject::load(&project_path)?;
+    let hints = hints(opts)?;
+    if hints != DisasmProject::default() {
+        project.merge(hints);
+        project.save(&project_path)?;
+    }
+
+    let mut map_file = MapFile::load(&opts.path)?;
+    map_file.merge_labels(&DebugInfo::load_or_default(&opts.path));
+    for label in &project.labels {
+        map_file.exports.retain(|e| e.name != label.name);
+        map_file.exports.push(Export {
+            name: label.name.clone(),
+            value: u32::from(label.addr),
+            referenced: true,
+            kind: ExportKind::Label,
+            address_size: AddressSize::Absolute,
+        });
+    }
+
+    let syntax = if opts.ca65 {
+        Syntax::Ca65
+    } else {
+        Syntax::Listing
+    };
+    let disassembler = Disassembler::new(&map_file, syntax);
+    let load = project.load.or(image.load()).unwrap_or_default();
+    let (base, bytes) = Disassembler::image_bytes(&image, load, address_range.as_ref());
+    let s = if opts.trace {
+        let mut entry_points = project.entry_points.clone();
+        entry_points.extend(image.start());
+        disassembler.disassemble_traced(base, bytes, &entry_points, &project.data)?
+    } else {
+        disassembler.disassemble(base, bytes)?
+    };
+    print!("{s}");
+    Ok(())
+}
+
+fn hints(opts: &DisasmOptions) -> Result<DisasmProject> {
+    Ok(DisasmProject {
+        load: opts.load,
+        entry_points: opts.entry_points.clone(),
+        data: opts.data.iter().map(|s| s.parse()).collect::<Result<_>>()?,
+        labels: opts
+            .labels
+            .iter()
+            .map(|s| {
+                let (addr, name) = s
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("invalid label {s}: expected <ADDRESS>=<NAME>"))?;
+                let addr = match addr.strip_prefix('$') {
+                    Some(suffix) => u16::from_str_radix(suffix, 16)?,
+                    None => addr.parse()?,
+                };
+                Ok(DisasmLabel {
+                    addr,
+                    name: String::from(name),
+                })
+            })
+            .collect::<Result<_>>()?,
+    })
+}
@@ -0,0 +1,17 @@
+use anyhow::{Result, bail};
+use r6502core::emulator::OpMetadata;
+
+pub fn run_opcodes(mnemonic: Option<&str>) -> Result<()> {
+    let metadata = match mnemonic {
+        Some(mnemonic) => {
+            let metadata = OpMetadata::for_mnemonic(mnemonic);
+            if metadata.is_empty() {
+                bail!("unknown instruction {mnemonic}")
+            }
+            metadata
+        }
+        None => OpMetadata::iter().collect(),
+    };
+    print!("{}", OpMetadata::format_table(metadata));
+    Ok(())
+}
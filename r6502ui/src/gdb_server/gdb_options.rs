@@ -0,0 +1,7 @@
+use std::path::PathBuf;
+
+pub struct GdbOptions {
+    pub path: PathBuf,
+    pub port: u16,
+    pub machine: Option<String>,
+}
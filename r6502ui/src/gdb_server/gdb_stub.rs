@@ -0,0 +1,392 @@
+use crate::gdb_server::packet::{
+    Received, decode_hex, encode_hex, encode_packet, poll_interrupt, read_packet,
+};
+use anyhow::{Result, anyhow, bail};
+use r6502core::debugger::{WatchHit, WatchKind, Watchpoint};
+use r6502core::messages::{DebugMessage, MonitorMessage, State};
+use r6502core::{_p, Reg};
+use r6502lib::AddressRange;
+use r6502lib::util::{make_word, split_word};
+use std::io::{BufReader, Write};
+use std::net::TcpStream;
+use std::ops::Range;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+const PACKET_SIZE: usize = 0x1000;
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+// Register numbers used by "p" and "P" packets: "g" and "G" use the same order with PC stored
+// little-endian
+const REG_A: usize = 0;
+const REG_X: usize = 1;
+const REG_Y: usize = 2;
+const REG_SP: usize = 3;
+const REG_PC: usize = 4;
+const REG_P: usize = 5;
+const REG_BYTES: usize = 7;
+
+// GDB remote serial protocol front-end for TuiHost
+pub struct GdbStub {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    debug_tx: Sender<DebugMessage>,
+    monitor_rx: Receiver<MonitorMessage>,
+    state: State,
+    reg: Reg,
+    stop_reply: String,
+    watch_hit: Option<WatchHit>,
+    invalid_brk: bool,
+    memory: Option<Vec<u8>>,
+}
+
+impl GdbStub {
+    pub fn new(
+        stream: TcpStream,
+        debug_tx: Sender<DebugMessage>,
+        monitor_rx: Receiver<MonitorMessage>,
+    ) -> Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            debug_tx,
+            monitor_rx,
+            state: State::Stepping,
+            reg: Reg::default(),
+            stop_reply: format!("S{SIGTRAP:02x}"),
+            watch_hit: None,
+            invalid_brk: false,
+            memory: None,
+        })
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        // Wait for the host to stop at the first instruction
+        while !matches!(self.recv()?, MonitorMessage::BeforeExecute { .. }) {}
+
+        loop {
+            let packet = match read_packet(&mut self.reader)? {
+                Some(Received::Packet(packet)) => packet,
+                Some(Received::Interrupt) => continue,
+                Some(Received::Disconnected) => return Ok(()),
+                None => {
+                    self.writer.write_all(b"-")?;
+                    continue;
+                }
+            };
+            self.writer.write_all(b"+")?;
+
+            match packet.as_str() {
+                "k" => return Ok(()),
+                "D" => {
+                    self.send_reply("OK")?;
+                    return Ok(());
+                }
+                _ => {}
+            }
+
+            let reply = match self.handle_packet(&packet) {
+                Ok(reply) => reply,
+                Err(_) if matches!(self.state, State::Stopped) => return Ok(()),
+                Err(_) => String::from("E01"),
+            };
+            self.send_reply(&reply)?;
+        }
+    }
+
+    fn handle_packet(&mut self, packet: &str) -> Result<String> {
+        let Some(command) = packet.chars().next() else {
+            return Ok(String::new());
+        };
+
+        let args = &packet[command.len_utf8()..];
+        match command {
+            '?' => Ok(self.stop_reply.clone()),
+            'g' => Ok(encode_hex(&self.read_registers())),
+            'G' => {
+                self.write_registers(&decode_hex(args)?)?;
+                Ok(String::from("OK"))
+            }
+            'p' => {
+                let bytes = self.read_registers();
+                let n = usize::from_str_radix(args, 16)?;
+                Ok(encode_hex(Self::register_bytes(&bytes, n)?))
+            }
+            'P' => {
+                let (n, value) = args
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("invalid packet {packet}"))?;
+                let mut bytes = self.read_registers();
+                let n = usize::from_str_radix(n, 16)?;
+                let value = decode_hex(value)?;
+                let target = Self::register_bytes_mut(&mut bytes, n)?;
+                if value.len() != target.len() {
+                    bail!("invalid value for register {n}")
+                }
+                target.copy_from_slice(&value);
+                self.write_registers(&bytes)?;
+                Ok(String::from("OK"))
+            }
+            'm' => {
+                let (addr, len) = Self::parse_addr_len(args)?;
+                Ok(encode_hex(&self.read_memory(addr, len)?))
+            }
+            'M' => {
+                let (addr_len, data) = args
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("invalid packet {packet}"))?;
+                let (addr, len) = Self::parse_addr_len(addr_len)?;
+                let bytes = decode_hex(data)?;
+                if bytes.len() != len {
+                    bail!("invalid length in packet {packet}")
+                }
+                self.debug_tx.send(DebugMessage::SetMemory(addr, bytes))?;
+                Ok(String::from("OK"))
+            }
+            's' if args.is_empty() => self.resume(&DebugMessage::Step),
+            'c' if args.is_empty() => self.resume(&DebugMessage::Run),
+            'Z' | 'z' => self.handle_breakpoint(command == 'Z', args),
+            'H' => Ok(String::from("OK")),
+            'q' if args.starts_with("Supported") => Ok(format!("PacketSize={PACKET_SIZE:x}")),
+            'q' if args == "Attached" => Ok(String::from("1")),
+            _ => Ok(String::new()),
+        }
+    }
+
+    // Z0/Z1 set software and hardware breakpoints, Z2/Z3/Z4 set write, read and access watchpoints
+    fn handle_breakpoint(&self, insert: bool, args: &str) -> Result<String> {
+        let mut parts = args.splitn(3, ',');
+        let (Some(kind), Some(addr), Some(len)) = (parts.next(), parts.next(), parts.next()) else {
+            bail!("invalid breakpoint {args}")
+        };
+        let addr = u16::from_str_radix(addr, 16)?;
+        let len = u16::from_str_radix(len, 16)?;
+
+        let watch_kind = match kind {
+            "0" | "1" => {
+                self.debug_tx.send(if insert {
+                    DebugMessage::AddBreakpoint(addr, None)
+                } else {
+                    DebugMessage::RemoveBreakpoint(addr)
+                })?;
+                return Ok(String::from("OK"));
+            }
+            "2" => WatchKind::Write,
+            "3" => WatchKind::Read,
+            "4" => WatchKind::Access,
+            _ => return Ok(String::new()),
+        };
+
+        let end = addr.saturating_add(len.max(1) - 1);
+        let watchpoint = Watchpoint {
+            address_range: AddressRange::new(addr, end)?,
+            kind: watch_kind,
+        };
+        self.debug_tx.send(if insert {
+            DebugMessage::AddWatchpoint(watchpoint)
+        } else {
+            DebugMessage::RemoveWatchpoint(watchpoint)
+        })?;
+        Ok(String::from("OK"))
+    }
+
+    fn resume(&mut self, message: &DebugMessage) -> Result<String> {
+        // Machine halted so there's nothing left to run
+        if matches!(self.state, State::Halted) {
+            return Ok(String::from("W00"));
+        }
+
+        self.watch_hit = None;
+        self.invalid_brk = false;
+
+        let interrupted = if matches!(message, DebugMessage::Step) {
+            self.debug_tx.send(DebugMessage::Step)?;
+            self.wait_for_step()?;
+            false
+        } else {
+            self.debug_tx.send(DebugMessage::Run)?;
+            self.wait_for_run()?
+        };
+
+        self.stop_reply = if matches!(self.state, State::Halted) {
+            if self.invalid_brk {
+                format!("S{SIGILL:02x}")
+            } else {
+                String::from("W00")
+            }
+        } else if interrupted {
+            format!("S{SIGINT:02x}")
+        } else if let Some(hit) = self.watch_hit {
+            let name = match hit.kind {
+                WatchKind::Write => "watch",
+                WatchKind::Read => "rwatch",
+                WatchKind::Access => "awatch",
+            };
+            format!("T{SIGTRAP:02x}{name}:{addr:04x};", addr = hit.addr)
+        } else {
+            format!("S{SIGTRAP:02x}")
+        };
+        Ok(self.stop_reply.clone())
+    }
+
+    // Host reports the registers after executing the instruction then stops at the next one
+    fn wait_for_step(&mut self) -> Result<()> {
+        let mut executed = false;
+        loop {
+            match self.recv()? {
+                MonitorMessage::AfterExecute { .. } => executed = true,
+                MonitorMessage::BeforeExecute { .. } if executed => return Ok(()),
+                MonitorMessage::NotifyState(State::Halted) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    // Returns true if the client interrupted execution
+    fn wait_for_run(&mut self) -> Result<bool> {
+        let mut interrupted = false;
+        let mut stopped = false;
+        loop {
+            let message = match self.monitor_rx.recv_timeout(POLL_INTERVAL) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    if !interrupted && self.poll_interrupt()? {
+                        interrupted = true;
+                        self.debug_tx.send(DebugMessage::Break)?;
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.state = State::Stopped;
+                    bail!("host stopped")
+                }
+            };
+
+            match self.on_message(message) {
+                MonitorMessage::NotifyState(State::Stepping) => stopped = true,
+                MonitorMessage::NotifyState(State::Halted) => return Ok(interrupted),
+                MonitorMessage::BeforeExecute { .. } if stopped => return Ok(interrupted),
+                _ => {}
+            }
+        }
+    }
+
+    fn poll_interrupt(&mut self) -> Result<bool> {
+        self.reader.get_ref().set_nonblocking(true)?;
+        let result = poll_interrupt(&mut self.reader);
+        self.reader.get_ref().set_nonblocking(false)?;
+        result
+    }
+
+    fn recv(&mut self) -> Result<MonitorMessage> {
+        if let Ok(message) = self.monitor_rx.recv() {
+            Ok(self.on_message(message))
+        } else {
+            self.state = State::Stopped;
+            bail!("host stopped")
+        }
+    }
+
+    // Track host state common to all requests
+    fn on_message(&mut self, message: MonitorMessage) -> MonitorMessage {
+        match &message {
+            MonitorMessage::NotifyState(state) => self.state = *state,
+            MonitorMessage::NotifyInvalidBrk => self.invalid_brk = true,
+            MonitorMessage::BeforeExecute { reg, .. }
+            | MonitorMessage::AfterExecute { reg, .. } => {
+                self.reg = reg.clone();
+            }
+            MonitorMessage::NotifyWatchpoint(hit) if self.watch_hit.is_none() => {
+                self.watch_hit = Some(*hit);
+            }
+            MonitorMessage::FetchMemoryResponse { snapshot, .. } => {
+                self.memory = Some(snapshot.clone());
+            }
+            _ => {}
+        }
+        message
+    }
+
+    fn read_memory(&mut self, addr: u16, len: usize) -> Result<Vec<u8>> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let end = u16::try_from((usize::from(addr) + len - 1).min(0xffff))?;
+        self.memory = None;
+        self.debug_tx
+            .send(DebugMessage::FetchMemory(AddressRange::new(addr, end)?))?;
+        loop {
+            _ = self.recv()?;
+            if let Some(bytes) = self.memory.take() {
+                return Ok(bytes);
+            }
+        }
+    }
+
+    fn read_registers(&self) -> [u8; REG_BYTES] {
+        let (pc_hi, pc_lo) = split_word(self.reg.pc);
+        [
+            self.reg.a,
+            self.reg.x,
+            self.reg.y,
+            self.reg.sp,
+            pc_lo,
+            pc_hi,
+            self.reg.p.bits(),
+        ]
+    }
+
+    fn write_registers(&mut self, bytes: &[u8]) -> Result<()> {
+        let [a, x, y, sp, pc_lo, pc_hi, p] = bytes else {
+            bail!("invalid register data")
+        };
+        self.reg = Reg {
+            a: *a,
+            x: *x,
+            y: *y,
+            p: _p!(*p),
+            pc: make_word(*pc_hi, *pc_lo),
+            sp: *sp,
+        };
+        self.debug_tx
+            .send(DebugMessage::SetRegisters(self.reg.clone()))?;
+        Ok(())
+    }
+
+    fn register_bytes(bytes: &[u8; REG_BYTES], n: usize) -> Result<&[u8]> {
+        Ok(&bytes[Self::register_offsets(n)?])
+    }
+
+    fn register_bytes_mut(bytes: &mut [u8; REG_BYTES], n: usize) -> Result<&mut [u8]> {
+        Ok(&mut bytes[Self::register_offsets(n)?])
+    }
+
+    fn register_offsets(n: usize) -> Result<Range<usize>> {
+        Ok(match n {
+            REG_A | REG_X | REG_Y | REG_SP => n..n + 1,
+            REG_PC => REG_PC..REG_PC + 2,
+            REG_P => REG_P + 1..REG_P + 2,
+            _ => bail!("invalid register {n}"),
+        })
+    }
+
+    fn parse_addr_len(s: &str) -> Result<(u16, usize)> {
+        let (addr, len) = s
+            .split_once(',')
+            .ok_or_else(|| anyhow!("invalid address and length {s}"))?;
+        Ok((
+            u16::from_str_radix(addr, 16)?,
+            usize::from_str_radix(len, 16)?,
+        ))
+    }
+
+    fn send_reply(&mut self, data: &str) -> Result<()> {
+        self.writer.write_all(encode_packet(data).as_bytes())?;
+        Ok(())
+    }
+}
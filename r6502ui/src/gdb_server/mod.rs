@@ -0,0 +1,8 @@
+mod gdb_options;
+mod gdb_stub;
+mod packet;
+mod run_gdb;
+
+pub use gdb_options::*;
+pub use gdb_stub::*;
+pub use run_gdb::*;
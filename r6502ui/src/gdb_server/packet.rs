@@ -0,0 +1,142 @@
+use anyhow::{Result, anyhow, bail};
+use std::fmt::Write;
+use std::io::{BufRead, ErrorKind};
+
+const INTERRUPT: u8 = 0x03;
+
+// Data received from a GDB remote protocol client
+#[derive(Debug, PartialEq)]
+pub enum Received {
+    Packet(String),
+    Interrupt,
+    Disconnected,
+}
+
+#[must_use]
+pub fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |acc, b| acc.wrapping_add(*b))
+}
+
+// Frame packet data e.g. "OK" becomes "$OK#9a"
+#[must_use]
+pub fn encode_packet(data: &str) -> String {
+    format!("${data}#{:02x}", checksum(data.as_bytes()))
+}
+
+// Read the next packet or out-of-band interrupt, skipping acknowledgements and returning corrupt
+// packets as None so that the caller can request retransmission
+pub fn read_packet(reader: &mut impl BufRead) -> Result<Option<Received>> {
+    loop {
+        match read_byte(reader)? {
+            None => return Ok(Some(Received::Disconnected)),
+            Some(b'$') => break,
+            Some(INTERRUPT) => return Ok(Some(Received::Interrupt)),
+            Some(_) => {}
+        }
+    }
+
+    let mut data = Vec::new();
+    reader.read_until(b'#', &mut data)?;
+    if data.pop() != Some(b'#') {
+        return Ok(Some(Received::Disconnected));
+    }
+
+    let mut s = [0; 2];
+    reader.read_exact(&mut s)?;
+    let expected = u8::from_str_radix(str::from_utf8(&s)?, 16)?;
+    if checksum(&data) != expected {
+        return Ok(None);
+    }
+
+    Ok(Some(Received::Packet(String::from_utf8(data)?)))
+}
+
+// Check for an interrupt without blocking
+pub fn poll_interrupt(reader: &mut impl BufRead) -> Result<bool> {
+    let buf = match reader.fill_buf() {
+        Ok(buf) => buf,
+        Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(false),
+        Err(e) => bail!(e),
+    };
+
+    if buf.is_empty() {
+        bail!("client disconnected")
+    }
+
+    let found = buf.iter().position(|b| *b == INTERRUPT);
+    let len = buf.len();
+    reader.consume(found.map_or(len, |i| i + 1));
+    Ok(found.is_some())
+}
+
+#[must_use]
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        _ = write!(s, "{b:02x}");
+    }
+    s
+}
+
+pub fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        bail!("invalid hex string {s}")
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            let digits = s
+                .get(i..i + 2)
+                .ok_or_else(|| anyhow!("invalid hex string {s}"))?;
+            Ok(u8::from_str_radix(digits, 16)?)
+        })
+        .collect()
+}
+
+fn read_byte(reader: &mut impl BufRead) -> Result<Option<u8>> {
+    let mut b = [0; 1];
+    match reader.read_exact(&mut b) {
+        Ok(()) => Ok(Some(b[0])),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => bail!(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gdb_server::packet::{Received, decode_hex, encode_hex, encode_packet, read_packet};
+    use anyhow::Result;
+    use rstest::rstest;
+    use std::io::Cursor;
+
+    #[rstest]
+    #[case("$#00", "")]
+    #[case("$OK#9a", "OK")]
+    #[case("$S05#b8", "S05")]
+    fn encode_packet_basics(#[case] expected_result: &str, #[case] input: &str) {
+        assert_eq!(expected_result, encode_packet(input));
+    }
+
+    #[test]
+    fn read_packet_basics() -> Result<()> {
+        let mut reader = Cursor::new(b"+$g#67$g#00\x03".to_vec());
+        assert_eq!(
+            Some(Received::Packet(String::from("g"))),
+            read_packet(&mut reader)?
+        );
+        assert_eq!(None, read_packet(&mut reader)?);
+        assert_eq!(Some(Received::Interrupt), read_packet(&mut reader)?);
+        assert_eq!(Some(Received::Disconnected), read_packet(&mut reader)?);
+        Ok(())
+    }
+
+    #[test]
+    fn hex() -> Result<()> {
+        assert_eq!("00a9ff", encode_hex(&[0x00, 0xa9, 0xff]));
+        assert_eq!(vec![0x00, 0xa9, 0xff], decode_hex("00A9ff")?);
+        assert!(decode_hex("0").is_err());
+        assert!(decode_hex("zz").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use crate::gdb_server::{GdbOptions, GdbStub};
+use crate::terminal_ui::SimpleOutput;
+use crate::text_ui::{HostOptions, spawn_host};
+use anyhow::{Result, anyhow};
+use r6502core::emulator::IoChannel;
+use r6502core::symbols::DebugInfo;
+use r6502hw::MachineInfo;
+use r6502snapshot::MemoryImage;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::Arc;
+
+pub fn run_gdb_server(opts: &GdbOptions) -> Result<()> {
+    let image = MemoryImage::from_file(&opts.path)?;
+    let machine_info = match image.machine_tag() {
+        Some(tag) => MachineInfo::find_by_tag(tag)?,
+        None => MachineInfo::find_by_name(&opts.machine)?,
+    };
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, opts.port))?;
+    println!("Listening for GDB on {}", listener.local_addr()?);
+    let (stream, addr) = listener.accept()?;
+    println!("Accepted connection from {addr}");
+    serve_gdb(stream, machine_info, image)
+}
+
+// Debug the image on behalf of a connected client until it detaches or kills the program
+pub fn serve_gdb(stream: TcpStream, machine_info: MachineInfo, image: MemoryImage) -> Result<()> {
+    let (handle, debug_tx, monitor_rx) = spawn_host(
+        machine_info,
+        image,
+        HostOptions {
+            output: Box::new(SimpleOutput),
+            input: IoChannel::new(),
+            debug_info: Arc::new(DebugInfo::default()),
+            sanitize: None,
+            post_mortem: None,
+        },
+    );
+
+    // Dropping the stub disconnects the host which then stops
+    let result = GdbStub::new(stream, debug_tx, monitor_rx).and_then(|mut stub| stub.run());
+    handle
+        .join()
+        .map_err(|_| anyhow!("host thread panicked"))??;
+    result
+}
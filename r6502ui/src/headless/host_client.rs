@@ -0,0 +1,324 @@
+use crate::headless::{HostEvent, HostInput, HostOutput, Received, StopReason};
+use crate::text_ui::{HostOptions, spawn_host};
+use anyhow::{Result, anyhow, bail};
+use r6502core::Reg;
+use r6502core::debugger::{CallFrame, Expr};
+use r6502core::emulator::IoChannel;
+use r6502core::messages::{DebugMessage, MonitorMessage, State};
+use r6502core::symbols::DebugInfo;
+use r6502hw::MachineInfo;
+use r6502lib::AddressRange;
+use r6502snapshot::MemoryImage;
+use std::collections::VecDeque;
+use std::mem::take;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::thread::{JoinHandle, spawn};
+use std::time::Duration;
+
+const OUTPUT_INTERVAL: Duration = Duration::from_millis(50);
+
+// Progress towards the next stop after the client resumes execution
+#[derive(Clone, Copy, PartialEq)]
+enum Execution {
+    Stopped,
+    Resumed,
+    Executed,
+}
+
+// Drives TuiHost without a terminal on behalf of a protocol server, tracking its execution and
+// buffering program output and events until the server next flushes
+pub struct HostClient<T> {
+    input_tx: Sender<HostInput<T>>,
+    input_rx: Receiver<HostInput<T>>,
+    pending: VecDeque<HostInput<T>>,
+    output: String,
+    events: Vec<HostEvent>,
+    debug_tx: Option<Sender<DebugMessage>>,
+    host: Option<JoinHandle<Result<()>>>,
+    state: State,
+    reg: Reg,
+    frames: Vec<CallFrame>,
+    execution: Execution,
+    stop_reason: Option<StopReason>,
+    invalid_brk: bool,
+    memory: Option<Vec<u8>>,
+    values: Option<Result<Vec<i32>>>,
+}
+
+impl<T: Send + Sync + 'static> HostClient<T> {
+    #[must_use]
+    pub fn new(input_tx: Sender<HostInput<T>>, input_rx: Receiver<HostInput<T>>) -> Self {
+        Self {
+            input_tx,
+            input_rx,
+            pending: VecDeque::new(),
+            output: String::new(),
+            events: Vec::new(),
+            debug_tx: None,
+            host: None,
+            state: State::Stopped,
+            reg: Reg::default(),
+            frames: Vec::new(),
+            execution: Execution::Stopped,
+            stop_reason: None,
+            invalid_brk: false,
+            memory: None,
+            values: None,
+        }
+    }
+
+    // Start the host and wait for it to stop at the first instruction
+    pub fn launch(
+        &mut self,
+        machine_info: MachineInfo,
+        image: MemoryImage,
+        debug_info: Arc<DebugInfo>,
+    ) -> Result<()> {
+        let (handle, debug_tx, monitor_rx) = spawn_host(
+            machine_info,
+            image,
+            HostOptions {
+                output: Box::new(HostOutput::new(self.input_tx.clone())),
+                input: IoChannel::new(),
+                debug_info,
+                sanitize: None,
+                post_mortem: None,
+            },
+        );
+        self.host = Some(handle);
+
+        // Host may exit without reporting that it stopped e.g. if the bus could not be created
+        let input_tx = self.input_tx.clone();
+        _ = spawn(move || {
+            for message in monitor_rx {
+                if input_tx.send(HostInput::Monitor(message)).is_err() {
+                    return;
+                }
+            }
+            _ = input_tx.send(HostInput::Monitor(MonitorMessage::NotifyState(
+                State::Stopped,
+            )));
+        });
+
+        self.debug_tx = Some(debug_tx);
+        if self
+            .wait_for(|m| matches!(m, MonitorMessage::NotifyCallStack { .. }))
+            .is_err()
+        {
+            self.stop()?;
+            bail!("host stopped")
+        }
+        Ok(())
+    }
+
+    // Dropping the debug channel stops the host
+    pub fn stop(&mut self) -> Result<()> {
+        self.debug_tx = None;
+        if let Some(host) = self.host.take() {
+            host.join().map_err(|_| anyhow!("host thread panicked"))??;
+        }
+        Ok(())
+    }
+
+    // Wait for the next request, handling host messages and output meanwhile
+    pub fn receive(&mut self) -> Received<T> {
+        let input = if let Some(input) = self.pending.pop_front() {
+            input
+        } else {
+            match self.input_rx.recv_timeout(OUTPUT_INTERVAL) {
+                Ok(input) => input,
+                Err(RecvTimeoutError::Timeout) => return Received::Idle,
+                Err(RecvTimeoutError::Disconnected) => return Received::Disconnected,
+            }
+        };
+
+        match input {
+            HostInput::Request(request) => return Received::Request(request),
+            HostInput::Monitor(message) => self.on_message(message),
+            HostInput::Output(c) => self.output.push(c),
+            HostInput::Disconnected => return Received::Disconnected,
+        }
+        Received::Idle
+    }
+
+    #[must_use]
+    pub const fn is_launched(&self) -> bool {
+        self.debug_tx.is_some()
+    }
+
+    // True while the client is not waiting for the host to stop
+    #[must_use]
+    pub fn is_stopped(&self) -> bool {
+        self.execution == Execution::Stopped
+    }
+
+    #[must_use]
+    pub const fn state(&self) -> State {
+        self.state
+    }
+
+    #[must_use]
+    pub const fn reg(&self) -> &Reg {
+        &self.reg
+    }
+
+    #[must_use]
+    pub fn frames(&self) -> &[CallFrame] {
+        &self.frames
+    }
+
+    // Program output since the last call
+    pub fn take_output(&mut self) -> String {
+        take(&mut self.output)
+    }
+
+    // Events since the last call in the order they happened
+    pub fn take_events(&mut self) -> Vec<HostEvent> {
+        take(&mut self.events)
+    }
+
+    pub fn send(&self, message: DebugMessage) -> Result<()> {
+        self.debug_tx
+            .as_ref()
+            .ok_or_else(|| anyhow!("program not launched"))?
+            .send(message)?;
+        Ok(())
+    }
+
+    pub fn set_registers(&mut self, reg: Reg) -> Result<()> {
+        self.check_stopped()?;
+        self.send(DebugMessage::SetRegisters(reg.clone()))?;
+        self.reg = reg;
+        Ok(())
+    }
+
+    // Stop reason is reported when the host next stops unless a breakpoint or watchpoint
+    // intervenes
+    pub fn resume(&mut self, message: DebugMessage, stop_reason: Option<StopReason>) -> Result<()> {
+        match self.state {
+            State::Halted => bail!("program halted"),
+            State::Stopped => bail!("program exited"),
+            State::Running | State::Stepping => {}
+        }
+        if self.execution != Execution::Stopped {
+            bail!("program is already running")
+        }
+
+        self.execution = Execution::Resumed;
+        self.stop_reason = stop_reason;
+        self.invalid_brk = false;
+        self.send(message)
+    }
+
+    pub fn pause(&mut self) -> Result<()> {
+        if self.execution == Execution::Stopped {
+            bail!("program is not running")
+        }
+
+        self.stop_reason = Some(StopReason::Pause);
+        self.send(DebugMessage::Break)
+    }
+
+    pub fn evaluate(&mut self, exprs: Vec<Expr>) -> Result<Vec<i32>> {
+        self.check_stopped()?;
+        self.values = None;
+        self.send(DebugMessage::Evaluate(exprs))?;
+        self.wait_for(|m| matches!(m, MonitorMessage::EvaluateResponse(_)))?;
+        self.values
+            .take()
+            .ok_or_else(|| anyhow!("no values received"))?
+    }
+
+    pub fn fetch_memory(&mut self, address_range: AddressRange) -> Result<Vec<u8>> {
+        self.check_stopped()?;
+        self.memory = None;
+        self.send(DebugMessage::FetchMemory(address_range))?;
+        self.wait_for(|m| matches!(m, MonitorMessage::FetchMemoryResponse { .. }))?;
+        self.memory
+            .take()
+            .ok_or_else(|| anyhow!("no memory received"))
+    }
+
+    // Host answers requests immediately while running but the values would be stale and it only
+    // responds to memory requests when stopped
+    pub fn check_stopped(&self) -> Result<()> {
+        if self.execution != Execution::Stopped
+            || !matches!(self.state, State::Stepping | State::Halted)
+        {
+            bail!("program is not stopped")
+        }
+        Ok(())
+    }
+
+    // Handle host messages up to the matching one, queueing other input meanwhile
+    fn wait_for(&mut self, f: impl Fn(&MonitorMessage) -> bool) -> Result<()> {
+        loop {
+            match self.input_rx.recv()? {
+                HostInput::Monitor(message) => {
+                    let done = f(&message);
+                    self.on_message(message);
+                    if done {
+                        return Ok(());
+                    }
+                }
+                input => self.pending.push_back(input),
+            }
+
+            if matches!(self.state, State::Stopped) {
+                bail!("host stopped")
+            }
+        }
+    }
+
+    fn on_message(&mut self, message: MonitorMessage) {
+        match message {
+            MonitorMessage::NotifyState(state) => {
+                self.state = state;
+                match state {
+                    State::Stepping if self.execution == Execution::Resumed => {
+                        self.execution = Execution::Executed;
+                    }
+                    State::Halted => {
+                        self.execution = Execution::Stopped;
+                        self.events.push(HostEvent::Halted {
+                            invalid_brk: self.invalid_brk,
+                        });
+                    }
+                    State::Stopped => {
+                        self.execution = Execution::Stopped;
+                        self.events.push(HostEvent::Exited);
+                    }
+                    State::Running | State::Stepping => {}
+                }
+            }
+            MonitorMessage::NotifyInvalidBrk => self.invalid_brk = true,
+            MonitorMessage::BeforeExecute { reg, .. } => self.reg = reg,
+            MonitorMessage::AfterExecute { reg, .. } => {
+                self.reg = reg;
+                if self.execution == Execution::Resumed {
+                    self.execution = Execution::Executed;
+                }
+            }
+            MonitorMessage::FetchMemoryResponse { snapshot, .. } => self.memory = Some(snapshot),
+            MonitorMessage::EvaluateResponse(values) => self.values = Some(values),
+            MonitorMessage::NotifyBreakpoint(_) => self.stop_reason = Some(StopReason::Breakpoint),
+            MonitorMessage::NotifyWatchpoint(_) => self.stop_reason = Some(StopReason::Watchpoint),
+            MonitorMessage::NotifyCallStack { frames, .. } => {
+                self.frames = frames;
+
+                // Call stack is the last thing the host reports on stopping at an instruction
+                if self.execution == Execution::Executed {
+                    self.execution = Execution::Stopped;
+                    let reason = self.stop_reason.take().unwrap_or(StopReason::Step);
+                    self.events.push(HostEvent::Stopped(reason));
+                }
+            }
+            MonitorMessage::NotifyMemoryView { .. }
+            | MonitorMessage::NotifyWatches(_)
+            | MonitorMessage::NotifyInterrupt { .. }
+            | MonitorMessage::NotifyDevices(_)
+            | MonitorMessage::NotifySanitizer(_) => {}
+        }
+    }
+}
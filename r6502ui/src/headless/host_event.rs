@@ -0,0 +1,9 @@
+use crate::headless::StopReason;
+
+// Change in the host's execution for the server to report to its client
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HostEvent {
+    Stopped(StopReason),
+    Halted { invalid_brk: bool },
+    Exited,
+}
@@ -0,0 +1,17 @@
+use r6502core::messages::MonitorMessage;
+
+// Everything a protocol server reacts to arrives through a single channel: requests are whatever
+// unit the protocol reads from its client
+pub enum HostInput<T> {
+    Request(T),
+    Monitor(MonitorMessage),
+    Output(char),
+    Disconnected,
+}
+
+// Result of waiting for the next request: the server flushes any output and events when idle
+pub enum Received<T> {
+    Request(T),
+    Idle,
+    Disconnected,
+}
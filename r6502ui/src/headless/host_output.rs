@@ -0,0 +1,27 @@
+use crate::headless::HostInput;
+use anyhow::Result;
+use r6502config::CharSet;
+use r6502core::emulator::OutputDevice;
+use r6502core::emulator::char_set_util::translate_out;
+use std::sync::mpsc::Sender;
+
+// Standard output may carry the protocol so program output is forwarded to the server instead
+pub struct HostOutput<T> {
+    input_tx: Sender<HostInput<T>>,
+}
+
+impl<T> HostOutput<T> {
+    #[must_use]
+    pub const fn new(input_tx: Sender<HostInput<T>>) -> Self {
+        Self { input_tx }
+    }
+}
+
+impl<T: Send + Sync + 'static> OutputDevice for HostOutput<T> {
+    fn write(&mut self, char_set: &CharSet, value: u8) -> Result<()> {
+        if let Some(value) = translate_out(char_set, value) {
+            self.input_tx.send(HostInput::Output(value as char))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+mod host_client;
+mod host_event;
+mod host_input;
+mod host_output;
+mod stop_reason;
+
+pub use host_client::*;
+pub use host_event::*;
+pub use host_input::*;
+pub use host_output::*;
+pub use stop_reason::*;
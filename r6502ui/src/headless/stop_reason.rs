@@ -0,0 +1,8 @@
+// Why the host stopped at an instruction after the client resumed execution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+    Step,
+    Breakpoint,
+    Watchpoint,
+    Pause,
+}
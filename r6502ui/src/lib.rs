@@ -1,3 +1,9 @@
 pub mod crossterm_util;
+pub mod dap_server;
+pub mod disasm;
+pub mod gdb_server;
+pub mod headless;
+pub mod rpc_server;
 pub mod terminal_ui;
 pub mod text_ui;
+pub mod trace;
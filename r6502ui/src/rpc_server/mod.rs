@@ -0,0 +1,6 @@
+mod rpc_options;
+mod rpc_session;
+mod run_rpc;
+
+pub use rpc_options::*;
+pub use run_rpc::*;
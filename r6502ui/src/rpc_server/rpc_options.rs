@@ -0,0 +1,7 @@
+use std::path::PathBuf;
+
+pub struct RpcOptions {
+    pub path: PathBuf,
+    pub port: Option<u16>,
+    pub machine: Option<String>,
+}
@@ -0,0 +1,371 @@
+use crate::headless::{HostClient, HostEvent, HostInput, Received, StopReason};
+use anyhow::{Result, anyhow, bail};
+use r6502core::P;
+use r6502core::debugger::Expr;
+use r6502core::messages::{DebugMessage, State};
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502hw::MachineInfo;
+use r6502lib::AddressRange;
+use r6502snapshot::MemoryImage;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::mem::take;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender};
+
+// Standard JSON-RPC 2.0 error codes with -32000 used for any request that fails
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const SERVER_ERROR: i32 = -32000;
+
+// Line-delimited JSON-RPC front-end for TuiHost
+pub struct RpcSession<W: Write> {
+    writer: W,
+    client: HostClient<String>,
+    notifications: Vec<Value>,
+    map_file: MapFile,
+    debug_info: Arc<DebugInfo>,
+    breakpoints: BTreeMap<u16, Option<Expr>>,
+}
+
+impl<W: Write> RpcSession<W> {
+    pub fn new(
+        writer: W,
+        input_tx: Sender<HostInput<String>>,
+        input_rx: Receiver<HostInput<String>>,
+        map_file: MapFile,
+        debug_info: Arc<DebugInfo>,
+    ) -> Self {
+        Self {
+            writer,
+            client: HostClient::new(input_tx, input_rx),
+            notifications: Vec::new(),
+            map_file,
+            debug_info,
+            breakpoints: BTreeMap::new(),
+        }
+    }
+
+    // Start the host and tell the client once it has stopped at the first instruction
+    pub fn launch(&mut self, machine_info: MachineInfo, image: MemoryImage) -> Result<()> {
+        self.client
+            .launch(machine_info, image, Arc::clone(&self.debug_info))?;
+        self.notify_stopped("entry");
+        self.flush()
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        let result = self.handle_inputs();
+
+        self.client.stop()?;
+        result
+    }
+
+    fn handle_inputs(&mut self) -> Result<()> {
+        loop {
+            match self.client.receive() {
+                Received::Request(line) => self.handle_line(&line)?,
+                Received::Idle => {}
+                Received::Disconnected => return Ok(()),
+            }
+
+            // Notifications triggered by a request must follow its response
+            self.flush()?;
+        }
+    }
+
+    fn handle_line(&mut self, line: &str) -> Result<()> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+
+        let request = match serde_json::from_str::<Value>(line) {
+            Ok(request) => request,
+            Err(e) => return self.send_error(&Value::Null, PARSE_ERROR, &e.to_string()),
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request["method"].as_str() else {
+            return self.send_error(
+                &id.unwrap_or(Value::Null),
+                INVALID_REQUEST,
+                "method not specified",
+            );
+        };
+
+        let params = &request["params"];
+        let result = match method {
+            "step" => self.resume(DebugMessage::Step, Some(StopReason::Step)),
+            "stepOver" => self.resume(DebugMessage::StepOver, Some(StopReason::Step)),
+            "stepOut" => self.resume(DebugMessage::StepOut, Some(StopReason::Step)),
+            "run" => self.resume(DebugMessage::Run, None),
+            "break" => self.client.pause().map(|()| json!({})),
+            "getState" => Ok(self.get_state()),
+            "readMemory" => self.read_memory(params),
+            "writeMemory" => self.write_memory(params),
+            "getRegisters" => Ok(self.registers()),
+            "setRegisters" => self.set_registers(params),
+            "setBreakpoint" => self.set_breakpoint(params),
+            "clearBreakpoint" => self.clear_breakpoint(params),
+            "listBreakpoints" => Ok(self.list_breakpoints()),
+            "lookupSymbol" => self.lookup_symbol(params),
+            "evaluate" => self.evaluate(params),
+            _ => {
+                return match id {
+                    Some(id) => {
+                        self.send_error(&id, METHOD_NOT_FOUND, &format!("unknown method {method}"))
+                    }
+                    None => Ok(()),
+                };
+            }
+        };
+
+        // Requests without an ID are notifications that don't get a response
+        match (id, result) {
+            (Some(id), Ok(result)) => {
+                self.send(&json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+            }
+            (Some(id), Err(e)) => self.send_error(&id, SERVER_ERROR, &e.to_string()),
+            (None, _) => Ok(()),
+        }
+    }
+
+    fn get_state(&self) -> Value {
+        let state = match self.client.state() {
+            State::Stopped => "exited",
+            State::Halted => "halted",
+            _ if self.client.is_stopped() => "stopped",
+            _ => "running",
+        };
+        json!({ "state": state, "pc": self.client.reg().pc })
+    }
+
+    fn read_memory(&mut self, params: &Value) -> Result<Value> {
+        let addr = self.address(&params["address"])?;
+        let len = params["length"]
+            .as_u64()
+            .filter(|len| *len > 0)
+            .ok_or_else(|| anyhow!("invalid length"))?;
+        let end = u16::try_from(u64::from(addr) + len - 1)?;
+        let bytes = self.client.fetch_memory(AddressRange::new(addr, end)?)?;
+        Ok(json!({ "address": addr, "data": bytes }))
+    }
+
+    fn write_memory(&mut self, params: &Value) -> Result<Value> {
+        let addr = self.address(&params["address"])?;
+        let bytes = params["data"]
+            .as_array()
+            .ok_or_else(|| anyhow!("data not specified"))?
+            .iter()
+            .map(|value| {
+                value
+                    .as_u64()
+                    .and_then(|value| u8::try_from(value).ok())
+                    .ok_or_else(|| anyhow!("invalid byte {value}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if usize::from(addr) + bytes.len() > 0x10000 {
+            bail!("data extends past end of memory")
+        }
+
+        self.client.send(DebugMessage::SetMemory(addr, bytes))?;
+        Ok(json!({}))
+    }
+
+    fn registers(&self) -> Value {
+        let reg = self.client.reg();
+        json!({
+            "a": reg.a,
+            "x": reg.x,
+            "y": reg.y,
+            "sp": reg.sp,
+            "pc": reg.pc,
+            "p": reg.p.bits(),
+        })
+    }
+
+    // Registers not given keep their current values
+    fn set_registers(&mut self, params: &Value) -> Result<Value> {
+        fn byte(params: &Value, name: &str, value: &mut u8) -> Result<()> {
+            if let Some(new_value) = params[name].as_u64() {
+                *value = u8::try_from(new_value)?;
+            }
+            Ok(())
+        }
+
+        let mut reg = self.client.reg().clone();
+        byte(params, "a", &mut reg.a)?;
+        byte(params, "x", &mut reg.x)?;
+        byte(params, "y", &mut reg.y)?;
+        byte(params, "sp", &mut reg.sp)?;
+        if let Some(pc) = params["pc"].as_u64() {
+            reg.pc = u16::try_from(pc)?;
+        }
+        let mut p = reg.p.bits();
+        byte(params, "p", &mut p)?;
+        reg.p = P::from_bits_retain(p);
+
+        self.client.set_registers(reg)?;
+        Ok(self.registers())
+    }
+
+    fn set_breakpoint(&mut self, params: &Value) -> Result<Value> {
+        let addr = self.address(&params["address"])?;
+        let condition = params["condition"]
+            .as_str()
+            .map(|condition| self.resolve_symbols(&condition.parse()?))
+            .transpose()?;
+
+        self.client
+            .send(DebugMessage::AddBreakpoint(addr, condition.clone()))?;
+        _ = self.breakpoints.insert(addr, condition);
+        Ok(self.breakpoint(addr))
+    }
+
+    fn clear_breakpoint(&mut self, params: &Value) -> Result<Value> {
+        let addr = self.address(&params["address"])?;
+        if self.breakpoints.remove(&addr).is_none() {
+            bail!("no breakpoint at ${addr:04X}")
+        }
+
+        self.client.send(DebugMessage::RemoveBreakpoint(addr))?;
+        Ok(json!({}))
+    }
+
+    fn list_breakpoints(&self) -> Value {
+        let breakpoints = self
+            .breakpoints
+            .keys()
+            .map(|addr| self.breakpoint(*addr))
+            .collect::<Vec<_>>();
+        json!({ "breakpoints": breakpoints })
+    }
+
+    fn breakpoint(&self, addr: u16) -> Value {
+        let mut breakpoint = json!({ "address": addr, "symbol": self.map_file.symbolize(addr) });
+        if let Some(Some(condition)) = self.breakpoints.get(&addr) {
+            breakpoint["condition"] = json!(condition.to_string());
+        }
+        breakpoint
+    }
+
+    // Look up symbol by name or find the nearest symbol to an address
+    fn lookup_symbol(&self, params: &Value) -> Result<Value> {
+        if let Some(name) = params["name"].as_str() {
+            let addr = self
+                .map_file
+                .lookup(name)
+                .ok_or_else(|| anyhow!("unknown symbol {name}"))?;
+            return Ok(json!({ "name": name, "address": addr }));
+        }
+
+        let addr = params["address"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("name or address not specified"))?;
+        let addr = u16::try_from(addr)?;
+        Ok(json!({ "address": addr, "symbol": self.map_file.symbolize(addr) }))
+    }
+
+    fn evaluate(&mut self, params: &Value) -> Result<Value> {
+        let expression = params["expression"]
+            .as_str()
+            .ok_or_else(|| anyhow!("expression not specified"))?;
+        let expr = self.resolve_symbols(&expression.parse()?)?;
+        let value = self.client.evaluate(vec![expr])?[0];
+        Ok(json!({ "value": value }))
+    }
+
+    // Address given as number or expression e.g. 3584 or "print+3"
+    fn address(&mut self, value: &Value) -> Result<u16> {
+        if let Some(addr) = value.as_u64() {
+            return Ok(u16::try_from(addr)?);
+        }
+
+        let s = value
+            .as_str()
+            .ok_or_else(|| anyhow!("address not specified"))?;
+        let expr = self.resolve_symbols(&s.parse()?)?;
+        Expr::to_addr(self.client.evaluate(vec![expr])?[0])
+    }
+
+    fn resolve_symbols(&self, expr: &Expr) -> Result<Expr> {
+        expr.resolve(&|name| self.map_file.lookup(name))
+    }
+
+    fn resume(&mut self, message: DebugMessage, stop_reason: Option<StopReason>) -> Result<Value> {
+        self.client.resume(message, stop_reason)?;
+        Ok(json!({}))
+    }
+
+    fn on_host_event(&mut self, event: HostEvent) {
+        match event {
+            HostEvent::Stopped(reason) => self.notify_stopped(match reason {
+                StopReason::Step => "step",
+                StopReason::Breakpoint => "breakpoint",
+                StopReason::Watchpoint => "watchpoint",
+                StopReason::Pause => "pause",
+            }),
+            HostEvent::Halted { invalid_brk } => {
+                self.notify_stopped(if invalid_brk { "invalidBrk" } else { "halted" });
+            }
+            HostEvent::Exited => {
+                self.notify("exited", &json!({ "exitCode": self.client.reg().a }));
+            }
+        }
+    }
+
+    fn notify_stopped(&mut self, reason: &str) {
+        let pc = self.client.reg().pc;
+        self.notify(
+            "stopped",
+            &json!({
+                "reason": reason,
+                "pc": pc,
+                "symbol": self.map_file.symbolize(pc),
+                "registers": self.registers(),
+            }),
+        );
+    }
+
+    // Notifications are sent once the current input has been handled
+    fn notify(&mut self, method: &str, params: &Value) {
+        self.notifications
+            .push(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    fn send_error(&mut self, id: &Value, code: i32, message: &str) -> Result<()> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }))
+    }
+
+    // Program output precedes any notifications it led up to
+    fn flush(&mut self) -> Result<()> {
+        for event in self.client.take_events() {
+            self.on_host_event(event);
+        }
+
+        let output = self.client.take_output();
+        if !output.is_empty() {
+            self.send(&json!({
+                "jsonrpc": "2.0",
+                "method": "output",
+                "params": { "text": output },
+            }))?;
+        }
+
+        for notification in take(&mut self.notifications) {
+            self.send(&notification)?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, message: &Value) -> Result<()> {
+        writeln!(self.writer, "{message}")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::headless::HostInput;
+use crate::rpc_server::RpcOptions;
+use crate::rpc_server::rpc_session::RpcSession;
+use anyhow::Result;
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502hw::MachineInfo;
+use r6502snapshot::MemoryImage;
+use std::io::{BufRead, BufReader, Read, Write, stdin, stdout};
+use std::net::{Ipv4Addr, TcpListener};
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+use std::thread::spawn;
+
+// Serve a single client over standard input and output or a local TCP port
+pub fn run_rpc_server(opts: &RpcOptions) -> Result<()> {
+    if let Some(port) = opts.port {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        eprintln!("Listening for JSON-RPC on {}", listener.local_addr()?);
+        let (stream, _) = listener.accept()?;
+        serve_rpc(stream.try_clone()?, stream, opts)
+    } else {
+        serve_rpc(stdin(), stdout(), opts)
+    }
+}
+
+// Debug the image on behalf of a client sending one JSON-RPC request per line until it
+// disconnects
+pub fn serve_rpc(
+    reader: impl Read + Send + 'static,
+    writer: impl Write,
+    opts: &RpcOptions,
+) -> Result<()> {
+    let image = MemoryImage::from_file(&opts.path)?;
+    let machine_info = match image.machine_tag() {
+        Some(tag) => MachineInfo::find_by_tag(tag)?,
+        None => MachineInfo::find_by_name(&opts.machine)?,
+    };
+    let mut map_file = MapFile::load(&opts.path)?;
+    let debug_info = Arc::new(DebugInfo::load_or_default(&opts.path));
+    map_file.merge_labels(&debug_info);
+
+    let (input_tx, input_rx) = channel();
+    let line_tx = input_tx.clone();
+
+    // Blocking reads can't be interrupted so this thread is left to finish when the client closes
+    // its end of the stream
+    _ = spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line_tx.send(HostInput::Request(line)).is_err() {
+                return;
+            }
+        }
+        _ = line_tx.send(HostInput::Disconnected);
+    });
+
+    let mut session = RpcSession::new(writer, input_tx, input_rx, map_file, debug_info);
+    session.launch(machine_info, image)?;
+    session.run()
+}
@@ -196,7 +196,7 @@ fn save_memory(
     end: u32,
 ) -> Result<bool> {
     assert!(end > start);
-    info!("Saving to {file_name} {load:08X} {exec:08X} {start:08X} {end:08X}",);
+    info!("Saving to {file_name} {load:08X} {exec:08X} {start:08X} {end:08X}");
 
     let start = u16::try_from(start & 0xffff).unwrap();
     let end_inclusive = u16::try_from((end - 1) & 0xffff).unwrap();
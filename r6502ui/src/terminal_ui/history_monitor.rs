@@ -0,0 +1,70 @@
+use r6502core::Reg;
+use r6502core::emulator::{InstructionInfo, Monitor};
+use r6502lib::TotalCycles;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+const HISTORY_SIZE: usize = 256;
+
+#[derive(Clone)]
+pub struct HistoryEntry {
+    pub total_cycles: TotalCycles,
+    // Registers before the instruction was executed
+    pub reg: Reg,
+    pub instruction_info: InstructionInfo,
+}
+
+pub type InstructionHistory = Rc<RefCell<VecDeque<HistoryEntry>>>;
+
+// Remembers the most recently executed instructions, passing them on to another monitor if any
+pub struct HistoryMonitor {
+    history: InstructionHistory,
+    inner: Option<Box<dyn Monitor>>,
+}
+
+impl HistoryMonitor {
+    #[must_use]
+    pub fn new(history: &InstructionHistory, inner: Option<Box<dyn Monitor>>) -> Self {
+        Self {
+            history: Rc::clone(history),
+            inner,
+        }
+    }
+}
+
+impl Monitor for HistoryMonitor {
+    fn on_before_execute(
+        &self,
+        total_cycles: TotalCycles,
+        reg: Reg,
+        instruction_info: InstructionInfo,
+    ) {
+        {
+            let mut history = self.history.borrow_mut();
+            if history.len() == HISTORY_SIZE {
+                _ = history.pop_front();
+            }
+            history.push_back(HistoryEntry {
+                total_cycles,
+                reg: reg.clone(),
+                instruction_info: instruction_info.clone(),
+            });
+        }
+
+        if let Some(inner) = &self.inner {
+            inner.on_before_execute(total_cycles, reg, instruction_info);
+        }
+    }
+
+    fn on_after_execute(
+        &self,
+        total_cycles: TotalCycles,
+        reg: Reg,
+        instruction_info: InstructionInfo,
+    ) {
+        if let Some(inner) = &self.inner {
+            inner.on_after_execute(total_cycles, reg, instruction_info);
+        }
+    }
+}
@@ -1,11 +1,16 @@
 pub mod output_device_type_util;
 
 mod acorn_host_hooks;
+mod history_monitor;
+mod post_mortem;
 mod raw_mode;
+mod redirectable_output;
 mod run;
+mod run_analysis;
 mod run_options;
 mod runner;
 mod simple_output;
+mod step_engine;
 mod stop_reason;
 mod terminal_event;
 mod util;
@@ -13,11 +18,16 @@ mod vdu;
 mod vdu_driver;
 
 pub use acorn_host_hooks::*;
+pub use history_monitor::*;
+pub use post_mortem::*;
 pub use raw_mode::*;
+pub use redirectable_output::*;
 pub use run::*;
+pub use run_analysis::*;
 pub use run_options::*;
 pub use runner::*;
 pub use simple_output::*;
+pub use step_engine::*;
 pub use stop_reason::*;
 pub use terminal_event::*;
 pub use util::*;
@@ -0,0 +1,365 @@
+use crate::terminal_ui::{HistoryEntry, InstructionHistory};
+use anyhow::{Error, Result, anyhow};
+use r6502config::{BusDevice, Machine};
+use r6502core::debugger::{CallFrame, CallStack, FrameKind, StackIssue};
+use r6502core::emulator::{Cpu, InstructionInfo};
+use r6502core::symbols::MapFile;
+use r6502core::{_p, Opcode, Operand, Reg};
+use r6502lib::TotalCycles;
+use r6502snapshot::Snapshot;
+use serde::{Deserialize, Serialize};
+use std::fs::{copy, create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_FILE_NAME: &str = "snapshot.r6502";
+const REPORT_FILE_NAME: &str = "post-mortem.json";
+
+// What was known about the machine when a run ended abnormally: memory goes in a snapshot in the
+// same directory and everything else in a JSON report
+pub struct PostMortem {
+    pub reason: String,
+    pub reg: Reg,
+    pub total_cycles: TotalCycles,
+    pub history: Vec<HistoryEntry>,
+    pub bus_devices: Vec<BusDevice>,
+    pub frames: Vec<CallFrame>,
+    pub issues: Vec<StackIssue>,
+}
+
+impl PostMortem {
+    #[must_use]
+    pub fn capture(
+        reason: &str,
+        cpu: &Cpu,
+        history: &InstructionHistory,
+        call_stack: &CallStack,
+        machine: &Machine,
+    ) -> Self {
+        Self {
+            reason: String::from(reason),
+            reg: cpu.reg.clone(),
+            total_cycles: cpu.total_cycles,
+            history: history.borrow().iter().cloned().collect(),
+            bus_devices: machine.bus_devices.clone(),
+            frames: call_stack.frames().to_vec(),
+            issues: call_stack.issues().to_vec(),
+        }
+    }
+
+    #[must_use]
+    pub fn snapshot_path(dir: &Path) -> PathBuf {
+        dir.join(SNAPSHOT_FILE_NAME)
+    }
+
+    // Symbol files are copied alongside the snapshot so that the debugger finds them
+    pub fn write(
+        &self,
+        dir: &Path,
+        snapshot: &Snapshot,
+        image_path: &Path,
+        map_file: &MapFile,
+    ) -> Result<()> {
+        create_dir_all(dir)?;
+        let snapshot_path = Self::snapshot_path(dir);
+        snapshot.write(&snapshot_path)?;
+        for extension in ["map", "dbg"] {
+            let path = image_path.with_extension(extension);
+            if path.is_file() {
+                _ = copy(path, snapshot_path.with_extension(extension))?;
+            }
+        }
+
+        let report = Report {
+            reason: self.reason.clone(),
+            registers: Registers::from(&self.reg),
+            total_cycles: self.total_cycles,
+            history: self.history.iter().map(Entry::from).collect(),
+            bus_devices: self.bus_devices.clone(),
+            stack: self
+                .frames
+                .iter()
+                .map(|frame| Frame::new(frame, map_file))
+                .collect(),
+            stack_issues: self.issues.iter().map(Issue::from).collect(),
+        };
+        write(
+            dir.join(REPORT_FILE_NAME),
+            serde_json::to_string_pretty(&report)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn read(dir: &Path) -> Result<Self> {
+        let report = serde_json::from_str::<Report>(&read_to_string(dir.join(REPORT_FILE_NAME))?)?;
+        Ok(Self {
+            reason: report.reason,
+            reg: report.registers.into(),
+            total_cycles: report.total_cycles,
+            history: report
+                .history
+                .into_iter()
+                .map(HistoryEntry::try_from)
+                .collect::<Result<_>>()?,
+            bus_devices: report.bus_devices,
+            frames: report
+                .stack
+                .into_iter()
+                .map(CallFrame::try_from)
+                .collect::<Result<_>>()?,
+            issues: report.stack_issues.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+// Layout of the JSON report: bus devices use the same format as machine configuration files
+#[derive(Deserialize, Serialize)]
+struct Report {
+    reason: String,
+    registers: Registers,
+    #[serde(rename = "totalCycles")]
+    total_cycles: TotalCycles,
+    history: Vec<Entry>,
+    #[serde(rename = "busDevices")]
+    bus_devices: Vec<BusDevice>,
+    stack: Vec<Frame>,
+    #[serde(rename = "stackIssues")]
+    stack_issues: Vec<Issue>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Registers {
+    a: u8,
+    x: u8,
+    y: u8,
+    sp: u8,
+    pc: u16,
+    p: u8,
+}
+
+impl From<&Reg> for Registers {
+    fn from(value: &Reg) -> Self {
+        Self {
+            a: value.a,
+            x: value.x,
+            y: value.y,
+            sp: value.sp,
+            pc: value.pc,
+            p: value.p.bits(),
+        }
+    }
+}
+
+impl From<Registers> for Reg {
+    fn from(value: Registers) -> Self {
+        Self {
+            a: value.a,
+            x: value.x,
+            y: value.y,
+            p: _p!(value.p),
+            pc: value.pc,
+            sp: value.sp,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    #[serde(rename = "totalCycles")]
+    total_cycles: TotalCycles,
+    registers: Registers,
+    pc: u16,
+    opcode: u8,
+    operand: Vec<u8>,
+}
+
+impl From<&HistoryEntry> for Entry {
+    fn from(value: &HistoryEntry) -> Self {
+        let instruction_info = &value.instruction_info;
+        Self {
+            total_cycles: value.total_cycles,
+            registers: Registers::from(&value.reg),
+            pc: instruction_info.pc,
+            opcode: instruction_info.opcode as u8,
+            operand: match instruction_info.operand {
+                Operand::None => Vec::new(),
+                Operand::Byte(value) => vec![value],
+                Operand::Word(value) => value.to_le_bytes().to_vec(),
+            },
+        }
+    }
+}
+
+impl TryFrom<Entry> for HistoryEntry {
+    type Error = Error;
+
+    fn try_from(value: Entry) -> Result<Self> {
+        let opcode = value.opcode;
+        Ok(Self {
+            total_cycles: value.total_cycles,
+            reg: value.registers.into(),
+            instruction_info: InstructionInfo {
+                pc: value.pc,
+                opcode: Opcode::from_u8(opcode)
+                    .ok_or_else(|| anyhow!("invalid opcode ${opcode:02X}"))?,
+                operand: match value.operand[..] {
+                    [] => Operand::None,
+                    [value] => Operand::Byte(value),
+                    [lo, hi] => Operand::Word(u16::from_le_bytes([lo, hi])),
+                    _ => return Err(anyhow!("invalid operand {:?}", value.operand)),
+                },
+            },
+        })
+    }
+}
+
+// Symbol is only for the reader's benefit
+#[derive(Deserialize, Serialize)]
+struct Frame {
+    kind: String,
+    site: u16,
+    target: u16,
+    #[serde(rename = "returnAddress")]
+    return_addr: u16,
+    sp: u8,
+    #[serde(default, skip_deserializing)]
+    symbol: String,
+}
+
+impl Frame {
+    fn new(frame: &CallFrame, map_file: &MapFile) -> Self {
+        Self {
+            kind: frame.kind.to_string(),
+            site: frame.site,
+            target: frame.target,
+            return_addr: frame.return_addr,
+            sp: frame.sp,
+            symbol: map_file.symbolize(frame.target),
+        }
+    }
+}
+
+impl TryFrom<Frame> for CallFrame {
+    type Error = Error;
+
+    fn try_from(value: Frame) -> Result<Self> {
+        Ok(Self {
+            kind: match value.kind.as_str() {
+                "JSR" => FrameKind::Jsr,
+                "BRK" => FrameKind::Brk,
+                "IRQ" => FrameKind::Irq,
+                "NMI" => FrameKind::Nmi,
+                kind => return Err(anyhow!("invalid frame kind {kind}")),
+            },
+            site: value.site,
+            target: value.target,
+            return_addr: value.return_addr,
+            sp: value.sp,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct Issue {
+    pc: u16,
+    message: String,
+}
+
+impl From<&StackIssue> for Issue {
+    fn from(value: &StackIssue) -> Self {
+        Self {
+            pc: value.pc,
+            message: value.message.clone(),
+        }
+    }
+}
+
+impl From<Issue> for StackIssue {
+    fn from(value: Issue) -> Self {
+        Self {
+            pc: value.pc,
+            message: value.message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::terminal_ui::{HistoryEntry, InstructionHistory, PostMortem};
+    use anyhow::Result;
+    use r6502config::Machine;
+    use r6502core::debugger::{CallFrame, CallStack, FrameKind, StackIssue};
+    use r6502core::emulator::{Bus, Cpu, InstructionInfo};
+    use r6502core::symbols::MapFile;
+    use r6502core::{InterruptChannel, Opcode, Operand};
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+    use std::process;
+
+    const MACHINE: &str = r#"{
+        "name": "AllRam",
+        "tag": "ALLR",
+        "busDevices": [
+            { "type": "ram", "addressRange": "$0000:$7FFF", "offset": "$0000" }
+        ]
+    }"#;
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let bus = Bus::default();
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.a = 0x12;
+        cpu.reg.pc = 0x0e00;
+        cpu.total_cycles = 1234;
+
+        let history = InstructionHistory::default();
+        history.borrow_mut().push_back(HistoryEntry {
+            total_cycles: 1230,
+            reg: cpu.reg.clone(),
+            instruction_info: InstructionInfo {
+                pc: 0x0dfd,
+                opcode: Opcode::Jsr,
+                operand: Operand::Word(0x0e00),
+            },
+        });
+        let call_stack = CallStack::new(
+            vec![CallFrame {
+                kind: FrameKind::Jsr,
+                site: 0x0dfd,
+                target: 0x0e00,
+                return_addr: 0x0e00,
+                sp: 0xfd,
+            }],
+            vec![StackIssue {
+                pc: 0x0e00,
+                message: String::from("stack overflow"),
+            }],
+        );
+        let machine = serde_json::from_str::<Machine>(MACHINE)?;
+
+        let dir = temp_dir().join(format!("r6502-post-mortem-{}", process::id()));
+        let post_mortem = PostMortem::capture("BRK", &cpu, &history, &call_stack, &machine);
+        post_mortem.write(
+            &dir,
+            &cpu.snapshot(),
+            &dir.join("image"),
+            &MapFile::default(),
+        )?;
+        let result = PostMortem::read(&dir);
+        assert!(PostMortem::snapshot_path(&dir).is_file());
+        remove_dir_all(&dir)?;
+
+        let result = result?;
+        assert_eq!("BRK", result.reason);
+        assert_eq!(0x12, result.reg.a);
+        assert_eq!(0x0e00, result.reg.pc);
+        assert_eq!(1234, result.total_cycles);
+        assert_eq!(1, result.history.len());
+        assert_eq!(Opcode::Jsr, result.history[0].instruction_info.opcode);
+        assert_eq!(1, result.bus_devices.len());
+        assert_eq!(1, result.frames.len());
+        assert_eq!(0x0e00, result.frames[0].target);
+        assert_eq!("stack overflow", result.issues[0].message);
+        Ok(())
+    }
+}
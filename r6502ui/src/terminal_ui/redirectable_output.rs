@@ -0,0 +1,88 @@
+use anyhow::Result;
+use r6502config::CharSet;
+use r6502core::emulator::OutputDevice;
+use std::sync::{Arc, Mutex};
+
+pub type OutputRedirect = Arc<Mutex<Option<Box<dyn OutputDevice>>>>;
+
+// Output device whose output can be sent elsewhere for a while e.g. to the debugger
+pub struct RedirectableOutput {
+    inner: Box<dyn OutputDevice>,
+    redirect: OutputRedirect,
+}
+
+impl RedirectableOutput {
+    #[must_use]
+    pub fn new(inner: Box<dyn OutputDevice>, redirect: &OutputRedirect) -> Self {
+        Self {
+            inner,
+            redirect: Arc::clone(redirect),
+        }
+    }
+}
+
+impl OutputDevice for RedirectableOutput {
+    fn write(&mut self, char_set: &CharSet, value: u8) -> Result<()> {
+        // Fall back to the terminal once whatever output was sent to has gone away
+        let mut redirect = self.redirect.lock().unwrap();
+        if let Some(output) = redirect.as_mut()
+            && output.write(char_set, value).is_ok()
+        {
+            return Ok(());
+        }
+        *redirect = None;
+        self.inner.write(char_set, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::terminal_ui::{OutputRedirect, RedirectableOutput};
+    use anyhow::{Result, bail};
+    use r6502config::CharSet;
+    use r6502core::emulator::OutputDevice;
+    use std::sync::{Arc, Mutex};
+
+    struct TestOutput {
+        values: Arc<Mutex<Vec<u8>>>,
+        fail: bool,
+    }
+
+    impl OutputDevice for TestOutput {
+        fn write(&mut self, _char_set: &CharSet, value: u8) -> Result<()> {
+            if self.fail {
+                bail!("output closed")
+            }
+            self.values.lock().unwrap().push(value);
+            Ok(())
+        }
+    }
+
+    fn test_output(values: &Arc<Mutex<Vec<u8>>>, fail: bool) -> Box<dyn OutputDevice> {
+        Box::new(TestOutput {
+            values: Arc::clone(values),
+            fail,
+        })
+    }
+
+    #[test]
+    fn redirect() -> Result<()> {
+        let char_set = CharSet::default();
+        let terminal = Arc::new(Mutex::new(Vec::new()));
+        let debugger = Arc::new(Mutex::new(Vec::new()));
+        let redirect = OutputRedirect::default();
+        let mut output = RedirectableOutput::new(test_output(&terminal, false), &redirect);
+
+        output.write(&char_set, b'A')?;
+        *redirect.lock().unwrap() = Some(test_output(&debugger, false));
+        output.write(&char_set, b'B')?;
+        *redirect.lock().unwrap() = Some(test_output(&debugger, true));
+        output.write(&char_set, b'C')?;
+        assert!(redirect.lock().unwrap().is_none());
+        output.write(&char_set, b'D')?;
+
+        assert_eq!(b"ACD".to_vec(), *terminal.lock().unwrap());
+        assert_eq!(b"B".to_vec(), *debugger.lock().unwrap());
+        Ok(())
+    }
+}
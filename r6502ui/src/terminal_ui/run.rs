@@ -1,18 +1,24 @@
 use crate::terminal_ui::raw_mode::RawMode;
-use crate::terminal_ui::{RunOptions, output_device_type_util};
-use crate::terminal_ui::{Runner, StopReason, TerminalChannel, Vectors, show_run_info};
+use crate::terminal_ui::{
+    HistoryMonitor, InstructionHistory, RunAnalysis, Runner, StopReason, TerminalChannel, Vectors,
+    show_run_info,
+};
+use crate::terminal_ui::{OutputRedirect, RedirectableOutput, RunOptions, output_device_type_util};
+use crate::text_ui::LiveDebugger;
 use anyhow::Result;
 use log::info;
 use r6502core::InterruptChannel;
-use r6502core::emulator::{Cpu, IoChannel, Monitor, TracingMonitor};
+use r6502core::emulator::{Cpu, IoChannel};
+use r6502core::symbols::{DebugInfo, MapFile};
 use r6502hw::MachineInfo;
 use r6502lib::constants::RESET;
 use r6502lib::util::make_word;
 use r6502snapshot::MemoryImage;
 use std::process::exit;
+use std::sync::Arc;
 
 pub fn run_terminal_ui(opts: &RunOptions) -> Result<()> {
-    fn run_inner(opts: &RunOptions) -> Result<i32> {
+    fn run_inner(opts: &RunOptions) -> Result<(i32, Option<String>)> {
         let image = MemoryImage::from_file(&opts.path)?;
         let machine_info = match image.machine_tag() {
             Some(tag) => MachineInfo::find_by_tag(tag)?,
@@ -24,8 +30,16 @@ pub fn run_terminal_ui(opts: &RunOptions) -> Result<()> {
         let io_tx = io_channel.tx.clone();
         let interrupt_channel = InterruptChannel::new();
 
-        let output =
-            output_device_type_util::create_output_device(&machine_info.machine.output_device_type);
+        let mut map_file = MapFile::load(&opts.path)?;
+        let debug_info = Arc::new(DebugInfo::load_or_default(&opts.path));
+        map_file.merge_labels(&debug_info);
+
+        // Output goes to the debugger instead of the terminal while the debugger is open
+        let output_redirect = OutputRedirect::default();
+        let output = Box::new(RedirectableOutput::new(
+            output_device_type_util::create_output_device(&machine_info.machine.output_device_type),
+            &output_redirect,
+        ));
         let (bus, bus_rx) =
             machine_info.create_bus(output, io_channel, interrupt_channel.tx, &image)?;
         bus.start();
@@ -35,13 +49,11 @@ pub fn run_terminal_ui(opts: &RunOptions) -> Result<()> {
         let irq = bus.load_irq_unsafe();
         let vectors = Vectors { nmi, reset, irq };
 
-        let monitor: Option<Box<dyn Monitor>> = if opts.trace {
-            Some(Box::new(TracingMonitor::default()))
-        } else {
-            None
-        };
+        let (analysis, monitor) = RunAnalysis::new(opts, &map_file, &bus.devices(), &image)?;
+        let history = InstructionHistory::default();
+        let monitor = Box::new(HistoryMonitor::new(&history, monitor));
 
-        let mut cpu = Cpu::new(bus.view(), monitor, interrupt_channel.rx);
+        let mut cpu = Cpu::new(analysis.bus_view(&bus), Some(monitor), interrupt_channel.rx);
         let reset_addr_lo = cpu.bus.load(RESET);
         let reset_addr_hi = cpu.bus.load(RESET.wrapping_add(1));
         let reset_addr = make_word(reset_addr_hi, reset_addr_lo);
@@ -49,6 +61,14 @@ pub fn run_terminal_ui(opts: &RunOptions) -> Result<()> {
         show_run_info(opts, &image, &cpu_state, &vectors);
         cpu.set_initial_state(&cpu_state);
 
+        let map_file = Arc::new(map_file);
+        let live_debugger = LiveDebugger {
+            map_file: Arc::clone(&map_file),
+            debug_info: Arc::clone(&debug_info),
+            char_set: machine_info.machine.char_set,
+            io_tx: io_tx.clone(),
+            output_redirect,
+        };
         let stop_reason = Runner {
             cpu: &mut cpu,
             bus_rx,
@@ -57,6 +77,10 @@ pub fn run_terminal_ui(opts: &RunOptions) -> Result<()> {
             stop_after: opts.stop_after,
             machine_info,
             bus: &bus,
+            live_debugger,
+            history,
+            image_path: opts.path.clone(),
+            analysis: &analysis,
         }
         .run()?;
 
@@ -79,6 +103,8 @@ pub fn run_terminal_ui(opts: &RunOptions) -> Result<()> {
             }
         };
 
+        analysis.write(total_cycles, &map_file, &debug_info, &bus.devices())?;
+
         if opts.cycles {
             if matches!(stop_reason, StopReason::RequestedCyclesExecuted { .. }) {
                 info!("Stopped after {total_cycles} cycles with exit code {code}");
@@ -87,12 +113,16 @@ pub fn run_terminal_ui(opts: &RunOptions) -> Result<()> {
             }
         }
 
-        Ok(code)
+        Ok((code, analysis.sanitizer_report(&map_file)))
     }
 
     let raw_mode = RawMode::enable()?;
-    let code = run_inner(opts)?;
+    let (code, sanitizer_report) = run_inner(opts)?;
     drop(raw_mode);
 
+    if let Some(report) = sanitizer_report {
+        eprint!("{report}");
+    }
+
     exit(code);
 }
@@ -0,0 +1,216 @@
+use crate::terminal_ui::RunOptions;
+use anyhow::Result;
+use log::warn;
+use r6502core::DeviceInfo;
+use r6502core::coverage::{CodeCoverage, CoverageMonitor, SharedCoverage};
+use r6502core::emulator::{
+    Bus, BusMonitor, BusView, CompositeMonitor, Cpu, Monitor, TracingMonitor,
+};
+use r6502core::heatmap::{HeatmapMonitor, MemoryHeatmap};
+use r6502core::profiler::{Profile, ProfilingMonitor, SharedProfile};
+use r6502core::sanitizer::RuntimeSanitizer;
+use r6502core::stats::{ExecutionStats, StatsFormat};
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502core::trace::{TraceFilter, TraceHeader, TraceRecorder};
+use r6502lib::{AddressRange, TotalCycles};
+use r6502snapshot::MemoryImage;
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::fs::{File, write};
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+// Data collected by monitors during a run and written out once it stops
+#[derive(Default)]
+pub struct RunAnalysis {
+    profile: Option<(PathBuf, SharedProfile)>,
+    coverage: Option<(PathBuf, SharedCoverage)>,
+    heatmap: Option<(PathBuf, HeatmapMonitor)>,
+    sanitizer: Option<RuntimeSanitizer>,
+    stats: Option<(PathBuf, StatsFormat, RefCell<ExecutionStats>)>,
+    trace: Option<TraceRecorder<BufWriter<File>>>,
+}
+
+impl RunAnalysis {
+    pub fn new(
+        opts: &RunOptions,
+        map_file: &MapFile,
+        devices: &[DeviceInfo],
+        image: &MemoryImage,
+    ) -> Result<(Self, Option<Box<dyn Monitor>>)> {
+        let mut monitor = CompositeMonitor::default();
+        if opts.trace {
+            monitor.push(Box::new(TracingMonitor::new(map_file.clone())));
+        }
+
+        let mut analysis = Self::default();
+        if let Some(path) = &opts.profile {
+            let profile = Rc::new(RefCell::new(Profile::default()));
+            monitor.push(Box::new(ProfilingMonitor::new(&profile)));
+            analysis.profile = Some((path.clone(), profile));
+        }
+        if let Some(path) = &opts.coverage {
+            let coverage = Rc::new(RefCell::new(CodeCoverage::default()));
+            monitor.push(Box::new(CoverageMonitor::new(&coverage)));
+            analysis.coverage = Some((path.clone(), coverage));
+        }
+
+        if let Some(path) = &opts.heatmap {
+            let heatmap = HeatmapMonitor::new(&Rc::new(RefCell::new(MemoryHeatmap::default())));
+            monitor.push(Box::new(heatmap.clone()));
+            analysis.heatmap = Some((path.clone(), heatmap));
+        }
+        if let Some(options) = &opts.sanitize {
+            let sanitizer = RuntimeSanitizer::new(options.clone(), devices);
+            sanitizer.mark_image(image);
+            analysis.sanitizer = Some(sanitizer);
+        }
+        if let Some(path) = &opts.stats {
+            analysis.stats = Some((
+                path.clone(),
+                opts.stats_format,
+                RefCell::new(ExecutionStats::default()),
+            ));
+        }
+
+        if let Some(path) = &opts.trace_file {
+            let filter = TraceFilter {
+                ranges: opts
+                    .trace_ranges
+                    .iter()
+                    .map(|s| s.parse::<AddressRange>())
+                    .collect::<Result<_>>()?,
+                from: opts.trace_from,
+                until: opts.trace_until,
+            };
+            let header = TraceHeader {
+                image_path: Some(opts.path.clone()),
+            };
+            analysis.trace = Some(TraceRecorder::new(
+                BufWriter::new(File::create(path)?),
+                &header,
+                filter,
+            )?);
+        }
+
+        Ok(if monitor.is_empty() {
+            (analysis, None)
+        } else {
+            (analysis, Some(Box::new(monitor)))
+        })
+    }
+
+    // Memory accesses are only seen through a view with the heatmap, sanitizer or trace attached
+    #[must_use]
+    pub fn bus_view<'a>(&'a self, bus: &'a Bus) -> BusView<'a> {
+        if self.heatmap.is_some() || self.sanitizer.is_some() || self.trace.is_some() {
+            BusView::with_monitor(bus, self)
+        } else {
+            bus.view()
+        }
+    }
+
+    pub fn before_step(&self, cpu: &Cpu) {
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.before_step(cpu);
+        }
+        if let Some((_, _, stats)) = &self.stats {
+            stats.borrow_mut().before_step(cpu);
+        }
+        if let Some(trace) = &self.trace {
+            trace.before_step(cpu);
+        }
+    }
+
+    pub fn after_step(&self, cpu: &Cpu) {
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.after_step(cpu);
+        }
+        if let Some((_, _, stats)) = &self.stats {
+            stats.borrow_mut().after_step(cpu);
+        }
+        if let Some(trace) = &self.trace {
+            trace.after_step(cpu);
+        }
+    }
+
+    // Problems found by the sanitizer, if enabled, to show once the terminal is back to normal
+    #[must_use]
+    pub fn sanitizer_report(&self, map_file: &MapFile) -> Option<String> {
+        let issues = self.sanitizer.as_ref()?.take_issues();
+        let mut s = format!("Sanitizer found {} issue(s)\n", issues.len());
+        for issue in issues {
+            let issue = issue.describe(map_file);
+            warn!("{issue}");
+            _ = writeln!(s, "{issue}");
+        }
+        Some(s)
+    }
+
+    pub fn write(
+        &self,
+        total_cycles: TotalCycles,
+        map_file: &MapFile,
+        debug_info: &DebugInfo,
+        devices: &[DeviceInfo],
+    ) -> Result<()> {
+        if let Some((path, profile)) = &self.profile {
+            let mut profile = profile.borrow_mut();
+            profile.finish(total_cycles);
+            write(path, profile.report(map_file))?;
+            write(path.with_extension("folded"), profile.folded(map_file))?;
+        }
+
+        if let Some((path, coverage)) = &self.coverage {
+            let coverage = coverage.borrow();
+            write(path, coverage.lcov(debug_info))?;
+            write(
+                path.with_extension("txt"),
+                coverage.summary(map_file, debug_info),
+            )?;
+        }
+
+        if let Some((path, heatmap)) = &self.heatmap {
+            let heatmap = heatmap.heatmap();
+            let heatmap = heatmap.borrow();
+            write(path, heatmap.csv(devices))?;
+            write(path.with_extension("ppm"), heatmap.ppm())?;
+        }
+
+        if let Some((path, format, stats)) = &self.stats {
+            let mut stats = stats.borrow_mut();
+            stats.finish();
+            write(path, stats.report(*format))?;
+        }
+
+        if let Some(trace) = &self.trace {
+            trace.finish()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl BusMonitor for RunAnalysis {
+    fn on_load(&self, addr: u16, value: u8) {
+        if let Some((_, heatmap)) = &self.heatmap {
+            heatmap.on_load(addr, value);
+        }
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.on_load(addr, value);
+        }
+    }
+
+    fn on_store(&self, addr: u16, value: u8) {
+        if let Some((_, heatmap)) = &self.heatmap {
+            heatmap.on_store(addr, value);
+        }
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.on_store(addr, value);
+        }
+        if let Some(trace) = &self.trace {
+            trace.on_store(addr, value);
+        }
+    }
+}
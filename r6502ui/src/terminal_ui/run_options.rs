@@ -1,3 +1,5 @@
+use r6502core::sanitizer::SanitizerOptions;
+use r6502core::stats::StatsFormat;
 use std::path::PathBuf;
 
 pub struct RunOptions {
@@ -5,7 +7,18 @@ pub struct RunOptions {
     pub load: Option<u16>,
     pub start: Option<u16>,
     pub trace: bool,
+    pub trace_file: Option<PathBuf>,
+    // Address ranges such as $0E00:$0EFF, parsed when the run starts
+    pub trace_ranges: Vec<String>,
+    pub trace_from: Option<u64>,
+    pub trace_until: Option<u64>,
     pub cycles: bool,
     pub stop_after: Option<u64>,
+    pub profile: Option<PathBuf>,
+    pub coverage: Option<PathBuf>,
+    pub heatmap: Option<PathBuf>,
+    pub sanitize: Option<SanitizerOptions>,
+    pub stats: Option<PathBuf>,
+    pub stats_format: StatsFormat,
     pub machine: Option<String>,
 }
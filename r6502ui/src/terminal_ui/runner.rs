@@ -1,17 +1,22 @@
 use crate::crossterm_util::translate_key_event;
-use crate::terminal_ui::acorn_host_hooks::handle_host_hook;
-use crate::terminal_ui::{StopReason, TerminalChannel, TerminalEvent};
-use anyhow::{Result, anyhow, bail};
-use cursive::backends::crossterm::crossterm::event::{Event, KeyEventKind, poll, read};
-use log::warn;
-use r6502config::HostHookType;
-use r6502core::Opcode;
-use r6502core::emulator::{Bus, BusEvent, Cpu, IoEvent, MOS_6502};
+use crate::terminal_ui::{
+    InstructionHistory, PostMortem, RunAnalysis, StepEngine, StopReason, TerminalChannel,
+    TerminalEvent,
+};
+use crate::text_ui::LiveDebugger;
+use anyhow::{Result, bail};
+use cursive::backends::crossterm::crossterm::event::{
+    Event, KeyCode, KeyEventKind, KeyModifiers, poll, read,
+};
+use cursive::backends::crossterm::crossterm::terminal::enable_raw_mode;
+use log::{info, warn};
+use r6502core::debugger::CallStack;
+use r6502core::emulator::{Bus, BusEvent, Cpu, IoEvent};
 use r6502hw::MachineInfo;
-use r6502lib::constants::RESET;
-use r6502lib::util::make_unique_snapshot_path;
-use r6502snapshot::Snapshot;
-use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use r6502lib::util::make_unique_post_mortem_dir;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
 use std::thread::spawn;
 use std::time::Duration;
 
@@ -24,102 +29,153 @@ pub struct Runner<'a> {
     pub stop_after: Option<u64>,
     pub machine_info: MachineInfo,
     pub bus: &'a Bus,
+    pub live_debugger: LiveDebugger,
+    pub history: InstructionHistory,
+    pub image_path: PathBuf,
+    pub analysis: &'a RunAnalysis,
 }
 
 impl Runner<'_> {
     pub fn run(self) -> Result<StopReason> {
+        let (suspend_tx, suspend_rx) = channel();
         let handle = spawn(move || {
-            Self::event_loop(&self.terminal_channel.rx, &self.io_tx).expect("Must succeed");
+            Self::event_loop(&self.terminal_channel.rx, &self.io_tx, &suspend_tx)
+                .expect("Must succeed");
         });
-        let stop_reason =
-            Self::do_steps(self.cpu, &self.bus_rx, self.stop_after, &self.machine_info)?;
+
+        // Evidence of an abnormal stop is kept for later: failure to write it is only logged
+        let write_post_mortem = |reason: &str, cpu: &Cpu, call_stack: &CallStack| {
+            let post_mortem = PostMortem::capture(
+                reason,
+                cpu,
+                &self.history,
+                call_stack,
+                &self.machine_info.machine,
+            );
+            let result = make_unique_post_mortem_dir().and_then(|dir| {
+                post_mortem.write(
+                    &dir,
+                    &cpu.snapshot(),
+                    &self.image_path,
+                    &self.live_debugger.map_file,
+                )?;
+                Ok(dir)
+            });
+            match result {
+                Ok(dir) => info!("Post-mortem written to {}", dir.display()),
+                Err(e) => warn!("could not write post-mortem: {e}"),
+            }
+        };
+
+        let mut engine = StepEngine::new(&self.machine_info, self.bus_rx);
+        let mut call_stack = CallStack::default();
+        let stop_reason = loop {
+            // Only catches anything while panics unwind: with panic = "abort" in the release
+            // profile no post-mortem would be written
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                Self::do_steps(
+                    self.cpu,
+                    &mut engine,
+                    &mut call_stack,
+                    &suspend_rx,
+                    self.stop_after,
+                    self.analysis,
+                )
+            }));
+            let Ok(result) = result else {
+                write_post_mortem("CPU panicked", self.cpu, &call_stack);
+                bail!(
+                    "internal error: most likely a thread panicked; check r6502.log for more info"
+                )
+            };
+            if let Some(stop_reason) = result? {
+                break stop_reason;
+            }
+
+            // Hand the machine over to the debugger until the user quits it
+            let (e, stop_reason) = self.live_debugger.debug(self.cpu, self.bus, engine)?;
+            engine = e;
+            enable_raw_mode()?;
+            _ = self.terminal_channel.tx.send(TerminalEvent::Resume);
+            if let Some(stop_reason) = stop_reason {
+                break stop_reason;
+            }
+        };
+
+        if matches!(stop_reason, StopReason::UnexpectedInterrupt { .. }) {
+            write_post_mortem("Unexpected interrupt (BRK)", self.cpu, &call_stack);
+        }
+
         _ = self.terminal_channel.tx.send(TerminalEvent::Shutdown);
         if handle.join().is_err() {
+            write_post_mortem("Thread panicked", self.cpu, &call_stack);
             bail!("internal error: most likely a thread panicked; check r6502.log for more info")
         }
         if !self.bus.stop() {
+            write_post_mortem("Thread panicked", self.cpu, &call_stack);
             bail!("internal error: most likely a thread panicked; check r6502.log for more info")
         }
         Ok(stop_reason)
     }
 
+    // Returns no stop reason if the user suspended the program in order to debug it
     fn do_steps(
         cpu: &mut Cpu,
-        bus_rx: &Receiver<BusEvent>,
+        engine: &mut StepEngine,
+        call_stack: &mut CallStack,
+        suspend_rx: &Receiver<()>,
         stop_after: Option<u64>,
-        machine_info: &MachineInfo,
-    ) -> Result<StopReason> {
-        let jmp_ind = MOS_6502
-            .get_op_info(&Opcode::JmpInd)
-            .ok_or_else(|| anyhow!("JMP_IND must exist"))?
-            .clone();
-
+        analysis: &RunAnalysis,
+    ) -> Result<Option<StopReason>> {
         loop {
-            cpu.step_with_monitor_callbacks();
-
-            match bus_rx.try_recv() {
-                Ok(BusEvent::UserBreak) => {
-                    return Ok(StopReason::UserBreak {
-                        total_cycles: cpu.total_cycles,
-                    });
-                }
-                Ok(BusEvent::Reset) => {
-                    jmp_ind.execute_word(cpu, RESET);
-                }
-                Ok(BusEvent::Snapshot) => {
-                    let snapshot = Snapshot::new(
-                        cpu.bus.machine_tag(),
-                        cpu.get_state(),
-                        (0..=0xffff).map(|addr| cpu.bus.load(addr)).collect(),
-                    );
-                    let snapshot_path = make_unique_snapshot_path()?;
-                    snapshot.write(&snapshot_path)?;
-                }
-                Err(TryRecvError::Disconnected | TryRecvError::Empty) => {}
-            }
-
-            if let Some(stop_after) = stop_after {
-                if cpu.total_cycles >= stop_after {
-                    return Ok(StopReason::RequestedCyclesExecuted {
-                        total_cycles: cpu.total_cycles,
-                    });
-                }
+            if suspend_rx.try_recv().is_ok() {
+                return Ok(None);
             }
 
-            if let Some(halt_addr) = machine_info.machine.halt_addr {
-                if cpu.reg.pc == halt_addr {
-                    return Ok(StopReason::Halt {
-                        total_cycles: cpu.total_cycles,
-                        a: cpu.reg.a,
-                    });
-                }
+            engine.before_step(cpu);
+            call_stack.before_step(cpu);
+            analysis.before_step(cpu);
+            cpu.step_with_monitor_callbacks();
+            analysis.after_step(cpu);
+            call_stack.after_step(cpu);
+            if let Some(stop_reason) = engine.after_step(cpu)? {
+                return Ok(Some(stop_reason));
             }
 
-            if let Some(host_hook) = &machine_info.machine.host_hook {
-                if cpu.reg.pc == host_hook.addr {
-                    match host_hook.r#type {
-                        HostHookType::Acorn => {
-                            handle_host_hook(cpu)?;
-                            let return_addr = cpu.pull_word().wrapping_add(1);
-                            cpu.reg.pc = return_addr;
-                        }
-                    }
-                }
+            if let Some(stop_after) = stop_after
+                && cpu.total_cycles >= stop_after
+            {
+                return Ok(Some(StopReason::RequestedCyclesExecuted {
+                    total_cycles: cpu.total_cycles,
+                }));
             }
         }
     }
 
-    fn event_loop(terminal_rx: &Receiver<TerminalEvent>, io_tx: &Sender<IoEvent>) -> Result<()> {
+    fn event_loop(
+        terminal_rx: &Receiver<TerminalEvent>,
+        io_tx: &Sender<IoEvent>,
+        suspend_tx: &Sender<()>,
+    ) -> Result<()> {
         loop {
             match terminal_rx.try_recv() {
                 Ok(TerminalEvent::Shutdown) | Err(TryRecvError::Disconnected) => break,
-                Err(TryRecvError::Empty) => {}
+                Ok(TerminalEvent::Resume) | Err(TryRecvError::Empty) => {}
             }
 
             if let Some(Event::Key(key_event)) = Self::try_read_event()?
                 && key_event.kind == KeyEventKind::Press
             {
-                if let Some(key_event) = translate_key_event(&key_event) {
+                if key_event.code == KeyCode::Char('d')
+                    && key_event.modifiers == KeyModifiers::CONTROL
+                {
+                    // Leave the terminal to the debugger until the program resumes
+                    _ = suspend_tx.send(());
+                    match terminal_rx.recv() {
+                        Ok(TerminalEvent::Resume) => {}
+                        Ok(TerminalEvent::Shutdown) | Err(_) => break,
+                    }
+                } else if let Some(key_event) = translate_key_event(&key_event) {
                     _ = io_tx.send(IoEvent::Input(key_event));
                 } else {
                     warn!("unhandled event: {key_event:?}");
@@ -0,0 +1,253 @@
+use crate::terminal_ui::StopReason;
+use crate::terminal_ui::acorn_host_hooks::handle_host_hook;
+use anyhow::{Result, anyhow};
+use r6502config::{HostHook, HostHookType};
+use r6502core::Opcode;
+use r6502core::emulator::{BusEvent, Cpu, MOS_6502};
+use r6502hw::MachineInfo;
+use r6502lib::constants::{IRQ, RESET};
+use r6502lib::util::{make_unique_snapshot_path, make_word};
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+// Work done between instructions that run mode and the debugger must agree on: bus events
+// (break, reset and snapshot), host hooks, halting and unhandled BRKs
+pub struct StepEngine {
+    bus_rx: Receiver<BusEvent>,
+    halt_addr: Option<u16>,
+    host_hook: Option<HostHook>,
+    opcode: Option<u8>,
+}
+
+impl StepEngine {
+    #[must_use]
+    pub fn new(machine_info: &MachineInfo, bus_rx: Receiver<BusEvent>) -> Self {
+        Self {
+            bus_rx,
+            halt_addr: machine_info.machine.halt_addr,
+            host_hook: machine_info.machine.host_hook.clone(),
+            opcode: None,
+        }
+    }
+
+    pub fn before_step(&mut self, cpu: &Cpu) {
+        self.opcode = Some(cpu.bus.peek(cpu.reg.pc));
+    }
+
+    pub fn after_step(&mut self, cpu: &mut Cpu) -> Result<Option<StopReason>> {
+        let total_cycles = cpu.total_cycles;
+
+        // A BRK with no handler to go to can only be a bug in the program
+        let opcode = self.opcode.take();
+        if cpu.last_interrupt.is_none()
+            && opcode == Some(Opcode::Brk as u8)
+            && make_word(cpu.bus.peek(IRQ.wrapping_add(1)), cpu.bus.peek(IRQ)) == 0x0000
+        {
+            return Ok(Some(StopReason::UnexpectedInterrupt { total_cycles }));
+        }
+
+        match self.bus_rx.try_recv() {
+            Ok(BusEvent::UserBreak) => return Ok(Some(StopReason::UserBreak { total_cycles })),
+            Ok(BusEvent::Reset) => {
+                MOS_6502
+                    .get_op_info(&Opcode::JmpInd)
+                    .ok_or_else(|| anyhow!("JMP_IND must exist"))?
+                    .execute_word(cpu, RESET);
+            }
+            Ok(BusEvent::Snapshot) => {
+                cpu.snapshot().write(&make_unique_snapshot_path()?)?;
+            }
+            Err(TryRecvError::Disconnected | TryRecvError::Empty) => {}
+        }
+
+        if let Some(halt_addr) = self.halt_addr
+            && cpu.reg.pc == halt_addr
+        {
+            return Ok(Some(StopReason::Halt {
+                total_cycles,
+                a: cpu.reg.a,
+            }));
+        }
+
+        if let Some(host_hook) = &self.host_hook
+            && cpu.reg.pc == host_hook.addr
+        {
+            match host_hook.r#type {
+                HostHookType::Acorn => {
+                    handle_host_hook(cpu)?;
+                    let return_addr = cpu.pull_word().wrapping_add(1);
+                    cpu.reg.pc = return_addr;
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::terminal_ui::{StepEngine, StopReason};
+    use anyhow::Result;
+    use r6502config::Machine;
+    use r6502core::emulator::{Bus, BusEvent, BusMonitor, BusView, Cpu};
+    use r6502core::{InterruptChannel, InterruptEvent, Opcode};
+    use r6502hw::MachineInfo;
+    use r6502lib::constants::{IRQ, RESET};
+    use rstest::rstest;
+    use std::cell::Cell;
+    use std::path::PathBuf;
+    use std::sync::mpsc::channel;
+
+    const MACHINE: &str = r#"{
+        "name": "AllRam",
+        "tag": "ALLR",
+        "haltAddress": "$FFF0",
+        "busDevices": []
+    }"#;
+
+    fn machine_info() -> Result<MachineInfo> {
+        Ok(MachineInfo {
+            config_dir: PathBuf::new(),
+            machine: serde_json::from_str::<Machine>(MACHINE)?,
+        })
+    }
+
+    #[rstest]
+    #[case(None, 0x0000)]
+    #[case(Some(0x1234), 0x1234)]
+    fn brk(#[case] expected_pc: Option<u16>, #[case] irq_addr: u16) -> Result<()> {
+        let bus = Bus::default();
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        let [lo, hi] = irq_addr.to_le_bytes();
+        bus.store(IRQ, lo);
+        bus.store(IRQ.wrapping_add(1), hi);
+        bus.store(0x0000, Opcode::Nop as u8);
+        bus.store(0x0200, Opcode::Brk as u8);
+        bus.store(0x1234, Opcode::Nop as u8);
+        cpu.reg.pc = 0x0200;
+
+        let (_bus_tx, bus_rx) = channel();
+        let mut engine = StepEngine::new(&machine_info()?, bus_rx);
+        engine.before_step(&cpu);
+        cpu.step_no_spin();
+        let stop_reason = engine.after_step(&mut cpu)?;
+        match expected_pc {
+            Some(pc) => {
+                assert!(stop_reason.is_none());
+                assert_eq!(pc, cpu.reg.pc);
+            }
+            None => assert!(matches!(
+                stop_reason,
+                Some(StopReason::UnexpectedInterrupt { .. })
+            )),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn irq() -> Result<()> {
+        let bus = Bus::default();
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        bus.store(0x0000, Opcode::Brk as u8);
+        bus.store(0x1234, Opcode::Nop as u8);
+        bus.store(IRQ, 0x34);
+        bus.store(IRQ.wrapping_add(1), 0x12);
+
+        let (_bus_tx, bus_rx) = channel();
+        let mut engine = StepEngine::new(&machine_info()?, bus_rx);
+        interrupt_channel.tx.send(InterruptEvent::Irq)?;
+        engine.before_step(&cpu);
+        cpu.step_no_spin();
+        assert!(engine.after_step(&mut cpu)?.is_none());
+        assert_eq!(0x1235, cpu.reg.pc);
+        Ok(())
+    }
+
+    #[test]
+    fn bus_events() -> Result<()> {
+        let bus = Bus::default();
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        bus.store(0x0000, Opcode::Nop as u8);
+        bus.store(0x0e00, Opcode::Nop as u8);
+        bus.store(RESET, 0x00);
+        bus.store(RESET.wrapping_add(1), 0x0e);
+
+        let (bus_tx, bus_rx) = channel();
+        let mut engine = StepEngine::new(&machine_info()?, bus_rx);
+        bus_tx.send(BusEvent::Reset)?;
+        engine.before_step(&cpu);
+        cpu.step_no_spin();
+        assert!(engine.after_step(&mut cpu)?.is_none());
+        assert_eq!(0x0e00, cpu.reg.pc);
+
+        bus_tx.send(BusEvent::UserBreak)?;
+        engine.before_step(&cpu);
+        cpu.step_no_spin();
+        assert!(matches!(
+            engine.after_step(&mut cpu)?,
+            Some(StopReason::UserBreak { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn halt() -> Result<()> {
+        let bus = Bus::default();
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(bus.view(), None, interrupt_channel.rx);
+        cpu.reg.a = 0x12;
+        cpu.reg.pc = 0xffef;
+        bus.store(0xffef, Opcode::Nop as u8);
+
+        let (_bus_tx, bus_rx) = channel();
+        let mut engine = StepEngine::new(&machine_info()?, bus_rx);
+        engine.before_step(&cpu);
+        cpu.step_no_spin();
+        assert!(matches!(
+            engine.after_step(&mut cpu)?,
+            Some(StopReason::Halt { a: 0x12, .. })
+        ));
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct LoadCounter(Cell<usize>);
+
+    impl BusMonitor for LoadCounter {
+        fn on_load(&self, _addr: u16, _value: u8) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    // Monitors such as the heatmap only see the loads made by the instruction itself
+    #[test]
+    fn unmonitored() -> Result<()> {
+        let bus = Bus::default();
+        bus.store(0x0000, Opcode::Nop as u8);
+        bus.store(0x0200, Opcode::Brk as u8);
+        let counter = LoadCounter::default();
+        let interrupt_channel = InterruptChannel::new();
+        let mut cpu = Cpu::new(
+            BusView::with_monitor(&bus, &counter),
+            None,
+            interrupt_channel.rx,
+        );
+        cpu.reg.pc = 0x0200;
+
+        let (_bus_tx, bus_rx) = channel();
+        let mut engine = StepEngine::new(&machine_info()?, bus_rx);
+        engine.before_step(&cpu);
+        assert_eq!(0, counter.0.get());
+        cpu.step_no_spin();
+        let loads = counter.0.get();
+        assert!(matches!(
+            engine.after_step(&mut cpu)?,
+            Some(StopReason::UnexpectedInterrupt { .. })
+        ));
+        assert_eq!(loads, counter.0.get());
+        Ok(())
+    }
+}
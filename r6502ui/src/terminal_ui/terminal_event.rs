@@ -3,6 +3,8 @@ use r6502lib::Channel;
 #[derive(Debug)]
 pub enum TerminalEvent {
     Shutdown,
+    // Debugger has finished with the terminal
+    Resume,
 }
 
 pub type TerminalChannel = Channel<TerminalEvent>;
@@ -0,0 +1,97 @@
+const HISTORY_SIZE: usize = 100;
+
+// Previously entered commands recalled with up and down arrows
+#[derive(Default)]
+pub struct CommandHistory {
+    entries: Vec<String>,
+    // Entry currently recalled or none if editing a new command
+    index: Option<usize>,
+}
+
+impl CommandHistory {
+    #[must_use]
+    pub fn new(entries: Vec<String>) -> Self {
+        let mut history = Self::default();
+        for entry in entries {
+            history.push(&entry);
+        }
+        history
+    }
+
+    #[must_use]
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    // Consecutive duplicates and blank lines aren't worth remembering
+    pub fn push(&mut self, text: &str) {
+        self.index = None;
+        let text = text.trim();
+        if text.is_empty() || self.entries.last().is_some_and(|e| e == text) {
+            return;
+        }
+        self.entries.push(String::from(text));
+        if self.entries.len() > HISTORY_SIZE {
+            _ = self.entries.remove(0);
+        }
+    }
+
+    pub fn previous(&mut self) -> Option<&str> {
+        let index = match self.index {
+            Some(0) => 0,
+            Some(index) => index - 1,
+            None => self.entries.len().checked_sub(1)?,
+        };
+        self.index = Some(index);
+        Some(&self.entries[index])
+    }
+
+    // Moving past the most recent entry returns to an empty command line
+    pub fn next(&mut self) -> &str {
+        match self.index {
+            Some(index) if index + 1 < self.entries.len() => {
+                self.index = Some(index + 1);
+                &self.entries[index + 1]
+            }
+            _ => {
+                self.index = None;
+                ""
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text_ui::command_history::CommandHistory;
+
+    #[test]
+    fn basics() {
+        let mut history = CommandHistory::default();
+        assert_eq!(None, history.previous());
+        assert_eq!("", history.next());
+
+        history.push("bp main");
+        history.push("bp main");
+        history.push(" ");
+        history.push("m $e00:$eff ");
+        assert_eq!(["bp main", "m $e00:$eff"], history.entries());
+
+        assert_eq!(Some("m $e00:$eff"), history.previous());
+        assert_eq!(Some("bp main"), history.previous());
+        assert_eq!(Some("bp main"), history.previous());
+        assert_eq!("m $e00:$eff", history.next());
+        assert_eq!("", history.next());
+        assert_eq!(Some("m $e00:$eff"), history.previous());
+
+        history.push("bl");
+        assert_eq!(Some("bl"), history.previous());
+    }
+
+    #[test]
+    fn size() {
+        let history = CommandHistory::new((0..150).map(|i| format!("m {i}")).collect());
+        assert_eq!(100, history.entries().len());
+        assert_eq!("m 50", history.entries()[0]);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::text_ui::command_history::CommandHistory;
+use crate::text_ui::pending_command::PendingCommand;
+use r6502core::DeviceInfo;
+use r6502core::debugger::{Expr, WatchExpr, Watchpoint};
+use std::collections::BTreeMap;
+
+// Command line state kept in the text UI's user data
+#[derive(Default)]
+pub struct CommandState {
+    // Breakpoints set from the command line with their conditions
+    pub breakpoints: BTreeMap<u16, Option<Expr>>,
+    pub watchpoints: Vec<Watchpoint>,
+    // Watch pane expressions as entered i.e. with symbols unresolved
+    pub watches: Vec<WatchExpr>,
+    pub history: CommandHistory,
+    pub stop_on_interrupt: bool,
+    // Address of next instruction while lines are being assembled instead of run as commands
+    pub assemble_addr: Option<u16>,
+    // Memory map as of the last stop
+    pub devices: Vec<DeviceInfo>,
+    pub pending: Option<PendingCommand>,
+}
@@ -0,0 +1,116 @@
+use r6502core::messages::COMMAND_NAMES;
+use r6502core::symbols::MapFile;
+
+// Result of completing the command line: the line extended by the longest prefix common to all
+// candidates, along with the candidates if there's more than one
+#[derive(Debug, PartialEq)]
+pub struct Completion {
+    pub text: String,
+    pub candidates: Vec<String>,
+}
+
+impl Completion {
+    // Complete the command name if still typing it, otherwise the symbol at the end of the line
+    #[must_use]
+    pub fn complete(text: &str, map_file: &MapFile) -> Self {
+        let word_start = text
+            .rfind(|c: char| !Self::is_symbol_char(c))
+            .map_or(0, |i| i + 1);
+        let (head, word) = text.split_at(word_start);
+
+        let mut candidates = if head.trim().is_empty() {
+            COMMAND_NAMES
+                .iter()
+                .filter(|name| name.starts_with(word))
+                .map(|name| String::from(*name))
+                .collect::<Vec<_>>()
+        } else if word.is_empty() {
+            Vec::new()
+        } else {
+            map_file
+                .exports
+                .iter()
+                .filter(|e| e.name.starts_with(word))
+                .map(|e| e.name.clone())
+                .collect()
+        };
+        candidates.sort();
+        candidates.dedup();
+
+        let Some(first) = candidates.first() else {
+            return Self {
+                text: String::from(text),
+                candidates,
+            };
+        };
+
+        let mut prefix_len = first.len();
+        for candidate in &candidates[1..] {
+            prefix_len = first
+                .bytes()
+                .zip(candidate.bytes())
+                .take(prefix_len)
+                .take_while(|(a, b)| a == b)
+                .count();
+        }
+
+        let mut text = format!("{head}{}", &first[..prefix_len]);
+        if candidates.len() == 1 {
+            text.push(' ');
+            candidates.clear();
+        }
+        Self { text, candidates }
+    }
+
+    fn is_symbol_char(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_' || c == '@' || c == '.' || c == '?'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text_ui::completion::Completion;
+    use r6502core::symbols::{AddressSize, Export, ExportKind, MapFile};
+    use rstest::rstest;
+
+    fn map_file() -> MapFile {
+        let export = |name: &str, value| Export {
+            name: String::from(name),
+            value,
+            referenced: true,
+            kind: ExportKind::Label,
+            address_size: AddressSize::Absolute,
+        };
+        MapFile {
+            exports: vec![
+                export("OSWRCH", 0xffee),
+                export("OSWORD", 0xfff1),
+                export("OSBYTE", 0xfff4),
+                export("main", 0x0e00),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case("bp ", &[], "bp")]
+    #[case("b", &["bc", "bl", "bp"], "b")]
+    #[case("mem", &["mem", "memory"], "me")]
+    #[case("w", &["watch", "wc", "wl", "wp"], "w")]
+    #[case("unwatch ", &[], "u")]
+    #[case("bp main ", &[], "bp ma")]
+    #[case("bp OS", &["OSBYTE", "OSWORD", "OSWRCH"], "bp OS")]
+    #[case("bp OSW", &["OSWORD", "OSWRCH"], "bp OSW")]
+    #[case("m main:OSBYTE ", &[], "m main:OSB")]
+    #[case("bp OSX", &[], "bp OSX")]
+    #[case("m $e00:", &[], "m $e00:")]
+    fn complete(
+        #[case] expected_text: &str,
+        #[case] expected_candidates: &[&str],
+        #[case] input: &str,
+    ) {
+        let completion = Completion::complete(input, &map_file());
+        assert_eq!(expected_text, completion.text);
+        assert_eq!(expected_candidates, completion.candidates);
+    }
+}
@@ -1,37 +1,69 @@
+use crate::text_ui::command_history::CommandHistory;
+use crate::text_ui::command_state::CommandState;
+use crate::text_ui::completion::Completion;
 use crate::text_ui::export_list_info::ExportListInfo;
+use crate::text_ui::memory_view::MemoryView;
+use crate::text_ui::pending_command::PendingCommand;
+use crate::text_ui::session::Session;
+use crate::text_ui::source_cache::SourceCache;
+use anyhow::{Result, anyhow, bail};
 use cursive::align::HAlign;
 use cursive::event::{Callback, Event, EventResult, EventTrigger, Key};
 use cursive::theme::{BaseColor, Color, ColorStyle, ColorType};
+use cursive::utils::markup::StyledString;
 use cursive::view::{Finder, Nameable, Resizable, ScrollStrategy, Scrollable, Selector};
 use cursive::views::{
-    EditView, Layer, LinearLayout, NamedView, Panel, ResizedView, ScrollView, TextView,
+    EditView, Layer, LinearLayout, NamedView, OnEventView, Panel, ResizedView, ScrollView, TextView,
 };
 use cursive::{Cursive, CursiveRunnable, CursiveRunner, View};
-use r6502core::Reg;
+use r6502config::CharSet;
+use r6502core::asm::AsmLine;
+use r6502core::debugger::{
+    CallFrame, Expr, FrameKind, StackIssue, WatchExpr, WatchHit, Watchpoint,
+};
 use r6502core::emulator::{InstructionInfo, IoEvent};
-use r6502core::messages::{Command, DebugMessage, IoMessage, MonitorMessage, State};
-use r6502core::symbols::MapFile;
+use r6502core::messages::{Command, DebugMessage, IoMessage, Location, MonitorMessage, State};
+use r6502core::sanitizer::SanitizerIssue;
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502core::{DeviceInfo, InterruptEvent, P, Reg};
 use r6502lib::AddressRange;
+use r6502lib::constants::STACK_BASE;
 use r6502lib::keyboard::{
     KeyCode as KeyCode_em, KeyEvent as KeyEvent_em, KeyModifiers as KeyModifiers_em,
 };
+use std::collections::HashMap;
 use std::fmt::Write;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
 
 const RIGHT_NAME: &str = "right";
 const CURRENT_NAME: &str = "current";
+const SOURCE_NAME: &str = "source";
 const DISASSEMBLY_NAME: &str = "disassembly";
 const STATE_NAME: &str = "state";
 const STDOUT_NAME: &str = "stdout";
 const STDOUT_CONTAINER_NAME: &str = "stdout-container";
 const SYMBOLS_NAME: &str = "symbols";
 const REGISTERS_NAME: &str = "registers";
+const WATCH_NAME: &str = "watch";
+const DEVICES_NAME: &str = "devices";
+const MEMORY_NAME: &str = "memory";
+const CALL_STACK_NAME: &str = "call-stack";
+const STACK_NAME: &str = "stack";
 const CYCLES_NAME: &str = "cycles";
 const COMMAND_RESPONSE_NAME: &str = "command-response";
 const COMMAND_NAME: &str = "command";
 const COMMAND_FEEDBACK_NAME: &str = "command-feedback";
 
+const SOURCE_HEIGHT: usize = 6;
+const CALL_STACK_HEIGHT: usize = 6;
+const WATCH_HEIGHT: usize = 4;
+const DEVICES_HEIGHT: usize = 6;
+const STACK_ROW_SIZE: u16 = 8;
+
+const WATCH_CHANGED_COLOUR: Color = Color::Light(BaseColor::Red);
+
 const STDOUT_TEXT_COLOUR_ACTIVE: Color = Color::Light(BaseColor::Yellow);
 const STDOUT_TEXT_COLOUR_INACTIVE: Color = Color::Dark(BaseColor::Blue);
 const STDOUT_BACKGROUND_COLOUR_ACTIVE: ColorType = ColorType::Color(Color::Dark(BaseColor::Black));
@@ -42,7 +74,13 @@ pub struct CursiveTui {
     cursive: CursiveRunner<CursiveRunnable>,
     monitor_rx: Receiver<MonitorMessage>,
     io_rx: Receiver<IoMessage>,
-    map_file: MapFile,
+    debug_tx: Sender<DebugMessage>,
+    map_file: Arc<MapFile>,
+    debug_info: Arc<DebugInfo>,
+    source_cache: SourceCache,
+    last_stack_issue: Option<StackIssue>,
+    // Most recent value shown for each watch to highlight changes
+    last_watch_values: HashMap<String, String>,
 }
 
 impl CursiveTui {
@@ -51,14 +89,22 @@ impl CursiveTui {
         io_rx: Receiver<IoMessage>,
         debug_tx: &Sender<DebugMessage>,
         io_tx: &Sender<IoEvent>,
-        map_file: MapFile,
+        map_file: Arc<MapFile>,
+        debug_info: Arc<DebugInfo>,
+        char_set: CharSet,
     ) -> Self {
         let export_list_info = ExportListInfo::new(&map_file);
         let mut cursive = cursive::default().into_runner();
+        cursive.set_user_data(CommandState::default());
         cursive.add_fullscreen_layer(
             LinearLayout::horizontal()
-                .child(Self::make_left())
-                .child(Self::make_right(debug_tx, &export_list_info)),
+                .child(Self::make_left(debug_tx, char_set))
+                .child(Self::make_right(
+                    debug_tx,
+                    &map_file,
+                    &debug_info,
+                    &export_list_info,
+                )),
         );
         cursive.set_fps(30);
         Self::add_global_callbacks(&mut cursive, debug_tx, export_list_info);
@@ -67,7 +113,12 @@ impl CursiveTui {
             cursive,
             monitor_rx,
             io_rx,
+            debug_tx: debug_tx.clone(),
             map_file,
+            source_cache: SourceCache::new(Arc::clone(&debug_info)),
+            debug_info,
+            last_stack_issue: None,
+            last_watch_values: HashMap::new(),
         }
     }
 
@@ -75,10 +126,66 @@ impl CursiveTui {
         while self.step() {}
     }
 
+    // Reinstate breakpoints, watchpoints, watches, memory pane and history from a previous session
+    pub fn restore(&mut self, session: Session) {
+        for (addr, condition) in &session.breakpoints {
+            _ = self
+                .debug_tx
+                .send(DebugMessage::AddBreakpoint(*addr, condition.clone()));
+        }
+        for watchpoint in &session.watchpoints {
+            _ = self
+                .debug_tx
+                .send(DebugMessage::AddWatchpoint(watchpoint.clone()));
+        }
+        self.cursive
+            .call_on_name(MEMORY_NAME, |view: &mut MemoryView| {
+                view.set_start(session.memory_start);
+            })
+            .expect("Must exist");
+        _ = self.cursive.with_user_data(|state: &mut CommandState| {
+            state.breakpoints = session.breakpoints.into_iter().collect();
+            state.watchpoints = session.watchpoints;
+            state.watches = session.watches;
+            state.history = CommandHistory::new(session.history);
+        });
+        if let Err(e) = Self::send_watches(&mut self.cursive, &self.debug_tx, &self.map_file) {
+            self.cursive
+                .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+                .expect("Must exist")
+                .set_content(format!("{e}"));
+        }
+    }
+
+    #[must_use]
+    pub fn session(&mut self) -> Session {
+        let memory_start = self
+            .cursive
+            .call_on_name(MEMORY_NAME, |view: &mut MemoryView| {
+                view.address_range().start()
+            })
+            .expect("Must exist");
+        self.cursive
+            .with_user_data(|state: &mut CommandState| Session {
+                breakpoints: state
+                    .breakpoints
+                    .iter()
+                    .map(|(addr, condition)| (*addr, condition.clone()))
+                    .collect(),
+                watchpoints: state.watchpoints.clone(),
+                watches: state.watches.clone(),
+                memory_start,
+                history: state.history.entries().to_vec(),
+            })
+            .expect("Must exist")
+    }
+
     fn step(&mut self) -> bool {
         use r6502core::messages::IoMessage::WriteChar;
         use r6502core::messages::MonitorMessage::{
-            AfterExecute, BeforeExecute, FetchMemoryResponse, NotifyInvalidBrk, NotifyState,
+            AfterExecute, BeforeExecute, EvaluateResponse, FetchMemoryResponse, NotifyBreakpoint,
+            NotifyCallStack, NotifyDevices, NotifyInterrupt, NotifyInvalidBrk, NotifyMemoryView,
+            NotifySanitizer, NotifyState, NotifyWatches, NotifyWatchpoint,
         };
 
         if !self.cursive.is_running() {
@@ -121,6 +228,25 @@ impl CursiveTui {
                     address_range,
                     snapshot,
                 } => self.on_fetch_memory_response(&address_range, &snapshot),
+                NotifyMemoryView {
+                    address_range,
+                    snapshot,
+                } => self.on_notify_memory_view(&address_range, &snapshot),
+                NotifyCallStack {
+                    frames,
+                    issues,
+                    sp,
+                    stack,
+                } => self.on_notify_call_stack(&frames, &issues, sp, &stack),
+                NotifyBreakpoint(addr) => self.on_notify_breakpoint(addr),
+                NotifyWatchpoint(hit) => self.on_notify_watchpoint(hit),
+                NotifySanitizer(issue) => self.on_notify_sanitizer(&issue),
+                EvaluateResponse(values) => self.on_evaluate_response(values),
+                NotifyWatches(values) => self.on_notify_watches(values),
+                NotifyInterrupt { interrupt, target } => {
+                    self.on_notify_interrupt(interrupt, target);
+                }
+                NotifyDevices(devices) => self.on_notify_devices(devices),
             }
         }
 
@@ -196,6 +322,11 @@ impl CursiveTui {
             .expect("Must exist")
             .set_content(format!("{total_cycles}"));
         self.update_current(Some(instruction_info));
+        let source = self.source_cache.format(reg.pc);
+        self.cursive
+            .find_name::<TextView>(SOURCE_NAME)
+            .expect("Must exist")
+            .set_content(source);
     }
 
     fn on_after_execute(
@@ -223,6 +354,42 @@ impl CursiveTui {
             .append(s);
     }
 
+    fn on_notify_breakpoint(&mut self, addr: u16) {
+        let s = Self::format_breakpoint(&self.map_file, &self.debug_info, addr);
+        self.cursive
+            .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+            .expect("Must exist")
+            .set_content(format!("Breakpoint at {s}"));
+    }
+
+    fn on_notify_watchpoint(&mut self, hit: WatchHit) {
+        self.cursive
+            .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+            .expect("Must exist")
+            .set_content(format!(
+                "Watchpoint ({}) hit at ${:04X}",
+                hit.kind, hit.addr
+            ));
+    }
+
+    fn on_notify_sanitizer(&mut self, issue: &SanitizerIssue) {
+        self.cursive
+            .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+            .expect("Must exist")
+            .set_content(format!("Sanitizer: {}", issue.describe(&self.map_file)));
+    }
+
+    fn on_notify_interrupt(&mut self, interrupt: InterruptEvent, target: u16) {
+        self.cursive
+            .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+            .expect("Must exist")
+            .set_content(format!(
+                "{interrupt} via ${:04X} to {}",
+                interrupt.vector(),
+                self.map_file.symbolize(target)
+            ));
+    }
+
     fn on_fetch_memory_response(&mut self, address_range: &AddressRange, snapshot: &[u8]) {
         let s = Self::format_snapshot(address_range, snapshot);
         self.cursive
@@ -231,15 +398,176 @@ impl CursiveTui {
             .append(s);
     }
 
-    fn format_snapshot(address_range: &AddressRange, bytes: &[u8]) -> String {
+    fn on_notify_memory_view(&mut self, address_range: &AddressRange, snapshot: &[u8]) {
+        self.cursive
+            .call_on_name(MEMORY_NAME, |view: &mut MemoryView| {
+                view.update(address_range, snapshot);
+            })
+            .expect("Must exist");
+    }
+
+    fn on_notify_call_stack(
+        &mut self,
+        frames: &[CallFrame],
+        issues: &[StackIssue],
+        sp: u8,
+        stack: &[u8],
+    ) {
+        self.cursive
+            .find_name::<TextView>(CALL_STACK_NAME)
+            .expect("Must exist")
+            .set_content(Self::format_call_stack(
+                &self.map_file,
+                frames,
+                issues,
+                sp,
+                stack,
+            ));
+        self.cursive
+            .find_name::<TextView>(STACK_NAME)
+            .expect("Must exist")
+            .set_content(Self::format_stack(sp, stack));
+
+        let issue = issues.last().cloned();
+        if let Some(new_issue) = &issue
+            && issue != self.last_stack_issue
+        {
+            self.cursive
+                .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+                .expect("Must exist")
+                .set_content(format!("Stack imbalance at {new_issue}"));
+        }
+        self.last_stack_issue = issue;
+    }
+
+    fn on_notify_watches(&mut self, values: Vec<Result<String>>) {
+        let watches = self
+            .cursive
+            .with_user_data(|state: &mut CommandState| state.watches.clone())
+            .unwrap_or_default();
+        let mut s = StyledString::new();
+        let mut last_watch_values = HashMap::new();
+        for (i, (watch, value)) in watches.iter().zip(values).enumerate() {
+            let label = watch.to_string();
+            let value = value.unwrap_or_else(|e| format!("<{e}>"));
+            s.append_plain(format!("{}: {label} = ", i + 1));
+            if self
+                .last_watch_values
+                .get(&label)
+                .is_some_and(|last_value| *last_value != value)
+            {
+                s.append_styled(&value, WATCH_CHANGED_COLOUR);
+            } else {
+                s.append_plain(&value);
+            }
+            s.append_plain("\n");
+            _ = last_watch_values.insert(label, value);
+        }
+        self.last_watch_values = last_watch_values;
+        self.cursive
+            .find_name::<TextView>(WATCH_NAME)
+            .expect("Must exist")
+            .set_content(s);
+    }
+
+    fn on_notify_devices(&mut self, devices: Vec<DeviceInfo>) {
+        self.cursive
+            .find_name::<TextView>(DEVICES_NAME)
+            .expect("Must exist")
+            .set_content(Self::format_devices(&devices));
+        _ = self
+            .cursive
+            .with_user_data(|state: &mut CommandState| state.devices = devices);
+    }
+
+    // Memory-mapped devices followed by their registers, if any, on the next line
+    fn format_devices(devices: &[DeviceInfo]) -> String {
+        let mut s = String::new();
+        for device in devices {
+            _ = writeln!(s, "{device}");
+            if !device.state.registers.is_empty() {
+                for (name, value) in &device.state.registers {
+                    _ = write!(s, " {name}=${value:02X}");
+                }
+                s.push('\n');
+            }
+        }
+        s
+    }
+
+    // Interrupts and BRKs also show the status register they stacked
+    fn format_call_stack(
+        map_file: &MapFile,
+        frames: &[CallFrame],
+        issues: &[StackIssue],
+        sp: u8,
+        stack: &[u8],
+    ) -> String {
+        let mut s = String::new();
+        if frames.is_empty() {
+            s.push_str("(empty)\n");
+        }
+        for frame in frames.iter().rev() {
+            write!(
+                s,
+                "{} {} ret {}",
+                frame.kind,
+                map_file.symbolize(frame.target),
+                map_file.symbolize(frame.return_addr)
+            )
+            .unwrap();
+            if frame.kind != FrameKind::Jsr
+                && let Some(p) = frame
+                    .sp
+                    .checked_sub(sp)
+                    .and_then(|offset| stack.get(usize::from(offset)))
+            {
+                _ = write!(s, " P={}", P::from_bits_retain(*p));
+            }
+            s.push('\n');
+        }
+        for issue in issues.iter().rev() {
+            _ = writeln!(s, "! {issue}");
+        }
+        s
+    }
+
+    fn format_stack(sp: u8, stack: &[u8]) -> String {
+        let mut s = format!("SP=${sp:02X}\n");
+        let mut addr = STACK_BASE + u16::from(sp) + 1;
+        for chunk in stack.chunks(STACK_ROW_SIZE as usize) {
+            _ = write!(s, "{addr:04X} ");
+            for b in chunk {
+                _ = write!(s, " {b:02X}");
+            }
+            s.push('\n');
+            addr += STACK_ROW_SIZE;
+        }
+        s
+    }
+
+    // Breakpoint address with source location and symbol where known e.g. "$0E05 main.s:6 (print)"
+    pub fn format_breakpoint(map_file: &MapFile, debug_info: &DebugInfo, addr: u16) -> String {
+        let mut s = format!("${addr:04X}");
+        if let Some(location) = debug_info.format_location(addr) {
+            _ = write!(s, " {location}");
+        }
+        let symbol = map_file.symbolize(addr);
+        if !symbol.starts_with('$') {
+            _ = write!(s, " ({symbol})");
+        }
+        s
+    }
+
+    pub fn format_snapshot(address_range: &AddressRange, bytes: &[u8]) -> String {
         const CHUNK_SIZE: usize = 16;
         let mut s = format!("{address_range}\n");
         let mut addr = address_range.start() as usize;
         for chunk in bytes.chunks(CHUNK_SIZE) {
-            write!(s, "{addr:04X} ").unwrap();
+            _ = write!(s, "{addr:04X} ");
             let mut chars = String::with_capacity(CHUNK_SIZE);
             for b in chunk {
-                write!(s, " {b:02X}").unwrap();
+                _ = write!(s, " {b:02X}");
                 let c: char = *b as char;
                 if c.is_ascii() && !c.is_ascii_control() {
                     chars.push(c);
@@ -248,7 +576,7 @@ impl CursiveTui {
                 }
             }
             s.push_str(&String::from("   ").repeat(CHUNK_SIZE - chunk.len()));
-            writeln!(s, "  {chars}").unwrap();
+            _ = writeln!(s, "  {chars}");
             addr += CHUNK_SIZE;
         }
         s
@@ -258,10 +586,30 @@ impl CursiveTui {
         Panel::new(view).title(label).title_position(HAlign::Left)
     }
 
-    fn make_left() -> LinearLayout {
+    fn make_left(debug_tx: &Sender<DebugMessage>, char_set: CharSet) -> LinearLayout {
         let current = TextView::new("").with_name(CURRENT_NAME).fixed_height(1);
         let registers = TextView::new("").with_name(REGISTERS_NAME);
+        let watch = TextView::new("")
+            .with_name(WATCH_NAME)
+            .full_width()
+            .fixed_height(WATCH_HEIGHT)
+            .scrollable()
+            .scroll_strategy(ScrollStrategy::KeepRow);
         let cycles = TextView::new("").with_name(CYCLES_NAME);
+        let memory = MemoryView::new(char_set, debug_tx.clone());
+        memory.watch();
+        let memory = memory.with_name(MEMORY_NAME);
+        let call_stack = TextView::new("")
+            .with_name(CALL_STACK_NAME)
+            .full_width()
+            .fixed_height(CALL_STACK_HEIGHT)
+            .scrollable()
+            .scroll_strategy(ScrollStrategy::KeepRow);
+        let stack = TextView::new("")
+            .with_name(STACK_NAME)
+            .fixed_height(CALL_STACK_HEIGHT)
+            .scrollable()
+            .scroll_strategy(ScrollStrategy::KeepRow);
         let disassembly = TextView::new("")
             .with_name(DISASSEMBLY_NAME)
             .full_height()
@@ -276,15 +624,28 @@ impl CursiveTui {
         LinearLayout::vertical()
             .child(Self::panel(current, "Current Instruction"))
             .child(Self::panel(registers, "Registers"))
+            .child(Self::panel(watch, "Watch"))
             .child(Self::panel(cycles, "Cycles"))
+            .child(Self::panel(memory, "Memory"))
+            .child(
+                LinearLayout::horizontal()
+                    .child(Self::panel(call_stack, "Call Stack"))
+                    .child(Self::panel(stack, "Stack")),
+            )
             .child(Self::panel(disassembly, "Disassembly"))
             .child(Self::panel(state, "Status"))
     }
 
     fn make_right(
         debug_tx: &Sender<DebugMessage>,
+        map_file: &Arc<MapFile>,
+        debug_info: &Arc<DebugInfo>,
         export_list_info: &ExportListInfo,
     ) -> NamedView<LinearLayout> {
+        let source = TextView::new("")
+            .with_name(SOURCE_NAME)
+            .full_width()
+            .fixed_height(SOURCE_HEIGHT);
         let stdout = TextView::new("")
             .style(STDOUT_TEXT_COLOUR_INACTIVE)
             .with_name(STDOUT_NAME)
@@ -293,6 +654,12 @@ impl CursiveTui {
             .scrollable()
             .scroll_strategy(ScrollStrategy::StickToBottom);
         let stdout_container = Layer::new(stdout).with_name(STDOUT_CONTAINER_NAME);
+        let devices = TextView::new("")
+            .with_name(DEVICES_NAME)
+            .full_width()
+            .fixed_height(DEVICES_HEIGHT)
+            .scrollable()
+            .scroll_strategy(ScrollStrategy::KeepRow);
         let symbols = TextView::new(export_list_info.toggle())
             .with_name(SYMBOLS_NAME)
             .min_height(10)
@@ -302,39 +669,35 @@ impl CursiveTui {
         let help = TextView::new(
             "Q: Quit\n\
             Space: Step\n\
+            L: Step source line\n\
             R: Run\n\
             B: Break\n\
             C: Command\n\
+            Up/Down: Command history\n\
+            Tab: Complete command or symbol\n\
             Esc: Exit command\n\
+            M: Focus memory (arrows/PgUp/PgDn: move, Enter: toggle edit)\n\
             S: Toggle symbol sort order\n\
             Ctrl+P: Toggle between debugger and program input",
         )
         .full_width()
         .scrollable()
         .scroll_strategy(ScrollStrategy::KeepRow);
-        let d = debug_tx.clone();
         let command_response = TextView::new("")
             .with_name(COMMAND_RESPONSE_NAME)
             .full_width()
             .min_height(5)
             .scrollable()
             .scroll_strategy(ScrollStrategy::StickToBottom);
-        let command = EditView::new()
-            .disabled()
-            .on_submit(move |c, text| {
-                c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
-                    _ = command.set_content("");
-                });
-                Self::run_command(c, text, &d);
-            })
-            .with_name(COMMAND_NAME)
-            .fixed_height(1);
+        let command = Self::make_command(debug_tx, map_file, debug_info);
         let command_feedback = TextView::new("")
             .with_name(COMMAND_FEEDBACK_NAME)
             .fixed_height(1);
 
         LinearLayout::vertical()
+            .child(Self::panel(source, "Source"))
             .child(Self::panel(stdout_container, "Output"))
+            .child(Self::panel(devices, "Devices"))
             .child(Self::panel(symbols, "Symbols"))
             .child(Self::panel(help, "Help"))
             .child(Self::panel(command_response, "Command Response"))
@@ -343,12 +706,63 @@ impl CursiveTui {
             .with_name(RIGHT_NAME)
     }
 
+    // Command line with history and completion
+    fn make_command(
+        debug_tx: &Sender<DebugMessage>,
+        map_file: &Arc<MapFile>,
+        debug_info: &Arc<DebugInfo>,
+    ) -> impl View {
+        let d = debug_tx.clone();
+        let m = Arc::clone(map_file);
+        let i = Arc::clone(debug_info);
+        let completion_map_file = Arc::clone(map_file);
+        OnEventView::new(
+            EditView::new()
+                .disabled()
+                .on_submit(move |c, text| {
+                    c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
+                        _ = command.set_content("");
+                    });
+                    _ = c.with_user_data(|state: &mut CommandState| state.history.push(text));
+                    Self::run_command(c, text, &d, &m, &i);
+                })
+                .with_name(COMMAND_NAME),
+        )
+        .on_pre_event(Key::Up, |c| {
+            if let Some(Some(text)) = c.with_user_data(|state: &mut CommandState| {
+                state.history.previous().map(String::from)
+            }) {
+                Self::set_command(c, &text);
+            }
+        })
+        .on_pre_event(Key::Down, |c| {
+            if let Some(text) =
+                c.with_user_data(|state: &mut CommandState| String::from(state.history.next()))
+            {
+                Self::set_command(c, &text);
+            }
+        })
+        .on_pre_event(Key::Tab, move |c| {
+            let Some(text) = c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
+                String::from(command.get_content().as_str())
+            }) else {
+                return;
+            };
+            let completion = Completion::complete(&text, &completion_map_file);
+            Self::set_command(c, &completion.text);
+            c.call_on_name(COMMAND_FEEDBACK_NAME, |view: &mut TextView| {
+                view.set_content(completion.candidates.join(" "));
+            });
+        })
+        .fixed_height(1)
+    }
+
     fn add_global_callbacks(
         c: &mut Cursive,
         debug_tx: &Sender<DebugMessage>,
         export_list_info: ExportListInfo,
     ) {
-        use r6502core::messages::DebugMessage::{Break, Run, Step};
+        use r6502core::messages::DebugMessage::{Break, Run, Step, StepLine};
 
         c.add_global_callback('q', Cursive::quit);
         c.add_global_callback('s', move |c| {
@@ -360,6 +774,8 @@ impl CursiveTui {
         let d = debug_tx.clone();
         c.add_global_callback(' ', move |_| _ = d.send(Step));
         let d = debug_tx.clone();
+        c.add_global_callback('l', move |_| _ = d.send(StepLine));
+        let d = debug_tx.clone();
         c.add_global_callback('r', move |_| _ = d.send(Run));
         let d = debug_tx.clone();
         c.add_global_callback('b', move |_| _ = d.send(Break));
@@ -374,6 +790,7 @@ impl CursiveTui {
                     .expect("Must succeed");
             });
         });
+        c.add_global_callback('m', |c| _ = c.focus_name(MEMORY_NAME));
         c.add_global_callback(Key::Esc, move |c| {
             c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
                 command.disable();
@@ -460,39 +877,409 @@ impl CursiveTui {
         });
     }
 
-    fn run_command(c: &mut Cursive, text: &str, d: &Sender<DebugMessage>) {
-        use r6502core::messages::DebugMessage::{FetchMemory, Go, SetPc};
+    fn set_command(c: &mut Cursive, text: &str) {
+        c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
+            _ = command.set_content(text);
+        });
+    }
+
+    fn run_command(
+        c: &mut Cursive,
+        text: &str,
+        d: &Sender<DebugMessage>,
+        map_file: &MapFile,
+        debug_info: &DebugInfo,
+    ) {
+        let assembling = c
+            .user_data::<CommandState>()
+            .is_some_and(|state| state.assemble_addr.is_some());
+        let result = if assembling {
+            Self::start_assemble_line(c, text, d, map_file)
+        } else {
+            text.parse::<Command>()
+                .and_then(|command| Self::start_command(c, command, d, map_file, debug_info))
+        };
+        if let Err(e) = result {
+            c.call_on_name(COMMAND_FEEDBACK_NAME, |view: &mut TextView| {
+                view.set_content(format!("{e}"));
+            });
+        }
+    }
+
+    // Empty line finishes assembling, otherwise the line's operand is evaluated by the host before
+    // the instruction is encoded
+    fn start_assemble_line(
+        c: &mut Cursive,
+        text: &str,
+        d: &Sender<DebugMessage>,
+        map_file: &MapFile,
+    ) -> Result<()> {
+        if text.trim().is_empty() {
+            _ = c.with_user_data(|state: &mut CommandState| state.assemble_addr = None);
+            c.find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+                .expect("Must exist")
+                .set_content("Finished assembling");
+            return Ok(());
+        }
+
+        let line = text.parse::<AsmLine>()?;
+        let line = AsmLine {
+            mnemonic: line.mnemonic,
+            operand: line
+                .operand
+                .map_expr(|expr| Self::resolve_symbols(map_file, expr))?,
+        };
+        let Some(expr) = line.operand.expr().cloned() else {
+            return Self::assemble_line(c, d, map_file, &line, None);
+        };
+        _ = c.with_user_data(|state: &mut CommandState| {
+            state.pending = Some(PendingCommand::AssembleLine(line));
+        });
+        d.send(DebugMessage::Evaluate(vec![expr]))?;
+        Ok(())
+    }
+
+    fn assemble_line(
+        c: &mut Cursive,
+        d: &Sender<DebugMessage>,
+        map_file: &MapFile,
+        line: &AsmLine,
+        value: Option<i32>,
+    ) -> Result<()> {
+        let Some(addr) = c
+            .user_data::<CommandState>()
+            .and_then(|state| state.assemble_addr)
+        else {
+            return Ok(());
+        };
+
+        let bytes = line.encode(addr, value)?;
+        let instruction_info = InstructionInfo::decode(addr, &bytes)
+            .ok_or_else(|| anyhow!("could not decode assembled instruction"))?;
+        let s = instruction_info.disassembly(map_file)?;
+        d.send(DebugMessage::SetMemory(addr, bytes))?;
+        c.find_name::<TextView>(COMMAND_RESPONSE_NAME)
+            .expect("Must exist")
+            .append(format!("{s}\n"));
+
+        let next_addr = addr.wrapping_add(instruction_info.size());
+        _ = c.with_user_data(|state: &mut CommandState| state.assemble_addr = Some(next_addr));
+        Self::show_assemble_prompt(c, next_addr);
+        Ok(())
+    }
+
+    fn show_assemble_prompt(c: &mut Cursive, addr: u16) {
+        c.find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+            .expect("Must exist")
+            .set_content(format!("Assembling at ${addr:04X}: empty line to finish"));
+    }
 
-        match text.parse::<Command>() {
-            Ok(Command::Help(help)) => {
+    fn list_breakpoints(c: &mut Cursive, map_file: &MapFile, debug_info: &DebugInfo) {
+        let mut s = String::new();
+        if let Some(state) = c.user_data::<CommandState>() {
+            if state.breakpoints.is_empty() {
+                s.push_str("(no breakpoints)\n");
+            }
+            for (addr, condition) in &state.breakpoints {
+                s.push_str(&Self::format_breakpoint(map_file, debug_info, *addr));
+                if let Some(condition) = condition {
+                    _ = write!(s, " if {condition}");
+                }
+                s.push('\n');
+            }
+        }
+        c.find_name::<TextView>(COMMAND_RESPONSE_NAME)
+            .expect("Must exist")
+            .append(s);
+    }
+
+    fn list_watchpoints(c: &mut Cursive) {
+        let mut s = String::new();
+        if let Some(state) = c.user_data::<CommandState>() {
+            if state.watchpoints.is_empty() {
+                s.push_str("(no watchpoints)\n");
+            }
+            for watchpoint in &state.watchpoints {
+                _ = writeln!(s, "{} {}", watchpoint.address_range, watchpoint.kind);
+            }
+        }
+        c.find_name::<TextView>(COMMAND_RESPONSE_NAME)
+            .expect("Must exist")
+            .append(s);
+    }
+
+    fn list_devices(c: &mut Cursive) {
+        let mut s = String::new();
+        if let Some(state) = c.user_data::<CommandState>() {
+            for device in &state.devices {
+                _ = writeln!(s, "{device}");
+            }
+        }
+        c.find_name::<TextView>(COMMAND_RESPONSE_NAME)
+            .expect("Must exist")
+            .append(s);
+    }
+
+    fn toggle_stop_on_interrupt(c: &mut Cursive, d: &Sender<DebugMessage>) -> Result<()> {
+        let value = c
+            .with_user_data(|state: &mut CommandState| {
+                state.stop_on_interrupt = !state.stop_on_interrupt;
+                state.stop_on_interrupt
+            })
+            .unwrap_or_default();
+        d.send(DebugMessage::StopOnInterrupt(value))?;
+        c.find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+            .expect("Must exist")
+            .set_content(if value {
+                "Stopping on interrupt entry"
+            } else {
+                "Not stopping on interrupt entry"
+            });
+        Ok(())
+    }
+
+    // Commands with address arguments complete once the host has evaluated them
+    fn start_command(
+        c: &mut Cursive,
+        command: Command,
+        d: &Sender<DebugMessage>,
+        map_file: &MapFile,
+        debug_info: &DebugInfo,
+    ) -> Result<()> {
+        let resolve = |expr: &Expr| Self::resolve_symbols(map_file, expr);
+        let (pending, exprs) = match command {
+            Command::Help(help) => {
                 c.find_name::<TextView>(COMMAND_RESPONSE_NAME)
                     .expect("Must exist")
                     .append(help);
+                return Ok(());
             }
-            Ok(Command::FetchMemory(address_range)) => {
-                _ = d.send(FetchMemory(address_range));
-                c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
-                    command.disable();
-                });
+            Command::ListBreakpoints => {
+                Self::list_breakpoints(c, map_file, debug_info);
+                return Ok(());
             }
-            Ok(Command::SetPc(addr)) => {
-                _ = d.send(SetPc(addr));
-                c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
-                    command.disable();
-                });
+            Command::ListWatchpoints => {
+                Self::list_watchpoints(c);
+                return Ok(());
+            }
+            Command::ListDevices => {
+                Self::list_devices(c);
+                return Ok(());
+            }
+            Command::AddWatch(watch) => {
+                // Check symbols now rather than every time the watches are sent
+                _ = Self::resolve_watch(map_file, &watch)?;
+                _ = c.with_user_data(|state: &mut CommandState| state.watches.push(watch));
+                return Self::send_watches(c, d, map_file);
+            }
+            Command::RemoveWatch(n) => {
+                let removed = c
+                    .with_user_data(|state: &mut CommandState| {
+                        (1..=state.watches.len())
+                            .contains(&n)
+                            .then(|| state.watches.remove(n - 1))
+                    })
+                    .flatten();
+                if removed.is_none() {
+                    bail!("no watch {n}")
+                }
+                return Self::send_watches(c, d, map_file);
+            }
+            Command::AssertIrq(cycles) => {
+                d.send(DebugMessage::AssertIrq(cycles))?;
+                return Ok(());
             }
-            Ok(Command::Go(addr)) => {
-                _ = d.send(Go(addr));
-                c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
-                    command.disable();
+            Command::Interrupt(interrupt) => {
+                d.send(DebugMessage::Interrupt(interrupt))?;
+                return Ok(());
+            }
+            Command::ToggleStopOnInterrupt => return Self::toggle_stop_on_interrupt(c, d),
+            Command::FetchMemory(start, end) => (
+                PendingCommand::FetchMemory,
+                vec![resolve(&start)?, resolve(&end)?],
+            ),
+            Command::SetPc(addr) => (PendingCommand::SetPc, vec![resolve(&addr)?]),
+            Command::Go(addr) => (PendingCommand::Go, vec![resolve(&addr)?]),
+            Command::ViewMemory(addr) => (PendingCommand::ViewMemory, vec![resolve(&addr)?]),
+            Command::Assemble(addr) => (PendingCommand::Assemble, vec![resolve(&addr)?]),
+            Command::SetBreakpoint(location, condition) => (
+                PendingCommand::SetBreakpoint(condition.as_ref().map(resolve).transpose()?),
+                vec![Self::resolve_location(map_file, debug_info, &location)?],
+            ),
+            Command::ClearBreakpoint(location) => (
+                PendingCommand::ClearBreakpoint,
+                vec![Self::resolve_location(map_file, debug_info, &location)?],
+            ),
+            Command::SetWatchpoint(start, end, kind) => (
+                PendingCommand::SetWatchpoint(kind),
+                vec![resolve(&start)?, resolve(&end)?],
+            ),
+            Command::ClearWatchpoint(start, end) => (
+                PendingCommand::ClearWatchpoint,
+                vec![resolve(&start)?, resolve(&end)?],
+            ),
+        };
+
+        _ = c.with_user_data(|state: &mut CommandState| state.pending = Some(pending));
+        _ = d.send(DebugMessage::Evaluate(exprs));
+        c.call_on_name(COMMAND_NAME, |command: &mut EditView| {
+            command.disable();
+        });
+        Ok(())
+    }
+
+    fn on_evaluate_response(&mut self, values: Result<Vec<i32>>) {
+        let Some(pending) = self
+            .cursive
+            .with_user_data(|state: &mut CommandState| state.pending.take())
+            .flatten()
+        else {
+            return;
+        };
+
+        if let Err(e) = values.and_then(|values| self.finish_command(pending, &values)) {
+            self.cursive
+                .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+                .expect("Must exist")
+                .set_content(format!("{e}"));
+        }
+    }
+
+    fn finish_command(&mut self, pending: PendingCommand, values: &[i32]) -> Result<()> {
+        // Operands such as immediate values needn't be addresses
+        if let PendingCommand::AssembleLine(line) = pending {
+            return Self::assemble_line(
+                &mut self.cursive,
+                &self.debug_tx,
+                &self.map_file,
+                &line,
+                Some(values[0]),
+            );
+        }
+
+        let addrs = values
+            .iter()
+            .map(|value| Expr::to_addr(*value))
+            .collect::<Result<Vec<_>>>()?;
+        match pending {
+            PendingCommand::FetchMemory => {
+                let address_range = AddressRange::new(addrs[0], addrs[1])?;
+                self.debug_tx
+                    .send(DebugMessage::FetchMemory(address_range))?;
+            }
+            PendingCommand::SetPc => self.debug_tx.send(DebugMessage::SetPc(addrs[0]))?,
+            PendingCommand::Go => self.debug_tx.send(DebugMessage::Go(addrs[0]))?,
+            PendingCommand::ViewMemory => {
+                self.cursive
+                    .call_on_name(MEMORY_NAME, |view: &mut MemoryView| {
+                        view.set_start(addrs[0]);
+                    });
+            }
+            PendingCommand::Assemble => {
+                let addr = addrs[0];
+                _ = self
+                    .cursive
+                    .with_user_data(|state: &mut CommandState| state.assemble_addr = Some(addr));
+                Self::show_assemble_prompt(&mut self.cursive, addr);
+            }
+            PendingCommand::AssembleLine(_) => unreachable!(),
+            PendingCommand::SetBreakpoint(condition) => {
+                let addr = addrs[0];
+                _ = self.cursive.with_user_data(|state: &mut CommandState| {
+                    state.breakpoints.insert(addr, condition.clone())
                 });
+                self.debug_tx
+                    .send(DebugMessage::AddBreakpoint(addr, condition))?;
+                let s = Self::format_breakpoint(&self.map_file, &self.debug_info, addr);
+                self.cursive
+                    .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+                    .expect("Must exist")
+                    .set_content(format!("Breakpoint set at {s}"));
+            }
+            PendingCommand::ClearBreakpoint => {
+                let addr = addrs[0];
+                _ = self
+                    .cursive
+                    .with_user_data(|state: &mut CommandState| state.breakpoints.remove(&addr));
+                self.debug_tx.send(DebugMessage::RemoveBreakpoint(addr))?;
             }
-            Err(e) => {
-                c.call_on_name(COMMAND_FEEDBACK_NAME, |view: &mut TextView| {
-                    view.set_content(format!("{e}"));
+            PendingCommand::SetWatchpoint(kind) => {
+                let watchpoint = Watchpoint {
+                    address_range: AddressRange::new(addrs[0], addrs[1])?,
+                    kind,
+                };
+                _ = self.cursive.with_user_data(|state: &mut CommandState| {
+                    if !state.watchpoints.contains(&watchpoint) {
+                        state.watchpoints.push(watchpoint.clone());
+                    }
                 });
+                self.cursive
+                    .find_name::<TextView>(COMMAND_FEEDBACK_NAME)
+                    .expect("Must exist")
+                    .set_content(format!(
+                        "Watchpoint ({}) set at {}",
+                        watchpoint.kind, watchpoint.address_range
+                    ));
+                self.debug_tx
+                    .send(DebugMessage::AddWatchpoint(watchpoint))?;
+            }
+            PendingCommand::ClearWatchpoint => {
+                // Clear every kind of watchpoint on the range
+                let address_range = AddressRange::new(addrs[0], addrs[1])?;
+                let removed = self
+                    .cursive
+                    .with_user_data(|state: &mut CommandState| {
+                        let (removed, kept) = state
+                            .watchpoints
+                            .drain(..)
+                            .partition::<Vec<_>, _>(|w| w.address_range == address_range);
+                        state.watchpoints = kept;
+                        removed
+                    })
+                    .unwrap_or_default();
+                for watchpoint in removed {
+                    self.debug_tx
+                        .send(DebugMessage::RemoveWatchpoint(watchpoint))?;
+                }
             }
         }
+        Ok(())
+    }
+
+    fn send_watches(c: &mut Cursive, d: &Sender<DebugMessage>, map_file: &MapFile) -> Result<()> {
+        let watches = c
+            .with_user_data(|state: &mut CommandState| state.watches.clone())
+            .unwrap_or_default()
+            .iter()
+            .map(|watch| Self::resolve_watch(map_file, watch))
+            .collect::<Result<_>>()?;
+        d.send(DebugMessage::SetWatches(watches))?;
+        Ok(())
+    }
+
+    fn resolve_watch(map_file: &MapFile, watch: &WatchExpr) -> Result<WatchExpr> {
+        Ok(WatchExpr {
+            expr: Self::resolve_symbols(map_file, &watch.expr)?,
+            format: watch.format,
+        })
+    }
+
+    pub fn resolve_symbols(map_file: &MapFile, expr: &Expr) -> Result<Expr> {
+        expr.resolve(&|name| map_file.lookup(name))
+    }
+
+    pub fn resolve_location(
+        map_file: &MapFile,
+        debug_info: &DebugInfo,
+        location: &Location,
+    ) -> Result<Expr> {
+        match location {
+            Location::Expr(expr) => Self::resolve_symbols(map_file, expr),
+            Location::SourceLine(file_name, line) => debug_info
+                .find_addr(file_name, *line)
+                .map(|addr| Expr::Number(i32::from(addr)))
+                .ok_or_else(|| anyhow!("no code at {file_name}:{line}")),
+        }
     }
 }
 
@@ -500,6 +1287,9 @@ impl CursiveTui {
 mod tests {
     use crate::text_ui::cursive_tui::CursiveTui;
     use anyhow::Result;
+    use r6502core::debugger::{CallFrame, FrameKind, StackIssue};
+    use r6502core::symbols::MapFile;
+    use r6502core::{DeviceInfo, DeviceState};
     use r6502lib::AddressRange;
 
     #[test]
@@ -516,4 +1306,80 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn format_call_stack() {
+        let frames = vec![
+            CallFrame {
+                kind: FrameKind::Jsr,
+                site: 0x0e07,
+                target: 0x0e20,
+                return_addr: 0x0e0a,
+                sp: 0xfd,
+            },
+            CallFrame {
+                kind: FrameKind::Irq,
+                site: 0x0e22,
+                target: 0xdc1c,
+                return_addr: 0x0e22,
+                sp: 0xfa,
+            },
+        ];
+        let issues = vec![StackIssue {
+            pc: 0x0e25,
+            message: String::from("RTS with empty call stack"),
+        }];
+        // Stacked by the IRQ at $01FA: P then return address
+        let stack = [0x00, 0xa4, 0x22, 0x0e, 0x00, 0x0a, 0x0e];
+        let s = CursiveTui::format_call_stack(&MapFile::default(), &frames, &issues, 0xf9, &stack);
+        assert_eq!(
+            "IRQ $DC1C ret $0E22 P=[N.-..I..]\n\
+            JSR $0E20 ret $0E0A\n\
+            ! $0E25: RTS with empty call stack\n",
+            s
+        );
+    }
+
+    #[test]
+    fn format_stack() {
+        let stack = (0xf6..=0xff).collect::<Vec<_>>();
+        let s = CursiveTui::format_stack(0xf5, &stack);
+        assert_eq!(
+            "SP=$F5\n\
+            01F6  F6 F7 F8 F9 FA FB FC FD\n\
+            01FE  FE FF\n",
+            s
+        );
+    }
+
+    #[test]
+    fn format_devices() -> Result<()> {
+        let devices = vec![
+            DeviceInfo {
+                address_range: AddressRange::new(0x0000, 0x7fff)?,
+                offset: 0x0000,
+                state: DeviceState {
+                    kind: String::from("RAM"),
+                    registers: Vec::new(),
+                    description: String::from("32768 bytes"),
+                },
+            },
+            DeviceInfo {
+                address_range: AddressRange::new(0xfc00, 0xfc03)?,
+                offset: 0xfc00,
+                state: DeviceState {
+                    kind: String::from("PIA"),
+                    registers: vec![(String::from("PA"), 0x41), (String::from("PACR"), 0x80)],
+                    description: String::from("key $41 pending"),
+                },
+            },
+        ];
+        assert_eq!(
+            "$0000:$7FFF RAM (32768 bytes)\n\
+            $FC00:$FC03 PIA offset $FC00 (key $41 pending)\n \
+            PA=$41 PACR=$80\n",
+            CursiveTui::format_devices(&devices)
+        );
+        Ok(())
+    }
 }
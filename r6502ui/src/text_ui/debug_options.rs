@@ -1,3 +1,4 @@
+use r6502core::sanitizer::SanitizerOptions;
 use std::path::PathBuf;
 
 pub struct DebugOptions {
@@ -5,4 +6,7 @@ pub struct DebugOptions {
     pub load: Option<u16>,
     pub start: Option<u16>,
     pub machine: Option<String>,
+    pub script: Option<PathBuf>,
+    pub post_mortem: bool,
+    pub sanitize: Option<SanitizerOptions>,
 }
@@ -39,7 +39,7 @@ impl ExportListInfo {
         let mut exports = map_file.exports.iter().collect::<Vec<_>>();
         match sort_order {
             ExportSortOrder::ByName => exports.sort_by(|a, b| a.name.cmp(&b.name)),
-            ExportSortOrder::ByValue => exports.sort_by(|a, b| a.value.cmp(&b.value)),
+            ExportSortOrder::ByValue => exports.sort_by_key(|e| e.value),
         }
 
         exports
@@ -0,0 +1,68 @@
+use crate::terminal_ui::{OutputRedirect, StepEngine, StopReason};
+use crate::text_ui::cursive_tui::CursiveTui;
+use crate::text_ui::tui_host::TuiHost;
+use crate::text_ui::tui_output::TuiOutput;
+use anyhow::{Result, anyhow};
+use r6502config::CharSet;
+use r6502core::emulator::{Bus, Cpu, IoEvent};
+use r6502core::symbols::{DebugInfo, MapFile};
+use std::sync::Arc;
+use std::sync::mpsc::{Sender, channel};
+use std::thread::spawn;
+
+// Opens the debugger on a machine that's already running in the terminal
+pub struct LiveDebugger {
+    pub map_file: Arc<MapFile>,
+    pub debug_info: Arc<DebugInfo>,
+    pub char_set: CharSet,
+    pub io_tx: Sender<IoEvent>,
+    pub output_redirect: OutputRedirect,
+}
+
+impl LiveDebugger {
+    // Debug until the user quits the debugger, showing the program's output in the debugger
+    // meanwhile, then hand back the engine and the halt or invalid BRK the program stopped at
+    pub fn debug(
+        &self,
+        cpu: &mut Cpu,
+        bus: &Bus,
+        engine: StepEngine,
+    ) -> Result<(StepEngine, Option<StopReason>)> {
+        let debug_channel = channel();
+        let monitor_channel = channel();
+        let io_channel = channel();
+        *self.output_redirect.lock().unwrap() = Some(Box::new(TuiOutput::new(io_channel.0)));
+
+        let io_tx = self.io_tx.clone();
+        let map_file = Arc::clone(&self.map_file);
+        let debug_info = Arc::clone(&self.debug_info);
+        let char_set = self.char_set;
+        let handle = spawn(move || {
+            CursiveTui::new(
+                monitor_channel.1,
+                io_channel.1,
+                &debug_channel.0,
+                &io_tx,
+                map_file,
+                debug_info,
+                char_set,
+            )
+            .run();
+        });
+
+        // Quitting the debugger disconnects the host which then hands the machine back
+        let host = TuiHost::new(
+            bus,
+            engine,
+            debug_channel.1,
+            monitor_channel.0,
+            Arc::clone(&self.debug_info),
+        );
+        let stop_reason = host.attach(cpu);
+        *self.output_redirect.lock().unwrap() = None;
+        handle
+            .join()
+            .map_err(|_| anyhow!("debugger thread panicked"))?;
+        Ok((host.into_engine(), stop_reason))
+    }
+}
@@ -0,0 +1,267 @@
+use cursive::event::{Event, EventResult, Key};
+use cursive::theme::{BaseColor, Color, ColorStyle, Effect};
+use cursive::view::CannotFocus;
+use cursive::{Printer, Vec2, View, direction::Direction};
+use r6502config::CharSet;
+use r6502core::emulator::char_set_util::to_display_char;
+use r6502core::messages::DebugMessage;
+use r6502lib::AddressRange;
+use std::sync::mpsc::Sender;
+
+const BYTES_PER_ROW: u16 = 16;
+const ROWS: u16 = 8;
+const PAGE_SIZE: u16 = BYTES_PER_ROW * ROWS;
+const MAX_START: u16 = 0xffff - PAGE_SIZE + 1;
+const HEX_COLUMN: usize = 6;
+const ASCII_COLUMN: usize = HEX_COLUMN + 3 * BYTES_PER_ROW as usize + 1;
+const CHANGED_COLOUR: Color = Color::Light(BaseColor::Red);
+
+// Persistent hex/ASCII view of a page of memory refreshed by the host at every stop
+pub struct MemoryView {
+    char_set: CharSet,
+    debug_tx: Sender<DebugMessage>,
+    start: u16,
+    cursor: u16,
+    bytes: Vec<u8>,
+    changed: Vec<bool>,
+    editing: bool,
+    high_nibble: Option<u8>,
+}
+
+impl MemoryView {
+    pub const fn new(char_set: CharSet, debug_tx: Sender<DebugMessage>) -> Self {
+        Self {
+            char_set,
+            debug_tx,
+            start: 0x0000,
+            cursor: 0x0000,
+            bytes: Vec::new(),
+            changed: Vec::new(),
+            editing: false,
+            high_nibble: None,
+        }
+    }
+
+    pub fn address_range(&self) -> AddressRange {
+        AddressRange::new(self.start, self.start + PAGE_SIZE - 1).expect("Must succeed")
+    }
+
+    // Ask the host to start sending snapshots of the visible range
+    pub fn watch(&self) {
+        _ = self
+            .debug_tx
+            .send(DebugMessage::WatchMemory(self.address_range()));
+    }
+
+    pub fn set_start(&mut self, addr: u16) {
+        self.cursor = addr;
+        self.scroll_to((addr / BYTES_PER_ROW * BYTES_PER_ROW).min(MAX_START));
+    }
+
+    pub fn update(&mut self, address_range: &AddressRange, snapshot: &[u8]) {
+        // Ignore stale snapshots of a range we've since scrolled away from
+        if address_range.start() != self.start {
+            return;
+        }
+
+        let mut changed = vec![false; snapshot.len()];
+        for (i, value) in snapshot.iter().enumerate() {
+            let addr = address_range.start() as usize + i;
+            if let Some(old_value) = self.get(addr) {
+                changed[i] = old_value != *value;
+            }
+        }
+        self.bytes = snapshot.to_vec();
+        self.changed = changed;
+    }
+
+    fn get(&self, addr: usize) -> Option<u8> {
+        let index = addr.checked_sub(self.start as usize)?;
+        self.bytes.get(index).copied()
+    }
+
+    fn scroll_to(&mut self, start: u16) {
+        if start != self.start {
+            self.start = start;
+            self.bytes.clear();
+            self.changed.clear();
+        }
+        self.watch();
+    }
+
+    fn move_cursor(&mut self, delta: i32) {
+        self.high_nibble = None;
+        self.cursor = u16::try_from((i32::from(self.cursor) + delta).clamp(0, 0xffff)).unwrap();
+        if self.cursor < self.start {
+            self.scroll_to(self.cursor / BYTES_PER_ROW * BYTES_PER_ROW);
+        } else if self.cursor > self.start + (PAGE_SIZE - 1) {
+            self.scroll_to((self.cursor / BYTES_PER_ROW + 1 - ROWS) * BYTES_PER_ROW);
+        }
+    }
+
+    fn edit(&mut self, digit: u8) {
+        match self.high_nibble.take() {
+            Some(high_nibble) => {
+                let value = (high_nibble << 4) | digit;
+                let index = (self.cursor - self.start) as usize;
+                if let Some(b) = self.bytes.get_mut(index) {
+                    *b = value;
+                }
+                _ = self
+                    .debug_tx
+                    .send(DebugMessage::SetMemory(self.cursor, vec![value]));
+                if self.cursor < 0xffff {
+                    self.move_cursor(1);
+                }
+            }
+            None => self.high_nibble = Some(digit),
+        }
+    }
+}
+
+impl View for MemoryView {
+    fn draw(&self, printer: &Printer) {
+        for row in 0..ROWS {
+            let y = row as usize;
+            let row_addr = self.start + row * BYTES_PER_ROW;
+            printer.print((0, y), &format!("{row_addr:04X}"));
+            for i in 0..BYTES_PER_ROW {
+                let addr = row_addr + i;
+                let index = (addr - self.start) as usize;
+                let x = HEX_COLUMN + 3 * i as usize;
+                let value = self.bytes.get(index).copied();
+                let (hex, ascii) = match value {
+                    Some(value) if addr == self.cursor && self.editing => (
+                        self.high_nibble
+                            .map_or_else(|| format!("{value:02X}"), |h| format!("{h:X}_")),
+                        to_display_char(&self.char_set, value),
+                    ),
+                    Some(value) => (
+                        format!("{value:02X}"),
+                        to_display_char(&self.char_set, value),
+                    ),
+                    None => (String::from("--"), ' '),
+                };
+                let ascii = ascii.to_string();
+                let style = if self.changed.get(index).copied().unwrap_or(false) {
+                    ColorStyle::front(CHANGED_COLOUR)
+                } else {
+                    ColorStyle::primary()
+                };
+                printer.with_color(style, |printer| {
+                    if addr == self.cursor && printer.focused {
+                        printer.with_effect(Effect::Reverse, |printer| {
+                            printer.print((x, y), &hex);
+                            printer.print((ASCII_COLUMN + i as usize, y), &ascii);
+                        });
+                    } else {
+                        printer.print((x, y), &hex);
+                        printer.print((ASCII_COLUMN + i as usize, y), &ascii);
+                    }
+                });
+            }
+        }
+    }
+
+    fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
+        Vec2::new(ASCII_COLUMN + BYTES_PER_ROW as usize, ROWS as usize)
+    }
+
+    fn take_focus(&mut self, _source: Direction) -> Result<EventResult, CannotFocus> {
+        Ok(EventResult::consumed())
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::Key(Key::Left) => self.move_cursor(-1),
+            Event::Key(Key::Right) => self.move_cursor(1),
+            Event::Key(Key::Up) => self.move_cursor(-i32::from(BYTES_PER_ROW)),
+            Event::Key(Key::Down) => self.move_cursor(i32::from(BYTES_PER_ROW)),
+            Event::Key(Key::PageUp) => self.move_cursor(-i32::from(PAGE_SIZE)),
+            Event::Key(Key::PageDown) => self.move_cursor(i32::from(PAGE_SIZE)),
+            Event::Key(Key::Enter) => {
+                self.editing = !self.editing;
+                self.high_nibble = None;
+            }
+            Event::Key(Key::Esc) if self.editing => {
+                self.editing = false;
+                self.high_nibble = None;
+            }
+            Event::Char(c) if self.editing && c.is_ascii_hexdigit() => {
+                self.edit(u8::try_from(c.to_digit(16).expect("Must be hex digit")).unwrap());
+            }
+            _ => return EventResult::Ignored,
+        }
+        EventResult::consumed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text_ui::memory_view::MemoryView;
+    use anyhow::Result;
+    use r6502config::CharSet;
+    use r6502core::messages::DebugMessage;
+    use r6502lib::AddressRange;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn update_highlights_changes() -> Result<()> {
+        let (debug_tx, _debug_rx) = channel();
+        let mut view = MemoryView::new(CharSet::Default, debug_tx);
+        view.set_start(0x0700);
+        let address_range = AddressRange::new(0x0700, 0x077f)?;
+        let mut bytes = vec![0x00; 0x80];
+        view.update(&address_range, &bytes);
+        assert!(view.changed.iter().all(|c| !c));
+
+        bytes[0x02] = 0x41;
+        view.update(&address_range, &bytes);
+        assert_eq!(Some(0x41), view.get(0x0702));
+        assert!(view.changed[0x02]);
+        assert_eq!(1, view.changed.iter().filter(|c| **c).count());
+        Ok(())
+    }
+
+    #[test]
+    fn move_cursor_scrolls() {
+        let (debug_tx, debug_rx) = channel();
+        let mut view = MemoryView::new(CharSet::Default, debug_tx);
+        view.set_start(0x0705);
+        assert_eq!(0x0700, view.start);
+        assert_eq!(0x0705, view.cursor);
+
+        view.move_cursor(0x80);
+        assert_eq!(0x0710, view.start);
+        assert_eq!(0x0785, view.cursor);
+
+        view.move_cursor(-0x1000);
+        assert_eq!(0x0000, view.start);
+        assert_eq!(0x0000, view.cursor);
+
+        let starts = debug_rx
+            .try_iter()
+            .map(|m| match m {
+                DebugMessage::WatchMemory(address_range) => address_range.start(),
+                _ => panic!("unexpected message"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0x0700, 0x0710, 0x0000], starts);
+    }
+
+    #[test]
+    fn edit_sends_byte() -> Result<()> {
+        let (debug_tx, debug_rx) = channel();
+        let mut view = MemoryView::new(CharSet::Default, debug_tx);
+        view.update(&AddressRange::new(0x0000, 0x007f)?, &[0x00; 0x80]);
+        view.edit(0x0a);
+        view.edit(0x05);
+        assert_eq!(Some(0xa5), view.get(0x0000));
+        assert_eq!(0x0001, view.cursor);
+        assert!(matches!(
+            debug_rx.try_recv()?,
+            DebugMessage::SetMemory(0x0000, bytes) if bytes == vec![0xa5]
+        ));
+        Ok(())
+    }
+}
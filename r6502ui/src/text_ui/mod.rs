@@ -1,10 +1,28 @@
+mod command_history;
+mod command_state;
+mod completion;
 mod cursive_tui;
 mod debug_options;
 mod export_list_info;
+mod live_debugger;
+mod memory_view;
+mod pending_command;
+mod run_post_mortem;
+mod run_script;
 mod run_tui;
+mod script_runner;
+mod session;
+mod source_cache;
+mod spawn_host;
 mod tui_host;
 mod tui_monitor;
+mod tui_output;
 
 pub use debug_options::*;
+pub use live_debugger::*;
+pub use run_post_mortem::*;
+pub use run_script::*;
 pub use run_tui::*;
+pub use spawn_host::*;
+pub use tui_host::*;
 pub use tui_monitor::*;
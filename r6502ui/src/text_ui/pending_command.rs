@@ -0,0 +1,17 @@
+use r6502core::asm::AsmLine;
+use r6502core::debugger::{Expr, WatchKind};
+
+// Command waiting for the host to evaluate its address expressions
+pub enum PendingCommand {
+    FetchMemory,
+    SetPc,
+    Go,
+    ViewMemory,
+    Assemble,
+    // Line waiting for the value of its operand
+    AssembleLine(AsmLine),
+    SetBreakpoint(Option<Expr>),
+    ClearBreakpoint,
+    SetWatchpoint(WatchKind),
+    ClearWatchpoint,
+}
@@ -0,0 +1,81 @@
+use crate::terminal_ui::PostMortem;
+use crate::text_ui::cursive_tui::CursiveTui;
+use crate::text_ui::debug_options::DebugOptions;
+use crate::text_ui::spawn_host::{HostOptions, spawn_host};
+use crate::text_ui::tui_output::TuiOutput;
+use anyhow::Result;
+use r6502core::debugger::CallStack;
+use r6502core::emulator::IoChannel;
+use r6502core::messages::IoMessage;
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502hw::MachineInfo;
+use r6502snapshot::MemoryImage;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+
+// Open a post-mortem bundle read-only in the text UI
+pub fn run_post_mortem(opts: &DebugOptions) -> Result<()> {
+    // Accept the report file as well as the bundle directory containing it
+    let dir = if opts.path.is_file() {
+        opts.path.parent().unwrap_or(&opts.path)
+    } else {
+        &opts.path
+    };
+    let post_mortem = PostMortem::read(dir)?;
+    let snapshot_path = PostMortem::snapshot_path(dir);
+    let image = MemoryImage::from_file(&snapshot_path)?;
+    let machine_info = match image.machine_tag() {
+        Some(tag) => MachineInfo::find_by_tag(tag)?,
+        None => MachineInfo::find_by_name(&opts.machine)?,
+    };
+
+    let mut map_file = MapFile::load(&snapshot_path)?;
+    let debug_info = Arc::new(DebugInfo::load_or_default(&snapshot_path));
+    map_file.merge_labels(&debug_info);
+    let char_set = machine_info.machine.char_set;
+
+    let io_channel = channel();
+
+    // Program output wasn't recorded so its pane shows the report instead
+    let mut report = format!("Post-mortem (read-only): {}\n", post_mortem.reason);
+    for bus_device in &post_mortem.bus_devices {
+        writeln!(
+            report,
+            "{:?} {} offset ${:04X}",
+            bus_device.r#type, bus_device.address_range, bus_device.offset
+        )?;
+    }
+    for ch in report.chars() {
+        io_channel.0.send(IoMessage::WriteChar(ch))?;
+    }
+
+    let input_channel = IoChannel::new();
+    let input_tx = input_channel.tx.clone();
+    let (_handle, debug_tx, monitor_rx) = spawn_host(
+        machine_info,
+        image,
+        HostOptions {
+            output: Box::new(TuiOutput::new(io_channel.0.clone())),
+            input: input_channel,
+            debug_info: Arc::clone(&debug_info),
+            sanitize: None,
+            post_mortem: Some((
+                CallStack::new(post_mortem.frames, post_mortem.issues),
+                post_mortem.history,
+            )),
+        },
+    );
+
+    let mut ui = CursiveTui::new(
+        monitor_rx,
+        io_channel.1,
+        &debug_tx,
+        &input_tx,
+        Arc::new(map_file),
+        debug_info,
+        char_set,
+    );
+    ui.run();
+    Ok(())
+}
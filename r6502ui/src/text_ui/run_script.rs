@@ -0,0 +1,78 @@
+use crate::terminal_ui::SimpleOutput;
+use crate::text_ui::debug_options::DebugOptions;
+use crate::text_ui::script_runner::ScriptRunner;
+use crate::text_ui::spawn_host::{HostOptions, spawn_host};
+use anyhow::{Result, anyhow};
+use r6502core::emulator::IoChannel;
+use r6502core::sanitizer::SanitizerOptions;
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502hw::MachineInfo;
+use r6502snapshot::MemoryImage;
+use std::fs::read_to_string;
+use std::io::{Write, stdout};
+use std::process::exit;
+use std::sync::Arc;
+
+pub fn run_script(opts: &DebugOptions) -> Result<()> {
+    let script_path = opts
+        .script
+        .as_ref()
+        .ok_or_else(|| anyhow!("script not specified"))?;
+    let script = read_to_string(script_path)?;
+
+    let image = MemoryImage::from_file(&opts.path)?;
+    let machine_info = match image.machine_tag() {
+        Some(tag) => MachineInfo::find_by_tag(tag)?,
+        None => MachineInfo::find_by_name(&opts.machine)?,
+    };
+
+    let mut map_file = MapFile::load(&opts.path)?;
+    let debug_info = Arc::new(DebugInfo::load_or_default(&opts.path));
+    map_file.merge_labels(&debug_info);
+
+    let failed = run_debug_script(
+        &script,
+        machine_info,
+        image,
+        &map_file,
+        &debug_info,
+        opts.sanitize.as_ref(),
+        &mut stdout(),
+    )?;
+    if failed > 0 {
+        exit(1)
+    }
+
+    Ok(())
+}
+
+// Run script against the image, writing results to out, and return the number of failed
+// assertions
+pub fn run_debug_script(
+    script: &str,
+    machine_info: MachineInfo,
+    image: MemoryImage,
+    map_file: &MapFile,
+    debug_info: &Arc<DebugInfo>,
+    sanitize: Option<&SanitizerOptions>,
+    out: &mut impl Write,
+) -> Result<usize> {
+    let (handle, debug_tx, monitor_rx) = spawn_host(
+        machine_info,
+        image,
+        HostOptions {
+            output: Box::new(SimpleOutput),
+            input: IoChannel::new(),
+            debug_info: Arc::clone(debug_info),
+            sanitize: sanitize.cloned(),
+            post_mortem: None,
+        },
+    );
+
+    // Dropping the runner disconnects the host which then stops
+    let result = ScriptRunner::new(debug_tx, monitor_rx, map_file, debug_info).run(script, out);
+    handle
+        .join()
+        .map_err(|_| anyhow!("host thread panicked"))??;
+    result
+}
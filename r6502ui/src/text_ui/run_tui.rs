@@ -1,36 +1,16 @@
 use crate::text_ui::cursive_tui::CursiveTui;
 use crate::text_ui::debug_options::DebugOptions;
-use crate::text_ui::tui_host::TuiHost;
+use crate::text_ui::session::Session;
+use crate::text_ui::spawn_host::{HostOptions, spawn_host};
+use crate::text_ui::tui_output::TuiOutput;
 use anyhow::Result;
-use r6502config::CharSet;
-use r6502core::InterruptChannel;
-use r6502core::emulator::char_set_util::translate_out;
-use r6502core::emulator::{IoChannel, OutputDevice};
-use r6502core::messages::IoMessage;
-use r6502core::symbols::MapFile;
+use log::warn;
+use r6502core::emulator::IoChannel;
+use r6502core::symbols::{DebugInfo, MapFile};
 use r6502hw::MachineInfo;
 use r6502snapshot::MemoryImage;
-use std::sync::mpsc::{Sender, channel};
-use std::thread::spawn;
-
-struct TuiOutput {
-    io_tx: Sender<IoMessage>,
-}
-
-impl TuiOutput {
-    const fn new(io_tx: Sender<IoMessage>) -> Self {
-        Self { io_tx }
-    }
-}
-
-impl OutputDevice for TuiOutput {
-    fn write(&mut self, char_set: &CharSet, value: u8) -> Result<()> {
-        if let Some(value) = translate_out(char_set, value) {
-            self.io_tx.send(IoMessage::WriteChar(value as char))?;
-        }
-        Ok(())
-    }
-}
+use std::sync::Arc;
+use std::sync::mpsc::channel;
 
 pub fn run_text_ui(opts: &DebugOptions) -> Result<()> {
     let image = MemoryImage::from_file(&opts.path)?;
@@ -39,41 +19,45 @@ pub fn run_text_ui(opts: &DebugOptions) -> Result<()> {
         None => MachineInfo::find_by_name(&opts.machine)?,
     };
 
-    let map_file = MapFile::load(&opts.path)?;
+    let mut map_file = MapFile::load(&opts.path)?;
+    let debug_info = Arc::new(DebugInfo::load_or_default(&opts.path));
+    map_file.merge_labels(&debug_info);
+    let char_set = machine_info.machine.char_set;
+    let session_path = Session::path(&opts.path)?;
 
-    let debug_channel = channel();
-    let monitor_channel = channel();
     let io_channel = channel();
-
-    let tui_output = TuiOutput::new(io_channel.0.clone());
-
     let input_channel = IoChannel::new();
     let input_tx = input_channel.tx.clone();
-
-    let interrupt_channel = InterruptChannel::new();
-
-    let _handle = spawn(move || {
-        let (bus, _) = machine_info
-            .create_bus(
-                Box::new(tui_output),
-                input_channel,
-                interrupt_channel.tx,
-                &image,
-            )
-            .expect("Must succeed");
-        bus.start();
-
-        TuiHost::new(machine_info, bus, debug_channel.1, monitor_channel.0).run(&image);
-    });
+    let (_handle, debug_tx, monitor_rx) = spawn_host(
+        machine_info,
+        image,
+        HostOptions {
+            output: Box::new(TuiOutput::new(io_channel.0.clone())),
+            input: input_channel,
+            debug_info: Arc::clone(&debug_info),
+            sanitize: opts.sanitize.clone(),
+            post_mortem: None,
+        },
+    );
 
     let mut ui = CursiveTui::new(
-        monitor_channel.1,
+        monitor_rx,
         io_channel.1,
-        &debug_channel.0,
+        &debug_tx,
         &input_tx,
-        map_file,
+        Arc::new(map_file),
+        debug_info,
+        char_set,
     );
+    match Session::load(&session_path) {
+        Ok(Some(session)) => ui.restore(session),
+        Ok(None) => {}
+        Err(e) => warn!("Could not load session {}: {e}", session_path.display()),
+    }
     ui.run();
+    if let Err(e) = ui.session().save(&session_path) {
+        warn!("Could not save session {}: {e}", session_path.display());
+    }
 
     // TBD: Signal to thread to shut down etc. by extending DebugMessage with a shutdown message
     //if handle.join().is_err() {
@@ -0,0 +1,264 @@
+use crate::text_ui::cursive_tui::CursiveTui;
+use anyhow::{Result, anyhow, bail};
+use r6502core::Reg;
+use r6502core::debugger::{BinaryOp, Expr};
+use r6502core::emulator::InstructionInfo;
+use r6502core::messages::{DebugMessage, MonitorMessage, ScriptCommand, State};
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502lib::AddressRange;
+use std::io::Write;
+use std::sync::mpsc::{Receiver, Sender};
+
+// Runs debugger commands from a script against TuiHost, printing results instead of showing them
+// in the text UI
+pub struct ScriptRunner<'a> {
+    debug_tx: Sender<DebugMessage>,
+    monitor_rx: Receiver<MonitorMessage>,
+    map_file: &'a MapFile,
+    debug_info: &'a DebugInfo,
+    state: State,
+    reg: Reg,
+    instruction_info: Option<InstructionInfo>,
+    stop_reason: Option<String>,
+    invalid_brk: bool,
+    memory: Option<Vec<u8>>,
+    passed: usize,
+    failed: usize,
+}
+
+impl<'a> ScriptRunner<'a> {
+    pub fn new(
+        debug_tx: Sender<DebugMessage>,
+        monitor_rx: Receiver<MonitorMessage>,
+        map_file: &'a MapFile,
+        debug_info: &'a DebugInfo,
+    ) -> Self {
+        Self {
+            debug_tx,
+            monitor_rx,
+            map_file,
+            debug_info,
+            state: State::Stepping,
+            reg: Reg::default(),
+            instruction_info: None,
+            stop_reason: None,
+            invalid_brk: false,
+            memory: None,
+            passed: 0,
+            failed: 0,
+        }
+    }
+
+    // Returns the number of failed assertions: other errors stop the script
+    pub fn run(&mut self, script: &str, out: &mut impl Write) -> Result<usize> {
+        // Wait for the host to stop at the first instruction
+        while !matches!(self.recv()?, MonitorMessage::BeforeExecute { .. }) {}
+
+        for (i, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            writeln!(out, "> {line}")?;
+            line.parse::<ScriptCommand>()
+                .and_then(|command| self.execute(command, out))
+                .map_err(|e| anyhow!("line {}: {e}", i + 1))?;
+        }
+
+        writeln!(
+            out,
+            "{} assertion(s) passed, {} failed",
+            self.passed, self.failed
+        )?;
+        Ok(self.failed)
+    }
+
+    fn execute(&mut self, command: ScriptCommand, out: &mut impl Write) -> Result<()> {
+        match command {
+            ScriptCommand::Break(location, condition) => {
+                let addr = CursiveTui::resolve_location(self.map_file, self.debug_info, &location)?;
+                let addr = Expr::to_addr(self.evaluate(vec![addr])?[0])?;
+                let condition = condition
+                    .map(|condition| CursiveTui::resolve_symbols(self.map_file, &condition))
+                    .transpose()?;
+                self.debug_tx
+                    .send(DebugMessage::AddBreakpoint(addr, condition))?;
+                writeln!(
+                    out,
+                    "Breakpoint set at {}",
+                    CursiveTui::format_breakpoint(self.map_file, self.debug_info, addr)
+                )?;
+            }
+            ScriptCommand::Run => {
+                self.check_not_halted()?;
+                self.debug_tx.send(DebugMessage::Run)?;
+                self.wait_for_run()?;
+                self.report_stop(out)?;
+            }
+            ScriptCommand::Step(count) => {
+                for _ in 0..count {
+                    self.check_not_halted()?;
+                    self.debug_tx.send(DebugMessage::Step)?;
+                    self.wait_for_step()?;
+                }
+                self.report_stop(out)?;
+            }
+            ScriptCommand::Memory(start, end) => {
+                let values = self.evaluate(vec![
+                    CursiveTui::resolve_symbols(self.map_file, &start)?,
+                    CursiveTui::resolve_symbols(self.map_file, &end)?,
+                ])?;
+                let address_range =
+                    AddressRange::new(Expr::to_addr(values[0])?, Expr::to_addr(values[1])?)?;
+                let bytes = self.read_memory(&address_range)?;
+                write!(
+                    out,
+                    "{}",
+                    CursiveTui::format_snapshot(&address_range, &bytes)
+                )?;
+            }
+            ScriptCommand::Registers => writeln!(out, "{}", self.reg)?,
+            ScriptCommand::Assert(expr) => self.check(&expr, out)?,
+        }
+        Ok(())
+    }
+
+    // Equality assertions report both sides on failure
+    fn check(&mut self, expr: &Expr, out: &mut impl Write) -> Result<()> {
+        let expr = CursiveTui::resolve_symbols(self.map_file, expr)?;
+        let failure = if let Expr::Binary(BinaryOp::Eq, actual, expected) = &expr {
+            let values = self.evaluate(vec![*actual.clone(), *expected.clone()])?;
+            (values[0] != values[1]).then(|| {
+                format!(
+                    "expected {}, got {}",
+                    Expr::Number(values[1]),
+                    Expr::Number(values[0])
+                )
+            })
+        } else {
+            (self.evaluate(vec![expr.clone()])?[0] == 0).then(|| format!("{expr} is false"))
+        };
+
+        if let Some(failure) = failure {
+            self.failed += 1;
+            writeln!(out, "FAIL: {failure}")?;
+        } else {
+            self.passed += 1;
+            writeln!(out, "PASS")?;
+        }
+        Ok(())
+    }
+
+    fn check_not_halted(&self) -> Result<()> {
+        if matches!(self.state, State::Halted) {
+            bail!("program halted")
+        }
+        Ok(())
+    }
+
+    fn report_stop(&mut self, out: &mut impl Write) -> Result<()> {
+        if matches!(self.state, State::Halted) {
+            if self.invalid_brk {
+                writeln!(out, "Halted: invalid BRK")?;
+            } else {
+                writeln!(out, "Halted")?;
+            }
+            return Ok(());
+        }
+
+        if let Some(stop_reason) = self.stop_reason.take() {
+            writeln!(out, "{stop_reason}")?;
+        }
+        if let Some(instruction_info) = &self.instruction_info {
+            writeln!(out, "{}", instruction_info.disassembly(self.map_file)?)?;
+        }
+        Ok(())
+    }
+
+    // Host reports the registers after executing the instruction then stops at the next one
+    fn wait_for_step(&mut self) -> Result<()> {
+        let mut executed = false;
+        loop {
+            match self.recv()? {
+                MonitorMessage::AfterExecute { .. } => executed = true,
+                MonitorMessage::BeforeExecute { .. } if executed => return Ok(()),
+                MonitorMessage::NotifyState(State::Halted) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    fn wait_for_run(&mut self) -> Result<()> {
+        let mut stopped = false;
+        loop {
+            match self.recv()? {
+                MonitorMessage::NotifyState(State::Stepping) => stopped = true,
+                MonitorMessage::BeforeExecute { .. } if stopped => return Ok(()),
+                MonitorMessage::NotifyState(State::Halted) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    fn read_memory(&mut self, address_range: &AddressRange) -> Result<Vec<u8>> {
+        self.memory = None;
+        self.debug_tx
+            .send(DebugMessage::FetchMemory(address_range.clone()))?;
+        loop {
+            _ = self.recv()?;
+            if let Some(bytes) = self.memory.take() {
+                return Ok(bytes);
+            }
+        }
+    }
+
+    fn evaluate(&mut self, exprs: Vec<Expr>) -> Result<Vec<i32>> {
+        self.debug_tx.send(DebugMessage::Evaluate(exprs))?;
+        loop {
+            if let MonitorMessage::EvaluateResponse(values) = self.recv()? {
+                return values;
+            }
+        }
+    }
+
+    fn recv(&mut self) -> Result<MonitorMessage> {
+        let message = self
+            .monitor_rx
+            .recv()
+            .map_err(|_| anyhow!("host stopped"))?;
+        match &message {
+            MonitorMessage::NotifyState(state) => self.state = *state,
+            MonitorMessage::NotifyInvalidBrk => self.invalid_brk = true,
+            MonitorMessage::BeforeExecute {
+                reg,
+                instruction_info,
+                ..
+            } => {
+                self.reg = reg.clone();
+                self.instruction_info = Some(instruction_info.clone());
+            }
+            MonitorMessage::AfterExecute { reg, .. } => self.reg = reg.clone(),
+            MonitorMessage::FetchMemoryResponse { snapshot, .. } => {
+                self.memory = Some(snapshot.clone());
+            }
+            MonitorMessage::NotifyBreakpoint(addr) => {
+                self.stop_reason = Some(format!(
+                    "Breakpoint hit at {}",
+                    CursiveTui::format_breakpoint(self.map_file, self.debug_info, *addr)
+                ));
+            }
+            MonitorMessage::NotifyWatchpoint(hit) => {
+                self.stop_reason = Some(format!(
+                    "Watchpoint ({}) hit at ${:04X}",
+                    hit.kind, hit.addr
+                ));
+            }
+            MonitorMessage::NotifySanitizer(issue) => {
+                self.stop_reason = Some(format!("Sanitizer: {}", issue.describe(self.map_file)));
+            }
+            _ => {}
+        }
+        Ok(message)
+    }
+}
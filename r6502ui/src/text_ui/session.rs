@@ -0,0 +1,200 @@
+use anyhow::{Error, Result, anyhow};
+use dirs::{cache_dir, state_dir};
+use path_absolutize::Absolutize;
+use r6502core::debugger::{Expr, WatchExpr, WatchKind, Watchpoint};
+use r6502lib::AddressRange;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+// Debugger working state saved per image so that it can be restored next time the image is debugged
+#[derive(Debug, Default, PartialEq)]
+pub struct Session {
+    pub breakpoints: Vec<(u16, Option<Expr>)>,
+    pub watchpoints: Vec<Watchpoint>,
+    pub watches: Vec<WatchExpr>,
+    pub memory_start: u16,
+    pub history: Vec<String>,
+}
+
+impl Session {
+    // Sessions live in the per-user state directory, or the cache directory on platforms without
+    // one, since they're not configuration
+    pub fn path(image_path: &Path) -> Result<PathBuf> {
+        let dir = state_dir()
+            .or_else(cache_dir)
+            .ok_or_else(|| anyhow!("could not get state directory"))?;
+        Self::path_in(&dir.join("r6502").join("sessions"), image_path)
+    }
+
+    // Named after the image's absolute path with separators and the escape character itself
+    // escaped so that different images can't share a session
+    fn path_in(dir: &Path, image_path: &Path) -> Result<PathBuf> {
+        let mut file_name = String::new();
+        for c in image_path.absolutize()?.to_string_lossy().chars() {
+            match c {
+                '%' | '/' | '\\' | ':' => _ = write!(file_name, "%{:02X}", u32::from(c)),
+                _ => file_name.push(c),
+            }
+        }
+        Ok(dir.join(format!("{file_name}.json")))
+    }
+
+    // Missing session file is fine: the image hasn't been debugged before
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let file = serde_json::from_str::<SessionFile>(&read_to_string(path)?)?;
+        Ok(Some(Self {
+            breakpoints: file
+                .breakpoints
+                .into_iter()
+                .map(|breakpoint| (breakpoint.addr, breakpoint.condition))
+                .collect(),
+            watchpoints: file
+                .watchpoints
+                .into_iter()
+                .map(Watchpoint::try_from)
+                .collect::<Result<_>>()?,
+            watches: file.watches,
+            memory_start: file.memory_start,
+            history: file.history,
+        }))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+
+        let file = SessionFile {
+            breakpoints: self
+                .breakpoints
+                .iter()
+                .map(|(addr, condition)| SessionBreakpoint {
+                    addr: *addr,
+                    condition: condition.clone(),
+                })
+                .collect(),
+            watchpoints: self
+                .watchpoints
+                .iter()
+                .map(SessionWatchpoint::from)
+                .collect(),
+            watches: self.watches.clone(),
+            memory_start: self.memory_start,
+            history: self.history.clone(),
+        };
+        write(path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+}
+
+// Layout of the session file: sessions saved before the watch pane existed have no watches
+#[derive(Deserialize, Serialize)]
+struct SessionFile {
+    breakpoints: Vec<SessionBreakpoint>,
+    watchpoints: Vec<SessionWatchpoint>,
+    #[serde(with = "r6502config::as_string::vec", default)]
+    watches: Vec<WatchExpr>,
+    #[serde(rename = "memoryStart")]
+    memory_start: u16,
+    history: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SessionBreakpoint {
+    #[serde(rename = "address")]
+    addr: u16,
+    #[serde(with = "r6502config::as_string::option", default)]
+    condition: Option<Expr>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SessionWatchpoint {
+    #[serde(rename = "addressRange", with = "r6502config::as_string")]
+    address_range: AddressRange,
+    kind: String,
+}
+
+impl From<&Watchpoint> for SessionWatchpoint {
+    fn from(value: &Watchpoint) -> Self {
+        Self {
+            address_range: value.address_range.clone(),
+            kind: value.kind.to_string(),
+        }
+    }
+}
+
+impl TryFrom<SessionWatchpoint> for Watchpoint {
+    type Error = Error;
+
+    fn try_from(value: SessionWatchpoint) -> Result<Self> {
+        Ok(Self {
+            address_range: value.address_range,
+            kind: match value.kind.as_str() {
+                "read" => WatchKind::Read,
+                "write" => WatchKind::Write,
+                "access" => WatchKind::Access,
+                kind => return Err(anyhow!("invalid watchpoint kind {kind}")),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text_ui::session::Session;
+    use anyhow::Result;
+    use r6502core::debugger::{WatchKind, Watchpoint};
+    use r6502lib::AddressRange;
+    use rstest::rstest;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+    use std::path::Path;
+    use std::process;
+
+    // Paths that used to share a session once separators were replaced no longer do
+    #[rstest]
+    #[case("/sessions/%2Fhome%2Fuser%2Ffoo.r6502.json", "/home/user/foo.r6502")]
+    #[case("/sessions/%2Fhome%2Fuser_foo.r6502.json", "/home/user_foo.r6502")]
+    #[case("/sessions/%2Fhome%2Fuser%2Fa%25b.r6502.json", "/home/user/a%b.r6502")]
+    #[case(
+        "/sessions/%2Fhome%2Fuser%2Ffoo.r6502.json",
+        "/home/user/../user/foo.r6502"
+    )]
+    fn path(#[case] expected_result: &str, #[case] image_path: &str) -> Result<()> {
+        assert_eq!(
+            Path::new(expected_result),
+            Session::path_in(Path::new("/sessions"), Path::new(image_path))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let session = Session {
+            breakpoints: vec![(0x0e00, None), (0xffee, Some("A = 13".parse()?))],
+            watchpoints: vec![Watchpoint {
+                address_range: AddressRange::new(0x0070, 0x0071)?,
+                kind: WatchKind::Access,
+            }],
+            watches: vec!["{$12}".parse()?, "buffer crstring".parse()?],
+            memory_start: 0x0700,
+            history: vec![String::from("bp main"), String::from("m $e00:$eff")],
+        };
+
+        let dir = temp_dir().join(format!("r6502-session-{}", process::id()));
+        let path = Session::path_in(&dir, Path::new("/home/user/foo.r6502"))?;
+        assert!(Session::load(&path)?.is_none());
+        session.save(&path)?;
+        let result = Session::load(&path);
+        remove_dir_all(&dir)?;
+
+        assert_eq!(Some(session), result?);
+        Ok(())
+    }
+}
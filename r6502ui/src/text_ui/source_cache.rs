@@ -0,0 +1,83 @@
+use r6502core::symbols::DebugInfo;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs::read_to_string;
+use std::sync::Arc;
+
+const CONTEXT_LINES: usize = 2;
+
+// Source files referenced by debug info, read on first use
+pub struct SourceCache {
+    debug_info: Arc<DebugInfo>,
+    files: HashMap<usize, Option<Vec<String>>>,
+}
+
+impl SourceCache {
+    pub fn new(debug_info: Arc<DebugInfo>) -> Self {
+        Self {
+            debug_info,
+            files: HashMap::new(),
+        }
+    }
+
+    // Source around the line containing address with the current line marked
+    pub fn format(&mut self, addr: u16) -> String {
+        let debug_info = Arc::clone(&self.debug_info);
+        if debug_info.lines.is_empty() {
+            return String::from("(no debug info)");
+        }
+
+        let Some(line) = debug_info.find_line(addr) else {
+            return format!("(no source for ${addr:04X})");
+        };
+
+        let file = debug_info.file(line);
+        let location = format!("{}:{}", file.name, line.line);
+        match self.lines(file.id) {
+            Some(lines) => format_source(&location, lines, line.line),
+            None => format!("{location}\n(source not found)"),
+        }
+    }
+
+    fn lines(&mut self, file_id: usize) -> Option<&[String]> {
+        let debug_info = &self.debug_info;
+        self.files
+            .entry(file_id)
+            .or_insert_with(|| {
+                let path = debug_info.source_path(&debug_info.files[file_id]);
+                read_to_string(path)
+                    .ok()
+                    .map(|s| s.lines().map(String::from).collect())
+            })
+            .as_deref()
+    }
+}
+
+fn format_source(location: &str, lines: &[String], line: usize) -> String {
+    let mut s = format!("{location}\n");
+    let first = line.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (line + CONTEXT_LINES).min(lines.len());
+    for n in first..=last {
+        let marker = if n == line { '>' } else { ' ' };
+        _ = writeln!(s, "{marker}{n:5} {}", lines[n - 1]);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text_ui::source_cache::format_source;
+
+    #[test]
+    fn format_source_basics() {
+        let lines = (1..=10).map(|i| format!("line {i}")).collect::<Vec<_>>();
+        assert_eq!(
+            "main.s:2\n     1 line 1\n>    2 line 2\n     3 line 3\n     4 line 4\n",
+            format_source("main.s:2", &lines, 2)
+        );
+        assert_eq!(
+            "main.s:10\n     8 line 8\n     9 line 9\n>   10 line 10\n",
+            format_source("main.s:10", &lines, 10)
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::terminal_ui::{HistoryEntry, StepEngine};
+use crate::text_ui::TuiHost;
+use anyhow::Result;
+use r6502core::InterruptChannel;
+use r6502core::debugger::CallStack;
+use r6502core::emulator::{IoChannel, OutputDevice};
+use r6502core::messages::{DebugMessage, MonitorMessage};
+use r6502core::sanitizer::SanitizerOptions;
+use r6502core::symbols::DebugInfo;
+use r6502hw::MachineInfo;
+use r6502snapshot::MemoryImage;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread::{JoinHandle, spawn};
+
+// How the host started by spawn_host is wired up to its front-end
+pub struct HostOptions {
+    pub output: Box<dyn OutputDevice>,
+    // Front-end keeps a sender for this if it passes keyboard input to the program
+    pub input: IoChannel,
+    pub debug_info: Arc<DebugInfo>,
+    pub sanitize: Option<SanitizerOptions>,
+    // Examine a machine restored from a post-mortem bundle instead of running it
+    pub post_mortem: Option<(CallStack, Vec<HistoryEntry>)>,
+}
+
+// Create the machine for the image on a thread of its own and debug it on behalf of a front-end:
+// dropping the sender stops the host
+#[must_use]
+pub fn spawn_host(
+    machine_info: MachineInfo,
+    image: MemoryImage,
+    options: HostOptions,
+) -> (
+    JoinHandle<Result<()>>,
+    Sender<DebugMessage>,
+    Receiver<MonitorMessage>,
+) {
+    let (debug_tx, debug_rx) = channel();
+    let (monitor_tx, monitor_rx) = channel();
+    let handle = spawn(move || -> Result<()> {
+        let interrupt_channel = InterruptChannel::new();
+        let (bus, bus_rx) =
+            machine_info.create_bus(options.output, options.input, interrupt_channel.tx, &image)?;
+        bus.start();
+
+        let host = TuiHost::new(
+            &bus,
+            StepEngine::new(&machine_info, bus_rx),
+            debug_rx,
+            monitor_tx,
+            options.debug_info,
+        )
+        .with_sanitizer(options.sanitize.as_ref(), &image);
+        match options.post_mortem {
+            Some((call_stack, history)) => host.post_mortem(&image, call_stack, &history),
+            None => host.run(&image, interrupt_channel.rx),
+        }
+        Ok(())
+    });
+    (handle, debug_tx, monitor_rx)
+}
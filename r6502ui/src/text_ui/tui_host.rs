@@ -1,57 +1,178 @@
+use crate::terminal_ui::{HistoryEntry, StepEngine, StopReason};
 use crate::text_ui::TuiMonitor;
-use r6502core::emulator::{Bus, Cpu, InstructionInfo};
+use anyhow::Result;
+use log::error;
+use r6502core::debugger::{CallStack, Expr, WatchExpr, WatchHit, Watchpoints};
+use r6502core::emulator::{Bus, BusMonitor, BusView, Cpu, InstructionInfo};
 use r6502core::messages::State::{Halted, Running, Stepping, Stopped};
 use r6502core::messages::{DebugMessage, MonitorMessage, State};
-use r6502core::{InterruptChannel, p_get, p_set};
-use r6502hw::MachineInfo;
-use r6502lib::AddressRange;
-use r6502lib::constants::RESET;
+use r6502core::sanitizer::{RuntimeSanitizer, SanitizerOptions};
+use r6502core::symbols::DebugInfo;
+use r6502core::{InterruptEvent, Opcode, p_get, p_set};
+use r6502lib::constants::{RESET, STACK_BASE};
 use r6502lib::util::make_word;
+use r6502lib::{AddressRange, TotalCycles};
 use r6502snapshot::MemoryImage;
-use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
+
+enum StopCondition {
+    // Stop on reaching a different line with debug info, skipping over calls deeper than max_depth
+    SourceLineChanged {
+        start_line: Option<usize>,
+        max_depth: Option<usize>,
+    },
+    // Stop once the call stack is shallower than the given depth
+    Return(usize),
+}
+
+// IRQ line held by the debugger
+#[derive(Clone, Copy)]
+enum IrqLine {
+    Released,
+    UntilTaken,
+    Until(TotalCycles),
+}
 
 // TBD: Come up with a better name for this struct!
-pub struct TuiHost {
-    machine_info: MachineInfo,
-    bus: Bus,
+pub struct TuiHost<'a> {
+    bus: &'a Bus,
+    engine: RefCell<StepEngine>,
+    // Halt or invalid BRK the program is currently stopped at
+    last_stop: RefCell<Option<StopReason>>,
+    // Machine may be examined but not executed or modified e.g. when restored from a post-mortem
+    read_only: Cell<bool>,
     debug_rx: Receiver<DebugMessage>,
     monitor_tx: Sender<MonitorMessage>,
+    debug_info: Arc<DebugInfo>,
+    memory_view: RefCell<Option<AddressRange>>,
+    watches: RefCell<Vec<WatchExpr>>,
+    call_stack: RefCell<CallStack>,
+    breakpoints: RefCell<HashMap<u16, Option<Expr>>>,
+    watchpoints: Watchpoints,
+    stop_condition: RefCell<Option<StopCondition>>,
+    irq_line: Cell<IrqLine>,
+    stop_on_interrupt: Cell<bool>,
+    sanitizer: Option<RuntimeSanitizer>,
 }
 
-impl TuiHost {
-    pub const fn new(
-        machine_info: MachineInfo,
-        bus: Bus,
+impl<'a> TuiHost<'a> {
+    #[must_use]
+    pub fn new(
+        bus: &'a Bus,
+        engine: StepEngine,
         debug_rx: Receiver<DebugMessage>,
         monitor_tx: Sender<MonitorMessage>,
+        debug_info: Arc<DebugInfo>,
     ) -> Self {
         Self {
-            machine_info,
             bus,
+            engine: RefCell::new(engine),
+            last_stop: RefCell::new(None),
+            read_only: Cell::new(false),
             debug_rx,
             monitor_tx,
+            debug_info,
+            memory_view: RefCell::new(None),
+            watches: RefCell::new(Vec::new()),
+            call_stack: RefCell::new(CallStack::default()),
+            breakpoints: RefCell::new(HashMap::new()),
+            watchpoints: Watchpoints::default(),
+            stop_condition: RefCell::new(None),
+            irq_line: Cell::new(IrqLine::Released),
+            stop_on_interrupt: Cell::new(false),
+            sanitizer: None,
         }
     }
 
-    pub fn run(&self, image: &MemoryImage) {
-        let monitor = Box::new(TuiMonitor::new(self.monitor_tx.clone()));
-        let interrupt_channel = InterruptChannel::new();
+    // Check the program's memory accesses and stack usage, treating the image as initialized
+    #[must_use]
+    pub fn with_sanitizer(
+        mut self,
+        options: Option<&SanitizerOptions>,
+        image: &MemoryImage,
+    ) -> Self {
+        self.sanitizer = options.map(|options| {
+            let sanitizer = RuntimeSanitizer::new(options.clone(), &self.bus.devices());
+            sanitizer.mark_image(image);
+            sanitizer
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn into_engine(self) -> StepEngine {
+        self.engine.into_inner()
+    }
 
-        let mut cpu = Cpu::new(self.bus.view(), Some(monitor), interrupt_channel.rx);
+    pub fn run(&self, image: &MemoryImage, interrupt_rx: Receiver<InterruptEvent>) {
+        let mut cpu = self.create_cpu(interrupt_rx);
         let reset_addr_lo = cpu.bus.load(RESET);
         let reset_addr_hi = cpu.bus.load(RESET.wrapping_add(1));
         let reset_addr = make_word(reset_addr_hi, reset_addr_lo);
         let cpu_state = image.get_initial_cpu_state(reset_addr);
         cpu.set_initial_state(&cpu_state);
+        self.debug(&mut cpu);
+    }
+
+    // Debug a CPU that was already running until the front-end disconnects, then hand the machine
+    // back along with the halt or invalid BRK it stopped at, if any
+    pub fn attach(&self, cpu: &mut Cpu) -> Option<StopReason> {
+        let mut debug_cpu = self.create_cpu(cpu.replace_irq_rx(channel().1));
+        debug_cpu.reg = cpu.reg.clone();
+        debug_cpu.total_cycles = cpu.total_cycles;
+        self.debug(&mut debug_cpu);
+
+        cpu.reg = debug_cpu.reg.clone();
+        cpu.total_cycles = debug_cpu.total_cycles;
+        _ = cpu.replace_irq_rx(debug_cpu.replace_irq_rx(channel().1));
+        self.last_stop.take()
+    }
 
+    // Examine the machine restored from a post-mortem bundle along with its call stack at the time,
+    // showing the instructions leading up to the stop as if they had just been executed
+    pub fn post_mortem(
+        &self,
+        image: &MemoryImage,
+        call_stack: CallStack,
+        history: &[HistoryEntry],
+    ) {
+        for entry in history {
+            _ = self.monitor_tx.send(MonitorMessage::AfterExecute {
+                total_cycles: entry.total_cycles,
+                reg: entry.reg.clone(),
+                instruction_info: entry.instruction_info.clone(),
+            });
+        }
+        self.read_only.set(true);
+        *self.call_stack.borrow_mut() = call_stack;
+        self.run(image, channel().1);
+    }
+
+    // Watch the CPU's memory accesses and report each instruction to the front-end
+    fn create_cpu(&self, interrupt_rx: Receiver<InterruptEvent>) -> Cpu<'_> {
+        let monitor = Box::new(TuiMonitor::new(self.monitor_tx.clone()));
+        Cpu::new(
+            BusView::with_monitor(self.bus, self),
+            Some(monitor),
+            interrupt_rx,
+        )
+    }
+
+    fn debug(&self, cpu: &mut Cpu) {
         let mut state = Stepping;
         loop {
             self.send_state(state);
 
             match state {
-                Running => state = self.handle_running(&mut cpu),
-                Stepping => state = self.handle_stepping(&mut cpu),
-                Halted => state = self.handle_halted(&mut cpu),
+                Running => {
+                    state = self.handle_running(cpu);
+                    *self.stop_condition.borrow_mut() = None;
+                }
+                Stepping => state = self.handle_stepping(cpu),
+                Halted => state = self.handle_halted(cpu),
                 Stopped => break,
             }
         }
@@ -70,30 +191,83 @@ impl TuiHost {
         });
     }
 
-    fn handle_brk(&self) -> State {
-        _ = self.monitor_tx.send(MonitorMessage::NotifyInvalidBrk);
-        Halted
+    // Translate the reason the engine stopped into the state to continue in
+    fn handle_stop_reason(&self, stop_reason: Result<Option<StopReason>>) -> Option<State> {
+        match stop_reason {
+            Ok(None) => None,
+            Ok(Some(stop_reason @ StopReason::UnexpectedInterrupt { .. })) => {
+                _ = self.monitor_tx.send(MonitorMessage::NotifyInvalidBrk);
+                *self.last_stop.borrow_mut() = Some(stop_reason);
+                Some(Halted)
+            }
+            Ok(Some(StopReason::UserBreak { .. } | StopReason::RequestedCyclesExecuted { .. })) => {
+                Some(Stepping)
+            }
+            Ok(Some(stop_reason @ StopReason::Halt { .. })) => {
+                *self.last_stop.borrow_mut() = Some(stop_reason);
+                Some(Halted)
+            }
+            Err(e) => {
+                error!("{e}");
+                Some(Halted)
+            }
+        }
     }
 
     fn handle_running(&self, cpu: &mut Cpu) -> State {
         loop {
             match self.debug_rx.try_recv() {
                 Err(TryRecvError::Disconnected) => return Stopped,
+                Ok(DebugMessage::Break) => return Stepping,
+                Ok(DebugMessage::WatchMemory(address_range)) => {
+                    *self.memory_view.borrow_mut() = Some(address_range);
+                }
+                Ok(DebugMessage::SetMemory(addr, bytes)) => Self::set_memory(cpu, addr, &bytes),
+                Ok(DebugMessage::AddBreakpoint(addr, condition)) => {
+                    _ = self.breakpoints.borrow_mut().insert(addr, condition);
+                }
+                Ok(DebugMessage::RemoveBreakpoint(addr)) => {
+                    _ = self.breakpoints.borrow_mut().remove(&addr);
+                }
+                Ok(DebugMessage::AddWatchpoint(watchpoint)) => self.watchpoints.add(watchpoint),
+                Ok(DebugMessage::RemoveWatchpoint(watchpoint)) => {
+                    self.watchpoints.remove(&watchpoint);
+                }
+                Ok(DebugMessage::Evaluate(exprs)) => self.evaluate(cpu, &exprs),
+                Ok(DebugMessage::SetWatches(watches)) => *self.watches.borrow_mut() = watches,
+                Ok(DebugMessage::Interrupt(interrupt)) => self.take_interrupt(cpu, interrupt),
+                Ok(DebugMessage::AssertIrq(cycles)) => self.assert_irq(cpu, cycles),
+                Ok(DebugMessage::StopOnInterrupt(value)) => self.stop_on_interrupt.set(value),
                 Err(TryRecvError::Empty) | Ok(_) => {}
             }
 
-            cpu.step();
-            if p_get!(cpu.reg, I) {
-                let new_state = self.handle_brk();
-                if !matches!(new_state, Stepping) {
-                    return new_state;
-                }
+            if self.service_interrupts(cpu) && self.stop_on_interrupt.get() {
+                return Stepping;
             }
 
-            if let Some(halt_addr) = self.machine_info.machine.halt_addr {
-                if cpu.reg.pc == halt_addr {
-                    return Halted;
-                }
+            let (hit, stop_reason) = self.step_cpu(cpu, false);
+            if let Some(new_state) = self.handle_stop_reason(stop_reason) {
+                return new_state;
+            }
+
+            if self.is_breakpoint_hit(cpu) {
+                _ = self
+                    .monitor_tx
+                    .send(MonitorMessage::NotifyBreakpoint(cpu.reg.pc));
+                return Stepping;
+            }
+
+            if let Some(hit) = hit {
+                _ = self.monitor_tx.send(MonitorMessage::NotifyWatchpoint(hit));
+                return Stepping;
+            }
+
+            if self.send_sanitizer_issues() {
+                return Stepping;
+            }
+
+            if self.is_stop_condition_met(cpu) {
+                return Stepping;
             }
         }
     }
@@ -101,55 +275,339 @@ impl TuiHost {
     fn handle_stepping(&self, cpu: &mut Cpu) -> State {
         loop {
             self.fetch_instruction(cpu);
+            self.send_memory_view();
+            self.send_watches(cpu);
+            self.send_devices();
+            self.send_call_stack(cpu);
 
             match self.debug_rx.recv() {
                 Err(_) => return Stopped,
+                Ok(m) if self.read_only.get() && Self::modifies_machine(&m) => continue,
                 Ok(m) => match m {
-                    DebugMessage::Step | DebugMessage::Break => {}
+                    DebugMessage::Step => {}
+                    DebugMessage::Break => continue,
                     DebugMessage::Run => return Running,
-                    DebugMessage::FetchMemory(address_range) => self.fetch_memory(&address_range),
+                    DebugMessage::StepLine | DebugMessage::StepOver | DebugMessage::StepOut => {
+                        if let Some(stop_condition) = self.stop_condition_for(cpu, &m) {
+                            *self.stop_condition.borrow_mut() = Some(stop_condition);
+                            return Running;
+                        }
+                    }
+                    DebugMessage::FetchMemory(address_range) => {
+                        self.fetch_memory(&address_range);
+                        continue;
+                    }
                     DebugMessage::SetPc(addr) => self.set_pc(cpu, addr),
                     DebugMessage::Go(addr) => {
                         p_set!(cpu.reg, B, false);
                         self.set_pc(cpu, addr);
                     }
+                    DebugMessage::WatchMemory(address_range) => {
+                        self.watch_memory(address_range);
+                        continue;
+                    }
+                    DebugMessage::SetMemory(addr, bytes) => {
+                        Self::set_memory(cpu, addr, &bytes);
+                        continue;
+                    }
+                    DebugMessage::AddBreakpoint(addr, condition) => {
+                        _ = self.breakpoints.borrow_mut().insert(addr, condition);
+                        continue;
+                    }
+                    DebugMessage::RemoveBreakpoint(addr) => {
+                        _ = self.breakpoints.borrow_mut().remove(&addr);
+                        continue;
+                    }
+                    DebugMessage::SetRegisters(reg) => {
+                        cpu.reg = reg;
+                        continue;
+                    }
+                    DebugMessage::AddWatchpoint(watchpoint) => {
+                        self.watchpoints.add(watchpoint);
+                        continue;
+                    }
+                    DebugMessage::RemoveWatchpoint(watchpoint) => {
+                        self.watchpoints.remove(&watchpoint);
+                        continue;
+                    }
+                    DebugMessage::Evaluate(exprs) => {
+                        self.evaluate(cpu, &exprs);
+                        continue;
+                    }
+                    DebugMessage::SetWatches(watches) => {
+                        self.set_watches(cpu, watches);
+                        continue;
+                    }
+                    DebugMessage::Interrupt(interrupt) => {
+                        self.take_interrupt(cpu, interrupt);
+                        continue;
+                    }
+                    DebugMessage::AssertIrq(cycles) => {
+                        self.assert_irq(cpu, cycles);
+                        _ = self.service_interrupts(cpu);
+                        continue;
+                    }
+                    DebugMessage::StopOnInterrupt(value) => {
+                        self.stop_on_interrupt.set(value);
+                        continue;
+                    }
                 },
             }
 
-            cpu.step_with_monitor_callbacks();
-            if p_get!(cpu.reg, I) {
-                let new_state = self.handle_brk();
-                if !matches!(new_state, Stepping) {
-                    return new_state;
-                }
+            if self.service_interrupts(cpu) && self.stop_on_interrupt.get() {
+                continue;
             }
 
-            if let Some(halt_addr) = self.machine_info.machine.halt_addr {
-                if cpu.reg.pc == halt_addr {
-                    return Halted;
-                }
+            let (hit, stop_reason) = self.step_cpu(cpu, true);
+            if let Some(hit) = hit {
+                _ = self.monitor_tx.send(MonitorMessage::NotifyWatchpoint(hit));
+            }
+            _ = self.send_sanitizer_issues();
+            if let Some(new_state) = self.handle_stop_reason(stop_reason)
+                && !matches!(new_state, Stepping)
+            {
+                return new_state;
             }
         }
     }
 
     fn handle_halted(&self, cpu: &mut Cpu) -> State {
+        self.send_memory_view();
+        self.send_watches(cpu);
+        self.send_devices();
+        self.send_call_stack(cpu);
         loop {
             match self.debug_rx.recv() {
                 Err(_) => return Stopped,
                 Ok(m) => match m {
-                    DebugMessage::Step | DebugMessage::Run | DebugMessage::Break => {}
+                    DebugMessage::Step
+                    | DebugMessage::Run
+                    | DebugMessage::Break
+                    | DebugMessage::StepLine
+                    | DebugMessage::StepOver
+                    | DebugMessage::StepOut => {}
                     DebugMessage::FetchMemory(address_range) => self.fetch_memory(&address_range),
                     DebugMessage::SetPc(addr) => self.set_pc(cpu, addr),
                     DebugMessage::Go(addr) => {
                         p_set!(cpu.reg, B, false);
                         self.set_pc(cpu, addr);
+                        *self.last_stop.borrow_mut() = None;
                         return Stepping;
                     }
+                    DebugMessage::WatchMemory(address_range) => self.watch_memory(address_range),
+                    DebugMessage::SetMemory(addr, bytes) => {
+                        Self::set_memory(cpu, addr, &bytes);
+                        self.send_memory_view();
+                    }
+                    DebugMessage::AddBreakpoint(addr, condition) => {
+                        _ = self.breakpoints.borrow_mut().insert(addr, condition);
+                    }
+                    DebugMessage::RemoveBreakpoint(addr) => {
+                        _ = self.breakpoints.borrow_mut().remove(&addr);
+                    }
+                    DebugMessage::SetRegisters(reg) => cpu.reg = reg,
+                    DebugMessage::AddWatchpoint(watchpoint) => self.watchpoints.add(watchpoint),
+                    DebugMessage::RemoveWatchpoint(watchpoint) => {
+                        self.watchpoints.remove(&watchpoint);
+                    }
+                    DebugMessage::Evaluate(exprs) => self.evaluate(cpu, &exprs),
+                    DebugMessage::SetWatches(watches) => self.set_watches(cpu, watches),
+                    DebugMessage::Interrupt(interrupt) => {
+                        self.take_interrupt(cpu, interrupt);
+                        *self.last_stop.borrow_mut() = None;
+                        return Stepping;
+                    }
+                    DebugMessage::AssertIrq(cycles) => {
+                        self.assert_irq(cpu, cycles);
+                        if self.service_interrupts(cpu) {
+                            *self.last_stop.borrow_mut() = None;
+                            return Stepping;
+                        }
+                    }
+                    DebugMessage::StopOnInterrupt(value) => self.stop_on_interrupt.set(value),
                 },
             }
         }
     }
 
+    const fn modifies_machine(m: &DebugMessage) -> bool {
+        matches!(
+            m,
+            DebugMessage::Step
+                | DebugMessage::Run
+                | DebugMessage::StepLine
+                | DebugMessage::StepOver
+                | DebugMessage::StepOut
+                | DebugMessage::SetPc(_)
+                | DebugMessage::Go(_)
+                | DebugMessage::SetMemory(..)
+                | DebugMessage::SetRegisters(_)
+                | DebugMessage::Interrupt(_)
+                | DebugMessage::AssertIrq(_)
+        )
+    }
+
+    fn assert_irq(&self, cpu: &Cpu, cycles: Option<TotalCycles>) {
+        self.irq_line.set(match cycles {
+            Some(cycles) => IrqLine::Until(cpu.total_cycles + cycles),
+            None => IrqLine::UntilTaken,
+        });
+    }
+
+    fn take_interrupt(&self, cpu: &mut Cpu, interrupt: InterruptEvent) {
+        let (pc, sp) = (cpu.reg.pc, cpu.reg.sp);
+        cpu.interrupt(interrupt);
+        self.notify_interrupt(cpu, interrupt, pc, sp);
+    }
+
+    // Take the IRQ if the debugger is holding the line and it isn't masked or, when stopping on
+    // interrupt entry, any interrupt raised by a device so that the handler isn't entered until the
+    // next step
+    fn service_interrupts(&self, cpu: &mut Cpu) -> bool {
+        let asserted = match self.irq_line.get() {
+            IrqLine::Released => false,
+            IrqLine::UntilTaken => true,
+            IrqLine::Until(cycles) if cpu.total_cycles < cycles => true,
+            IrqLine::Until(_) => {
+                self.irq_line.set(IrqLine::Released);
+                false
+            }
+        };
+
+        let (pc, sp) = (cpu.reg.pc, cpu.reg.sp);
+        let interrupt = if asserted && !p_get!(cpu.reg, I) {
+            if matches!(self.irq_line.get(), IrqLine::UntilTaken) {
+                self.irq_line.set(IrqLine::Released);
+            }
+            cpu.interrupt(InterruptEvent::Irq);
+            Some(InterruptEvent::Irq)
+        } else if self.stop_on_interrupt.get() {
+            cpu.poll_interrupt()
+        } else {
+            None
+        };
+
+        interrupt.is_some_and(|interrupt| {
+            self.notify_interrupt(cpu, interrupt, pc, sp);
+            true
+        })
+    }
+
+    fn notify_interrupt(&self, cpu: &Cpu, interrupt: InterruptEvent, pc: u16, sp: u8) {
+        self.call_stack
+            .borrow_mut()
+            .interrupt(interrupt, pc, sp, cpu.reg.pc);
+        _ = self.monitor_tx.send(MonitorMessage::NotifyInterrupt {
+            interrupt,
+            target: cpu.reg.pc,
+        });
+    }
+
+    // Execute one instruction and report the first watchpoint it triggered, ignoring accesses made
+    // by the debugger itself and by the engine
+    fn step_cpu(
+        &self,
+        cpu: &mut Cpu,
+        monitor_callbacks: bool,
+    ) -> (Option<WatchHit>, Result<Option<StopReason>>) {
+        self.call_stack.borrow_mut().before_step(cpu);
+        self.engine.borrow_mut().before_step(cpu);
+        _ = self.watchpoints.take_hit();
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.before_step(cpu);
+        }
+        if monitor_callbacks {
+            cpu.step_with_monitor_callbacks();
+        } else {
+            cpu.step();
+        }
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.after_step(cpu);
+        }
+        let hit = self.watchpoints.take_hit();
+        self.call_stack.borrow_mut().after_step(cpu);
+        let stop_reason = self.engine.borrow_mut().after_step(cpu);
+        (hit, stop_reason)
+    }
+
+    // Condition to run until when stepping by line, over or out, or none to step a single
+    // instruction
+    fn stop_condition_for(&self, cpu: &Cpu, m: &DebugMessage) -> Option<StopCondition> {
+        match m {
+            // Without debug info this is the same as stepping a single instruction
+            DebugMessage::StepLine if !self.debug_info.lines.is_empty() => {
+                Some(StopCondition::SourceLineChanged {
+                    start_line: self.debug_info.find_line(cpu.reg.pc).map(|l| l.id),
+                    max_depth: None,
+                })
+            }
+            DebugMessage::StepOver => self.step_over_condition(cpu),
+            // Outside any subroutine this is the same as stepping a single instruction
+            DebugMessage::StepOut => {
+                let depth = self.call_stack.borrow().frames().len();
+                (depth > 0).then_some(StopCondition::Return(depth))
+            }
+            _ => None,
+        }
+    }
+
+    // Step over subroutine calls by line if there's debug info or by instruction otherwise
+    fn step_over_condition(&self, cpu: &Cpu) -> Option<StopCondition> {
+        let depth = self.call_stack.borrow().frames().len();
+        if !self.debug_info.lines.is_empty() {
+            return Some(StopCondition::SourceLineChanged {
+                start_line: self.debug_info.find_line(cpu.reg.pc).map(|l| l.id),
+                max_depth: Some(depth),
+            });
+        }
+
+        if matches!(Opcode::from_u8(cpu.bus.load(cpu.reg.pc)), Some(Opcode::Jsr)) {
+            return Some(StopCondition::Return(depth + 1));
+        }
+
+        None
+    }
+
+    fn is_stop_condition_met(&self, cpu: &Cpu) -> bool {
+        let depth = self.call_stack.borrow().frames().len();
+        match &*self.stop_condition.borrow() {
+            Some(StopCondition::SourceLineChanged {
+                start_line,
+                max_depth,
+            }) => {
+                max_depth.is_none_or(|max_depth| depth <= max_depth)
+                    && self
+                        .debug_info
+                        .find_line(cpu.reg.pc)
+                        .is_some_and(|line| Some(line.id) != *start_line)
+            }
+            Some(StopCondition::Return(return_depth)) => depth < *return_depth,
+            None => false,
+        }
+    }
+
+    // Conditions that fail to evaluate stop the program so the problem can be seen
+    fn is_breakpoint_hit(&self, cpu: &Cpu) -> bool {
+        match self.breakpoints.borrow().get(&cpu.reg.pc) {
+            Some(Some(condition)) => condition
+                .eval(&cpu.reg, &|addr| self.bus.load(addr))
+                .map_or(true, |value| value != 0),
+            Some(None) => true,
+            None => false,
+        }
+    }
+
+    // Read memory through the bus so that watchpoints don't see the debugger's accesses
+    fn evaluate(&self, cpu: &Cpu, exprs: &[Expr]) {
+        let values = exprs
+            .iter()
+            .map(|expr| expr.eval(&cpu.reg, &|addr| self.bus.load(addr)))
+            .collect();
+        _ = self
+            .monitor_tx
+            .send(MonitorMessage::EvaluateResponse(values));
+    }
+
     fn fetch_memory(&self, address_range: &AddressRange) {
         let snapshot = self.bus.snapshot(address_range);
         _ = self.monitor_tx.send(MonitorMessage::FetchMemoryResponse {
@@ -158,8 +616,95 @@ impl TuiHost {
         });
     }
 
+    fn watch_memory(&self, address_range: AddressRange) {
+        *self.memory_view.borrow_mut() = Some(address_range);
+        self.send_memory_view();
+    }
+
+    fn send_memory_view(&self) {
+        if let Some(address_range) = self.memory_view.borrow().as_ref() {
+            let snapshot = self.bus.snapshot(address_range);
+            _ = self.monitor_tx.send(MonitorMessage::NotifyMemoryView {
+                address_range: address_range.clone(),
+                snapshot,
+            });
+        }
+    }
+
+    fn set_watches(&self, cpu: &Cpu, watches: Vec<WatchExpr>) {
+        *self.watches.borrow_mut() = watches;
+        self.send_watches(cpu);
+    }
+
+    fn send_watches(&self, cpu: &Cpu) {
+        let values = self
+            .watches
+            .borrow()
+            .iter()
+            .map(|watch| watch.eval(&cpu.reg, &|addr| self.bus.load(addr)))
+            .collect();
+        _ = self.monitor_tx.send(MonitorMessage::NotifyWatches(values));
+    }
+
+    // Returns true if the sanitizer found any new issues
+    fn send_sanitizer_issues(&self) -> bool {
+        let Some(sanitizer) = &self.sanitizer else {
+            return false;
+        };
+
+        let issues = sanitizer.take_issues();
+        let found = !issues.is_empty();
+        for issue in issues {
+            _ = self.monitor_tx.send(MonitorMessage::NotifySanitizer(issue));
+        }
+        found
+    }
+
+    fn send_devices(&self) {
+        _ = self
+            .monitor_tx
+            .send(MonitorMessage::NotifyDevices(self.bus.devices()));
+    }
+
+    fn send_call_stack(&self, cpu: &Cpu) {
+        let call_stack = self.call_stack.borrow();
+        let stack = (u16::from(cpu.reg.sp) + 1..=0xff)
+            .map(|offset| cpu.bus.load(STACK_BASE + offset))
+            .collect();
+        _ = self.monitor_tx.send(MonitorMessage::NotifyCallStack {
+            frames: call_stack.frames().to_vec(),
+            issues: call_stack.issues().to_vec(),
+            sp: cpu.reg.sp,
+            stack,
+        });
+    }
+
+    fn set_memory(cpu: &Cpu, addr: u16, bytes: &[u8]) {
+        let mut addr = addr;
+        for value in bytes {
+            cpu.bus.store(addr, *value);
+            addr = addr.wrapping_add(1);
+        }
+    }
+
     fn set_pc(&self, cpu: &mut Cpu, addr: u16) {
         cpu.reg.pc = addr;
         self.fetch_instruction(cpu);
     }
 }
+
+impl BusMonitor for TuiHost<'_> {
+    fn on_load(&self, addr: u16, value: u8) {
+        self.watchpoints.on_load(addr, value);
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.on_load(addr, value);
+        }
+    }
+
+    fn on_store(&self, addr: u16, value: u8) {
+        self.watchpoints.on_store(addr, value);
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.on_store(addr, value);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use r6502config::CharSet;
+use r6502core::emulator::OutputDevice;
+use r6502core::emulator::char_set_util::translate_out;
+use r6502core::messages::IoMessage;
+use std::sync::mpsc::Sender;
+
+pub struct TuiOutput {
+    io_tx: Sender<IoMessage>,
+}
+
+impl TuiOutput {
+    pub const fn new(io_tx: Sender<IoMessage>) -> Self {
+        Self { io_tx }
+    }
+}
+
+impl OutputDevice for TuiOutput {
+    fn write(&mut self, char_set: &CharSet, value: u8) -> Result<()> {
+        if let Some(value) = translate_out(char_set, value) {
+            self.io_tx.send(IoMessage::WriteChar(value as char))?;
+        }
+        Ok(())
+    }
+}
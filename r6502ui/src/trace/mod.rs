@@ -0,0 +1,9 @@
+mod run_trace_diff;
+mod run_trace_show;
+mod trace_options;
+mod util;
+
+pub use run_trace_diff::*;
+pub use run_trace_show::*;
+pub use trace_options::*;
+pub use util::*;
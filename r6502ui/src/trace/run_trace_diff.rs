@@ -0,0 +1,139 @@
+use crate::trace::{TraceDiffOptions, load_symbols};
+use anyhow::Result;
+use r6502core::symbols::MapFile;
+use r6502core::trace::{TraceDivergence, TraceReader};
+use std::io::{Read, Write, stdout};
+use std::process::exit;
+
+// Exits with status 1 if the traces differ so that it can drive git bisect run
+pub fn run_trace_diff(opts: &TraceDiffOptions) -> Result<()> {
+    let left = TraceReader::open(&opts.left)?;
+    let right = TraceReader::open(&opts.right)?;
+    let map_file = load_symbols(
+        opts.image
+            .as_deref()
+            .or(left.header().image_path.as_deref()),
+    )?;
+
+    if diff_traces(left, right, &map_file, opts.context, &mut stdout().lock())? {
+        exit(1);
+    }
+    Ok(())
+}
+
+// Returns true if the traces differ
+fn diff_traces<L: Read, R: Read>(
+    left: TraceReader<L>,
+    right: TraceReader<R>,
+    map_file: &MapFile,
+    context: usize,
+    out: &mut impl Write,
+) -> Result<bool> {
+    let Some(divergence) = TraceDivergence::find(left, right, context)? else {
+        writeln!(out, "Traces match")?;
+        return Ok(false);
+    };
+
+    writeln!(
+        out,
+        "Traces diverge after {} matching instruction(s) in: {}",
+        divergence.index,
+        divergence.differences().join(", ")
+    )?;
+    for record in &divergence.context {
+        writeln!(out, "  {}", record.describe(map_file))?;
+    }
+    for (prefix, record) in [('<', &divergence.left), ('>', &divergence.right)] {
+        match record {
+            Some(record) => writeln!(out, "{prefix} {}", record.describe(map_file))?,
+            None => writeln!(out, "{prefix} (end of trace)")?,
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::run_trace_diff::diff_traces;
+    use anyhow::Result;
+    use r6502core::symbols::MapFile;
+    use r6502core::trace::{TraceHeader, TraceReader, TraceRecord};
+    use r6502core::{Opcode, Reg};
+
+    // NOPs from $0E00 with A set to the given values
+    fn trace(values: &[u8]) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        TraceHeader { image_path: None }.write_to(&mut bytes)?;
+        for (pc, a) in (0x0e00..).zip(values) {
+            TraceRecord {
+                total_cycles: u64::from(pc - 0x0e00) * 2,
+                pc,
+                opcode: Opcode::Nop as u8,
+                operands: Vec::new(),
+                cycles: 2,
+                interrupt: None,
+                reg: Reg {
+                    a: *a,
+                    pc: pc + 1,
+                    ..Reg::default()
+                },
+                writes: Vec::new(),
+            }
+            .write_to(&mut bytes)?;
+        }
+        Ok(bytes)
+    }
+
+    fn diff(left: &[u8], right: &[u8]) -> Result<(bool, Vec<String>)> {
+        let left = trace(left)?;
+        let right = trace(right)?;
+        let mut out = Vec::new();
+        let differ = diff_traces(
+            TraceReader::new(left.as_slice())?,
+            TraceReader::new(right.as_slice())?,
+            &MapFile::default(),
+            1,
+            &mut out,
+        )?;
+        Ok((
+            differ,
+            String::from_utf8(out)?.lines().map(String::from).collect(),
+        ))
+    }
+
+    #[test]
+    fn basics() -> Result<()> {
+        let (differ, lines) = diff(&[1, 2, 3], &[1, 2, 3])?;
+        assert!(!differ);
+        assert_eq!(vec!["Traces match"], lines);
+
+        let (differ, lines) = diff(&[1, 2, 3], &[1, 2, 4])?;
+        assert!(differ);
+        assert_eq!(4, lines.len());
+        assert_eq!(
+            "Traces diverge after 2 matching instruction(s) in: A",
+            lines[0]
+        );
+        assert!(lines[1].starts_with("           2  0E01"));
+        assert!(lines[2].starts_with("<          4  0E02") && lines[2].contains("A=03"));
+        assert!(lines[3].starts_with(">          4  0E02") && lines[3].contains("A=04"));
+
+        let (differ, lines) = diff(&[1, 2], &[5, 2, 3])?;
+        assert!(differ);
+        assert_eq!(3, lines.len());
+        assert_eq!(
+            "Traces diverge after 0 matching instruction(s) in: A",
+            lines[0]
+        );
+
+        let (differ, lines) = diff(&[1, 2], &[1, 2, 3])?;
+        assert!(differ);
+        assert_eq!(
+            "Traces diverge after 2 matching instruction(s) in: length",
+            lines[0]
+        );
+        assert_eq!("< (end of trace)", lines[2]);
+        assert!(lines[3].starts_with(">          4  0E02"));
+        Ok(())
+    }
+}
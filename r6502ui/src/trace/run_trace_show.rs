@@ -0,0 +1,82 @@
+use crate::trace::{TraceShowOptions, load_symbols};
+use anyhow::Result;
+use r6502core::symbols::MapFile;
+use r6502core::trace::TraceReader;
+use std::io::{Read, Write, stdout};
+
+pub fn run_trace_show(opts: &TraceShowOptions) -> Result<()> {
+    let reader = TraceReader::open(&opts.path)?;
+    let map_file = load_symbols(
+        opts.image
+            .as_deref()
+            .or(reader.header().image_path.as_deref()),
+    )?;
+
+    show_trace(reader, &map_file, &mut stdout().lock())
+}
+
+fn show_trace<R: Read>(
+    reader: TraceReader<R>,
+    map_file: &MapFile,
+    out: &mut impl Write,
+) -> Result<()> {
+    for record in reader {
+        writeln!(out, "{}", record?.describe(map_file))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::run_trace_show::show_trace;
+    use anyhow::Result;
+    use r6502core::symbols::{DebugInfo, MapFile};
+    use r6502core::trace::{TraceHeader, TraceReader, TraceRecord};
+    use r6502core::{Opcode, Reg};
+
+    const DEBUG_INFO: &str = r#"version	major=2,minor=0
+sym	id=0,name="start",addrsize=absolute,scope=0,def=0,val=0xE00,type=lab
+"#;
+
+    #[test]
+    fn basics() -> Result<()> {
+        let mut bytes = Vec::new();
+        TraceHeader { image_path: None }.write_to(&mut bytes)?;
+        for (pc, a) in [(0x0e00, 0x41), (0x0e01, 0x42)] {
+            TraceRecord {
+                total_cycles: u64::from(pc - 0x0e00) * 2,
+                pc,
+                opcode: Opcode::Nop as u8,
+                operands: Vec::new(),
+                cycles: 2,
+                interrupt: None,
+                reg: Reg {
+                    a,
+                    pc: pc + 1,
+                    ..Reg::default()
+                },
+                writes: vec![(0x0200, a)],
+            }
+            .write_to(&mut bytes)?;
+        }
+        let mut map_file = MapFile::default();
+        map_file.merge_labels(&DEBUG_INFO.parse::<DebugInfo>()?);
+
+        let mut out = Vec::new();
+        show_trace(TraceReader::new(bytes.as_slice())?, &map_file, &mut out)?;
+        let out = String::from_utf8(out)?;
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+        assert!(lines[0].contains("0E00  EA"));
+        assert!(lines[0].contains("(start)"));
+        assert!(lines[0].ends_with("A=41 X=00 Y=00 P=[..-.....] SP=FF  $0200=41"));
+        assert!(lines[1].contains("0E01  EA"));
+        assert!(lines[1].contains("(start+$01)"));
+
+        // Trace cut short part way through a record
+        let mut out = Vec::new();
+        let reader = TraceReader::new(&bytes[..bytes.len() - 1])?;
+        assert!(show_trace(reader, &map_file, &mut out).is_err());
+        Ok(())
+    }
+}
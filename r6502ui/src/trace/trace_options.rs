@@ -0,0 +1,14 @@
+use std::path::PathBuf;
+
+pub struct TraceShowOptions {
+    pub path: PathBuf,
+    // Image whose symbols are used instead of the one the trace was recorded from
+    pub image: Option<PathBuf>,
+}
+
+pub struct TraceDiffOptions {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    pub image: Option<PathBuf>,
+    pub context: usize,
+}
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use r6502core::symbols::{DebugInfo, MapFile};
+use std::path::Path;
+
+// Symbols are optional: a trace can still be shown once its image has moved or been rebuilt
+pub fn load_symbols(image_path: Option<&Path>) -> Result<MapFile> {
+    let Some(image_path) = image_path else {
+        return Ok(MapFile::default());
+    };
+    let mut map_file = MapFile::load(image_path)?;
+    map_file.merge_labels(&DebugInfo::load_or_default(image_path));
+    Ok(map_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::load_symbols;
+    use anyhow::Result;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+    use std::process;
+
+    const DEBUG_INFO: &str = r#"version	major=2,minor=0
+sym	id=0,name="start",addrsize=absolute,scope=0,def=0,val=0xE00,type=lab
+"#;
+
+    #[test]
+    fn basics() -> Result<()> {
+        assert_eq!("$0E00", load_symbols(None)?.symbolize(0x0e00));
+
+        let dir = temp_dir().join(format!("r6502-trace-symbols-{}", process::id()));
+        create_dir_all(&dir)?;
+        let image_path = dir.join("main.r6502");
+        let missing = load_symbols(Some(&image_path))?.symbolize(0x0e00);
+        write(dir.join("main.dbg"), DEBUG_INFO)?;
+        let found = load_symbols(Some(&image_path))?.symbolize(0x0e00);
+        remove_dir_all(&dir)?;
+
+        // Image itself need not exist any more
+        assert_eq!("$0E00", missing);
+        assert_eq!("start", found);
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{Result, anyhow, bail};
+use r6502ui::dap_server::serve_dap;
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::env::temp_dir;
+use std::fs::{create_dir_all, remove_dir_all, write};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::Path;
+use std::process;
+use std::thread::{sleep, spawn};
+use std::time::Duration;
+
+const SOURCE: &str = "start:  lda #$41
+        jsr print
+        inx
+done:   jmp done
+print:  sta $0200
+        rts
+";
+
+const PROGRAM: [u8; 13] = [
+    0xa9, 0x41, // $0E00: LDA #$41
+    0x20, 0x09, 0x0e, // $0E02: JSR $0E09
+    0xe8, // $0E05: INX
+    0x4c, 0x06, 0x0e, // $0E06: JMP $0E06
+    0x8d, 0x00, 0x02, // $0E09: STA $0200
+    0x60, // $0E0C: RTS
+];
+
+const DEBUG_INFO: &str = r#"version	major=2,minor=0
+info	csym=0,file=1,lib=0,line=6,mod=1,scope=1,seg=1,span=6,sym=3,type=1
+file	id=0,name="main.s",size=100,mtime=0x60000000,mod=0
+line	id=0,file=0,line=1,span=0
+line	id=1,file=0,line=2,span=1
+line	id=2,file=0,line=3,span=2
+line	id=3,file=0,line=4,span=3
+line	id=4,file=0,line=5,span=4
+line	id=5,file=0,line=6,span=5
+mod	id=0,name="main.o",file=0
+seg	id=0,name="CODE",start=0x000E00,size=0x000D,addrsize=absolute,type=ro,oname="main.r6502",ooffs=0
+span	id=0,seg=0,start=0,size=2
+span	id=1,seg=0,start=2,size=3
+span	id=2,seg=0,start=5,size=1
+span	id=3,seg=0,start=6,size=3
+span	id=4,seg=0,start=9,size=3
+span	id=5,seg=0,start=12,size=1
+scope	id=0,name="",mod=0,size=13,span=0+1+2+3+4+5
+sym	id=0,name="start",addrsize=absolute,scope=0,def=0,val=0xE00,seg=0,type=lab
+sym	id=1,name="done",addrsize=absolute,scope=0,def=3,val=0xE06,seg=0,type=lab
+sym	id=2,name="print",addrsize=absolute,scope=0,def=4,val=0xE09,seg=0,type=lab
+"#;
+
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    seq: u64,
+    events: VecDeque<Value>,
+}
+
+impl Client {
+    fn connect(port: u16) -> Result<Self> {
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            seq: 0,
+            events: VecDeque::new(),
+        })
+    }
+
+    fn receive(&mut self) -> Result<Value> {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            self.reader.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                content_length = value.parse()?;
+            }
+        }
+
+        let mut content = vec![0; content_length];
+        self.reader.read_exact(&mut content)?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    // Returns the response body, keeping any events received meanwhile
+    fn request(&mut self, command: &str, arguments: &Value) -> Result<Value> {
+        self.seq += 1;
+        let content = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{content}",
+            content.len()
+        )?;
+
+        loop {
+            let message = self.receive()?;
+            if message["type"] == "event" {
+                self.events.push_back(message);
+                continue;
+            }
+
+            if message["request_seq"] != self.seq {
+                bail!("unexpected message {message}")
+            }
+            if message["success"] != true {
+                bail!("{command} failed: {}", message["message"])
+            }
+            return Ok(message["body"].clone());
+        }
+    }
+
+    // Returns the body of the next event with the given name, skipping program output
+    fn event(&mut self, name: &str) -> Result<Value> {
+        loop {
+            let message = match self.events.pop_front() {
+                Some(message) => message,
+                None => self.receive()?,
+            };
+            if message["event"] == "output" {
+                continue;
+            }
+            if message["event"] != name {
+                bail!("expected {name} event, got {message}")
+            }
+            return Ok(message["body"].clone());
+        }
+    }
+
+    fn stopped(&mut self) -> Result<String> {
+        let body = self.event("stopped")?;
+        body["reason"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| anyhow!("missing reason"))
+    }
+
+    fn frames(&mut self) -> Result<Vec<(String, u64)>> {
+        let body = self.request("stackTrace", &json!({ "threadId": 1 }))?;
+        Ok(body["stackFrames"]
+            .as_array()
+            .ok_or_else(|| anyhow!("missing stack frames"))?
+            .iter()
+            .map(|frame| {
+                (
+                    frame["name"].as_str().unwrap_or_default().to_string(),
+                    frame["line"].as_u64().unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    fn evaluate(&mut self, expression: &str) -> Result<Value> {
+        let body = self.request("evaluate", &json!({ "expression": expression }))?;
+        Ok(body["result"].clone())
+    }
+}
+
+fn write_files(dir: &Path) -> Result<()> {
+    let mut image = b"sim65".to_vec();
+    image.extend([2, 0, 0xff, 0x00, 0x0e, 0x00, 0x0e]);
+    image.extend(PROGRAM);
+    write(dir.join("main.r6502"), image)?;
+    write(dir.join("main.s"), SOURCE)?;
+    write(dir.join("main.dbg"), DEBUG_INFO)?;
+    Ok(())
+}
+
+fn scripted_session(client: &mut Client, dir: &Path) -> Result<()> {
+    let body = client.request("initialize", &json!({ "adapterID": "r6502" }))?;
+    assert_eq!(true, body["supportsConfigurationDoneRequest"]);
+
+    client.request(
+        "launch",
+        &json!({
+            "program": dir.join("main.r6502"),
+            "machine": "AllRam",
+            "stopOnEntry": true,
+        }),
+    )?;
+    client.event("initialized")?;
+
+    let body = client.request(
+        "setBreakpoints",
+        &json!({
+            "source": { "path": dir.join("main.s") },
+            "breakpoints": [{ "line": 5 }, { "line": 10 }],
+        }),
+    )?;
+    assert_eq!(true, body["breakpoints"][0]["verified"]);
+    assert_eq!("0x0E09", body["breakpoints"][0]["instructionReference"]);
+    assert_eq!(false, body["breakpoints"][1]["verified"]);
+
+    client.request("configurationDone", &json!({}))?;
+    assert_eq!("entry", client.stopped()?);
+    assert_eq!(vec![(String::from("start"), 1)], client.frames()?);
+
+    // Stepping
+    client.request("stepIn", &json!({ "threadId": 1 }))?;
+    assert_eq!("step", client.stopped()?);
+    assert_eq!(vec![(String::from("start+$02"), 2)], client.frames()?);
+
+    client.request("next", &json!({ "threadId": 1 }))?;
+    assert_eq!("breakpoint", client.stopped()?);
+    assert_eq!(
+        vec![(String::from("print"), 5), (String::from("start+$02"), 2)],
+        client.frames()?
+    );
+
+    let body = client.request("variables", &json!({ "variablesReference": 1 }))?;
+    assert_eq!("A", body["variables"][0]["name"]);
+    assert_eq!("$41", body["variables"][0]["value"]);
+
+    client.request("stepOut", &json!({ "threadId": 1 }))?;
+    assert_eq!("step", client.stopped()?);
+    assert_eq!(vec![(String::from("start+$05"), 3)], client.frames()?);
+
+    // Memory
+    assert_eq!("$0200: 41", client.evaluate("$0200,1")?);
+    assert_eq!("$41", client.evaluate("[$0200]")?);
+    assert_eq!("$0E09: 8D 00 02", client.evaluate("print,3")?);
+    assert_eq!("$41", client.evaluate("a")?);
+    assert_eq!("$0E0C", client.evaluate("print+3")?);
+    let body = client.request("variables", &json!({ "variablesReference": 3 }))?;
+    assert_eq!(16, body["variables"].as_array().map_or(0, Vec::len));
+
+    // Continue and pause
+    client.request(
+        "setBreakpoints",
+        &json!({ "source": { "path": dir.join("main.s") }, "breakpoints": [] }),
+    )?;
+    client.request("continue", &json!({ "threadId": 1 }))?;
+    sleep(Duration::from_millis(100));
+    client.request("pause", &json!({ "threadId": 1 }))?;
+    assert_eq!("pause", client.stopped()?);
+    assert_eq!(vec![(String::from("done"), 4)], client.frames()?);
+
+    client.request("disconnect", &json!({}))?;
+    Ok(())
+}
+
+#[test]
+fn scripted_dap_session() -> Result<()> {
+    let dir = temp_dir().join(format!("r6502-dap-{}", process::id()));
+    create_dir_all(&dir)?;
+    write_files(&dir)?;
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let port = listener.local_addr()?.port();
+    let handle = spawn(move || -> Result<()> {
+        let (stream, _) = listener.accept()?;
+        serve_dap(stream.try_clone()?, stream)
+    });
+
+    let mut client = Client::connect(port)?;
+    let result = scripted_session(&mut client, &dir);
+    drop(client);
+    remove_dir_all(&dir)?;
+    result?;
+
+    match handle.join() {
+        Ok(result) => result,
+        Err(_) => bail!("server thread panicked"),
+    }
+}
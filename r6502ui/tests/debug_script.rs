@@ -0,0 +1,132 @@
+use anyhow::Result;
+use r6502config::Machine;
+use r6502core::symbols::{DebugInfo, MapFile};
+use r6502hw::MachineInfo;
+use r6502snapshot::{MemoryImage, OtherImage};
+use r6502ui::text_ui::run_debug_script;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const MACHINE: &str = r#"{
+    "name": "AllRam",
+    "tag": "ALLR",
+    "busDevices": [
+        {
+            "type": "ram",
+            "addressRange": "$0000:$FFFF",
+            "offset": "$0000"
+        }
+    ]
+}"#;
+
+const PROGRAM: [u8; 13] = [
+    0xa9, 0x41, // $0E00: LDA #$41
+    0x20, 0x09, 0x0e, // $0E02: JSR $0E09
+    0xe8, // $0E05: INX
+    0x4c, 0x06, 0x0e, // $0E06: JMP $0E06
+    0x8d, 0x00, 0x02, // $0E09: STA $0200
+    0x60, // $0E0C: RTS
+];
+
+const DEBUG_INFO: &str = r#"version	major=2,minor=0
+seg	id=0,name="CODE",start=0x000E00,size=0x000D,addrsize=absolute,type=ro,oname="main.r6502",ooffs=0
+scope	id=0,name="",mod=0,size=13
+sym	id=0,name="start",addrsize=absolute,scope=0,def=0,val=0xE00,seg=0,type=lab
+sym	id=1,name="done",addrsize=absolute,scope=0,def=3,val=0xE06,seg=0,type=lab
+sym	id=2,name="print",addrsize=absolute,scope=0,def=4,val=0xE09,seg=0,type=lab
+"#;
+
+const SCRIPT: &str = "# Stop in subroutine
+break print
+run
+assert PC=print
+assert A=$41
+
+step 2
+assert [$200] = $41
+assert pc=$e05
+assert C=0
+mem $200:$203
+reg
+assert X=1
+break done if X=1 && [$200]=A
+run
+assert {$0E07}=done
+";
+
+fn run(script: &str) -> Result<(usize, String)> {
+    let machine_info = MachineInfo {
+        config_dir: PathBuf::new(),
+        machine: serde_json::from_str::<Machine>(MACHINE)?,
+    };
+    let image = MemoryImage::Other(OtherImage::new_sim6502(
+        0x0e00,
+        0x0e00,
+        0xff,
+        PROGRAM.to_vec(),
+    ));
+    let debug_info = Arc::new(DEBUG_INFO.parse::<DebugInfo>()?);
+    let mut map_file = MapFile::default();
+    map_file.merge_labels(&debug_info);
+
+    let mut out = Vec::new();
+    let failed = run_debug_script(
+        script,
+        machine_info,
+        image,
+        &map_file,
+        &debug_info,
+        None,
+        &mut out,
+    )?;
+    Ok((failed, String::from_utf8(out)?))
+}
+
+#[test]
+fn scripted_session() -> Result<()> {
+    let (failed, output) = run(SCRIPT)?;
+    assert_eq!(1, failed);
+    assert_eq!(
+        "> break print
+Breakpoint set at $0E09 (print)
+> run
+Breakpoint hit at $0E09 (print)
+0E09  8D 00 02  STA $0200
+> assert PC=print
+PASS
+> assert A=$41
+PASS
+> step 2
+0E05  E8        INX
+> assert [$200] = $41
+PASS
+> assert pc=$e05
+PASS
+> assert C=0
+PASS
+> mem $200:$203
+$0200:$0203
+0200  41 00 00 00                                      A...
+> reg
+PC:0E05 A:41 X:00 Y:00 S:FF [..-.....]
+> assert X=1
+FAIL: expected $01, got $00
+> break done if X=1 && [$200]=A
+Breakpoint set at $0E06 (done)
+> run
+Breakpoint hit at $0E06 (done)
+0E06  4C 06 0E  JMP done
+> assert {$0E07}=done
+PASS
+6 assertion(s) passed, 1 failed
+",
+        output
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_command() {
+    let error = run("reg\nstep\njump $0e00\n").expect_err("Must fail");
+    assert_eq!("line 3: unsupported command jump $0e00", error.to_string());
+}
@@ -0,0 +1,137 @@
+use anyhow::{Result, bail};
+use r6502config::Machine;
+use r6502hw::MachineInfo;
+use r6502snapshot::{MemoryImage, OtherImage};
+use r6502ui::gdb_server::serve_gdb;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::thread::{sleep, spawn};
+use std::time::Duration;
+
+const MACHINE: &str = r#"{
+    "name": "AllRam",
+    "tag": "ALLR",
+    "busDevices": [
+        {
+            "type": "ram",
+            "addressRange": "$0000:$FFFF",
+            "offset": "$0000"
+        }
+    ]
+}"#;
+
+const PROGRAM: [u8; 9] = [
+    0xa9, 0x41, // $0E00: LDA #$41
+    0x8d, 0x00, 0x02, // $0E02: STA $0200
+    0xe8, // $0E05: INX
+    0x4c, 0x05, 0x0e, // $0E06: JMP $0E05
+];
+
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    fn connect(port: u16) -> Result<Self> {
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    fn send(&mut self, data: &str) -> Result<()> {
+        let checksum = data.bytes().fold(0u8, u8::wrapping_add);
+        write!(self.writer, "${data}#{checksum:02x}")?;
+        let mut ack = [0; 1];
+        self.reader.read_exact(&mut ack)?;
+        if ack[0] != b'+' {
+            bail!("packet {data} not acknowledged")
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<String> {
+        let mut data = Vec::new();
+        self.reader.read_until(b'$', &mut data)?;
+        data.clear();
+        self.reader.read_until(b'#', &mut data)?;
+        _ = data.pop();
+        let mut checksum = [0; 2];
+        self.reader.read_exact(&mut checksum)?;
+        self.writer.write_all(b"+")?;
+        Ok(String::from_utf8(data)?)
+    }
+
+    fn request(&mut self, data: &str) -> Result<String> {
+        self.send(data)?;
+        self.receive()
+    }
+}
+
+#[test]
+fn scripted_session() -> Result<()> {
+    let machine_info = MachineInfo {
+        config_dir: PathBuf::new(),
+        machine: serde_json::from_str::<Machine>(MACHINE)?,
+    };
+    let image = MemoryImage::Other(OtherImage::new_sim6502(
+        0x0e00,
+        0x0e00,
+        0xff,
+        PROGRAM.to_vec(),
+    ));
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let port = listener.local_addr()?.port();
+    let handle = spawn(move || -> Result<()> {
+        let (stream, _) = listener.accept()?;
+        serve_gdb(stream, machine_info, image)
+    });
+
+    let mut client = Client::connect(port)?;
+    assert_eq!("PacketSize=1000", client.request("qSupported:swbreak+")?);
+    assert_eq!("S05", client.request("?")?);
+    assert_eq!("ff", client.request("p3")?);
+    assert_eq!("000e", client.request("p4")?);
+
+    // Registers
+    assert_eq!("S05", client.request("s")?);
+    assert!(client.request("g")?.starts_with("410000ff020e"));
+    assert_eq!("OK", client.request("P1=7f")?);
+    assert_eq!("7f", client.request("p1")?);
+
+    // Watchpoints
+    assert_eq!("OK", client.request("Z2,200,1")?);
+    assert_eq!("T05watch:0200;", client.request("c")?);
+    assert_eq!("050e", client.request("p4")?);
+    assert_eq!("OK", client.request("z2,200,1")?);
+
+    // Breakpoints
+    assert_eq!("OK", client.request("Z0,e06,1")?);
+    assert_eq!("S05", client.request("c")?);
+    assert_eq!("060e", client.request("p4")?);
+    assert_eq!("80", client.request("p1")?);
+    assert_eq!("OK", client.request("z0,e06,1")?);
+
+    // Memory
+    assert_eq!("41", client.request("m200,1")?);
+    assert_eq!("OK", client.request("M300,2:abcd")?);
+    assert_eq!("abcd", client.request("m300,2")?);
+
+    // Interrupt
+    client.send("c")?;
+    sleep(Duration::from_millis(100));
+    client.writer.write_all(&[0x03])?;
+    assert_eq!("S02", client.receive()?);
+
+    assert_eq!("", client.request("vMustReplyEmpty")?);
+    client.send("k")?;
+    match handle.join() {
+        Ok(result) => result,
+        Err(_) => bail!("server thread panicked"),
+    }
+}
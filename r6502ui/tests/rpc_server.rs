@@ -0,0 +1,205 @@
+use anyhow::{Result, bail};
+use r6502ui::rpc_server::{RpcOptions, serve_rpc};
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::env::temp_dir;
+use std::fs::{create_dir_all, remove_dir_all, write};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::Path;
+use std::process;
+use std::thread::{sleep, spawn};
+use std::time::Duration;
+
+const PROGRAM: [u8; 13] = [
+    0xa9, 0x41, // $0E00: LDA #$41
+    0x20, 0x09, 0x0e, // $0E02: JSR $0E09
+    0xe8, // $0E05: INX
+    0x4c, 0x06, 0x0e, // $0E06: JMP $0E06
+    0x8d, 0x00, 0x02, // $0E09: STA $0200
+    0x60, // $0E0C: RTS
+];
+
+const DEBUG_INFO: &str = r#"version	major=2,minor=0
+seg	id=0,name="CODE",start=0x000E00,size=0x000D,addrsize=absolute,type=ro,oname="main.r6502",ooffs=0
+scope	id=0,name="",mod=0,size=13
+sym	id=0,name="start",addrsize=absolute,scope=0,def=0,val=0xE00,seg=0,type=lab
+sym	id=1,name="done",addrsize=absolute,scope=0,def=3,val=0xE06,seg=0,type=lab
+sym	id=2,name="print",addrsize=absolute,scope=0,def=4,val=0xE09,seg=0,type=lab
+"#;
+
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    id: u64,
+    notifications: VecDeque<Value>,
+}
+
+impl Client {
+    fn connect(port: u16) -> Result<Self> {
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            id: 0,
+            notifications: VecDeque::new(),
+        })
+    }
+
+    fn receive(&mut self) -> Result<Value> {
+        let mut line = String::new();
+        self.reader.read_line(&mut line)?;
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    // Returns the result or error, keeping any notifications received meanwhile
+    fn call(&mut self, method: &str, params: &Value) -> Result<Result<Value, Value>> {
+        self.id += 1;
+        let request =
+            json!({ "jsonrpc": "2.0", "id": self.id, "method": method, "params": params });
+        writeln!(self.writer, "{request}")?;
+
+        loop {
+            let message = self.receive()?;
+            if message.get("id").is_none() {
+                self.notifications.push_back(message);
+                continue;
+            }
+
+            if message["id"] != self.id {
+                bail!("unexpected message {message}")
+            }
+            return Ok(match message.get("result") {
+                Some(result) => Ok(result.clone()),
+                None => Err(message["error"].clone()),
+            });
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value> {
+        match self.call(method, params)? {
+            Ok(result) => Ok(result),
+            Err(error) => bail!("{method} failed: {error}"),
+        }
+    }
+
+    // Returns the reason and symbol from the next stopped notification
+    fn stopped(&mut self) -> Result<(String, String)> {
+        let message = match self.notifications.pop_front() {
+            Some(message) => message,
+            None => self.receive()?,
+        };
+        if message["method"] != "stopped" {
+            bail!("expected stopped notification, got {message}")
+        }
+
+        let params = &message["params"];
+        Ok((
+            params["reason"].as_str().unwrap_or_default().to_string(),
+            params["symbol"].as_str().unwrap_or_default().to_string(),
+        ))
+    }
+}
+
+fn write_files(dir: &Path) -> Result<()> {
+    let mut image = b"sim65".to_vec();
+    image.extend([2, 0, 0xff, 0x00, 0x0e, 0x00, 0x0e]);
+    image.extend(PROGRAM);
+    write(dir.join("main.r6502"), image)?;
+    write(dir.join("main.dbg"), DEBUG_INFO)?;
+    Ok(())
+}
+
+fn stopped(reason: &str, symbol: &str) -> (String, String) {
+    (String::from(reason), String::from(symbol))
+}
+
+fn scripted_session(client: &mut Client) -> Result<()> {
+    assert_eq!(stopped("entry", "start"), client.stopped()?);
+
+    // Breakpoints
+    let result = client.request("setBreakpoint", &json!({ "address": "print" }))?;
+    assert_eq!(json!({ "address": 0x0e09, "symbol": "print" }), result);
+    client.request(
+        "setBreakpoint",
+        &json!({ "address": 0x0e06, "condition": "X=1" }),
+    )?;
+    let result = client.request("listBreakpoints", &json!({}))?;
+    assert_eq!("X = $01", result["breakpoints"][0]["condition"]);
+
+    client.request("run", &json!({}))?;
+    assert_eq!(stopped("breakpoint", "print"), client.stopped()?);
+    let result = client.request("getRegisters", &json!({}))?;
+    assert_eq!(0x41, result["a"]);
+    assert_eq!(0xfd, result["sp"]);
+
+    // Stepping and memory
+    client.request("step", &json!({}))?;
+    assert_eq!(stopped("step", "print+$03"), client.stopped()?);
+    let result = client.request("readMemory", &json!({ "address": "$0200", "length": 2 }))?;
+    assert_eq!(json!([0x41, 0x00]), result["data"]);
+    client.request("writeMemory", &json!({ "address": 0x0201, "data": [0x12] }))?;
+    let result = client.request("evaluate", &json!({ "expression": "{$0200}" }))?;
+    assert_eq!(0x1241, result["value"]);
+
+    client.request("stepOut", &json!({}))?;
+    assert_eq!(stopped("step", "start+$05"), client.stopped()?);
+    client.request("setRegisters", &json!({ "x": 0 }))?;
+    client.request("run", &json!({}))?;
+    assert_eq!(stopped("breakpoint", "done"), client.stopped()?);
+
+    // Symbols
+    let result = client.request("lookupSymbol", &json!({ "name": "print" }))?;
+    assert_eq!(0x0e09, result["address"]);
+    let result = client.request("lookupSymbol", &json!({ "address": 0x0e0c }))?;
+    assert_eq!("print+$03", result["symbol"]);
+
+    // Run and break
+    client.request("clearBreakpoint", &json!({ "address": "done" }))?;
+    client.request("run", &json!({}))?;
+    sleep(Duration::from_millis(100));
+    let result = client.request("getState", &json!({}))?;
+    assert_eq!("running", result["state"]);
+    let error = client
+        .call("readMemory", &json!({ "address": 0, "length": 1 }))?
+        .expect_err("Must fail");
+    assert_eq!(-32000, error["code"]);
+    client.request("break", &json!({}))?;
+    assert_eq!(stopped("pause", "done"), client.stopped()?);
+
+    let error = client.call("jump", &json!({}))?.expect_err("Must fail");
+    assert_eq!(-32601, error["code"]);
+    Ok(())
+}
+
+#[test]
+fn scripted_rpc_session() -> Result<()> {
+    let dir = temp_dir().join(format!("r6502-rpc-{}", process::id()));
+    create_dir_all(&dir)?;
+    write_files(&dir)?;
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let port = listener.local_addr()?.port();
+    let opts = RpcOptions {
+        path: dir.join("main.r6502"),
+        port: None,
+        machine: Some(String::from("AllRam")),
+    };
+    let handle = spawn(move || -> Result<()> {
+        let (stream, _) = listener.accept()?;
+        serve_rpc(stream.try_clone()?, stream, &opts)
+    });
+
+    let mut client = Client::connect(port)?;
+    let result = scripted_session(&mut client);
+    drop(client);
+    let joined = handle.join();
+    remove_dir_all(&dir)?;
+    result?;
+
+    match joined {
+        Ok(result) => result,
+        Err(_) => bail!("server thread panicked"),
+    }
+}
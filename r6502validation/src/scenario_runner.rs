@@ -40,7 +40,7 @@ pub fn run_scenarios_with_filter(report_path: &Path, filter_str: &Option<String>
                 let scenarios = loader.read_scenarios(path)?;
 
                 let scenarios = filter.filter(scenarios);
-                println!("Running {} scenarios for {opcode}", scenarios.len(),);
+                println!("Running {} scenarios for {opcode}", scenarios.len());
 
                 let mut total_count = 0;
                 let mut failure_count = 0;
@@ -68,7 +68,7 @@ pub fn run_scenarios_with_filter(report_path: &Path, filter_str: &Option<String>
     }
 
     if !failure_counts.is_empty() {
-        failure_counts.sort_by(|a, b| b.1.cmp(&a.1));
+        failure_counts.sort_by_key(|p| std::cmp::Reverse(p.1));
         record_message(report_path, "Failure counts:")?;
         for p in failure_counts {
             record_message(report_path, &format!("{} {}", p.0, p.1))?;
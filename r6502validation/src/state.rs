@@ -41,7 +41,7 @@ impl Display for State {
         )?;
 
         let mut ram = self.ram.clone();
-        ram.sort_by(|a, b| a.address.cmp(&b.address));
+        ram.sort_by_key(|a| a.address);
         for address_value in &ram {
             writeln!(
                 f,